@@ -0,0 +1,561 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    parser::XMLNode,
+    Node,
+    Queryable,
+    Soup,
+};
+
+fn elements<'x>(node: &'x XMLNode, name: &'x str) -> impl Iterator<Item = &'x XMLNode> {
+    node.children()
+        .iter()
+        .filter(move |c| c.name().is_some_and(|n| n == name))
+}
+
+fn flag(node: &XMLNode, name: &str) -> Option<bool> {
+    match node.get(name)?.as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn number(node: &XMLNode, name: &str) -> Option<u32> {
+    node.get(name)?.parse().ok()
+}
+
+/// A permission requested with `uses-permission` or `uses-permission-sdk-23`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Permission {
+    /// `android:name`, like `android.permission.CAMERA`
+    pub name: String,
+    /// `android:maxSdkVersion`, the last API level the permission is requested on
+    pub max_sdk: Option<u32>,
+}
+
+/// Kind of a [`Component`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    /// `activity`
+    Activity,
+    /// `activity-alias`
+    ActivityAlias,
+    /// `service`
+    Service,
+    /// `receiver`
+    Receiver,
+    /// `provider`
+    Provider,
+}
+
+impl ComponentKind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "activity" => Self::Activity,
+            "activity-alias" => Self::ActivityAlias,
+            "service" => Self::Service,
+            "receiver" => Self::Receiver,
+            "provider" => Self::Provider,
+            _ => return None,
+        })
+    }
+}
+
+/// An `intent-filter` of a [`Component`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntentFilter {
+    /// `action` names, like `android.intent.action.MAIN`
+    pub actions: Vec<String>,
+    /// `category` names, like `android.intent.category.LAUNCHER`
+    pub categories: Vec<String>,
+}
+
+/// An application component declared in the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    /// The element the component is declared with
+    pub kind: ComponentKind,
+    /// `android:name`, the fully qualified class name
+    pub name: String,
+    /// `android:exported`, if set
+    pub exported: Option<bool>,
+    /// `android:enabled`, which defaults to `true`
+    pub enabled: bool,
+    /// `android:permission` required to use the component
+    pub permission: Option<String>,
+    /// Intent filters, in order
+    pub intent_filters: Vec<IntentFilter>,
+}
+
+impl Component {
+    /// Returns `true` if the component can be used by other apps
+    ///
+    /// Components without `android:exported` are exported if they have an intent filter.
+    #[must_use]
+    pub fn is_exported(&self) -> bool {
+        self.exported.unwrap_or(!self.intent_filters.is_empty())
+    }
+
+    /// Returns `true` if the component is shown in the launcher
+    #[must_use]
+    pub fn is_launcher(&self) -> bool {
+        self.intent_filters.iter().any(|f| {
+            f.actions.iter().any(|a| a == "android.intent.action.MAIN")
+                && f.categories
+                    .iter()
+                    .any(|c| c == "android.intent.category.LAUNCHER")
+        })
+    }
+}
+
+/// An `AndroidManifest.xml`
+///
+/// XML attributes are stored by local name, so `android:name` is read as `name`.
+///
+/// # Example
+/// ```rust
+/// # use soupy::android::Manifest;
+/// let manifest = Manifest::parse(r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android"
+///     package="com.example.app" android:versionCode="7">
+///     <uses-sdk android:minSdkVersion="24" />
+///     <uses-permission android:name="android.permission.INTERNET" />
+///     <application>
+///         <activity android:name=".MainActivity">
+///             <intent-filter>
+///                 <action android:name="android.intent.action.MAIN" />
+///                 <category android:name="android.intent.category.LAUNCHER" />
+///             </intent-filter>
+///         </activity>
+///     </application>
+/// </manifest>"#).unwrap();
+///
+/// assert_eq!(manifest.version_code, Some(7));
+/// assert_eq!(manifest.permissions[0].name, "android.permission.INTERNET");
+/// assert_eq!(manifest.components[0].name, "com.example.app.MainActivity");
+/// assert!(manifest.components[0].is_launcher());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// `package`, the application id
+    pub package: Option<String>,
+    /// `android:versionCode`
+    pub version_code: Option<u32>,
+    /// `android:versionName`
+    pub version_name: Option<String>,
+    /// `android:minSdkVersion` of `uses-sdk`
+    pub min_sdk: Option<u32>,
+    /// `android:targetSdkVersion` of `uses-sdk`
+    pub target_sdk: Option<u32>,
+    /// Requested permissions, in order
+    pub permissions: Vec<Permission>,
+    /// Components of the `application`, in order
+    pub components: Vec<Component>,
+}
+
+impl Manifest {
+    /// Reads a parsed manifest
+    ///
+    /// Class names starting with `.`, or without any `.`, are qualified with the package.
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let Some(manifest) = soup.tag("manifest").first() else {
+            return Self::default();
+        };
+
+        let package = manifest.get("package").cloned();
+        let class = |name: &str| match &package {
+            Some(package) if name.starts_with('.') => format!("{package}{name}"),
+            Some(package) if !name.contains('.') => format!("{package}.{name}"),
+            _ => name.to_string(),
+        };
+
+        let sdk = elements(&manifest, "uses-sdk").next();
+
+        let permissions = manifest
+            .children()
+            .iter()
+            .filter(|c| {
+                c.name()
+                    .is_some_and(|n| n == "uses-permission" || n == "uses-permission-sdk-23")
+            })
+            .filter_map(|p| {
+                Some(Permission {
+                    name: p.get("name")?.clone(),
+                    max_sdk: number(p, "maxSdkVersion"),
+                })
+            })
+            .collect();
+
+        let components = elements(&manifest, "application")
+            .flat_map(Node::children)
+            .filter_map(|c| {
+                Some(Component {
+                    kind: ComponentKind::from_name(c.name()?)?,
+                    name: class(c.get("name")?),
+                    exported: flag(c, "exported"),
+                    enabled: flag(c, "enabled").unwrap_or(true),
+                    permission: c.get("permission").cloned(),
+                    intent_filters: elements(c, "intent-filter")
+                        .map(|f| {
+                            let names = |tag| {
+                                elements(f, tag)
+                                    .filter_map(|e| e.get("name").cloned())
+                                    .collect()
+                            };
+
+                            IntentFilter {
+                                actions: names("action"),
+                                categories: names("category"),
+                            }
+                        })
+                        .collect(),
+                })
+            })
+            .collect();
+
+        Self {
+            version_code: number(&manifest, "versionCode"),
+            version_name: manifest.get("versionName").cloned(),
+            min_sdk: sdk.and_then(|s| number(s, "minSdkVersion")),
+            target_sdk: sdk.and_then(|s| number(s, "targetSdkVersion")),
+            permissions,
+            components,
+            package,
+        }
+    }
+
+    /// Parses an `AndroidManifest.xml`
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Components of the given kind, in order
+    pub fn components_of(&self, kind: ComponentKind) -> impl Iterator<Item = &Component> {
+        self.components.iter().filter(move |c| c.kind == kind)
+    }
+}
+
+/// A reference to a resource, like `@string/app_name` or `@android:color/white`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// Package, like `android`, or `None` for the app's own resources
+    pub package: Option<String>,
+    /// Resource type, like `string` or `drawable`
+    pub kind: String,
+    /// Resource name
+    pub name: String,
+}
+
+impl Reference {
+    /// Parses a reference starting with `@`, or `?` for theme attributes
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.strip_prefix(['@', '?'])?.trim_start_matches('+');
+        let (qualified, name) = s.split_once('/')?;
+
+        let (package, kind) = match qualified.split_once(':') {
+            Some((package, kind)) => (Some(package.to_string()), kind),
+            None => (None, qualified),
+        };
+
+        Some(Self {
+            package,
+            kind: kind.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// A typed value from a `res/values` file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resource {
+    /// `string`
+    String(String),
+    /// `bool`
+    Bool(bool),
+    /// `integer`
+    Integer(i64),
+    /// `color`, as `0xAARRGGBB`
+    Color(u32),
+    /// `dimen`, with its unit, like `16dp`
+    Dimension(f64, String),
+    /// `string-array` or `integer-array` items, or `plurals` items by quantity
+    Array(Vec<String>),
+    /// A reference to another resource
+    Reference(Reference),
+    /// Any other value, or a value that could not be typed, as written
+    Other(String),
+}
+
+impl Resource {
+    fn from_node(node: &XMLNode, kind: &str) -> Self {
+        let text = node.all_text().trim().to_string();
+
+        if let Some(reference) = Reference::parse(&text) {
+            return Self::Reference(reference);
+        }
+
+        let typed = match kind {
+            "string" => Some(Self::String(text.clone())),
+            "bool" => text.parse().ok().map(Self::Bool),
+            "integer" => text.parse().ok().map(Self::Integer),
+            "color" => parse_color(&text).map(Self::Color),
+            "dimen" => {
+                let split = text
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .unwrap_or(text.len());
+
+                text[..split]
+                    .parse()
+                    .ok()
+                    .map(|v| Self::Dimension(v, text[split..].to_string()))
+            }
+            "string-array" | "integer-array" | "array" | "plurals" => Some(Self::Array(
+                node.children()
+                    .iter()
+                    .filter(|c| c.name().is_some())
+                    .map(|c| c.all_text().trim().to_string())
+                    .collect(),
+            )),
+            _ => None,
+        };
+
+        typed.unwrap_or(Self::Other(text))
+    }
+}
+
+/// Parses a color like `#RGB`, `#ARGB`, `#RRGGBB`, or `#AARRGGBB` into `0xAARRGGBB`
+///
+/// # Example
+/// ```rust
+/// # use soupy::android;
+/// assert_eq!(android::parse_color("#f80"), Some(0xFFFF_8800));
+/// assert_eq!(android::parse_color("#80000000"), Some(0x8000_0000));
+/// ```
+#[must_use]
+pub fn parse_color(s: &str) -> Option<u32> {
+    let hex = s.strip_prefix('#')?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+
+    // Expands each digit of the short forms, like `f` to `ff`
+    let expand = |v: u32| (0..4).fold(0, |out, i| out | (((v >> (i * 4)) & 0xF) * 0x11) << (i * 8));
+
+    match hex.len() {
+        3 => Some(0xFF00_0000 | expand(value)),
+        4 => Some(expand(value)),
+        6 => Some(0xFF00_0000 | value),
+        8 => Some(value),
+        _ => None,
+    }
+}
+
+/// Values of a `res/values` file, by type and name
+///
+/// # Example
+/// ```rust
+/// # use soupy::android::{Resource, Resources};
+/// let res = Resources::parse(r#"<resources>
+///     <string name="app_name">Example</string>
+///     <string name="title">@string/app_name</string>
+///     <dimen name="margin">16dp</dimen>
+/// </resources>"#).unwrap();
+///
+/// assert_eq!(res.string("title"), Some("Example"));
+/// assert_eq!(res.get("dimen", "margin"), Some(&Resource::Dimension(16.0, "dp".into())));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resources {
+    /// Values by type, then name
+    ///
+    /// `item` elements are stored under their `type` attribute.
+    pub values: BTreeMap<String, BTreeMap<String, Resource>>,
+}
+
+impl Resources {
+    /// Reads a parsed `res/values` file
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let mut values = BTreeMap::<String, BTreeMap<_, _>>::new();
+
+        for resources in soup.tag("resources").all() {
+            for node in resources.children() {
+                let (Some(tag), Some(name)) = (node.name(), node.get("name")) else {
+                    continue;
+                };
+
+                let kind = match tag.as_str() {
+                    "item" => node.get("type").map_or("item", String::as_str),
+                    tag => tag,
+                };
+
+                values
+                    .entry(kind.to_string())
+                    .or_default()
+                    .insert(name.clone(), Resource::from_node(node, kind));
+            }
+        }
+
+        Self { values }
+    }
+
+    /// Parses a `res/values` file
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Returns the value of type `kind` named `name`, as written
+    #[must_use]
+    pub fn get(&self, kind: &str, name: &str) -> Option<&Resource> {
+        self.values.get(kind)?.get(name)
+    }
+
+    /// Returns the value of type `kind` named `name`, following references to the app's own
+    /// resources
+    #[must_use]
+    pub fn resolve(&self, kind: &str, name: &str) -> Option<&Resource> {
+        let mut value = self.get(kind, name)?;
+
+        // Bounded, in case references form a cycle
+        for _ in 0..16 {
+            match value {
+                Resource::Reference(r) if r.package.is_none() => {
+                    value = self.get(&r.kind, &r.name)?;
+                }
+                value => return Some(value),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the string named `name`, following references
+    #[must_use]
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.resolve("string", name)? {
+            Resource::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let manifest = Manifest::parse(
+            r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android" package="org.app"
+                android:versionName="1.2">
+                <uses-sdk android:minSdkVersion="21" android:targetSdkVersion="34"/>
+                <uses-permission android:name="android.permission.READ_EXTERNAL_STORAGE" android:maxSdkVersion="32"/>
+                <uses-permission-sdk-23 android:name="android.permission.CAMERA"/>
+                <application android:label="@string/app_name">
+                    <service android:name="Sync" android:exported="false" android:enabled="false"/>
+                    <receiver android:name="com.lib.Boot">
+                        <intent-filter><action android:name="android.intent.action.BOOT_COMPLETED"/></intent-filter>
+                    </receiver>
+                    <provider android:name=".Files" android:permission="org.app.READ"/>
+                    <meta-data android:name="key" android:value="x"/>
+                </application>
+            </manifest>"#,
+        )
+        .expect("Failed to parse manifest");
+
+        assert_eq!(manifest.version_name.as_deref(), Some("1.2"));
+        assert_eq!(
+            (manifest.min_sdk, manifest.target_sdk),
+            (Some(21), Some(34))
+        );
+        assert_eq!(manifest.permissions, [
+            Permission {
+                name: "android.permission.READ_EXTERNAL_STORAGE".into(),
+                max_sdk: Some(32),
+            },
+            Permission {
+                name: "android.permission.CAMERA".into(),
+                max_sdk: None,
+            },
+        ]);
+
+        assert_eq!(
+            manifest
+                .components
+                .iter()
+                .map(|c| (c.kind, c.name.as_str(), c.is_exported()))
+                .collect::<Vec<_>>(),
+            [
+                (ComponentKind::Service, "org.app.Sync", false),
+                (ComponentKind::Receiver, "com.lib.Boot", true),
+                (ComponentKind::Provider, "org.app.Files", false),
+            ]
+        );
+        assert!(!manifest.components[0].enabled);
+        assert_eq!(
+            manifest
+                .components_of(ComponentKind::Provider)
+                .next()
+                .and_then(|p| p.permission.as_deref()),
+            Some("org.app.READ")
+        );
+        assert!(!manifest.components[1].is_launcher());
+    }
+
+    #[test]
+    fn test_resources() {
+        let res = Resources::parse(
+            r#"<resources>
+                <color name="accent">#FF4081</color>
+                <color name="link">@android:color/holo_blue_dark</color>
+                <bool name="tablet">true</bool>
+                <integer name="columns">x</integer>
+                <item name="ratio" type="dimen" format="float">1.5</item>
+                <string-array name="sizes"><item>S</item><item>M</item></string-array>
+                <string name="a">@string/b</string>
+                <string name="b">@string/a</string>
+            </resources>"#,
+        )
+        .expect("Failed to parse resources");
+
+        assert_eq!(
+            res.get("color", "accent"),
+            Some(&Resource::Color(0xFFFF_4081))
+        );
+        assert_eq!(
+            res.resolve("color", "link"),
+            Some(&Resource::Reference(Reference {
+                package: Some("android".into()),
+                kind: "color".into(),
+                name: "holo_blue_dark".into(),
+            }))
+        );
+        assert_eq!(res.get("bool", "tablet"), Some(&Resource::Bool(true)));
+        assert_eq!(
+            res.get("integer", "columns"),
+            Some(&Resource::Other("x".into()))
+        );
+        assert_eq!(
+            res.get("dimen", "ratio"),
+            Some(&Resource::Dimension(1.5, String::new()))
+        );
+        assert_eq!(
+            res.get("string-array", "sizes"),
+            Some(&Resource::Array(vec!["S".into(), "M".into()]))
+        );
+        assert_eq!(res.string("a"), None);
+
+        assert_eq!(parse_color("#8f00"), Some(0x88FF_0000));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(
+            Reference::parse("@+id/button").map(|r| r.kind),
+            Some("id".into())
+        );
+    }
+}
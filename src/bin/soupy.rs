@@ -0,0 +1,280 @@
+//! Query HTML from the command line
+//!
+//! ```text
+//! soupy 'div.article a' --attr href < page.html
+//! ```
+
+use std::{
+    collections::BTreeSet,
+    fmt::Write as _,
+    io::Read,
+    process::ExitCode,
+};
+
+use soupy::{
+    parser::HTMLNode,
+    prelude::*,
+    query::QueryItem,
+    selector::Selector,
+};
+
+const USAGE: &str = "Usage: soupy <SELECTOR> [--text | --attr <NAME>] [--json] < page.html
+
+Prints every element matching the CSS selector.
+Supports compound selectors joined by descendant (` `) and child (`>`) combinators,
+and selector lists separated by `,`.
+
+Options:
+  --text         Print the text content of each match
+  --attr <NAME>  Print the value of an attribute of each match
+  --json         Print a JSON array instead of one match per line
+  -h, --help     Print this help";
+
+type Element = HTMLNode<html5ever::tendril::StrTendril>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+enum Output {
+    Html,
+    Text,
+    Attr(String),
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("soupy: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut selector = None;
+    let mut output = Output::Html;
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            "--text" => output = Output::Text,
+            "--attr" => output = Output::Attr(args.next().ok_or("--attr requires a name")?),
+            "--json" => json = true,
+            _ if selector.is_none() && !arg.starts_with("--") => selector = Some(arg),
+            _ => return Err(format!("unexpected argument '{arg}'\n\n{USAGE}")),
+        }
+    }
+
+    let selector = selector.ok_or(USAGE)?;
+    let groups = parse(&selector)?;
+
+    let mut html = String::new();
+    std::io::stdin()
+        .read_to_string(&mut html)
+        .map_err(|e| e.to_string())?;
+
+    let soup = Soup::html(html);
+
+    let values = select(&soup, &groups)
+        .into_iter()
+        .filter_map(|item| match &output {
+            Output::Html => Some(item.to_string()),
+            Output::Text => Some(item.all_text()),
+            Output::Attr(name) => item.get(name.as_str()).map(ToString::to_string),
+        })
+        .collect::<Vec<_>>();
+
+    if json {
+        let items = values.iter().map(|v| quote(v)).collect::<Vec<_>>();
+        println!("[{}]", items.join(","));
+    } else {
+        for value in values {
+            println!("{value}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a selector list into groups of compound selectors and the combinators before them
+fn parse(selector: &str) -> Result<Vec<Vec<(Combinator, Selector)>>, String> {
+    split(selector, ',')
+        .into_iter()
+        .map(|group| {
+            let mut compounds = Vec::new();
+            let mut combinator = Combinator::Descendant;
+
+            for part in split(&group, ' ') {
+                if part == ">" {
+                    combinator = Combinator::Child;
+                } else if !part.is_empty() {
+                    let compound = Selector::parse_isolated(&part)
+                        .map_err(|e| format!("invalid selector '{part}': {e}"))?;
+
+                    compounds.push((combinator, compound));
+                    combinator = Combinator::Descendant;
+                }
+            }
+
+            if compounds.is_empty() {
+                Err(format!("empty selector in '{selector}'"))
+            } else {
+                Ok(compounds)
+            }
+        })
+        .collect()
+}
+
+/// Splits on `sep`, ignoring separators inside brackets or quotes
+///
+/// When splitting on whitespace, `>` is also split out on its own.
+fn split(s: &str, sep: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut depth = 0usize;
+    let mut quote = None;
+
+    for c in s.chars() {
+        match (c, quote) {
+            (q, Some(open)) if q == open => quote = None,
+            (_, Some(_)) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            ('[' | '(', None) => depth += 1,
+            (']' | ')', None) => depth = depth.saturating_sub(1),
+            (c, None) if depth == 0 && (c == sep || (sep == ' ' && c.is_whitespace())) => {
+                parts.push(String::new());
+                continue;
+            }
+            // The child combinator is a part of its own
+            ('>', None) if depth == 0 && sep == ' ' => {
+                parts.extend([">".to_string(), String::new()]);
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(last) = parts.last_mut() {
+            last.push(c);
+        }
+    }
+
+    parts.into_iter().map(|p| p.trim().to_string()).collect()
+}
+
+/// Runs each group of selectors, returning matches in the order found without duplicates
+fn select<'x>(
+    soup: &'x Soup<Element>,
+    groups: &[Vec<(Combinator, Selector)>],
+) -> Vec<QueryItem<'x, Element>> {
+    let mut seen = BTreeSet::new();
+    let mut results = Vec::new();
+
+    for group in groups {
+        let mut current = Vec::new();
+
+        for (i, (combinator, compound)) in group.iter().enumerate() {
+            current = if i == 0 {
+                soup.filter(compound.clone()).all().collect()
+            } else {
+                current
+                    .iter()
+                    .flat_map(|item| {
+                        let view = item.query_ref();
+
+                        match combinator {
+                            Combinator::Descendant => view.recursive(),
+                            Combinator::Child => view.strict(),
+                        }
+                        .filter(compound.clone())
+                        .all()
+                    })
+                    .collect()
+            };
+        }
+
+        for item in current {
+            if seen.insert(std::ptr::from_ref::<Element>(&item)) {
+                results.push(item);
+            }
+        }
+    }
+
+    results
+}
+
+/// Quotes a string as a JSON string literal
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(selector: &str, html: &str) -> Vec<String> {
+        let soup = Soup::html(html);
+        let groups = parse(selector).expect("Failed to parse selector");
+
+        select(&soup, &groups)
+            .into_iter()
+            .map(|item| item.all_text())
+            .collect()
+    }
+
+    #[test]
+    fn test_combinators() {
+        let html = r#"<div class="article"><p><a>1</a></p><a href="x">2</a></div><a>3</a><div><a>4</a></div>"#;
+
+        assert_eq!(run("div.article a", html), ["1", "2"]);
+        assert_eq!(run("div.article>a", html), ["2"]);
+        assert_eq!(run("div > a, a[href]", html), ["2", "4"]);
+        assert_eq!(run("div a", html), ["1", "2", "4"]);
+        assert_eq!(run(r#"a[href="x"]"#, html), ["2"]);
+        assert_eq!(run(r#"a[title="a > b"]"#, r#"<a title="a > b">5</a>"#), [
+            "5"
+        ]);
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(r#"a[title="x, y"], b"#, ','), [
+            r#"a[title="x, y"]"#,
+            "b"
+        ]);
+        assert!(parse("div >").is_ok());
+        assert!(parse(",").is_err());
+        assert!(parse("[").is_err());
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("a\"b\\\n\u{1}é"), r#""a\"b\\\n\u0001é""#);
+    }
+}
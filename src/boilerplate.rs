@@ -0,0 +1,220 @@
+use alloc::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+use alloc::vec::Vec;
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+use crate::parser::HTMLNode;
+use crate::{
+    fingerprint::{
+        self,
+        HashOptions,
+    },
+    Node,
+    Soup,
+};
+
+/// Options for [`Boilerplate::detect`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoilerplateOptions {
+    /// Number of documents a subtree must appear in to count as boilerplate
+    pub min_documents: usize,
+
+    /// How subtrees are compared
+    ///
+    /// Including text and attribute values, the default, only matches regions that are
+    /// repeated exactly.
+    pub hash: HashOptions,
+}
+
+impl Default for BoilerplateOptions {
+    fn default() -> Self {
+        Self {
+            min_documents: 2,
+            hash: HashOptions::default(),
+        }
+    }
+}
+
+/// Regions repeated across several documents from the same site, like navigation and footers
+///
+/// # Example
+/// ```rust
+/// # use soupy::{boilerplate::{Boilerplate, BoilerplateOptions}, prelude::*};
+/// let pages = [
+///     "<nav><a href=\"/\">Home</a></nav><main>First article</main><footer>(c) Site</footer>",
+///     "<nav><a href=\"/\">Home</a></nav><main>Second article</main><footer>(c) Site</footer>",
+/// ]
+/// .map(|p| Soup::html_strict(p).unwrap());
+///
+/// let boilerplate = Boilerplate::detect(&pages, &BoilerplateOptions::default());
+///
+/// let content = boilerplate.mask(&pages[0]);
+/// assert_eq!(content.all().map(|n| n.all_text()).next(), Some("First article".into()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Boilerplate {
+    hashes: BTreeSet<u64>,
+    options: HashOptions,
+}
+
+impl Boilerplate {
+    /// Finds the element subtrees that appear in at least `options.min_documents` of `soups`
+    #[must_use]
+    pub fn detect<'a, N, I>(soups: I, options: &BoilerplateOptions) -> Self
+    where
+        N: Node + 'a,
+        N::Text: AsRef<str>,
+        I: IntoIterator<Item = &'a Soup<N>>,
+    {
+        let mut counts = BTreeMap::<u64, usize>::new();
+
+        for soup in soups {
+            let hashes = soup
+                .iter()
+                .filter(|n| n.name().is_some())
+                .map(|n| fingerprint::hash([n.node()], options.hash))
+                .collect::<BTreeSet<_>>();
+
+            for hash in hashes {
+                *counts.entry(hash).or_default() += 1;
+            }
+        }
+
+        Self {
+            hashes: counts
+                .into_iter()
+                .filter(|(_, count)| *count >= options.min_documents)
+                .map(|(hash, _)| hash)
+                .collect(),
+            options: options.hash,
+        }
+    }
+
+    /// Number of distinct boilerplate subtrees
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns `true` if no boilerplate was found
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Returns `true` if `node` is an element whose subtree is boilerplate
+    ///
+    /// Text directly inside boilerplate elements is not boilerplate by itself, so use
+    /// [`Boilerplate::mask`] to remove whole regions.
+    #[must_use]
+    pub fn contains<N>(&self, node: &N) -> bool
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        node.name().is_some()
+            && self
+                .hashes
+                .contains(&fingerprint::hash([node], self.options))
+    }
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl Boilerplate {
+    /// Returns a copy of `soup` with every boilerplate subtree removed
+    #[must_use]
+    pub fn mask<S>(&self, soup: &Soup<HTMLNode<S>>) -> Soup<HTMLNode<S>>
+    where
+        S: AsRef<str> + Clone,
+    {
+        Soup::from_nodes(self.mask_nodes(&soup.nodes))
+    }
+
+    fn mask_nodes<S>(&self, nodes: &[HTMLNode<S>]) -> Vec<HTMLNode<S>>
+    where
+        S: AsRef<str> + Clone,
+    {
+        nodes
+            .iter()
+            .filter(|n| !self.contains(*n))
+            .map(|n| match n {
+                HTMLNode::Element {
+                    name,
+                    attrs,
+                    children,
+                } => HTMLNode::Element {
+                    name: name.clone(),
+                    attrs: attrs.clone(),
+                    children: self.mask_nodes(children),
+                },
+                n => n.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_boilerplate() {
+        let page = |title, body| {
+            alloc::format!(
+                r#"<html><head><title>{title}</title></head><body>
+                    <nav><ul><li><a href="/">Home</a></li><li><a href="/about">About</a></li></ul></nav>
+                    <article><h1>{title}</h1><p>{body}</p><p>Share this</p></article>
+                    <footer><p>Copyright</p></footer>
+                </body></html>"#
+            )
+        };
+
+        let pages = [
+            page("One", "First"),
+            page("Two", "Second"),
+            page("Three", "Third"),
+        ];
+        let soups = pages
+            .iter()
+            .map(|p| Soup::html_strict(p).expect("Failed to parse HTML"))
+            .collect::<Vec<_>>();
+
+        let boilerplate = Boilerplate::detect(&soups, &BoilerplateOptions::default());
+        let nav = soups[1].tag("nav").first().expect("Couldn't find nav");
+        assert!(boilerplate.contains(&*nav));
+        assert!(!boilerplate.contains(&*soups[1].tag("h1").first().expect("Couldn't find h1")));
+
+        let masked = boilerplate.mask(&soups[1]);
+        assert!(masked.tag("nav").first().is_none());
+        assert!(masked.tag("footer").first().is_none());
+
+        let text = masked
+            .tag("article")
+            .first()
+            .map(|a| a.all_text())
+            .expect("Couldn't find article");
+        assert_eq!(text, "Two\nSecond");
+
+        let options = BoilerplateOptions {
+            min_documents: 4,
+            ..BoilerplateOptions::default()
+        };
+        assert!(Boilerplate::detect(&soups, &options).is_empty());
+    }
+}
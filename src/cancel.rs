@@ -0,0 +1,102 @@
+use alloc::sync::Arc;
+use core::{
+    fmt,
+    sync::atomic::{
+        AtomicBool,
+        Ordering,
+    },
+};
+
+/// A flag for stopping a parse or query from another thread or task
+///
+/// Clones share the same flag. Operations given a token check it regularly, and stop soon after
+/// [`CancelToken::cancel`] is called.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{cancel::CancelToken, prelude::*};
+/// # #[cfg(feature = "html-loose")] {
+/// let token = CancelToken::new();
+/// let html = "<p>Text</p>".repeat(1000);
+///
+/// let soup = Soup::html_loose_cancellable(&html, &token).unwrap();
+/// let mut paragraphs = soup.tag("p").all().cancel_on(&token);
+/// assert!(paragraphs.next().is_some());
+///
+/// token.clone().cancel();
+/// assert!(paragraphs.next().is_none());
+/// assert!(Soup::html_loose_cancellable(&html, &token).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a token that has not been cancelled
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every operation using this token, or a clone of it, to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if [`CancelToken::cancel`] has been called
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Error returned by operations stopped with a [`CancelToken`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Cancelled {}
+
+#[cfg(all(test, feature = "html-loose"))]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{
+        Queryable,
+        Soup,
+    };
+
+    #[test]
+    fn test_cancel() {
+        let html = "<ul><li>1</li><li>2</li></ul>".repeat(10);
+        let soup = Soup::html_loose(&html);
+        let token = CancelToken::new();
+
+        let visited = soup.tag("li").all().cancel_on(&token).fold(0, |n, _| {
+            if n == 2 {
+                token.cancel();
+            }
+            n + 1
+        });
+        assert_eq!(visited, 3);
+        assert!(token.is_cancelled());
+
+        let cancelled = CancelToken::new();
+        cancelled.cancel();
+        assert_eq!(soup.tag("li").all().cancel_on(&cancelled).count(), 0);
+        assert_eq!(
+            crate::parser::LooseHTMLParser::parse_cancellable(&html, &cancelled),
+            Err(Cancelled)
+        );
+        assert_eq!(Cancelled.to_string(), "operation cancelled");
+    }
+}
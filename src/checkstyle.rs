@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    parser::XMLNode,
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// Severity of a [`Diagnostic`], ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// `ignore`
+    Ignore,
+    /// `info`
+    Info,
+    /// `warning`
+    Warning,
+    /// `error`
+    Error,
+}
+
+impl Severity {
+    /// Parses a severity name, ignoring ASCII case
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        [
+            ("ignore", Self::Ignore),
+            ("info", Self::Info),
+            ("warning", Self::Warning),
+            ("error", Self::Error),
+        ]
+        .into_iter()
+        .find_map(|(name, severity)| s.trim().eq_ignore_ascii_case(name).then_some(severity))
+    }
+}
+
+/// An `error` element, reported by a tool against a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// `name` of the enclosing `file`
+    pub file: String,
+    /// `line`, starting at 1
+    pub line: Option<u32>,
+    /// `column`, starting at 1
+    pub column: Option<u32>,
+    /// `severity`, which is [`Severity::Error`] if missing or unrecognized, as in Checkstyle
+    pub severity: Severity,
+    /// `message`
+    pub message: String,
+    /// `source`, usually the rule that was broken, like `eslint.rules.semi`
+    pub source: Option<String>,
+}
+
+/// A Checkstyle-format XML report, as written by Checkstyle, `ESLint`, `PHP_CodeSniffer`, and
+/// many other linters
+///
+/// # Example
+/// ```rust
+/// # use soupy::checkstyle::{Report, Severity};
+/// let report = Report::parse(r#"<checkstyle version="4.3">
+///     <file name="src/app.js">
+///         <error line="3" column="14" severity="warning" message="Missing semicolon." source="eslint.rules.semi"/>
+///         <error line="9" severity="error" message="'x' is not defined."/>
+///     </file>
+///     <file name="src/util.js"/>
+/// </checkstyle>"#).unwrap();
+///
+/// assert_eq!(report.diagnostics.len(), 2);
+/// assert_eq!(report.diagnostics[0].column, Some(14));
+/// assert_eq!(report.at_least(Severity::Error).map(|d| d.line).collect::<Vec<_>>(), [Some(9)]);
+/// assert_eq!(report.files, ["src/app.js", "src/util.js"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The `version` attribute of the root
+    pub version: Option<String>,
+    /// Names of every checked `file`, in order, including those without diagnostics
+    pub files: Vec<String>,
+    /// Diagnostics, in order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    /// Reads a parsed report
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let mut report = Self {
+            version: soup
+                .tag("checkstyle")
+                .first()
+                .and_then(|c| c.get("version").cloned()),
+            ..Self::default()
+        };
+
+        for file in soup.tag("file").all() {
+            let name = file.get("name").cloned().unwrap_or_default();
+
+            report.diagnostics.extend(
+                file.children()
+                    .iter()
+                    .filter(|e| e.name().is_some_and(|n| n == "error"))
+                    .map(|e| {
+                        let number = |name| e.get(name).and_then(|n| n.trim().parse().ok());
+
+                        Diagnostic {
+                            file: name.clone(),
+                            line: number("line"),
+                            column: number("column"),
+                            severity: e
+                                .get("severity")
+                                .and_then(|s| Severity::parse(s))
+                                .unwrap_or(Severity::Error),
+                            message: e.get("message").cloned().unwrap_or_default(),
+                            source: e.get("source").cloned(),
+                        }
+                    }),
+            );
+
+            report.files.push(name);
+        }
+
+        report
+    }
+
+    /// Parses a report
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Diagnostics at `severity` or above, in order
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |d| d.severity >= severity)
+    }
+
+    /// Diagnostics grouped by file name, each group in order
+    #[must_use]
+    pub fn by_file(&self) -> BTreeMap<&str, Vec<&Diagnostic>> {
+        let mut files = BTreeMap::<_, Vec<_>>::new();
+
+        for diagnostic in &self.diagnostics {
+            files
+                .entry(diagnostic.file.as_str())
+                .or_default()
+                .push(diagnostic);
+        }
+
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let report = Report::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <checkstyle>
+                <file name="a.php">
+                    <error line=" 12 " column="x" severity="INFO" message="Line too long"/>
+                    <error message="No severity"/>
+                </file>
+                <file name="b.php">
+                    <error line="1" severity="fatal" message="Unknown severity" source="Generic.Files"/>
+                </file>
+            </checkstyle>"#,
+        )
+        .expect("Failed to parse report");
+
+        assert_eq!(report.version, None);
+        assert_eq!(report.diagnostics[0], Diagnostic {
+            file: "a.php".into(),
+            line: Some(12),
+            column: None,
+            severity: Severity::Info,
+            message: "Line too long".into(),
+            source: None,
+        });
+        assert_eq!(
+            report
+                .diagnostics
+                .iter()
+                .map(|d| d.severity)
+                .collect::<Vec<_>>(),
+            [Severity::Info, Severity::Error, Severity::Error]
+        );
+        assert_eq!(report.at_least(Severity::Warning).count(), 2);
+
+        let files = report.by_file();
+        assert_eq!(files["a.php"].len(), 2);
+        assert_eq!(files["b.php"][0].source.as_deref(), Some("Generic.Files"));
+        assert!(Severity::Warning < Severity::Error);
+    }
+}
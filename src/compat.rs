@@ -0,0 +1,221 @@
+use alloc::vec::Vec;
+
+use crate::{
+    filter::Filter,
+    query::QueryItem,
+    Node,
+    Queryable,
+};
+
+/// Attributes that `BeautifulSoup` treats as whitespace-separated lists
+const MULTI_VALUED_ATTRIBUTES: [&str; 7] = [
+    "class",
+    "rel",
+    "rev",
+    "accept-charset",
+    "headers",
+    "accesskey",
+    "dropzone",
+];
+
+/// Filter matching the arguments of `BeautifulSoup`'s `find_all`
+///
+/// - `name` matches the tag name exactly.
+/// - Each of `attrs` must be present. For list attributes like `class`, the value may match
+///   any single entry, as well as the whole attribute.
+/// - `string` matches elements whose only text content is `string`. Without `name` or `attrs`,
+///   it matches the text nodes themselves instead.
+///
+/// Without any arguments, every element matches.
+#[derive(Debug, Clone, Default)]
+pub struct Find<'a> {
+    /// Tag name
+    pub name: Option<&'a str>,
+
+    /// Attribute name/value pairs that must all be present
+    pub attrs: Vec<(&'a str, &'a str)>,
+
+    /// Text content
+    pub string: Option<&'a str>,
+}
+
+impl<N> Filter<N> for Find<'_>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    fn matches(&self, node: &N) -> bool {
+        if self.name.is_none() && self.attrs.is_empty() {
+            return match self.string {
+                Some(string) => node.text().is_some_and(|t| t.as_ref() == string),
+                None => node.name().is_some(),
+            };
+        }
+
+        let Some(name) = node.name() else {
+            return false;
+        };
+
+        if self.name.is_some_and(|n| n != name.as_ref()) {
+            return false;
+        }
+
+        let attrs_match = self.attrs.iter().all(|(key, value)| {
+            node.attrs()
+                .into_iter()
+                .flatten()
+                .find(|(k, _)| k.as_ref() == *key)
+                .is_some_and(|(_, v)| {
+                    let v = v.as_ref();
+
+                    v == *value
+                        || (MULTI_VALUED_ATTRIBUTES.contains(key)
+                            && v.split_ascii_whitespace().any(|v| v == *value))
+                })
+        });
+
+        attrs_match && self.string.is_none_or(|s| string(node) == Some(s))
+    }
+}
+
+/// `BeautifulSoup`'s `.string`: the text of a node, or of its only child, recursively
+fn string<N>(node: &N) -> Option<&str>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    if let Some(text) = node.text() {
+        return Some(text.as_ref());
+    }
+
+    match node.children() {
+        [child] => string(child),
+        _ => None,
+    }
+}
+
+/// `BeautifulSoup`-style `find` and `find_all`, to ease porting scrapers
+///
+/// Available on everything that is [`Queryable`].
+///
+/// # Example
+/// ```rust
+/// use soupy::{compat::BeautifulSoup, prelude::*};
+///
+/// let soup = Soup::html_strict(
+///     r#"<div class="card featured"><a href="/a">A</a></div><div class="card"><a href="/b">B</a></div>"#,
+/// )
+/// .unwrap();
+///
+/// // soup.find("div", {"class": "featured"})
+/// let featured = soup.find(Some("div"), [("class", "featured")], true, None).unwrap();
+/// assert_eq!(featured.all_text(), "A");
+///
+/// // soup.find_all("a", limit=1)
+/// let links = soup.find_all(Some("a"), [], true, None, Some(1));
+/// assert_eq!(links.len(), 1);
+///
+/// // soup.find_all(string="B")
+/// assert_eq!(soup.find_all(None, [], true, Some("B"), None).len(), 1);
+/// ```
+pub trait BeautifulSoup<'x>: Queryable<'x>
+where
+    Self::Node: 'x,
+    <Self::Node as Node>::Text: AsRef<str>,
+{
+    /// Returns the first match, like `find(name, attrs, recursive, string)`
+    fn find<'a, A>(
+        self,
+        name: Option<&'a str>,
+        attrs: A,
+        recursive: bool,
+        string: Option<&'a str>,
+    ) -> Option<QueryItem<'x, Self::Node>>
+    where
+        A: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        self.find_all(name, attrs, recursive, string, Some(1))
+            .into_iter()
+            .next()
+    }
+
+    /// Returns all matches, like `find_all(name, attrs, recursive, string, limit)`
+    ///
+    /// A `limit` of `None` returns every match.
+    fn find_all<'a, A>(
+        self,
+        name: Option<&'a str>,
+        attrs: A,
+        recursive: bool,
+        string: Option<&'a str>,
+        limit: Option<usize>,
+    ) -> Vec<QueryItem<'x, Self::Node>>
+    where
+        A: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let query = if recursive {
+            self.recursive()
+        } else {
+            self.strict()
+        };
+
+        let find = Find {
+            name,
+            attrs: attrs.into_iter().collect(),
+            string,
+        };
+
+        query
+            .filter(find)
+            .into_iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+impl<'x, Q> BeautifulSoup<'x> for Q
+where
+    Q: Queryable<'x>,
+    Q::Node: 'x,
+    <Q::Node as Node>::Text: AsRef<str>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use super::*;
+    use crate::*;
+
+    const HTML: &str = r#"<p id="first" class="note">One</p><div><p class="note big"><b>Two</b></p><p>Three</p></div>"#;
+
+    #[test]
+    fn test_find_all() {
+        let soup = Soup::html_strict(HTML).expect("Failed to parse HTML");
+
+        let notes = soup.find_all(Some("p"), [("class", "note")], true, None, None);
+        assert_eq!(notes.len(), 2);
+
+        let attrs = BTreeMap::from([("class", "note big")]);
+        let big = soup.find(Some("p"), attrs, true, None);
+        assert_eq!(big.map(|p| p.all_text()), Some("Two".into()));
+
+        let top = soup.find_all(Some("p"), [], false, None, None);
+        assert_eq!(top.len(), 1);
+
+        let two = soup.find(Some("p"), [], true, Some("Two"));
+        assert_eq!(two.and_then(|p| p.get("class").copied()), Some("note big"));
+
+        assert_eq!(soup.find_all(None, [], true, None, None).len(), 5);
+        assert_eq!(
+            soup.find_all(None, [("id", "first")], true, None, None)
+                .len(),
+            1
+        );
+        assert_eq!(soup.find_all(None, [], true, Some("Three"), None).len(), 1);
+        assert!(soup
+            .find(Some("p"), [("class", "big note")], true, None)
+            .is_none());
+    }
+}
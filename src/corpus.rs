@@ -0,0 +1,158 @@
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+};
+
+use crate::{
+    query::QueryItem,
+    Node,
+    Soup,
+};
+
+/// A collection of named [`Soup`]s that can be queried together
+///
+/// # Example
+/// ```rust
+/// # use soupy::prelude::*;
+/// let mut corpus = Corpus::new();
+/// corpus.insert("index.html", Soup::html_strict(r#"<link rel="canonical" href="/">"#).unwrap());
+/// corpus.insert("about.html", Soup::html_strict(r#"<link rel="canonical">"#).unwrap());
+/// corpus.insert("blog.html", Soup::html_strict(r#"<p>No canonical</p>"#).unwrap());
+///
+/// let broken = corpus
+///     .query(|soup| soup.tag("link").attr("rel", "canonical"))
+///     .filter(|(_, link)| link.get("href").is_none())
+///     .map(|(name, _)| name)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(broken, ["about.html"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Corpus<N> {
+    documents: BTreeMap<String, Soup<N>>,
+}
+
+impl<N> Default for Corpus<N> {
+    fn default() -> Self {
+        Self {
+            documents: BTreeMap::new(),
+        }
+    }
+}
+
+impl<N> Corpus<N> {
+    /// Creates an empty `Corpus`
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a document, returning the previous document with the same name, if any
+    pub fn insert<S>(&mut self, name: S, soup: Soup<N>) -> Option<Soup<N>>
+    where
+        S: Into<String>,
+    {
+        self.documents.insert(name.into(), soup)
+    }
+
+    /// Removes a document by name
+    pub fn remove(&mut self, name: &str) -> Option<Soup<N>> {
+        self.documents.remove(name)
+    }
+
+    /// Returns the document with the given name
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Soup<N>> {
+        self.documents.get(name)
+    }
+
+    /// Returns the number of documents
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Returns `true` if the `Corpus` contains no documents
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Iterates over the documents and their names, ordered by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Soup<N>)> {
+        self.documents.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+impl<N> Corpus<N>
+where
+    N: Node,
+{
+    /// Runs the query built by `query` against every document,
+    /// yielding each match along with the name of the document it was found in
+    pub fn query<'x, F, Q>(&'x self, query: F) -> impl Iterator<Item = (&'x str, QueryItem<'x, N>)>
+    where
+        F: Fn(&'x Soup<N>) -> Q + 'x,
+        Q: IntoIterator<Item = QueryItem<'x, N>>,
+    {
+        self.iter()
+            .flat_map(move |(name, soup)| query(soup).into_iter().map(move |item| (name, item)))
+    }
+
+    /// Returns the names of the documents for which the query built by `query` has any matches
+    pub fn documents<'x, F, Q>(&'x self, query: F) -> impl Iterator<Item = &'x str>
+    where
+        F: Fn(&'x Soup<N>) -> Q + 'x,
+        Q: IntoIterator<Item = QueryItem<'x, N>>,
+    {
+        self.iter()
+            .filter(move |(_, soup)| query(soup).into_iter().next().is_some())
+            .map(|(name, _)| name)
+    }
+}
+
+impl<N, S> FromIterator<(S, Soup<N>)> for Corpus<N>
+where
+    S: Into<String>,
+{
+    fn from_iter<T: IntoIterator<Item = (S, Soup<N>)>>(iter: T) -> Self {
+        Self {
+            documents: iter.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::*;
+
+    #[test]
+    fn test_corpus_query() {
+        let corpus = [
+            ("b", "<a href='/1'>One</a><a href='/2'>Two</a>"),
+            ("a", "<p><a href='/3'>Three</a></p>"),
+            ("c", "<p>Nothing</p>"),
+        ]
+        .into_iter()
+        .map(|(name, html)| (name, Soup::html_strict(html).expect("Failed to parse HTML")))
+        .collect::<Corpus<_>>();
+
+        let links = corpus
+            .query(|soup| soup.tag("a"))
+            .map(|(name, a)| (name, a.get("href").copied()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(links, [
+            ("a", Some("/3")),
+            ("b", Some("/1")),
+            ("b", Some("/2"))
+        ]);
+
+        assert_eq!(
+            corpus.documents(|soup| soup.tag("p")).collect::<Vec<_>>(),
+            ["a", "c"]
+        );
+    }
+}
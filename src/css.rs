@@ -0,0 +1,596 @@
+use alloc::{
+    collections::BTreeMap,
+    string::{
+        String,
+        ToString,
+    },
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    query::{
+        NodeId,
+        QueryItem,
+    },
+    selector::Selector,
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// Conditional group rules, whose rules are read into [`StyleRule::conditions`]
+const GROUP_RULES: &[&str] = &[
+    "@media",
+    "@supports",
+    "@container",
+    "@layer",
+    "@document",
+    "@-moz-document",
+];
+
+/// Group rules nested deeper than this are skipped
+const MAX_DEPTH: usize = 8;
+
+/// Orders declarations by `!important`, then inline, then specificity, then source order
+pub(crate) type Key = (bool, bool, (u32, u32, u32), usize);
+
+/// A CSS declaration, like `color: red !important`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    /// Name of the property, in lowercase
+    pub property: String,
+    /// Value, without `!important`
+    pub value: String,
+    /// `true` if the declaration is `!important`
+    pub important: bool,
+}
+
+impl Declaration {
+    /// Parses the declarations of a block or `style` attribute, like `color: red; margin: 0`
+    ///
+    /// Comments and malformed declarations are skipped.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::css::Declaration;
+    /// let declarations = Declaration::parse_list("COLOR: red !important; /* note */ background: url(a;b.png);;");
+    /// assert_eq!(declarations.len(), 2);
+    /// assert_eq!(declarations[0].to_string(), "color: red !important");
+    /// assert_eq!(declarations[1].value, "url(a;b.png)");
+    /// ```
+    #[must_use]
+    pub fn parse_list(block: &str) -> Vec<Self> {
+        split_top(&strip_comments(block), ';')
+            .into_iter()
+            .filter_map(|d| {
+                let (property, value) = d.split_once(':')?;
+                let property = property.trim().to_ascii_lowercase();
+                let mut value = value.trim();
+
+                let important = match value.rfind('!') {
+                    Some(i) if value[i + 1..].trim().eq_ignore_ascii_case("important") => {
+                        value = value[..i].trim_end();
+                        true
+                    }
+                    _ => false,
+                };
+
+                (!property.is_empty() && !value.is_empty()).then(|| Self {
+                    property,
+                    value: value.to_string(),
+                    important,
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Declaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.property, self.value)?;
+
+        if self.important {
+            f.write_str(" !important")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A style rule with a single selector
+///
+/// Rules with a selector list, like `h1, h2 { ... }`, are read as one rule per selector, so
+/// each has its own specificity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleRule {
+    /// The selector as written, like `ul > li.active`
+    pub selector: String,
+    /// Declarations, in order
+    pub declarations: Vec<Declaration>,
+    /// Preludes of the enclosing group rules, outermost first, like `@media (max-width: 600px)`
+    pub conditions: Vec<String>,
+    /// The parsed selector, or `None` if it isn't supported, like `a:hover`
+    parsed: Option<Selector>,
+}
+
+impl StyleRule {
+    /// Specificity of the selector, or `None` if it isn't supported
+    #[must_use]
+    pub fn specificity(&self) -> Option<(u32, u32, u32)> {
+        self.parsed.as_ref().map(Selector::specificity)
+    }
+
+    /// Returns `true` if the selector is supported and matches the node `id` of `soup`
+    ///
+    /// [`conditions`](Self::conditions) are not checked.
+    #[must_use]
+    pub fn matches<N>(&self, soup: &Soup<N>, id: NodeId) -> bool
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        self.parsed
+            .as_ref()
+            .is_some_and(|s| s.select(soup.filter(())).into_iter().any(|i| i.id() == id))
+    }
+}
+
+/// The style rules of a style sheet
+///
+/// At-rules like `@font-face` and `@import` are skipped, and the rules inside conditional
+/// group rules like `@media` are kept with their [`conditions`](StyleRule::conditions).
+///
+/// # Example
+/// ```rust
+/// # use soupy::{css::StyleSheet, prelude::*};
+/// let soup = Soup::html_strict(r#"<style>
+///     p { color: black; display: block }
+///     .hidden { display: none }
+///     @media print { p { color: gray } }
+/// </style>
+/// <p class="hidden" style="color: red">Secret</p>"#).unwrap();
+///
+/// let sheet = StyleSheet::from_html(&soup);
+/// let p = soup.tag("p").first().unwrap().id();
+///
+/// let style = sheet.computed(&soup, p);
+/// assert_eq!(style.get("display").map(String::as_str), Some("none"));
+/// assert_eq!(style.get("color").map(String::as_str), Some("red"));
+///
+/// let selectors = sheet.matching(&soup, p).iter().map(|r| r.selector.as_str()).collect::<Vec<_>>();
+/// assert_eq!(selectors, ["p", ".hidden"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyleSheet {
+    /// Style rules, in source order
+    pub rules: Vec<StyleRule>,
+}
+
+impl StyleSheet {
+    /// Parses a style sheet
+    #[must_use]
+    pub fn parse(css: &str) -> Self {
+        let mut sheet = Self::default();
+        sheet.extend(css);
+        sheet
+    }
+
+    /// Reads the `<style>` elements of an HTML document, in document order
+    #[cfg(any(
+        feature = "html-lenient",
+        feature = "html-loose",
+        feature = "html-strict"
+    ))]
+    #[must_use]
+    pub fn from_html<S>(soup: &Soup<crate::parser::HTMLNode<S>>) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let mut sheet = Self::default();
+
+        for item in soup {
+            if let Some(css) = style_content(item.node()) {
+                sheet.extend(&css);
+            }
+        }
+
+        sheet
+    }
+
+    fn extend(&mut self, css: &str) {
+        self.read(&strip_comments(css), &mut Vec::new());
+    }
+
+    fn read(&mut self, css: &str, conditions: &mut Vec<String>) {
+        for rule in raw_rules(css) {
+            match rule {
+                RawRule::Style(selectors, block) => {
+                    let declarations = Declaration::parse_list(block);
+
+                    self.rules
+                        .extend(split_top(selectors, ',').into_iter().map(|s| StyleRule {
+                            selector: s.trim().to_string(),
+                            declarations: declarations.clone(),
+                            conditions: conditions.clone(),
+                            parsed: Selector::parse(s).ok(),
+                        }));
+                }
+                RawRule::At(prelude, Some(block))
+                    if conditions.len() < MAX_DEPTH && is_group(prelude) =>
+                {
+                    conditions.push(prelude.to_string());
+                    self.read(block, conditions);
+                    conditions.pop();
+                }
+                RawRule::At(..) => {}
+            }
+        }
+    }
+
+    /// Unconditional rules that match the node `id` of `soup`, from lowest to highest
+    /// precedence
+    ///
+    /// Rules are ordered by specificity, then source order. Rules inside group rules like
+    /// `@media` are skipped, as their conditions can't be evaluated without a browser.
+    #[must_use]
+    pub fn matching<N>(&self, soup: &Soup<N>, id: NodeId) -> Vec<&StyleRule>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let mut rules = self
+            .rules
+            .iter()
+            .filter(|r| r.conditions.is_empty() && r.matches(soup, id))
+            .collect::<Vec<_>>();
+
+        rules.sort_by_key(|r| r.specificity());
+        rules
+    }
+
+    /// The value of each property set on the node `id` of `soup`, by the matching rules and
+    /// its `style` attribute
+    ///
+    /// Follows the cascade: `!important` declarations win, then the `style` attribute, then
+    /// rules by specificity and source order. Inherited values and defaults are not included.
+    #[must_use]
+    pub fn computed<N>(&self, soup: &Soup<N>, id: NodeId) -> BTreeMap<String, String>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let mut declarations = Vec::new();
+
+        for rule in self.matching(soup, id) {
+            let specificity = rule.specificity().unwrap_or_default();
+            let order = declarations.len();
+
+            declarations.extend(
+                rule.declarations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| ((d.important, false, specificity, order + i), d.clone())),
+            );
+        }
+
+        if let Some(style) = soup.get(id).and_then(|n| n.node().get_str("style")) {
+            declarations.extend(
+                Declaration::parse_list(style)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, d)| ((d.important, true, (0, 0, 0), i), d)),
+            );
+        }
+
+        cascade(declarations)
+            .into_iter()
+            .map(|d| (d.property, d.value))
+            .collect()
+    }
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Returns `true` if the item is probably not shown, going by its markup alone
+    ///
+    /// The item counts as hidden if it or an element containing it has the `hidden` attribute,
+    /// `aria-hidden="true"` or an inline `display: none`, or the nearest inline `visibility` is
+    /// `hidden` or `collapse`. Elements with a `width` or `height` attribute of zero, and
+    /// `<input type="hidden">`, are hidden too. Style sheets are not applied; see
+    /// [`StyleSheet::computed`] for that.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<div style="display: none"><a>One</a></div>
+    ///     <a aria-hidden="true">Two</a><img width="0" height="0"><a>Three</a>"#).unwrap();
+    ///
+    /// let visible = soup.tag("a").all().filter(|a| !a.is_probably_hidden()).map(|a| a.all_text()).collect::<Vec<_>>();
+    /// assert_eq!(visible, ["Three"]);
+    /// assert!(soup.tag("img").first().unwrap().is_probably_hidden());
+    /// ```
+    #[must_use]
+    pub fn is_probably_hidden(&self) -> bool {
+        let mut visible = true;
+
+        for node in self.ancestors().into_iter().chain([self.node()]) {
+            let attr = |name| node.get_str(name).map(str::trim);
+
+            if attr("hidden").is_some()
+                || attr("aria-hidden").is_some_and(|v| v.eq_ignore_ascii_case("true"))
+            {
+                return true;
+            }
+
+            for declaration in attr("style")
+                .map(Declaration::parse_list)
+                .unwrap_or_default()
+            {
+                let value = declaration.value.to_ascii_lowercase();
+
+                match declaration.property.as_str() {
+                    "display" if value == "none" => return true,
+                    "visibility" => visible = value != "hidden" && value != "collapse",
+                    _ => {}
+                }
+            }
+        }
+
+        let attr = |name| self.get_str(name).map(str::trim);
+        let zero = |v: &str| {
+            let v = v.strip_suffix("px").unwrap_or(v);
+            v.contains('0') && v.chars().all(|c| c == '0' || c == '.')
+        };
+
+        !visible
+            || attr("width").is_some_and(zero)
+            || attr("height").is_some_and(zero)
+            || (self
+                .name()
+                .is_some_and(|n| n.as_ref().eq_ignore_ascii_case("input"))
+                && attr("type").is_some_and(|t| t.eq_ignore_ascii_case("hidden")))
+    }
+}
+
+fn is_group(prelude: &str) -> bool {
+    let name = prelude
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or("");
+    GROUP_RULES.iter().any(|g| g.eq_ignore_ascii_case(name))
+}
+
+/// Applies the cascade, returning the winning declaration of each property
+pub(crate) fn cascade(mut declarations: Vec<(Key, Declaration)>) -> Vec<Declaration> {
+    declarations.sort_by_key(|(key, _)| *key);
+
+    let mut winners = Vec::<Declaration>::new();
+
+    for (_, declaration) in declarations {
+        winners.retain(|w| w.property != declaration.property);
+        winners.push(declaration);
+    }
+
+    winners
+}
+
+/// Returns the content of a `<style>` element, which is raw text or text children depending on
+/// the parser
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub(crate) fn style_content<S>(node: &crate::parser::HTMLNode<S>) -> Option<String>
+where
+    S: AsRef<str>,
+{
+    use crate::parser::HTMLNode;
+
+    match node {
+        HTMLNode::RawElement { name, content, .. }
+            if name.as_ref().eq_ignore_ascii_case("style") =>
+        {
+            Some(content.as_ref().to_string())
+        }
+        HTMLNode::Element { name, children, .. } if name.as_ref().eq_ignore_ascii_case("style") => {
+            Some(
+                children
+                    .iter()
+                    .filter_map(|c| c.text())
+                    .map(AsRef::as_ref)
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// A rule of a style sheet, as written
+pub(crate) enum RawRule<'c> {
+    /// `selectors { declarations }`
+    Style(&'c str, &'c str),
+    /// `@name prelude;` or `@name prelude { block }`
+    At(&'c str, Option<&'c str>),
+}
+
+/// Removes comments, and the `<!--` and `-->` that hid style sheets from very old browsers
+pub(crate) fn strip_comments(css: &str) -> String {
+    let mut out = String::new();
+    let mut rest = css;
+
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + end + 4..]);
+    }
+
+    out.push_str(rest);
+    out.replace("<!--", "").replace("-->", "")
+}
+
+/// Finds the first of `targets` outside of strings and brackets
+pub(crate) fn find_top(s: &str, targets: &[char]) -> Option<(usize, char)> {
+    let mut depth = 0_usize;
+    let mut quote = None;
+    let mut chars = s.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            if c == '\\' {
+                chars.next();
+            } else if c == q {
+                quote = None;
+            }
+
+            continue;
+        }
+
+        match c {
+            _ if depth == 0 && targets.contains(&c) => return Some((i, c)),
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Splits at each `separator` outside of strings and brackets
+pub(crate) fn split_top(mut s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+
+    while let Some((i, _)) = find_top(s, &[separator]) {
+        parts.push(&s[..i]);
+        s = &s[i + 1..];
+    }
+
+    parts.push(s);
+    parts
+}
+
+/// Splits a style sheet without comments into rules, skipping anything malformed
+pub(crate) fn raw_rules(mut css: &str) -> Vec<RawRule<'_>> {
+    let mut rules = Vec::new();
+
+    loop {
+        css = css.trim_start();
+
+        let Some((i, c)) = find_top(css, &['{', ';', '}']) else {
+            break;
+        };
+
+        let prelude = css[..i].trim();
+
+        if c != '{' {
+            if c == ';' && prelude.starts_with('@') {
+                rules.push(RawRule::At(prelude, None));
+            }
+
+            css = &css[i + 1..];
+            continue;
+        }
+
+        let body = &css[i + 1..];
+        let (block, rest) = match find_top(body, &['}']) {
+            Some((end, _)) => (&body[..end], &body[end + 1..]),
+            None => (body, ""),
+        };
+
+        rules.push(if prelude.starts_with('@') {
+            RawRule::At(prelude, Some(block))
+        } else {
+            RawRule::Style(prelude, block)
+        });
+
+        css = rest;
+    }
+
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let sheet = StyleSheet::parse(
+            r#"@charset "utf-8";
+            @import url("a.css");
+            h1, h2 > a:hover { color: red; content: "}" }
+            @font-face { font-family: X }
+            @media screen { @supports (display: grid) { #x { display: grid } } }
+            @keyframes spin { from { opacity: 0 } }
+            broken { color: blue"#,
+        );
+
+        let selectors = sheet
+            .rules
+            .iter()
+            .map(|r| (r.selector.as_str(), r.conditions.len(), r.specificity()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(selectors, [
+            ("h1", 0, Some((0, 0, 1))),
+            ("h2 > a:hover", 0, None),
+            ("#x", 2, Some((1, 0, 0))),
+            ("broken", 0, Some((0, 0, 1))),
+        ]);
+        assert_eq!(sheet.rules[0].declarations[1].value, r#""}""#);
+        assert_eq!(sheet.rules[2].conditions, [
+            "@media screen",
+            "@supports (display: grid)"
+        ]);
+    }
+
+    #[test]
+    fn test_computed() {
+        let soup = Soup::html_strict(
+            r#"<div id="main"><p class="a b">One</p><p class="b" style="color: green">Two</p></div>"#,
+        )
+        .expect("Failed to parse HTML");
+
+        let sheet = StyleSheet::parse(
+            "#main .b { color: blue !important } .a.b { color: red; margin: 0 } p { margin: 1px !important } div p { color: black }",
+        );
+
+        let ps = soup.tag("p").all().map(|p| p.id()).collect::<Vec<_>>();
+
+        let first = sheet.computed(&soup, ps[0]);
+        assert_eq!(first["color"], "blue");
+        assert_eq!(first["margin"], "1px");
+
+        let second = sheet.computed(&soup, ps[1]);
+        assert_eq!(second["color"], "blue");
+        assert_eq!(sheet.matching(&soup, ps[1]).len(), 3);
+    }
+
+    #[test]
+    fn test_hidden() {
+        let soup = Soup::html_strict(
+            r#"<div style="visibility: hidden"><p>1</p><p style="VISIBILITY: visible">2</p></div>
+            <section hidden><p>3</p></section><p aria-hidden="false" width="10">4</p>
+            <input type="Hidden"><input><p style="color: red; display: none !important">5</p>"#,
+        )
+        .expect("Failed to parse HTML");
+
+        let hidden = soup
+            .filter(())
+            .all()
+            .filter(|n| n.name().is_some())
+            .map(|n| n.is_probably_hidden())
+            .collect::<Vec<_>>();
+
+        assert_eq!(hidden, [
+            true, true, false, true, true, false, true, false, true
+        ]);
+    }
+}
@@ -0,0 +1,1009 @@
+use alloc::{
+    borrow::ToOwned,
+    collections::BTreeSet,
+    format,
+    string::{
+        String,
+        ToString,
+    },
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use serde::de::{
+    self,
+    DeserializeOwned,
+    DeserializeSeed,
+    IntoDeserializer,
+    MapAccess,
+    SeqAccess,
+    Visitor,
+};
+
+use crate::{
+    Node,
+    Soup,
+};
+
+/// Deserializes a `T` from the HTML document `html`, parsed with [`Soup::html`]
+///
+/// See [`Deserializer`] for how fields are mapped to the document.
+///
+/// # Errors
+/// If a required field does not match, or a value cannot be converted.
+#[cfg(feature = "html-lenient")]
+pub fn from_html<T>(html: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_soup(&Soup::html(html))
+}
+
+/// Deserializes a `T` from the XML document `xml`, parsed with [`Soup::xml`]
+///
+/// Fields are matched against the children of the root element rather than all descendants, so
+/// plain field names map to child element names and `@name` to attributes of the root.
+///
+/// # Errors
+/// If the XML is malformed, a required field does not match, or a value cannot be converted.
+#[cfg(feature = "xml")]
+pub fn from_xml<T>(xml: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let soup = Soup::xml(xml.as_bytes()).map_err(|e| Error(e.to_string()))?;
+
+    let root = soup
+        .nodes
+        .iter()
+        .find(|n| n.name().is_some())
+        .ok_or_else(|| Error("no root element".into()))?;
+
+    T::deserialize(Deserializer::from_node(root).strict())
+}
+
+/// Deserializes a `T` from the nodes of a [`Soup`]
+///
+/// See [`Deserializer`] for how fields are mapped to the document.
+///
+/// # Errors
+/// If a required field does not match, or a value cannot be converted.
+pub fn from_soup<T, N>(soup: &Soup<N>) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    T::deserialize(Deserializer::new(soup))
+}
+
+/// Error returned when deserializing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl de::StdError for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+enum Content<'x, N> {
+    /// Every top-level node of a document
+    Root(&'x [N]),
+    Element(&'x N),
+    /// An attribute value or text, if present
+    Value(Option<String>),
+    /// Everything matched by a field's selector
+    Matches(Vec<Content<'x, N>>),
+}
+
+/// A [`serde::Deserializer`] over the nodes of a [`Soup`]
+///
+/// Struct fields are looked up by their (possibly renamed) name:
+///
+/// - `selector` matches descendant elements, where the selector is a space-separated list of
+///   compound selectors made of a tag name or `*`, `#id`, `.class`, `[attr]` and `[attr=value]`.
+///   Scalars take the text of the first match, sequences take every match, and structs use the
+///   first match as their root. A plain field name matches elements with that tag name.
+/// - `@attr` reads an attribute of the current element, and `selector@attr` an attribute of the
+///   matched elements.
+/// - `$text` reads the text of the current element.
+///
+/// Text is the element's text content, with each text node trimmed and joined by spaces.
+/// Numbers and booleans are parsed from text, sequences of `@attr` or `$text` values split on
+/// whitespace, and maps collect an element's attributes. Missing values deserialize as `None` in `Option`s.
+///
+/// # Example
+/// ```rust
+/// use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, Visitor};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Product {
+///     name: String,
+///     price: f64,
+/// }
+///
+/// // Equivalent to `#[derive(Deserialize)]` with `#[serde(rename = "h1.name")]`
+/// // and `#[serde(rename = "span@data-price")]`
+/// impl<'de> Deserialize<'de> for Product {
+///     fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+///         struct ProductVisitor;
+///
+///         impl<'de> Visitor<'de> for ProductVisitor {
+///             type Value = Product;
+///
+///             fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///                 f.write_str("a product")
+///             }
+///
+///             fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Product, A::Error> {
+///                 let (mut name, mut price) = (None, None);
+///
+///                 while let Some(key) = map.next_key::<String>()? {
+///                     match key.as_str() {
+///                         "h1.name" => name = Some(map.next_value()?),
+///                         "span@data-price" => price = Some(map.next_value()?),
+///                         _ => map.next_value::<IgnoredAny>().map(|_| ())?,
+///                     }
+///                 }
+///
+///                 Ok(Product { name: name.unwrap(), price: price.unwrap() })
+///             }
+///         }
+///
+///         de.deserialize_struct("Product", &["h1.name", "span@data-price"], ProductVisitor)
+///     }
+/// }
+///
+/// let product: Product = soupy::from_html(
+///     r#"<h1 class="name">Widget</h1><span data-price="12.5">$12.50</span>"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(product, Product { name: "Widget".into(), price: 12.5 });
+/// ```
+pub struct Deserializer<'x, N> {
+    content: Content<'x, N>,
+    recursive: bool,
+    key: &'static str,
+}
+
+impl<'x, N> Deserializer<'x, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Creates a `Deserializer` over a whole document
+    #[must_use]
+    pub fn new(soup: &'x Soup<N>) -> Self {
+        Self {
+            content: Content::Root(&soup.nodes),
+            recursive: true,
+            key: "document",
+        }
+    }
+
+    /// Creates a `Deserializer` rooted at a single node, such as a query result
+    #[must_use]
+    pub fn from_node(node: &'x N) -> Self {
+        Self {
+            content: Content::Element(node),
+            recursive: true,
+            key: "node",
+        }
+    }
+
+    /// Matches field selectors against direct children only, instead of all descendants
+    #[must_use]
+    pub fn strict(self) -> Self {
+        Self {
+            recursive: false,
+            ..self
+        }
+    }
+
+    /// Resolves `Matches` to its first item
+    fn first(self) -> Result<Self, Error> {
+        match self.content {
+            Content::Matches(matches) => match matches.into_iter().next() {
+                Some(first) => Ok(Self {
+                    content: first,
+                    recursive: self.recursive,
+                    key: self.key,
+                }),
+                None => Err(missing(self.key)),
+            },
+            _ => Ok(self),
+        }
+    }
+
+    fn is_missing(&self) -> bool {
+        match &self.content {
+            Content::Value(value) => value.is_none(),
+            Content::Matches(matches) => matches
+                .first()
+                .is_none_or(|m| matches!(m, Content::Value(None))),
+            _ => false,
+        }
+    }
+
+    fn text(self) -> Result<String, Error> {
+        let this = self.first()?;
+
+        match this.content {
+            Content::Root(nodes) => Ok(text(nodes.iter().flat_map(Node::descendants))),
+            Content::Element(node) => Ok(text(node.descendants())),
+            Content::Value(Some(value)) => Ok(value),
+            Content::Value(None) | Content::Matches(_) => Err(missing(this.key)),
+        }
+    }
+
+    fn parse<T: FromStr>(self) -> Result<T, Error> {
+        let key = self.key;
+        let text = self.text()?;
+
+        text.trim()
+            .parse()
+            .map_err(|_| Error(format!("invalid value `{text}` for `{key}`")))
+    }
+
+    /// Resolves a struct field of an element or document
+    fn field(&self, key: &'static str) -> Result<Content<'x, N>, Error> {
+        let (node, scope) = match self.content {
+            Content::Root(nodes) => (None, nodes),
+            Content::Element(node) => (Some(node), node.children()),
+            _ => unreachable!("fields are only resolved on elements"),
+        };
+
+        if key == "$text" {
+            let text = node.map_or_else(
+                || text(scope.iter().flat_map(Node::descendants)),
+                |n| text(n.descendants()),
+            );
+
+            return Ok(Content::Value(Some(text)));
+        }
+
+        let (selector, attr) = match key.rsplit_once('@') {
+            Some((selector, attr)) => (selector, Some(attr)),
+            None => (key, None),
+        };
+
+        let value = |n: &N, attr: &str| Content::Value(n.get_str(attr).map(ToOwned::to_owned));
+
+        if selector.is_empty() {
+            return Ok(match (node, attr) {
+                (Some(node), Some(attr)) => value(node, attr),
+                _ => Content::Value(None),
+            });
+        }
+
+        let matches = select(scope, &parse_selector(selector)?, self.recursive);
+
+        Ok(Content::Matches(
+            matches
+                .into_iter()
+                .map(|n| match attr {
+                    Some(attr) => value(n, attr),
+                    None => Content::Element(n),
+                })
+                .collect(),
+        ))
+    }
+}
+
+fn missing(key: &str) -> Error {
+    Error(format!("no value for `{key}`"))
+}
+
+/// Text content of `nodes`, with each text node trimmed and joined by spaces
+fn text<'x, N>(nodes: impl Iterator<Item = &'x N>) -> String
+where
+    N: Node + 'x,
+    N::Text: AsRef<str>,
+{
+    nodes
+        .filter_map(Node::text)
+        .map(|t| t.as_ref().trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A compound selector, like `a.nav[href]`
+#[derive(Default)]
+struct Compound<'s> {
+    tag: Option<&'s str>,
+    id: Option<&'s str>,
+    classes: Vec<&'s str>,
+    attrs: Vec<(&'s str, Option<&'s str>)>,
+}
+
+impl Compound<'_> {
+    fn matches<N>(&self, node: &N) -> bool
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let Some(name) = node.name() else {
+            return false;
+        };
+
+        self.tag.is_none_or(|t| t == name.as_ref())
+            && self.id.is_none_or(|id| node.get_str("id") == Some(id))
+            && self.classes.iter().all(|class| {
+                node.get_str("class")
+                    .is_some_and(|c| c.split_ascii_whitespace().any(|c| c == *class))
+            })
+            && self.attrs.iter().all(|(attr, value)| match value {
+                Some(value) => node.get_str(attr) == Some(*value),
+                None => node.get_str(attr).is_some(),
+            })
+    }
+}
+
+fn parse_selector(selector: &str) -> Result<Vec<Compound<'_>>, Error> {
+    let invalid = || Error(format!("invalid selector `{selector}`"));
+    let is_ident = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | ':');
+
+    selector
+        .split_ascii_whitespace()
+        .map(|part| {
+            let mut compound = Compound::default();
+            let mut rest = part;
+
+            let end = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+
+            if let Some(r) = rest.strip_prefix('*') {
+                rest = r;
+            } else if end > 0 {
+                compound.tag = Some(&rest[..end]);
+                rest = &rest[end..];
+            }
+
+            while let Some(c) = rest.chars().next() {
+                let r = &rest[1..];
+
+                if c == '[' {
+                    let (inner, r) = r.split_once(']').ok_or_else(invalid)?;
+
+                    compound.attrs.push(match inner.split_once('=') {
+                        Some((attr, value)) => (attr, Some(value.trim_matches(['"', '\'']))),
+                        None => (inner, None),
+                    });
+
+                    rest = r;
+                    continue;
+                }
+
+                let end = r.find(|c| !is_ident(c)).unwrap_or(r.len());
+
+                if end == 0 {
+                    return Err(invalid());
+                }
+
+                match c {
+                    '#' => compound.id = Some(&r[..end]),
+                    '.' => compound.classes.push(&r[..end]),
+                    _ => return Err(invalid()),
+                }
+
+                rest = &r[end..];
+            }
+
+            Ok(compound)
+        })
+        .collect()
+}
+
+/// Elements in `scope` matching each compound in turn, in document order without duplicates
+fn select<'x, N>(scope: &'x [N], selector: &[Compound<'_>], recursive: bool) -> Vec<&'x N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    let mut current = Vec::new();
+
+    for (i, compound) in selector.iter().enumerate() {
+        let scopes = if i == 0 {
+            vec![scope]
+        } else {
+            current.iter().map(|n: &&N| n.children()).collect()
+        };
+
+        let mut seen = BTreeSet::new();
+        let mut next = Vec::new();
+
+        for node in scopes.into_iter().flatten() {
+            let candidates: &mut dyn Iterator<Item = &N> = if recursive {
+                &mut node.descendants()
+            } else {
+                &mut core::iter::once(node)
+            };
+
+            for candidate in candidates {
+                if compound.matches(candidate) && seen.insert(core::ptr::from_ref(candidate)) {
+                    next.push(candidate);
+                }
+            }
+        }
+
+        current = next;
+    }
+
+    current
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de, N> de::Deserializer<'de> for Deserializer<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    type Error = Error;
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.text()?)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.text()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.text()?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.text()?.into_bytes())
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.text()?.into_bytes())
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_missing() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Self {
+            content,
+            recursive,
+            key,
+        } = self;
+
+        let items = match content {
+            Content::Matches(items) => items,
+            Content::Root(nodes) => nodes
+                .iter()
+                .filter(|n| n.name().is_some())
+                .map(Content::Element)
+                .collect(),
+            Content::Element(node) => node
+                .children()
+                .iter()
+                .filter(|n| n.name().is_some())
+                .map(Content::Element)
+                .collect(),
+            Content::Value(Some(value)) => value
+                .split_whitespace()
+                .map(|v| Content::Value(Some(v.into())))
+                .collect(),
+            Content::Value(None) => return Err(missing(key)),
+        };
+
+        visitor.visit_seq(Seq {
+            items: items.into_iter(),
+            recursive,
+            key,
+        })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let this = self.first()?;
+
+        let attrs = match this.content {
+            Content::Element(node) => node
+                .attrs()
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| (k.as_ref().to_owned(), v.as_ref().to_owned()))
+                .collect(),
+            Content::Root(_) => Vec::new(),
+            Content::Value(_) | Content::Matches(_) => {
+                return Err(Error(format!("expected an element for `{}`", this.key)))
+            }
+        };
+
+        visitor.visit_map(Attributes {
+            attrs: attrs.into_iter(),
+            value: None,
+            key: this.key,
+            node: PhantomData::<&N>,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let this = self.first()?;
+
+        if let Content::Value(_) = this.content {
+            return Err(Error(format!("expected an element for `{}`", this.key)));
+        }
+
+        visitor.visit_map(Struct {
+            de: this,
+            fields: fields.iter(),
+            current: None,
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let text: String = self.text()?;
+        visitor.visit_enum(text.trim().to_owned().into_deserializer())
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.text()?)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+struct Seq<'x, N> {
+    items: vec::IntoIter<Content<'x, N>>,
+    recursive: bool,
+    key: &'static str,
+}
+
+impl<'de, N> SeqAccess<'de> for Seq<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.items
+            .next()
+            .map(|content| {
+                seed.deserialize(Deserializer {
+                    content,
+                    recursive: self.recursive,
+                    key: self.key,
+                })
+            })
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct Attributes<'x, N> {
+    attrs: vec::IntoIter<(String, String)>,
+    value: Option<String>,
+    key: &'static str,
+    node: PhantomData<&'x N>,
+}
+
+impl<'de, N> MapAccess<'de> for Attributes<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some((key, value)) = self.attrs.next() else {
+            return Ok(None);
+        };
+
+        self.value = Some(value);
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer::<N> {
+            content: Content::Value(self.value.take()),
+            recursive: false,
+            key: self.key,
+        })
+    }
+}
+
+struct Struct<'x, N> {
+    de: Deserializer<'x, N>,
+    fields: core::slice::Iter<'static, &'static str>,
+    current: Option<&'static str>,
+}
+
+impl<'de, N> MapAccess<'de> for Struct<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+
+        self.current = Some(field);
+        seed.deserialize((*field).into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let key = self
+            .current
+            .take()
+            .ok_or_else(|| Error("value without a key".into()))?;
+
+        seed.deserialize(Deserializer {
+            content: self.de.field(key)?,
+            recursive: self.de.recursive,
+            key,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use serde::de::{
+        Deserialize,
+        IgnoredAny,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Page {
+        title: String,
+        links: Vec<String>,
+        rating: Option<u8>,
+        missing: Option<String>,
+        items: Vec<Item>,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Item {
+        id: u32,
+        text: String,
+        classes: Vec<String>,
+        attrs: BTreeMap<String, String>,
+    }
+
+    macro_rules! impl_deserialize {
+        ($ty:ident { $($field:ident: $key:literal),* $(,)? }) => {
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                    struct V;
+
+                    impl<'de> Visitor<'de> for V {
+                        type Value = $ty;
+
+                        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                            f.write_str(stringify!($ty))
+                        }
+
+                        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<$ty, A::Error> {
+                            $(let mut $field = None;)*
+
+                            while let Some(key) = map.next_key::<String>()? {
+                                match key.as_str() {
+                                    $($key => $field = Some(map.next_value()?),)*
+                                    _ => map.next_value::<IgnoredAny>().map(|_| ())?,
+                                }
+                            }
+
+                            Ok($ty {
+                                $($field: $field.ok_or_else(|| de::Error::missing_field($key))?,)*
+                            })
+                        }
+                    }
+
+                    de.deserialize_struct(stringify!($ty), &[$($key),*], V)
+                }
+            }
+        };
+    }
+
+    impl_deserialize!(Page {
+        title: "title",
+        links: "a@href",
+        rating: "#rating",
+        missing: "p.missing",
+        items: "ul li.item",
+        tags: "body@data-tags",
+    });
+
+    impl_deserialize!(Item {
+        id: "@data-id",
+        text: "$text",
+        classes: "@class",
+        attrs: "b",
+    });
+
+    const HTML: &str = r#"
+        <html>
+            <head><title>  The Page </title></head>
+            <body data-tags="a b c">
+                <a href="/one">One</a>
+                <span id="rating">4</span>
+                <ul>
+                    <li class="item first" data-id="1"><b x="y">Hello</b> world</li>
+                    <li data-id="2">Skipped</li>
+                    <li class="item" data-id="3"><b>Bye</b></li>
+                </ul>
+                <a href="/two">Two</a>
+            </body>
+        </html>"#;
+
+    #[test]
+    fn test_from_html() {
+        let page: Page = from_html(HTML).expect("Failed to deserialize");
+
+        assert_eq!(page, Page {
+            title: "The Page".into(),
+            links: vec!["/one".into(), "/two".into()],
+            rating: Some(4),
+            missing: None,
+            items: vec![
+                Item {
+                    id: 1,
+                    text: "Hello world".into(),
+                    classes: vec!["item".into(), "first".into()],
+                    attrs: [("x".into(), "y".into())].into(),
+                },
+                Item {
+                    id: 3,
+                    text: "Bye".into(),
+                    classes: vec!["item".into()],
+                    attrs: BTreeMap::new(),
+                },
+            ],
+            tags: vec!["a b c".into()],
+        });
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        version: String,
+        name: String,
+        port: u16,
+        server: Vec<Server>,
+        nested: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        host: String,
+        enabled: bool,
+    }
+
+    impl_deserialize!(Config {
+        version: "@version",
+        name: "name",
+        port: "port",
+        server: "server",
+        nested: "host",
+    });
+
+    impl_deserialize!(Server {
+        host: "host",
+        enabled: "@enabled",
+    });
+
+    #[test]
+    fn test_from_xml() {
+        let xml = r#"<?xml version="1.0"?>
+            <config version="2">
+                <name>app</name>
+                <port> 8080 </port>
+                <server enabled="true"><host>a.example</host></server>
+                <server enabled="false"><host>b.example</host></server>
+            </config>"#;
+
+        let config: Config = from_xml(xml).expect("Failed to deserialize");
+
+        assert_eq!(config, Config {
+            version: "2".into(),
+            name: "app".into(),
+            port: 8080,
+            server: vec![
+                Server {
+                    host: "a.example".into(),
+                    enabled: true,
+                },
+                Server {
+                    host: "b.example".into(),
+                    enabled: false,
+                },
+            ],
+            nested: None,
+        });
+
+        assert!(from_xml::<Config>("<config>").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let soup =
+            Soup::html_strict(r#"<span id="rating">four</span>"#).expect("Failed to parse HTML");
+
+        let err = from_soup::<Page, _>(&soup).unwrap_err();
+        assert_eq!(err.to_string(), "no value for `title`");
+
+        let rating = u8::deserialize(Deserializer::from_node(&soup.nodes[0]));
+        assert_eq!(
+            rating.unwrap_err().to_string(),
+            "invalid value `four` for `node`"
+        );
+
+        assert!(parse_selector("a[href").is_err());
+        assert!(parse_selector("a.").is_err());
+        assert!(parse_selector("*.a#b[c=\"d\"] e").is_ok());
+    }
+}
@@ -0,0 +1,392 @@
+use alloc::{
+    collections::BTreeMap,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    query::NodeId,
+    Node,
+    Soup,
+};
+
+/// Nodes whose children and attributes can be changed in place
+pub trait NodeMut: Node + Sized {
+    /// Mutable list of children, or `None` if the node can't have children
+    fn children_mut(&mut self) -> Option<&mut Vec<Self>>;
+
+    /// Mutable attributes, or `None` if the node can't have attributes
+    fn attrs_mut(&mut self) -> Option<&mut BTreeMap<Self::Text, Self::Text>>;
+}
+
+/// Error returned when a [`Soup`] can't be edited as asked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditError {
+    /// No node has the given id
+    NotFound(NodeId),
+    /// The node can't have children, like text or a void element
+    NoChildren(NodeId),
+    /// The node can't have attributes, like text
+    NoAttributes(NodeId),
+    /// The child index is past the end of the node's children
+    OutOfBounds(usize),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "no node with index {}", id.index()),
+            Self::NoChildren(id) => write!(f, "node {} can't have children", id.index()),
+            Self::NoAttributes(id) => write!(f, "node {} can't have attributes", id.index()),
+            Self::OutOfBounds(index) => write!(f, "child index {index} is out of bounds"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EditError {}
+
+/// Editing documents in place
+///
+/// Nodes are addressed by the [`NodeId`]s of query results. Inserting or removing a node
+/// changes the ids of the nodes after it, so edit in reverse document order, or query again
+/// after each change.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{parser::HTMLNode, prelude::*};
+/// let mut soup = Soup::html_strict(r#"<ul><li>One</li><li class="ad">Buy</li><li>Two</li></ul>"#).unwrap();
+///
+/// let ads = soup.attr("class", "ad").all().map(|li| li.id()).collect::<Vec<_>>();
+/// for id in ads.into_iter().rev() {
+///     soup.remove(id).unwrap();
+/// }
+///
+/// let ul = soup.tag("ul").first().unwrap().id();
+/// soup.set_attr(ul, "id", "list").unwrap();
+/// soup.append_child(ul, HTMLNode::Element {
+///     name: "li",
+///     attrs: Default::default(),
+///     children: vec![HTMLNode::Text("Three")],
+/// }).unwrap();
+///
+/// let ul = soup.tag("ul").first().unwrap();
+/// assert_eq!((*ul).to_string(), r#"<ul id="list"><li>One</li><li>Two</li><li>Three</li></ul>"#);
+/// ```
+impl<N> Soup<N>
+where
+    N: NodeMut,
+{
+    /// Resolves a [`NodeId`] to a mutable node
+    #[must_use]
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        let (siblings, i) = self.locate(id)?;
+        siblings.get_mut(i)
+    }
+
+    /// Removes a node and everything inside it, returning it
+    ///
+    /// # Errors
+    /// If there is no node with the id.
+    pub fn remove(&mut self, id: NodeId) -> Result<N, EditError> {
+        let (siblings, i) = self.locate(id).ok_or(EditError::NotFound(id))?;
+        Ok(siblings.remove(i))
+    }
+
+    /// Puts `node` in place of a node, returning the old node
+    ///
+    /// # Errors
+    /// If there is no node with the id.
+    pub fn replace_with(&mut self, id: NodeId, node: N) -> Result<N, EditError> {
+        let old = self.get_mut(id).ok_or(EditError::NotFound(id))?;
+        Ok(core::mem::replace(old, node))
+    }
+
+    /// Inserts `node` before a node, as its sibling, returning the id of the new node
+    ///
+    /// # Errors
+    /// If there is no node with the id.
+    pub fn insert_before(&mut self, id: NodeId, node: N) -> Result<NodeId, EditError> {
+        let (siblings, i) = self.locate(id).ok_or(EditError::NotFound(id))?;
+        siblings.insert(i, node);
+        Ok(id)
+    }
+
+    /// Inserts `node` after a node, as its sibling, returning the id of the new node
+    ///
+    /// # Errors
+    /// If there is no node with the id.
+    pub fn insert_after(&mut self, id: NodeId, node: N) -> Result<NodeId, EditError> {
+        let (siblings, i) = self.locate(id).ok_or(EditError::NotFound(id))?;
+        let size = siblings[i].descendants().count();
+        siblings.insert(i + 1, node);
+        Ok(NodeId(id.index() + size))
+    }
+
+    /// Inserts `node` as the child at `index` of a node, returning the id of the new node
+    ///
+    /// # Errors
+    /// If there is no node with the id, it can't have children, or `index` is past the end of
+    /// its children.
+    pub fn insert_child(
+        &mut self,
+        parent: NodeId,
+        index: usize,
+        node: N,
+    ) -> Result<NodeId, EditError> {
+        let children = self
+            .get_mut(parent)
+            .ok_or(EditError::NotFound(parent))?
+            .children_mut()
+            .ok_or(EditError::NoChildren(parent))?;
+
+        if index > children.len() {
+            return Err(EditError::OutOfBounds(index));
+        }
+
+        let before = children[..index]
+            .iter()
+            .map(|c| c.descendants().count())
+            .sum::<usize>();
+
+        children.insert(index, node);
+        Ok(NodeId(parent.index() + 1 + before))
+    }
+
+    /// Inserts `node` as the last child of a node, returning the id of the new node
+    ///
+    /// # Errors
+    /// If there is no node with the id, or it can't have children.
+    pub fn append_child(&mut self, parent: NodeId, node: N) -> Result<NodeId, EditError> {
+        let len = self
+            .get_mut(parent)
+            .ok_or(EditError::NotFound(parent))?
+            .children_mut()
+            .ok_or(EditError::NoChildren(parent))?
+            .len();
+
+        self.insert_child(parent, len, node)
+    }
+
+    /// Sets an attribute of a node, returning the old value if there was one
+    ///
+    /// # Errors
+    /// If there is no node with the id, or it can't have attributes.
+    pub fn set_attr(
+        &mut self,
+        id: NodeId,
+        name: impl Into<N::Text>,
+        value: impl Into<N::Text>,
+    ) -> Result<Option<N::Text>, EditError>
+    where
+        N::Text: Ord,
+    {
+        Ok(self.attrs_mut(id)?.insert(name.into(), value.into()))
+    }
+
+    /// Removes an attribute of a node, returning its value if there was one
+    ///
+    /// # Errors
+    /// If there is no node with the id, or it can't have attributes.
+    pub fn remove_attr(&mut self, id: NodeId, name: &str) -> Result<Option<N::Text>, EditError>
+    where
+        N::Text: AsRef<str> + Ord + Clone,
+    {
+        let attrs = self.attrs_mut(id)?;
+        let key = attrs.keys().find(|k| k.as_ref() == name).cloned();
+        Ok(key.and_then(|k| attrs.remove(&k)))
+    }
+
+    /// Finds the list holding the node with `id`, and its position in the list
+    ///
+    /// The caller may add or remove nodes through the list, so the cached subtree sizes are
+    /// dropped.
+    fn locate(&mut self, id: NodeId) -> Option<(&mut Vec<N>, usize)> {
+        let path = self
+            .root()
+            .steps(id.index())?
+            .into_iter()
+            .map(|(_, pos)| pos)
+            .collect::<Vec<_>>();
+        self.sizes.clear();
+
+        let (&last, parents) = path.split_last()?;
+        let mut siblings = &mut self.nodes;
+
+        for &pos in parents {
+            siblings = siblings[pos].children_mut()?;
+        }
+
+        Some((siblings, last))
+    }
+
+    fn attrs_mut(&mut self, id: NodeId) -> Result<&mut BTreeMap<N::Text, N::Text>, EditError> {
+        self.get_mut(id)
+            .ok_or(EditError::NotFound(id))?
+            .attrs_mut()
+            .ok_or(EditError::NoAttributes(id))
+    }
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl<S> NodeMut for crate::parser::HTMLNode<S> {
+    fn children_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Self::Element { children, .. } => Some(children),
+            _ => None,
+        }
+    }
+
+    fn attrs_mut(&mut self) -> Option<&mut BTreeMap<S, S>> {
+        match self {
+            Self::Element { attrs, .. }
+            | Self::RawElement { attrs, .. }
+            | Self::Void { attrs, .. } => Some(attrs),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+impl NodeMut for crate::parser::XMLNode {
+    fn children_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Self::Element(e) => Some(&mut e.children),
+            _ => None,
+        }
+    }
+
+    fn attrs_mut(&mut self) -> Option<&mut BTreeMap<String, String>> {
+        match self {
+            Self::Element(e) => Some(&mut e.attributes),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl NodeMut for crate::parser::JSONNode {
+    fn children_mut(&mut self) -> Option<&mut Vec<Self>> {
+        use crate::parser::JSONValue;
+
+        match &mut self.value {
+            JSONValue::Array(c) | JSONValue::Object(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    fn attrs_mut(&mut self) -> Option<&mut BTreeMap<Self::Text, Self::Text>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{
+        parser::{
+            Escaping,
+            HTMLNode,
+        },
+        Queryable,
+    };
+
+    #[test]
+    fn test_edit() {
+        let mut soup = Soup::html_strict(r#"<p id="a">One <b>two</b></p><br><p>three</p>"#)
+            .expect("Failed to parse HTML");
+        let id = |soup: &Soup<HTMLNode<&str>>, name| {
+            soup.tag(name)
+                .first()
+                .map(|n| n.id())
+                .expect("Missing node")
+        };
+
+        let b = id(&soup, "b");
+        let text = HTMLNode::Text("!");
+        let new = soup
+            .insert_after(b, text.clone())
+            .expect("Failed to insert");
+        assert_eq!(soup.get(new).map(|n| n.node()), Some(&text));
+
+        let p = id(&soup, "p");
+        let new = soup
+            .insert_child(p, 1, HTMLNode::Text("1 "))
+            .expect("Failed to insert");
+        assert_eq!(soup.get(new).and_then(|n| n.text().copied()), Some("1 "));
+        assert_eq!(
+            soup.insert_child(p, 9, HTMLNode::Text("")),
+            Err(EditError::OutOfBounds(9))
+        );
+
+        let br = id(&soup, "br");
+        assert_eq!(
+            soup.append_child(br, HTMLNode::Text("")),
+            Err(EditError::NoChildren(br))
+        );
+        assert_eq!(soup.set_attr(br, "class", "x"), Ok(None));
+        assert_eq!(soup.remove_attr(p, "ID"), Ok(None));
+        assert_eq!(soup.remove_attr(p, "id"), Ok(Some("a")));
+
+        let b = id(&soup, "b");
+        let old = soup
+            .replace_with(b, HTMLNode::Text("2"))
+            .expect("Failed to replace");
+        assert_eq!(old.all_text(), "two");
+        assert_eq!(soup.insert_before(p, HTMLNode::Comment("c")), Ok(p));
+
+        let last = NodeId(soup.iter().count() - 1);
+        assert_eq!(
+            soup.remove(last).map(|n| n.text().copied()),
+            Ok(Some("three"))
+        );
+        assert_eq!(soup.remove(last), Err(EditError::NotFound(last)));
+
+        assert_eq!(
+            soup.to_html(Escaping::Raw),
+            r#"<!--c--><p>One1 2!</p><br class="x"><p></p>"#
+        );
+        assert_eq!(soup.get_mut(NodeId(0)), Some(&mut HTMLNode::Comment("c")));
+        assert_eq!(
+            soup.get_mut(NodeId(1))
+                .and_then(|p| p.children_mut())
+                .map(|c| c.len()),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_nested_edit() {
+        let mut soup =
+            Soup::html_strict(r#"<ul><li><a href="/1">1</a></li></ul><div><ul><li><a href="/2">2</a></li></ul></div>"#)
+                .expect("Failed to parse HTML");
+
+        let (a, li) = {
+            let div = soup.tag("div").first().expect("Couldn't find div");
+            let a = div.tag("a").first().expect("Couldn't find a").id();
+            let li = div
+                .query_ref()
+                .tag("li")
+                .first()
+                .expect("Couldn't find li")
+                .id();
+            (a, li)
+        };
+
+        assert_eq!(soup.set_attr(a, "href", "/two"), Ok(Some("/2")));
+        assert_eq!(
+            soup.remove(li).map(|li| li.all_text()),
+            Ok(String::from("2"))
+        );
+        assert_eq!(
+            soup.to_html(Escaping::Raw),
+            r#"<ul><li><a href="/1">1</a></li></ul><div><ul></ul></div>"#
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use alloc::{
+    collections::BTreeMap,
+    string::{
+        String,
+        ToString,
+    },
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use html5ever::tendril::StrTendril;
+
+use crate::{
+    css::{
+        self,
+        find_top,
+        raw_rules,
+        split_top,
+        strip_comments,
+        style_content,
+        Declaration,
+        Key,
+        RawRule,
+    },
+    links,
+    parser::HTMLNode,
+    selector::Selector,
+    transform::Action,
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// Elements removed with their content, as they run code or load other documents
+const REMOVED_ELEMENTS: &[&str] = &[
+    "applet", "base", "embed", "frame", "frameset", "iframe", "link", "object", "script",
+];
+
+/// Elements that are never rendered, so never receive inlined styles
+const HIDDEN_ELEMENTS: &[&str] = &[
+    "base", "head", "link", "meta", "script", "style", "template", "title",
+];
+
+/// Attributes holding a single URL, on any element
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "dynsrc",
+    "formaction",
+    "href",
+    "longdesc",
+    "lowsrc",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// Nested blocks deeper than this are dropped from style sheets
+const MAX_DEPTH: usize = 8;
+
+/// Options for [`clean`]
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailOptions {
+    /// Copy the rules of `<style>` elements into the `style` attributes of the elements they
+    /// match, as many mail clients ignore style sheets
+    pub inline_styles: bool,
+
+    /// Keep `<style>` elements, with the rules that couldn't be inlined, like `:hover` and
+    /// `@media` rules
+    pub keep_style_elements: bool,
+
+    /// Remove comments, including the conditional comments read by Outlook
+    pub remove_comments: bool,
+
+    /// Allow `data:` URLs of raster images, which many messages embed
+    pub allow_data_images: bool,
+}
+
+impl Default for EmailOptions {
+    fn default() -> Self {
+        Self {
+            inline_styles: true,
+            keep_style_elements: true,
+            remove_comments: false,
+            allow_data_images: true,
+        }
+    }
+}
+
+/// Parses and cleans an HTML email with the default [`EmailOptions`]
+///
+/// The lenient parser is used, so deprecated presentational markup like `<font>`, `<center>`,
+/// and `bgcolor` is read like a browser would, and kept.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{email, prelude::*};
+/// let soup = email::parse(r#"<html><head><style>
+///     p { color: #333; margin: 0 }
+///     .note { color: red }
+///     a:hover { color: blue }
+/// </style></head><body>
+///     <center><font face="Arial">Hi</font></center>
+///     <p class="note" style="margin: 4px" onclick="steal()">Read this</p>
+///     <a href="javascript:steal()">Click</a>
+///     <script>steal()</script>
+/// </body></html>"#);
+///
+/// let p = soup.tag("p").first().unwrap();
+/// assert_eq!(p.get_str("style"), Some("color: red; margin: 4px"));
+/// assert_eq!(p.get_str("onclick"), None);
+///
+/// assert_eq!(soup.tag("a").first().unwrap().get_str("href"), None);
+/// assert_eq!(soup.tag("script").all().count(), 0);
+/// assert_eq!(soup.tag("font").all().count(), 1);
+/// ```
+#[must_use]
+pub fn parse(html: &str) -> Soup<HTMLNode<StrTendril>> {
+    parse_with(html, &EmailOptions::default())
+}
+
+/// Parses and cleans an HTML email, like [`parse`]
+#[must_use]
+pub fn parse_with(html: &str, options: &EmailOptions) -> Soup<HTMLNode<StrTendril>> {
+    clean(&Soup::html(html), options)
+}
+
+/// Builds a cleaned copy of a parsed HTML email
+///
+/// Removes scripts, frames, plugins, `<base>`, `<link>`, and `<meta>` refreshes, event handler
+/// attributes, `javascript:`, `vbscript:`, and `data:` URLs (except raster images, if allowed),
+/// and style declarations that run code, like `expression()`. Forms are unwrapped, keeping
+/// their content.
+#[must_use]
+pub fn clean<S>(soup: &Soup<HTMLNode<S>>, options: &EmailOptions) -> Soup<HTMLNode<S>>
+where
+    S: AsRef<str> + Clone + Ord + From<String>,
+{
+    let mut cleaner = Cleaner {
+        options,
+        cascades: BTreeMap::new(),
+        sheets: BTreeMap::new(),
+    };
+
+    let mut order = 0;
+
+    for item in soup {
+        let Some(content) = style_content(item.node()) else {
+            continue;
+        };
+
+        let css = strip_comments(&content);
+        let mut sheet = String::new();
+
+        for rule in raw_rules(&css) {
+            let RawRule::Style(selectors, block) = rule else {
+                write_rule(&mut sheet, &rule, 0);
+                continue;
+            };
+
+            if !options.inline_styles {
+                write_style(&mut sheet, selectors, block);
+                continue;
+            }
+
+            let declarations = declarations(block);
+            let mut rest = Vec::new();
+
+            for part in split_top(selectors, ',') {
+                let Ok(selector) = Selector::parse(part) else {
+                    rest.push(part.trim());
+                    continue;
+                };
+
+                let specificity = selector.specificity();
+
+                for matched in selector.select(soup.filter(())) {
+                    if is(HIDDEN_ELEMENTS, matched.node()) {
+                        continue;
+                    }
+
+                    cleaner
+                        .cascades
+                        .entry(matched.id().index())
+                        .or_default()
+                        .extend(declarations.iter().enumerate().map(|(i, d)| {
+                            ((d.important, false, specificity, order + i), d.clone())
+                        }));
+                }
+            }
+
+            order += declarations.len();
+
+            if !rest.is_empty() {
+                write_style(&mut sheet, &rest.join(", "), block);
+            }
+        }
+
+        cleaner.sheets.insert(item.id().index(), sheet);
+    }
+
+    let mut index = 0;
+
+    soup.transform(|node| {
+        let action = cleaner.action(node, index);
+
+        index += match action {
+            Action::Remove | Action::Replace(_) => node.descendants().count(),
+            _ => 1,
+        };
+
+        action
+    })
+}
+
+struct Cleaner<'o> {
+    options: &'o EmailOptions,
+    /// Style sheet declarations matching each element, by index in document order
+    cascades: BTreeMap<usize, Vec<(Key, Declaration)>>,
+    /// What is left of each `<style>` element after inlining, by index in document order
+    sheets: BTreeMap<usize, String>,
+}
+
+impl Cleaner<'_> {
+    fn action<S>(&self, node: &HTMLNode<S>, index: usize) -> Action<HTMLNode<S>>
+    where
+        S: AsRef<str> + Clone + Ord + From<String>,
+    {
+        let Some(name) = node.name() else {
+            return match node {
+                HTMLNode::Comment(_) if self.options.remove_comments => Action::Remove,
+                _ => Action::Keep,
+            };
+        };
+
+        let name = name.as_ref();
+
+        let refresh = name.eq_ignore_ascii_case("meta")
+            && node
+                .attrs_ignore_case()
+                .and_then(|a| a.get("http-equiv"))
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case("refresh"));
+
+        if is(REMOVED_ELEMENTS, node) || refresh {
+            return Action::Remove;
+        }
+
+        if name.eq_ignore_ascii_case("form") {
+            return Action::Unwrap;
+        }
+
+        let sheet = self.sheets.get(&index);
+
+        if sheet.is_some_and(|s| s.is_empty() || !self.options.keep_style_elements) {
+            return Action::Remove;
+        }
+
+        let attrs = node.attrs().and_then(|a| self.attrs(a, index));
+
+        if let (Some(sheet), Some(name), Some(original)) = (sheet, node.name(), node.attrs()) {
+            let (name, attrs) = (name.clone(), attrs.unwrap_or_else(|| original.clone()));
+            let content = S::from(sheet.clone());
+
+            return Action::Replace(match node {
+                HTMLNode::RawElement { .. } => HTMLNode::RawElement {
+                    name,
+                    attrs,
+                    content,
+                },
+                _ => HTMLNode::Element {
+                    name,
+                    attrs,
+                    children: alloc::vec![HTMLNode::Text(content)],
+                },
+            });
+        }
+
+        match (node, attrs) {
+            (HTMLNode::Element { name, .. }, Some(attrs)) => Action::Edit(HTMLNode::Element {
+                name: name.clone(),
+                attrs,
+                children: Vec::new(),
+            }),
+            (HTMLNode::RawElement { name, content, .. }, Some(attrs)) => {
+                Action::Replace(HTMLNode::RawElement {
+                    name: name.clone(),
+                    attrs,
+                    content: content.clone(),
+                })
+            }
+            (HTMLNode::Void { name, .. }, Some(attrs)) => Action::Replace(HTMLNode::Void {
+                name: name.clone(),
+                attrs,
+            }),
+            _ => Action::Keep,
+        }
+    }
+
+    /// Returns the cleaned attributes, with inlined styles, or `None` if nothing changed
+    fn attrs<S>(&self, attrs: &BTreeMap<S, S>, index: usize) -> Option<BTreeMap<S, S>>
+    where
+        S: AsRef<str> + Clone + Ord + From<String>,
+    {
+        let mut changed = false;
+        let mut clean = BTreeMap::new();
+        let mut style = None;
+
+        for (key, value) in attrs {
+            let name = key.as_ref().to_ascii_lowercase();
+
+            let safe = if name.starts_with("on") || name == "srcdoc" {
+                false
+            } else if URL_ATTRIBUTES.contains(&name.as_str()) {
+                !self.is_dangerous(value.as_ref())
+            } else if name == "srcset" {
+                !links::srcset(value.as_ref()).any(|url| self.is_dangerous(url))
+            } else {
+                true
+            };
+
+            if !safe {
+                changed = true;
+            } else if name == "style" {
+                style = Some((key, value));
+            } else {
+                clean.insert(key.clone(), value.clone());
+            }
+        }
+
+        let mut cascade = self.cascades.get(&index).cloned().unwrap_or_default();
+
+        if let Some((_, value)) = style {
+            let inline = declarations(value.as_ref());
+
+            cascade.extend(
+                inline
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, d)| ((d.important, true, (0, 0, 0), i), d)),
+            );
+        }
+
+        if style.is_some() || !cascade.is_empty() {
+            let value = join(&css::cascade(cascade));
+            changed |= style.map(|(_, v)| v.as_ref()) != Some(value.as_str());
+
+            if !value.is_empty() {
+                let key = style.map_or_else(|| S::from("style".to_string()), |(k, _)| k.clone());
+                clean.insert(key, S::from(value));
+            }
+        }
+
+        changed.then_some(clean)
+    }
+
+    /// Returns `true` if following `url` could run code or load an unwanted document
+    fn is_dangerous(&self, url: &str) -> bool {
+        // Browsers ignore whitespace and control characters in schemes
+        let scheme = url
+            .chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .take(16)
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match scheme.strip_prefix("data:") {
+            Some(data) => {
+                !(self.options.allow_data_images
+                    && data.starts_with("image/")
+                    && !data.starts_with("image/svg"))
+            }
+            None => scheme.starts_with("javascript:") || scheme.starts_with("vbscript:"),
+        }
+    }
+}
+
+/// Returns `true` if `node` is an element named in `names`, ignoring ASCII case
+fn is<S>(names: &[&str], node: &HTMLNode<S>) -> bool
+where
+    S: AsRef<str>,
+{
+    node.name().is_some_and(|n| {
+        names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(n.as_ref()))
+    })
+}
+
+/// Returns `false` if the declaration can run code in old browsers and mail clients
+fn is_safe(declaration: &Declaration) -> bool {
+    let value = declaration
+        .value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\\')
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    !matches!(declaration.property.as_str(), "behavior" | "-moz-binding")
+        && !["expression(", "javascript:", "vbscript:"]
+            .iter()
+            .any(|s| value.contains(s))
+}
+
+/// Parses the safe declarations of a block, like `color: red; margin: 0`
+fn declarations(block: &str) -> Vec<Declaration> {
+    let mut declarations = Declaration::parse_list(block);
+    declarations.retain(is_safe);
+    declarations
+}
+
+/// Writes declarations as a `style` attribute or block
+fn join(declarations: &[Declaration]) -> String {
+    declarations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Writes a style rule, without unsafe declarations
+fn write_style(out: &mut String, selectors: &str, block: &str) {
+    let declarations = declarations(block);
+
+    if !declarations.is_empty() {
+        let _ = writeln!(out, "{} {{ {} }}", selectors.trim(), join(&declarations));
+    }
+}
+
+/// Writes a rule, without unsafe declarations or `@import`
+fn write_rule(out: &mut String, rule: &RawRule, depth: usize) {
+    match rule {
+        RawRule::Style(selectors, block) => write_style(out, selectors, block),
+        RawRule::At(prelude, None) => {
+            if !prelude
+                .get(..7)
+                .is_some_and(|p| p.eq_ignore_ascii_case("@import"))
+            {
+                let _ = writeln!(out, "{prelude};");
+            }
+        }
+        // Group rules like `@media` and `@keyframes` hold more rules
+        RawRule::At(prelude, Some(block)) if find_top(block, &['{']).is_some() => {
+            if depth < MAX_DEPTH {
+                let mut inner = String::new();
+
+                for rule in raw_rules(block) {
+                    write_rule(&mut inner, &rule, depth + 1);
+                }
+
+                if !inner.is_empty() {
+                    let _ = writeln!(out, "{prelude} {{\n{inner}}}");
+                }
+            }
+        }
+        RawRule::At(prelude, Some(block)) => write_style(out, prelude, block),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheets<S: AsRef<str>>(soup: &Soup<HTMLNode<S>>) -> Vec<String> {
+        soup.iter()
+            .filter_map(|item| style_content(item.node()))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean() {
+        let html = r##"<!DOCTYPE html><html><head>
+            <meta http-equiv="Refresh" content="0; url=https://evil.example/">
+            <base href="https://evil.example/">
+            <style>
+                @import url(https://evil.example/a.css);
+                /* comment */ td, a:hover { color: red !important; behavior: url(x.htc) }
+                #main td.x { color: green; width: expression(alert(1)) }
+                @media (max-width: 600px) { td { width: 100% } .y { } }
+            </style>
+        </head><body bgcolor="#fff">
+            <!--[if mso]><table><![endif]-->
+            <table id="main"><tr><td class="x" style="COLOR: blue; padding:0">1</td></tr></table>
+            <form action="/post"><input formaction="JaVa&#x09;Script:alert(1)"></form>
+            <img src=" data:image/png;base64,AA==" srcset="a.png 1x, data:image/svg+xml,x 2x">
+            <iframe srcdoc="<script>alert(1)</script>"></iframe>
+            <object data="x.swf"><b>Fallback</b></object>
+            <p style="margin: 0">after</p>
+        </body></html>"##;
+
+        let soup = parse(html);
+
+        assert_eq!(
+            soup.tag("td").first().unwrap().get_str("style"),
+            Some("padding: 0; color: red !important")
+        );
+        assert_eq!(
+            soup.tag("body").first().unwrap().get_str("bgcolor"),
+            Some("#fff")
+        );
+        assert_eq!(
+            soup.tag("p").first().unwrap().get_str("style"),
+            Some("margin: 0")
+        );
+        assert_eq!(
+            soup.tag("input").first().unwrap().get_str("formaction"),
+            None
+        );
+
+        let img = soup.tag("img").first().unwrap();
+        assert_eq!(img.get_str("src"), Some(" data:image/png;base64,AA=="));
+        assert_eq!(img.get_str("srcset"), None);
+
+        for name in ["meta", "base", "form", "iframe", "object", "b"] {
+            assert_eq!(soup.tag(name).all().count(), 0, "{name}");
+        }
+
+        assert_eq!(soup.iter().filter(|i| i.comment().is_some()).count(), 1);
+        assert_eq!(sheets(&soup), [
+            "a:hover { color: red !important }\n@media (max-width: 600px) {\ntd { width: 100% }\n}\n"
+        ]);
+
+        let strict = parse_with(html, &EmailOptions {
+            inline_styles: false,
+            keep_style_elements: false,
+            remove_comments: true,
+            allow_data_images: false,
+        });
+
+        assert_eq!(
+            strict.tag("td").first().unwrap().get_str("style"),
+            Some("color: blue; padding: 0")
+        );
+        assert_eq!(strict.tag("img").first().unwrap().get_str("src"), None);
+        assert!(sheets(&strict).is_empty());
+        assert_eq!(strict.iter().filter(|i| i.comment().is_some()).count(), 0);
+    }
+}
@@ -0,0 +1,437 @@
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    string::{
+        String,
+        ToString,
+    },
+};
+use core::num::IntErrorKind;
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+use crate::parser::HTMLNode;
+#[cfg(feature = "xml")]
+use crate::parser::{
+    XMLElement,
+    XMLNode,
+};
+use crate::Soup;
+
+/// Named character references decoded by default
+///
+/// The XML entities, plus the HTML ones most often found in the wild.
+/// Others can be added with [`EntityOptions::with_entity`].
+const NAMED: &[(&str, &str)] = &[
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", "\u{A0}"),
+    ("shy", "\u{AD}"),
+    ("copy", "\u{A9}"),
+    ("reg", "\u{AE}"),
+    ("trade", "\u{2122}"),
+    ("deg", "\u{B0}"),
+    ("plusmn", "\u{B1}"),
+    ("times", "\u{D7}"),
+    ("divide", "\u{F7}"),
+    ("micro", "\u{B5}"),
+    ("para", "\u{B6}"),
+    ("sect", "\u{A7}"),
+    ("middot", "\u{B7}"),
+    ("frac14", "\u{BC}"),
+    ("frac12", "\u{BD}"),
+    ("frac34", "\u{BE}"),
+    ("iexcl", "\u{A1}"),
+    ("iquest", "\u{BF}"),
+    ("laquo", "\u{AB}"),
+    ("raquo", "\u{BB}"),
+    ("lsquo", "\u{2018}"),
+    ("rsquo", "\u{2019}"),
+    ("sbquo", "\u{201A}"),
+    ("ldquo", "\u{201C}"),
+    ("rdquo", "\u{201D}"),
+    ("bdquo", "\u{201E}"),
+    ("ndash", "\u{2013}"),
+    ("mdash", "\u{2014}"),
+    ("hellip", "\u{2026}"),
+    ("bull", "\u{2022}"),
+    ("dagger", "\u{2020}"),
+    ("Dagger", "\u{2021}"),
+    ("prime", "\u{2032}"),
+    ("larr", "\u{2190}"),
+    ("rarr", "\u{2192}"),
+    ("uarr", "\u{2191}"),
+    ("darr", "\u{2193}"),
+    ("cent", "\u{A2}"),
+    ("pound", "\u{A3}"),
+    ("yen", "\u{A5}"),
+    ("euro", "\u{20AC}"),
+    ("ensp", "\u{2002}"),
+    ("emsp", "\u{2003}"),
+    ("thinsp", "\u{2009}"),
+    ("zwnj", "\u{200C}"),
+    ("zwj", "\u{200D}"),
+];
+
+/// Longest reference looked for, in bytes, not counting `&` and `;`
+const MAX_REFERENCE: usize = 32;
+
+/// Controls which character references [`decode`] and [`DecodeEntities`] replace
+///
+/// By default, named and numeric references are decoded in text and attribute values,
+/// but not in CDATA sections.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityOptions {
+    /// Decode named references, like `&amp;`
+    pub named: bool,
+
+    /// Decode numeric references, like `&#233;` or `&#xE9;`
+    pub numeric: bool,
+
+    /// Decode text nodes
+    pub text: bool,
+
+    /// Decode attribute values
+    pub attributes: bool,
+
+    /// Decode the contents of XML CDATA sections, which are normally taken literally
+    pub cdata: bool,
+
+    /// Additional named references, which take priority over the built-in ones
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Default for EntityOptions {
+    fn default() -> Self {
+        Self {
+            named: true,
+            numeric: true,
+            text: true,
+            attributes: true,
+            cdata: false,
+            custom: BTreeMap::new(),
+        }
+    }
+}
+
+impl EntityOptions {
+    /// Creates the default options
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named reference, so `&name;` decodes to `value`
+    #[must_use]
+    pub fn with_entity(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.custom.insert(name.into(), value.into());
+        self
+    }
+
+    fn named(&self, name: &str) -> Option<&str> {
+        if let Some(value) = self.custom.get(name) {
+            return Some(value);
+        }
+
+        if !self.named {
+            return None;
+        }
+
+        NAMED.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+}
+
+/// Replaces character references in `text`
+///
+/// References must end with a semicolon. Unknown references are left as they are, and numeric
+/// references to invalid code points decode to U+FFFD.
+///
+/// # Example
+/// ```rust
+/// # use soupy::entities::{decode, EntityOptions};
+/// let options = EntityOptions::new().with_entity("product", "Soupy");
+/// assert_eq!(decode("&product; &copy; 2024 &#8212; &unknown;", &options), "Soupy \u{a9} 2024 \u{2014} &unknown;");
+/// ```
+#[must_use]
+pub fn decode<'a>(text: &'a str, options: &EntityOptions) -> Cow<'a, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Bounded, so text full of stray ampersands is not scanned repeatedly
+        let end = rest
+            .bytes()
+            .skip(1)
+            .take(MAX_REFERENCE)
+            .position(|b| b == b';');
+
+        let decoded = end.and_then(|end| {
+            let reference = &rest[1..=end];
+            let value = match reference.strip_prefix('#') {
+                Some(number) if options.numeric => Cow::Owned(numeric(number)?.to_string()),
+                Some(_) => return None,
+                None => Cow::Borrowed(options.named(reference)?),
+            };
+
+            Some((value, end + 2))
+        });
+
+        if let Some((value, len)) = decoded {
+            out.push_str(&value);
+            rest = &rest[len..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn numeric(number: &str) -> Option<char> {
+    let code = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => number.parse(),
+    };
+
+    match code {
+        Ok(0) => Some(char::REPLACEMENT_CHARACTER),
+        Ok(code) => Some(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)),
+        Err(e) if *e.kind() == IntErrorKind::PosOverflow => Some(char::REPLACEMENT_CHARACTER),
+        Err(_) => None,
+    }
+}
+
+/// Nodes that can have their character references decoded
+///
+/// Implement this for the node type of a custom [`Parser`](crate::parser::Parser) to use
+/// [`decode_entities`] with it.
+pub trait DecodeEntities {
+    /// The decoded node
+    type Output;
+
+    /// Decodes the character references in the node and its children
+    #[must_use]
+    fn decode_entities(self, options: &EntityOptions) -> Self::Output;
+}
+
+impl<N> DecodeEntities for Soup<N>
+where
+    N: DecodeEntities,
+{
+    type Output = Soup<N::Output>;
+
+    fn decode_entities(self, options: &EntityOptions) -> Self::Output {
+        Soup::from_nodes(
+            self.nodes
+                .into_iter()
+                .map(|n| n.decode_entities(options))
+                .collect(),
+        )
+    }
+}
+
+/// Decodes the character references in every node of `soup`
+///
+/// Useful for parsers that leave references as they are, like the strict HTML parser.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{entities::{decode_entities, EntityOptions}, prelude::*};
+/// let soup = Soup::html_strict(r#"<a title="Fish &amp; Chips">Fish &amp; Chips</a>"#).unwrap();
+///
+/// let options = EntityOptions { attributes: false, ..EntityOptions::default() };
+/// let soup = decode_entities(soup, &options);
+///
+/// let a = soup.tag("a").first().unwrap();
+/// assert_eq!(a.all_text(), "Fish & Chips");
+/// assert_eq!(a.get_str("title"), Some("Fish &amp; Chips"));
+/// ```
+#[must_use]
+pub fn decode_entities<N>(soup: Soup<N>, options: &EntityOptions) -> Soup<N::Output>
+where
+    N: DecodeEntities,
+{
+    soup.decode_entities(options)
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict",
+    feature = "xml"
+))]
+fn decode_if(text: &str, enabled: bool, options: &EntityOptions) -> String {
+    if enabled {
+        decode(text, options).into_owned()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict",
+    feature = "xml"
+))]
+fn decode_attrs<'s, I>(attrs: I, options: &EntityOptions) -> BTreeMap<String, String>
+where
+    I: IntoIterator<Item = (&'s str, &'s str)>,
+{
+    attrs
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), decode_if(v, options.attributes, options)))
+        .collect()
+}
+
+/// Decodes text and attribute values, leaving raw element content and comments untouched
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl<S> DecodeEntities for HTMLNode<S>
+where
+    S: AsRef<str>,
+{
+    type Output = HTMLNode<String>;
+
+    fn decode_entities(self, options: &EntityOptions) -> Self::Output {
+        let attrs = |attrs: &BTreeMap<S, S>| {
+            decode_attrs(attrs.iter().map(|(k, v)| (k.as_ref(), v.as_ref())), options)
+        };
+
+        match self {
+            HTMLNode::Comment(c) => HTMLNode::Comment(c.as_ref().to_string()),
+            HTMLNode::Doctype(d) => HTMLNode::Doctype(d.as_ref().to_string()),
+            HTMLNode::Element {
+                name,
+                attrs: a,
+                children,
+            } => HTMLNode::Element {
+                name: name.as_ref().to_string(),
+                attrs: attrs(&a),
+                children: children
+                    .into_iter()
+                    .map(|c| c.decode_entities(options))
+                    .collect(),
+            },
+            HTMLNode::RawElement {
+                name,
+                attrs: a,
+                content,
+            } => HTMLNode::RawElement {
+                name: name.as_ref().to_string(),
+                attrs: attrs(&a),
+                content: content.as_ref().to_string(),
+            },
+            HTMLNode::Void { name, attrs: a } => HTMLNode::Void {
+                name: name.as_ref().to_string(),
+                attrs: attrs(&a),
+            },
+            HTMLNode::Text(t) => HTMLNode::Text(decode_if(t.as_ref(), options.text, options)),
+        }
+    }
+}
+
+/// Decodes text and attribute values, and CDATA sections if [`EntityOptions::cdata`] is set
+#[cfg(feature = "xml")]
+impl DecodeEntities for XMLNode {
+    type Output = XMLNode;
+
+    fn decode_entities(self, options: &EntityOptions) -> Self::Output {
+        match self {
+            XMLNode::Element(e) => XMLNode::Element(XMLElement {
+                attributes: decode_attrs(
+                    e.attributes.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                    options,
+                ),
+                children: e
+                    .children
+                    .into_iter()
+                    .map(|c| c.decode_entities(options))
+                    .collect(),
+                ..e
+            }),
+            XMLNode::CData(d) => XMLNode::CData(decode_if(&d, options.cdata, options)),
+            XMLNode::Text(t) => XMLNode::Text(decode_if(&t, options.text, options)),
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let options = EntityOptions::default();
+
+        assert_eq!(decode("plain", &options), Cow::Borrowed("plain"));
+        assert_eq!(decode("&lt;p&gt; &amp;amp;", &options), "<p> &amp;");
+        assert_eq!(decode("&#65;&#x42;&#X43;", &options), "ABC");
+        assert_eq!(
+            decode("&#0;&#xD800;&#99999999999;", &options),
+            "\u{FFFD}".repeat(3)
+        );
+        assert_eq!(
+            decode("a & b &; &#; &#xZ; &amp", &options),
+            "a & b &; &#; &#xZ; &amp"
+        );
+
+        let named = EntityOptions {
+            numeric: false,
+            ..EntityOptions::default()
+        };
+        assert_eq!(decode("&#65;&eacute;&amp;", &named), "&#65;&eacute;&");
+
+        let custom = EntityOptions {
+            named: false,
+            ..EntityOptions::default()
+        }
+        .with_entity("eacute", "\u{E9}");
+        assert_eq!(decode("&eacute;&amp;", &custom), "\u{E9}&amp;");
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_decode_xml_cdata() {
+        use crate::Node;
+
+        let soup = Soup::from_nodes(vec![XMLNode::Element(XMLElement {
+            name: "a".into(),
+            children: vec![XMLNode::CData("&lt;".into()), XMLNode::Text("&lt;".into())],
+            ..XMLElement::default()
+        })]);
+
+        let decoded = decode_entities(soup.clone(), &EntityOptions::default());
+        assert_eq!(decoded.nodes[0].children(), [
+            XMLNode::CData("&lt;".into()),
+            XMLNode::Text("<".into())
+        ]);
+
+        let options = EntityOptions {
+            cdata: true,
+            ..EntityOptions::default()
+        };
+        let decoded = decode_entities(soup, &options);
+        assert_eq!(decoded.nodes[0].children()[0], XMLNode::CData("<".into()));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{
+    parser::{
+        ns::{
+            QName,
+            DUBLIN_CORE,
+            OCF_CONTAINER,
+            OPF,
+        },
+        XMLNode,
+    },
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// Media type of OPF package documents
+pub const PACKAGE_MEDIA_TYPE: &str = "application/oebps-package+xml";
+
+/// A package document listed in `META-INF/container.xml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rootfile {
+    /// Path of the package document, relative to the root of the archive
+    pub full_path: String,
+
+    /// Media type, normally [`PACKAGE_MEDIA_TYPE`]
+    pub media_type: Option<String>,
+}
+
+/// The `META-INF/container.xml` file of an EPUB
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Container {
+    /// Listed package documents, in order
+    pub rootfiles: Vec<Rootfile>,
+}
+
+impl Container {
+    /// Reads a parsed `container.xml`
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let rootfiles = soup
+            .filter(QName::new(OCF_CONTAINER, "rootfile"))
+            .all()
+            .filter_map(|r| {
+                Some(Rootfile {
+                    full_path: r.get("full-path")?.clone(),
+                    media_type: r.get("media-type").cloned(),
+                })
+            })
+            .collect();
+
+        Self { rootfiles }
+    }
+
+    /// Parses `container.xml`
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Path of the first OPF package document, which readers treat as the default rendition
+    #[must_use]
+    pub fn package_path(&self) -> Option<&str> {
+        self.rootfiles
+            .iter()
+            .find(|r| {
+                r.media_type
+                    .as_deref()
+                    .is_none_or(|t| t == PACKAGE_MEDIA_TYPE)
+            })
+            .map(|r| r.full_path.as_str())
+    }
+}
+
+/// Dublin Core metadata from a package document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// `dc:title`s, in order
+    pub titles: Vec<String>,
+    /// `dc:creator`s, in order
+    pub creators: Vec<String>,
+    /// `dc:language`s, in order
+    pub languages: Vec<String>,
+    /// `dc:identifier`s, in order
+    pub identifiers: Vec<String>,
+    /// `dc:publisher`
+    pub publisher: Option<String>,
+    /// `dc:date`
+    pub date: Option<String>,
+    /// `dc:description`
+    pub description: Option<String>,
+}
+
+/// A resource listed in the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    /// Unique id, referenced by the spine
+    pub id: String,
+    /// Path relative to the package document
+    pub href: String,
+    /// Media type, like `application/xhtml+xml`
+    pub media_type: String,
+    /// EPUB 3 properties, like `nav` or `cover-image`
+    pub properties: Vec<String>,
+}
+
+/// An entry of the spine, which lists content documents in reading order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemRef {
+    /// Id of the manifest [`Item`]
+    pub idref: String,
+    /// `false` for auxiliary content that is not part of the linear reading order
+    pub linear: bool,
+}
+
+/// An OPF package document, describing the contents of an EPUB
+///
+/// # Example
+/// ```rust
+/// # use soupy::epub::Package;
+/// let opf = r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+///     <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>A Book</dc:title></metadata>
+///     <manifest>
+///         <item id="c1" href="one.xhtml" media-type="application/xhtml+xml"/>
+///         <item id="c2" href="two.xhtml" media-type="application/xhtml+xml"/>
+///     </manifest>
+///     <spine><itemref idref="c2"/><itemref idref="c1"/></spine>
+/// </package>"#;
+///
+/// let package = Package::parse(opf).unwrap();
+/// assert_eq!(package.metadata.titles, ["A Book"]);
+///
+/// let order = package.reading_order().map(|i| i.href.as_str()).collect::<Vec<_>>();
+/// assert_eq!(order, ["two.xhtml", "one.xhtml"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Package {
+    /// The `version` attribute, like `2.0` or `3.0`
+    pub version: Option<String>,
+    /// Metadata
+    pub metadata: Metadata,
+    /// Every resource in the publication
+    pub manifest: Vec<Item>,
+    /// Content documents in reading order
+    pub spine: Vec<ItemRef>,
+    /// Id of the EPUB 2 NCX table of contents, from the spine's `toc` attribute
+    pub toc: Option<String>,
+    /// Id of the cover image, from EPUB 2 `<meta name="cover">`
+    cover: Option<String>,
+}
+
+impl Package {
+    /// Reads a parsed package document
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let package = soup.filter(QName::new(OPF, "package")).first();
+        let spine = soup.filter(QName::new(OPF, "spine")).first();
+
+        let dc = |name| {
+            soup.filter(QName::new(DUBLIN_CORE, name))
+                .all()
+                .map(|e| e.all_text().trim().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let first = |name| dc(name).into_iter().next();
+
+        Self {
+            version: package.and_then(|p| p.get("version").cloned()),
+            metadata: Metadata {
+                titles: dc("title"),
+                creators: dc("creator"),
+                languages: dc("language"),
+                identifiers: dc("identifier"),
+                publisher: first("publisher"),
+                date: first("date"),
+                description: first("description"),
+            },
+            manifest: soup
+                .filter(QName::new(OPF, "item"))
+                .all()
+                .filter_map(|item| {
+                    Some(Item {
+                        id: item.get("id")?.clone(),
+                        href: item.get("href")?.clone(),
+                        media_type: item.get("media-type").cloned().unwrap_or_default(),
+                        properties: item
+                            .get("properties")
+                            .map(|p| p.split_ascii_whitespace().map(String::from).collect())
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect(),
+            spine: soup
+                .filter(QName::new(OPF, "itemref"))
+                .all()
+                .filter_map(|itemref| {
+                    Some(ItemRef {
+                        idref: itemref.get("idref")?.clone(),
+                        linear: itemref.get("linear").is_none_or(|l| l != "no"),
+                    })
+                })
+                .collect(),
+            toc: spine.and_then(|s| s.get("toc").cloned()),
+            cover: soup
+                .filter(QName::new(OPF, "meta"))
+                .attr("name", "cover")
+                .first()
+                .and_then(|m| m.get("content").cloned()),
+        }
+    }
+
+    /// Parses a package document
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Returns the manifest item with the given id
+    #[must_use]
+    pub fn item(&self, id: &str) -> Option<&Item> {
+        self.manifest.iter().find(|i| i.id == id)
+    }
+
+    /// Manifest items of the linear spine entries, in reading order
+    pub fn reading_order(&self) -> impl Iterator<Item = &Item> {
+        self.spine
+            .iter()
+            .filter(|r| r.linear)
+            .filter_map(|r| self.item(&r.idref))
+    }
+
+    /// The EPUB 3 navigation document
+    #[must_use]
+    pub fn nav(&self) -> Option<&Item> {
+        self.item_with_property("nav")
+    }
+
+    /// The cover image, from EPUB 3 properties or EPUB 2 metadata
+    #[must_use]
+    pub fn cover(&self) -> Option<&Item> {
+        self.item_with_property("cover-image")
+            .or_else(|| self.item(self.cover.as_deref()?))
+    }
+
+    fn item_with_property(&self, property: &str) -> Option<&Item> {
+        self.manifest
+            .iter()
+            .find(|i| i.properties.iter().any(|p| p == property))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container() {
+        let container = Container::parse(
+            r#"<?xml version="1.0"?>
+            <container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+                <rootfiles>
+                    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+                </rootfiles>
+            </container>"#,
+        )
+        .expect("Failed to parse container");
+
+        assert_eq!(container.package_path(), Some("OEBPS/content.opf"));
+        assert!(Container::parse("<container>").is_err());
+    }
+
+    #[test]
+    fn test_package() {
+        let package = Package::parse(
+            r#"<?xml version="1.0"?>
+            <opf:package xmlns:opf="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="id">
+                <opf:metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+                    <dc:title>Main</dc:title>
+                    <dc:title>Subtitle</dc:title>
+                    <dc:creator> An Author </dc:creator>
+                    <dc:language>en</dc:language>
+                    <dc:identifier id="id">urn:isbn:123</dc:identifier>
+                    <opf:meta name="cover" content="img"/>
+                </opf:metadata>
+                <opf:manifest>
+                    <opf:item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+                    <opf:item id="img" href="cover.jpg" media-type="image/jpeg"/>
+                    <opf:item id="a" href="a.xhtml" media-type="application/xhtml+xml"/>
+                    <opf:item id="b" href="b.xhtml" media-type="application/xhtml+xml"/>
+                </opf:manifest>
+                <opf:spine toc="ncx">
+                    <opf:itemref idref="a"/>
+                    <opf:itemref idref="notes" />
+                    <opf:itemref idref="b" linear="no"/>
+                </opf:spine>
+            </opf:package>"#,
+        )
+        .expect("Failed to parse package");
+
+        assert_eq!(package.version.as_deref(), Some("2.0"));
+        assert_eq!(package.metadata.titles, ["Main", "Subtitle"]);
+        assert_eq!(package.metadata.creators, ["An Author"]);
+        assert_eq!(package.metadata.identifiers, ["urn:isbn:123"]);
+        assert_eq!(package.metadata.publisher, None);
+        assert_eq!(package.toc.as_deref(), Some("ncx"));
+        assert_eq!(package.cover().map(|i| i.href.as_str()), Some("cover.jpg"));
+        assert_eq!(package.nav(), None);
+
+        let order = package.reading_order().map(|i| &i.id).collect::<Vec<_>>();
+        assert_eq!(order, ["a"]);
+        assert_eq!(package.spine.len(), 3);
+    }
+}
@@ -0,0 +1,49 @@
+use core::fmt::{
+    self,
+    Write,
+};
+
+/// Writes `s` as text content, escaping `&`, `<`, and `>`
+pub(crate) fn text<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    escape(w, s, |c| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        _ => None,
+    })
+}
+
+/// Writes `s` as a double-quoted attribute value, escaping `&`, `<`, and `"`
+pub(crate) fn attr<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    escape(w, s, |c| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '"' => Some("&quot;"),
+        _ => None,
+    })
+}
+
+/// Writes `s` as a double-quoted attribute value, escaping only `"`
+///
+/// For values that are still source text, where any character references are already encoded.
+pub(crate) fn quote<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    escape(w, s, |c| (c == '"').then_some("&quot;"))
+}
+
+fn escape<W, F>(w: &mut W, s: &str, f: F) -> fmt::Result
+where
+    W: Write,
+    F: Fn(char) -> Option<&'static str>,
+{
+    let mut last = 0;
+
+    for (i, c) in s.char_indices() {
+        if let Some(entity) = f(c) {
+            w.write_str(&s[last..i])?;
+            w.write_str(entity)?;
+            last = i + c.len_utf8();
+        }
+    }
+
+    w.write_str(&s[last..])
+}
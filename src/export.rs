@@ -0,0 +1,322 @@
+use alloc::{
+    boxed::Box,
+    string::String,
+};
+use core::fmt::{
+    self,
+    Write,
+};
+
+use crate::{
+    filter::Filter,
+    query::{
+        QueryItem,
+        QueryIter,
+    },
+    Node,
+};
+
+type Extractor<'c, N> = Box<dyn Fn(&N) -> Option<String> + 'c>;
+
+/// A field extracted from each matched node, like a column of a table
+pub struct Column<'c, N> {
+    name: &'c str,
+    extract: Extractor<'c, N>,
+}
+
+impl<'c, N> Column<'c, N>
+where
+    N: Node,
+{
+    /// A column named `name` holding whatever `extract` returns for each node
+    ///
+    /// Nodes for which `extract` returns `None` have an empty field in CSV, or `null` in NDJSON.
+    pub fn new<F>(name: &'c str, extract: F) -> Self
+    where
+        F: Fn(&N) -> Option<String> + 'c,
+    {
+        Self {
+            name,
+            extract: Box::new(extract),
+        }
+    }
+
+    /// A column holding the value of the attribute `attr`
+    #[must_use]
+    pub fn attr(name: &'c str, attr: &'c str) -> Self
+    where
+        N::Text: AsRef<str>,
+    {
+        Self::new(name, move |node| node.get_str(attr).map(String::from))
+    }
+
+    /// A column holding all the text inside each node
+    #[must_use]
+    pub fn text(name: &'c str) -> Self
+    where
+        N::Text: fmt::Display,
+    {
+        Self::new(name, |node| Some(node.all_text()))
+    }
+
+    /// A column holding all the text of the first descendant matching `filter`
+    #[must_use]
+    pub fn select<F>(name: &'c str, filter: F) -> Self
+    where
+        N::Text: fmt::Display,
+        F: Filter<N> + 'c,
+    {
+        Self::new(name, move |node| {
+            node.children()
+                .iter()
+                .flat_map(Node::descendants)
+                .find(|n| filter.matches(n))
+                .map(Node::all_text)
+        })
+    }
+
+    /// Name of the column, used as its header
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Extracts the column's value from `node`
+    #[must_use]
+    pub fn extract(&self, node: &N) -> Option<String> {
+        (self.extract)(node)
+    }
+}
+
+impl<N> fmt::Debug for Column<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Column")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Writes `field` to `w`, quoting it if it contains a delimiter, quote, or line break
+fn csv_field<W: Write>(w: &mut W, field: &str) -> fmt::Result {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return w.write_str(field);
+    }
+
+    w.write_char('"')?;
+    for (i, part) in field.split('"').enumerate() {
+        if i > 0 {
+            w.write_str("\"\"")?;
+        }
+        w.write_str(part)?;
+    }
+    w.write_char('"')
+}
+
+/// Writes one CSV row per item, after a header row with the names of `columns`
+///
+/// Fields are separated by commas and quoted as described in RFC 4180, and each row ends
+/// with `\n`. Missing values are written as empty fields.
+///
+/// # Errors
+/// If writing to `writer` fails.
+pub fn to_csv<'x, N, I, W>(items: I, columns: &[Column<'_, N>], writer: &mut W) -> fmt::Result
+where
+    N: Node + 'x,
+    I: IntoIterator<Item = QueryItem<'x, N>>,
+    W: Write,
+{
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            writer.write_char(',')?;
+        }
+        csv_field(writer, column.name)?;
+    }
+    writer.write_char('\n')?;
+
+    for item in items {
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                writer.write_char(',')?;
+            }
+            csv_field(writer, &column.extract(item.node()).unwrap_or_default())?;
+        }
+        writer.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Writes `s` as a JSON string, escaping quotes, backslashes, and control characters
+fn json_string<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    w.write_char('"')?;
+
+    for c in s.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            c if u32::from(c) < 0x20 => write!(w, "\\u{:04x}", u32::from(c))?,
+            c => w.write_char(c)?,
+        }
+    }
+
+    w.write_char('"')
+}
+
+/// Writes one JSON object per item, each on its own line, as newline-delimited JSON
+///
+/// Each object has a key for every column, in order. Missing values are written as `null`.
+///
+/// # Errors
+/// If writing to `writer` fails.
+pub fn to_ndjson<'x, N, I, W>(items: I, columns: &[Column<'_, N>], writer: &mut W) -> fmt::Result
+where
+    N: Node + 'x,
+    I: IntoIterator<Item = QueryItem<'x, N>>,
+    W: Write,
+{
+    for item in items {
+        writer.write_char('{')?;
+
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                writer.write_char(',')?;
+            }
+            json_string(writer, column.name)?;
+            writer.write_char(':')?;
+
+            match column.extract(item.node()) {
+                Some(value) => json_string(writer, &value)?,
+                None => writer.write_str("null")?,
+            }
+        }
+
+        writer.write_str("}\n")?;
+    }
+
+    Ok(())
+}
+
+impl<N, F> QueryIter<'_, N, F>
+where
+    N: Node,
+    F: Filter<N>,
+{
+    /// Writes every matching item to `writer` as a CSV row, like [`to_csv`]
+    ///
+    /// # Errors
+    /// If writing to `writer` fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{export::Column, filter::Attr, prelude::*};
+    /// let soup = Soup::html_strict(r#"
+    ///     <tr data-id="1"><td class="name">Widget</td><td class="price">$5</td></tr>
+    ///     <tr data-id="2"><td class="name">Gadget, large</td></tr>
+    /// "#).unwrap();
+    ///
+    /// let mut csv = String::new();
+    /// soup.tag("tr").all().to_csv(&mut csv, &[
+    ///     Column::attr("id", "data-id"),
+    ///     Column::select("name", Attr { name: "class", value: "name" }),
+    ///     Column::select("price", Attr { name: "class", value: "price" }),
+    /// ]).unwrap();
+    ///
+    /// assert_eq!(csv, "id,name,price\n1,Widget,$5\n2,\"Gadget, large\",\n");
+    /// ```
+    pub fn to_csv<W>(self, writer: &mut W, columns: &[Column<'_, N>]) -> fmt::Result
+    where
+        W: Write,
+    {
+        to_csv(self, columns, writer)
+    }
+
+    /// Writes every matching item to `writer` as a line of JSON, like [`to_ndjson`]
+    ///
+    /// # Errors
+    /// If writing to `writer` fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{export::Column, prelude::*};
+    /// let soup = Soup::html_strict(r#"<a href="/home">Home</a><a>"Quoted"</a>"#).unwrap();
+    ///
+    /// let mut ndjson = String::new();
+    /// soup.tag("a").all().to_ndjson(&mut ndjson, &[
+    ///     Column::attr("url", "href"),
+    ///     Column::text("label"),
+    /// ]).unwrap();
+    ///
+    /// assert_eq!(ndjson, concat!(
+    ///     r#"{"url":"/home","label":"Home"}"#, "\n",
+    ///     r#"{"url":null,"label":"\"Quoted\""}"#, "\n",
+    /// ));
+    /// ```
+    pub fn to_ndjson<W>(self, writer: &mut W, columns: &[Column<'_, N>]) -> fmt::Result
+    where
+        W: Write,
+    {
+        to_ndjson(self, columns, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Queryable,
+        Soup,
+    };
+
+    #[test]
+    fn test_csv() {
+        let soup = Soup::html_strict(
+            r#"<a href="/a" title='Say "hi"'>One</a><a href="/b">Two
+lines</a><a>Three</a>"#,
+        )
+        .expect("Failed to parse HTML");
+
+        let columns = [
+            Column::attr("href", "href"),
+            Column::attr("title", "title"),
+            Column::text("text"),
+            Column::new("length", |node: &crate::parser::HTMLNode<&str>| {
+                Some(alloc::format!("{}", node.all_text().len()))
+            }),
+        ];
+
+        let mut csv = String::new();
+        soup.tag("a")
+            .all()
+            .to_csv(&mut csv, &columns)
+            .expect("Failed to write CSV");
+        assert_eq!(
+            csv,
+            "href,title,text,length\n/a,\"Say \"\"hi\"\"\",One,3\n/b,,\"Two\nlines\",9\n,,Three,5\n"
+        );
+        assert_eq!(columns[2].name(), "text");
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let soup =
+            Soup::html_strict("<p data-x='a\\b'>Tab\tand\u{1}</p>").expect("Failed to parse HTML");
+
+        let mut ndjson = String::new();
+        soup.tag("p")
+            .all()
+            .to_ndjson(&mut ndjson, &[
+                Column::attr("x", "data-x"),
+                Column::text("text"),
+            ])
+            .expect("Failed to write NDJSON");
+        assert_eq!(ndjson, "{\"x\":\"a\\\\b\",\"text\":\"Tab\\tand\\u0001\"}\n");
+
+        let mut empty = String::new();
+        to_ndjson(soup.tag("b").all(), &[Column::text("text")], &mut empty)
+            .expect("Failed to write NDJSON");
+        assert!(empty.is_empty());
+    }
+}
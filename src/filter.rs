@@ -1,4 +1,17 @@
+use alloc::boxed::Box;
+#[cfg(feature = "xml")]
+use alloc::string::String;
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+use crate::parser::HTMLNode;
+#[cfg(feature = "xml")]
+use crate::parser::XMLNode;
 use crate::{
+    pattern::Lookup,
     Node,
     Pattern,
 };
@@ -7,6 +20,44 @@ use crate::{
 pub trait Filter<N> {
     /// Matches the `Filter` with the [`Node`]
     fn matches(&self, node: &N) -> bool;
+
+    /// Returns `false` if no descendant of `node` can match, so recursive queries skip them
+    ///
+    /// Skipped descendants are not passed to [`Filter::matches`]. Defaults to `true`.
+    fn can_match_descendants(&self, node: &N) -> bool {
+        let _ = node;
+        true
+    }
+}
+
+/// A filter whose type is chosen at runtime, like one returned by
+/// [`Query::parse`](crate::query::Query::parse)
+pub type DynFilter<N> = Box<dyn Filter<N> + Send + Sync>;
+
+impl<N, F> Filter<N> for Box<F>
+where
+    F: Filter<N> + ?Sized,
+{
+    fn matches(&self, node: &N) -> bool {
+        (**self).matches(node)
+    }
+
+    fn can_match_descendants(&self, node: &N) -> bool {
+        (**self).can_match_descendants(node)
+    }
+}
+
+impl<N, F> Filter<N> for &F
+where
+    F: Filter<N> + ?Sized,
+{
+    fn matches(&self, node: &N) -> bool {
+        (**self).matches(node)
+    }
+
+    fn can_match_descendants(&self, node: &N) -> bool {
+        (**self).can_match_descendants(node)
+    }
 }
 
 impl<N> Filter<N> for () {
@@ -16,6 +67,7 @@ impl<N> Filter<N> for () {
 }
 
 /// Returns `true` if `A && B`
+#[derive(Debug)]
 pub struct And<A, B>(pub A, pub B);
 
 impl<N, A, B> Filter<N> for And<A, B>
@@ -26,9 +78,14 @@ where
     fn matches(&self, node: &N) -> bool {
         self.0.matches(node) && self.1.matches(node)
     }
+
+    fn can_match_descendants(&self, node: &N) -> bool {
+        self.0.can_match_descendants(node) && self.1.can_match_descendants(node)
+    }
 }
 
 /// Returns `true` if `A || B`
+#[derive(Debug)]
 pub struct Or<A, B>(pub A, pub B);
 
 impl<N, A, B> Filter<N> for Or<A, B>
@@ -39,9 +96,53 @@ where
     fn matches(&self, node: &N) -> bool {
         self.0.matches(node) || self.1.matches(node)
     }
+
+    fn can_match_descendants(&self, node: &N) -> bool {
+        self.0.can_match_descendants(node) || self.1.can_match_descendants(node)
+    }
+}
+
+/// Matches like `filter`, without searching inside nodes matched by `subtrees`
+///
+/// Nodes matched by `subtrees` are still tested against `filter` themselves.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{filter::{Prune, Tag}, prelude::*};
+/// let soup = Soup::html_strict(r#"<nav><a href="/">Home</a></nav><p><a href="/more">More</a></p>"#).unwrap();
+///
+/// let mut links = soup.filter(Prune { filter: Tag { tag: "a" }, subtrees: Tag { tag: "nav" } }).all();
+/// assert_eq!(links.next().and_then(|a| a.get("href").copied()), Some("/more"));
+/// assert!(links.next().is_none());
+///
+/// // The `a` inside the `nav` was never visited
+/// assert_eq!(links.stats().visited, 4);
+/// ```
+#[derive(Debug)]
+pub struct Prune<F, P> {
+    /// Filter for matching nodes
+    pub filter: F,
+
+    /// Filter for nodes whose descendants are skipped
+    pub subtrees: P,
+}
+
+impl<N, F, P> Filter<N> for Prune<F, P>
+where
+    F: Filter<N>,
+    P: Filter<N>,
+{
+    fn matches(&self, node: &N) -> bool {
+        self.filter.matches(node)
+    }
+
+    fn can_match_descendants(&self, node: &N) -> bool {
+        !self.subtrees.matches(node) && self.filter.can_match_descendants(node)
+    }
 }
 
 /// Filters elements by attribute
+#[derive(Debug)]
 pub struct Attr<N, V> {
     /// Attribute name pattern
     pub name: N,
@@ -59,12 +160,8 @@ where
 {
     fn matches(&self, node: &T) -> bool {
         if let Some(attrs) = node.attrs() {
-            if let Some(name) = self.name.value() {
-                if let Some(value) = attrs.get(&name) {
-                    self.value.matches(value)
-                } else {
-                    false
-                }
+            if let Lookup::Key(value) = self.name.lookup(attrs) {
+                value.is_some_and(|v| self.value.matches(v))
             } else {
                 for (name, value) in attrs {
                     if self.name.matches(name) && self.value.matches(value) {
@@ -81,6 +178,7 @@ where
 }
 
 /// Filters elements by tag
+#[derive(Debug)]
 pub struct Tag<P> {
     /// Tag pattern
     pub tag: P,
@@ -99,3 +197,90 @@ where
         }
     }
 }
+
+/// Filters raw elements, like `<script>` and `<style>`, by their unparsed content
+///
+/// # Example
+/// ```rust
+/// # use soupy::{filter::RawContent, prelude::*};
+/// # #[cfg(feature = "regex")] {
+/// let soup = Soup::html_strict(r#"<script src="app.js"></script><script>window.__DATA__ = {"id": 1};</script>"#).unwrap();
+///
+/// let pattern = regex::Regex::new(r"window\.__DATA__\s*=").unwrap();
+/// let script = soup.filter(RawContent { content: pattern }).first().expect("Couldn't find data");
+///
+/// let data = script.raw_content().and_then(|c| c.split_once('=')).map(|(_, json)| json.trim());
+/// assert_eq!(data, Some(r#"{"id": 1};"#));
+/// # }
+/// ```
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+#[derive(Debug)]
+pub struct RawContent<P> {
+    /// Content pattern
+    pub content: P,
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl<S, P> Filter<HTMLNode<S>> for RawContent<P>
+where
+    S: AsRef<str>,
+    P: Pattern<S>,
+{
+    fn matches(&self, node: &HTMLNode<S>) -> bool {
+        node.raw_content().is_some_and(|c| self.content.matches(c))
+    }
+}
+
+/// Filters comment nodes by their content
+///
+/// The content includes any whitespace inside the delimiters, so `<!-- a -->` has the content
+/// `" a "`.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{filter::Comment, prelude::*};
+/// # #[cfg(feature = "regex")] {
+/// let soup = Soup::html_strict("<div><!-- BEGIN ad-slot --><p>Ad</p><!-- END ad-slot --><!-- note --></div>").unwrap();
+///
+/// let markers = regex::Regex::new(r"^\s*(BEGIN|END) ad-slot").unwrap();
+/// assert_eq!(soup.filter(Comment { content: markers }).all().count(), 2);
+/// assert_eq!(soup.filter(Comment { content: " note " }).all().count(), 1);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Comment<P> {
+    /// Content pattern
+    pub content: P,
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl<S, P> Filter<HTMLNode<S>> for Comment<P>
+where
+    P: Pattern<S>,
+{
+    fn matches(&self, node: &HTMLNode<S>) -> bool {
+        matches!(node, HTMLNode::Comment(c) if self.content.matches(c))
+    }
+}
+
+#[cfg(feature = "xml")]
+impl<P> Filter<XMLNode> for Comment<P>
+where
+    P: Pattern<String>,
+{
+    fn matches(&self, node: &XMLNode) -> bool {
+        matches!(node, XMLNode::Comment(c) if self.content.matches(c))
+    }
+}
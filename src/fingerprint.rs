@@ -0,0 +1,210 @@
+use crate::{
+    query::QueryItem,
+    Node,
+    Soup,
+};
+
+/// What to include in a structural hash
+///
+/// Element names, attribute names, and the shape of the tree are always included.
+/// Nodes other than elements and text, like comments, are always ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashOptions {
+    /// Include the content of text nodes
+    ///
+    /// Text nodes that are only whitespace are ignored either way.
+    pub text: bool,
+
+    /// Include attribute values
+    pub attribute_values: bool,
+}
+
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self {
+            text: true,
+            attribute_values: true,
+        }
+    }
+}
+
+impl HashOptions {
+    /// Hashes only the structure: element names, attribute names, and where text appears
+    #[must_use]
+    pub fn structure() -> Self {
+        Self {
+            text: false,
+            attribute_values: false,
+        }
+    }
+}
+
+/// 64-bit FNV-1a, chosen because it is stable across platforms, builds, and crate versions
+pub(crate) struct Fnv(pub(crate) u64);
+
+impl Fnv {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub(crate) fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Writes a length-prefixed string, so adjacent strings cannot run together
+    pub(crate) fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+
+    fn node<N>(&mut self, node: &N, options: HashOptions)
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        if let Some(name) = node.name() {
+            self.write(b"<");
+            self.write_str(name.as_ref());
+
+            for (key, value) in node.attrs().into_iter().flatten() {
+                self.write(b"=");
+                self.write_str(key.as_ref());
+
+                if options.attribute_values {
+                    self.write_str(value.as_ref());
+                }
+            }
+
+            for child in node.children() {
+                self.node(child, options);
+            }
+
+            self.write(b">");
+        } else if let Some(text) = node.text() {
+            let text = text.as_ref();
+
+            if !text.trim().is_empty() {
+                self.write(b"#");
+
+                if options.text {
+                    self.write_str(text);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn hash<'a, N>(nodes: impl IntoIterator<Item = &'a N>, options: HashOptions) -> u64
+where
+    N: Node + 'a,
+    N::Text: AsRef<str>,
+{
+    let mut fnv = Fnv::new();
+
+    for node in nodes {
+        fnv.node(node, options);
+    }
+
+    fnv.0
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Computes a structural hash of the item and everything inside it
+    ///
+    /// Hashes are stable across runs and platforms, so they can be stored and compared
+    /// between crawls.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{fingerprint::HashOptions, prelude::*};
+    /// let soup = Soup::html_strict(r#"<div class="ad"><a href="/a">Buy</a></div><div class="ad"><a href="/b">Sell</a></div>"#).unwrap();
+    /// let ads = soup.tag("div").all().collect::<Vec<_>>();
+    ///
+    /// assert_ne!(ads[0].hash(&HashOptions::default()), ads[1].hash(&HashOptions::default()));
+    /// assert_eq!(ads[0].hash(&HashOptions::structure()), ads[1].hash(&HashOptions::structure()));
+    /// ```
+    #[must_use]
+    pub fn hash(&self, options: &HashOptions) -> u64 {
+        hash([self.node()], *options)
+    }
+}
+
+impl<N> Soup<N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Computes a structural hash of the whole document, like [`QueryItem::hash`]
+    #[must_use]
+    pub fn fingerprint(&self, options: &HashOptions) -> u64 {
+        hash(&self.nodes, *options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_hash() {
+        let fingerprint = |html, options| {
+            Soup::html_strict(html)
+                .expect("Failed to parse HTML")
+                .fingerprint(&options)
+        };
+
+        let all = HashOptions::default();
+        let no_text = HashOptions { text: false, ..all };
+        let structure = HashOptions::structure();
+
+        let page = r#"<ul id="a"><li>One</li></ul>"#;
+        assert_eq!(fingerprint(page, all), fingerprint(page, all));
+        assert_eq!(
+            fingerprint(page, all),
+            fingerprint("<ul id=\"a\">\n  <li>One</li>\n</ul><!-- c -->", all)
+        );
+        assert_ne!(
+            fingerprint(page, all),
+            fingerprint(r#"<ul id="a"><li>Two</li></ul>"#, all)
+        );
+        assert_eq!(
+            fingerprint(page, no_text),
+            fingerprint(r#"<ul id="a"><li>Two</li></ul>"#, no_text)
+        );
+        assert_ne!(
+            fingerprint(page, no_text),
+            fingerprint(r#"<ul id="b"><li>One</li></ul>"#, no_text)
+        );
+        assert_eq!(
+            fingerprint(page, structure),
+            fingerprint(r#"<ul id="b"><li>Two</li></ul>"#, structure)
+        );
+        assert_ne!(
+            fingerprint(page, structure),
+            fingerprint("<ul><li>One</li></ul>", structure)
+        );
+        assert_ne!(
+            fingerprint(page, structure),
+            fingerprint(r#"<ul id="a"><li></li></ul>"#, structure)
+        );
+        assert_ne!(
+            fingerprint("<a>bc</a>", all),
+            fingerprint("<a>b</a><a>c</a>", all)
+        );
+
+        let soup = Soup::html_strict(page).expect("Failed to parse HTML");
+        let ul = soup.tag("ul").first().expect("Couldn't find ul");
+        assert_eq!(ul.hash(&all), soup.fingerprint(&all));
+        assert_eq!(fingerprint("", all), Fnv::OFFSET);
+    }
+}
@@ -0,0 +1,246 @@
+use alloc::{
+    collections::BTreeMap,
+    vec::Vec,
+};
+use core::{
+    cell::OnceCell,
+    fmt,
+    ops::Range,
+};
+
+use crate::{
+    Node,
+    Soup,
+    SoupRef,
+};
+
+/// A document stored as parallel arrays, one entry per node
+///
+/// Names, text, attributes and the tree's shape are kept in separate columns, in breadth-first
+/// order so the children of every node are next to each other. Analytics that only need one
+/// column, like counting element names over many documents, scan a single contiguous array
+/// instead of chasing pointers through the tree.
+///
+/// The regular [`Node`] and [`Queryable`](crate::Queryable) API is available through
+/// [`FlatSoup::view`], which borrows the `FlatSoup` for as long as its nodes are in use.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{flat::FlatSoup, prelude::*};
+/// let soup = Soup::html_strict("<ul><li>One</li><li>Two</li></ul><p>Three</p>").unwrap();
+/// let flat = FlatSoup::new(&soup);
+///
+/// let elements = flat.names().iter().flatten().count();
+/// assert_eq!(elements, 4);
+///
+/// let items = flat.view().tag("li").all().map(|li| li.all_text()).collect::<Vec<_>>();
+/// assert_eq!(items, ["One", "Two"]);
+/// ```
+pub struct FlatSoup<'x, S> {
+    names: Vec<Option<S>>,
+    texts: Vec<Option<S>>,
+    attrs: Vec<Option<BTreeMap<S, S>>>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Range<usize>>,
+    roots: usize,
+    nodes: OnceCell<Vec<FlatNode<'x, S>>>,
+}
+
+impl<'x, S> FlatSoup<'x, S> {
+    /// Copies every node of `soup` into columns
+    #[must_use]
+    pub fn new<N>(soup: &Soup<N>) -> Self
+    where
+        N: Node<Text = S>,
+        S: Clone,
+    {
+        let mut order = soup.nodes.iter().collect::<Vec<_>>();
+        let mut parents = alloc::vec![None; order.len()];
+        let mut children = Vec::with_capacity(order.len());
+
+        let mut i = 0;
+        while let Some(node) = order.get(i).copied() {
+            let start = order.len();
+            order.extend(node.children());
+            parents.resize(order.len(), Some(i));
+            children.push(start..order.len());
+            i += 1;
+        }
+
+        Self {
+            names: order.iter().map(|n| n.name().cloned()).collect(),
+            texts: order.iter().map(|n| n.text().cloned()).collect(),
+            attrs: order.iter().map(|n| n.attrs().cloned()).collect(),
+            parents,
+            children,
+            roots: soup.nodes.len(),
+            nodes: OnceCell::new(),
+        }
+    }
+
+    /// Number of nodes in the document
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if the document has no nodes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Name of each node, for elements
+    #[must_use]
+    pub fn names(&self) -> &[Option<S>] {
+        &self.names
+    }
+
+    /// Direct text content of each node, for text nodes
+    #[must_use]
+    pub fn texts(&self) -> &[Option<S>] {
+        &self.texts
+    }
+
+    /// Attributes of each node, for elements
+    #[must_use]
+    pub fn attrs(&self) -> &[Option<BTreeMap<S, S>>] {
+        &self.attrs
+    }
+
+    /// Index of each node's parent, or `None` for top-level nodes
+    #[must_use]
+    pub fn parents(&self) -> &[Option<usize>] {
+        &self.parents
+    }
+
+    /// Indexes of each node's direct children
+    #[must_use]
+    pub fn children(&self) -> &[Range<usize>] {
+        &self.children
+    }
+
+    /// Borrows the document as regular nodes, which can be queried like a [`Soup`]
+    #[must_use]
+    pub fn view(&'x self) -> SoupRef<'x, FlatNode<'x, S>> {
+        SoupRef::from(&self.nodes()[..self.roots])
+    }
+
+    fn nodes(&'x self) -> &'x [FlatNode<'x, S>] {
+        self.nodes.get_or_init(|| {
+            (0..self.len())
+                .map(|index| FlatNode { soup: self, index })
+                .collect()
+        })
+    }
+}
+
+impl<S> fmt::Debug for FlatSoup<'_, S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlatSoup")
+            .field("names", &self.names)
+            .field("texts", &self.texts)
+            .field("attrs", &self.attrs)
+            .field("parents", &self.parents)
+            .field("children", &self.children)
+            .field("roots", &self.roots)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A node of a [`FlatSoup`], returned by [`FlatSoup::view`]
+pub struct FlatNode<'x, S> {
+    soup: &'x FlatSoup<'x, S>,
+    index: usize,
+}
+
+impl<S> FlatNode<'_, S> {
+    /// Index of the node in the columns of its [`FlatSoup`]
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<S> Clone for FlatNode<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for FlatNode<'_, S> {}
+
+impl<S> fmt::Debug for FlatNode<'_, S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlatNode")
+            .field("index", &self.index)
+            .field("name", &self.name())
+            .field("text", &self.text())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Node for FlatNode<'_, S> {
+    type Text = S;
+
+    fn name(&self) -> Option<&S> {
+        self.soup.names[self.index].as_ref()
+    }
+
+    fn text(&self) -> Option<&S> {
+        self.soup.texts[self.index].as_ref()
+    }
+
+    fn attrs(&self) -> Option<&BTreeMap<S, S>> {
+        self.soup.attrs[self.index].as_ref()
+    }
+
+    fn children(&self) -> &[Self] {
+        &self.soup.nodes()[self.soup.children[self.index].clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_flat() {
+        let soup = Soup::html_strict(
+            r#"<div id="a"><p>One <b>two</b></p><p>Three</p></div><div id="b"><p>Four</p></div>"#,
+        )
+        .expect("Failed to parse HTML");
+        let flat = FlatSoup::new(&soup);
+
+        assert_eq!(flat.len(), soup.iter().count());
+        assert_eq!(&flat.names()[..5], [
+            Some("div"),
+            Some("div"),
+            Some("p"),
+            Some("p"),
+            Some("p")
+        ]);
+        assert_eq!(flat.parents()[..3], [None, None, Some(0)]);
+        assert_eq!(flat.children()[0], 2..4);
+
+        let tree = soup.iter().map(|n| n.all_text()).collect::<Vec<_>>();
+        let view = flat.view().iter().map(|n| n.all_text()).collect::<Vec<_>>();
+        assert_eq!(tree, view);
+
+        let div = flat
+            .view()
+            .attr("id", "b")
+            .first()
+            .expect("Couldn't find div");
+        let p = div.query_ref().tag("p").first().expect("Couldn't find p");
+        assert_eq!(p.all_text(), "Four");
+        assert_eq!(p.node().index(), 4);
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{
+    parser::XMLNode,
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// Returns the first child element of `node` named `name`
+fn child<'x>(node: &'x XMLNode, name: &str) -> Option<&'x XMLNode> {
+    node.children()
+        .iter()
+        .find(|c| c.name().is_some_and(|n| n == name))
+}
+
+/// Returns the children of `node` named `name`
+fn children<'x>(node: &'x XMLNode, name: &'x str) -> impl Iterator<Item = &'x XMLNode> {
+    node.children()
+        .iter()
+        .filter(move |c| c.name().is_some_and(|n| n == name))
+}
+
+/// Returns the trimmed text of the first child element of `node` named `name`
+fn child_text(node: &XMLNode, name: &str) -> Option<String> {
+    Some(child(node, name)?.all_text().trim().to_string())
+}
+
+/// A GPX waypoint, route point, or track point
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    /// Latitude, in degrees
+    pub lat: f64,
+    /// Longitude, in degrees
+    pub lon: f64,
+    /// `ele`, the elevation in meters
+    pub ele: Option<f64>,
+    /// `time`, as written
+    pub time: Option<String>,
+    /// `name`
+    pub name: Option<String>,
+}
+
+impl Point {
+    /// Reads a `wpt`, `rtept`, or `trkpt` element, returning `None` if its coordinates are
+    /// missing or invalid
+    #[must_use]
+    pub fn from_node(node: &XMLNode) -> Option<Self> {
+        let coordinate = |name| {
+            node.get(name)?
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|c| c.is_finite())
+        };
+
+        Some(Self {
+            lat: coordinate("lat")?,
+            lon: coordinate("lon")?,
+            ele: child_text(node, "ele").and_then(|e| e.parse().ok()),
+            time: child_text(node, "time"),
+            name: child_text(node, "name"),
+        })
+    }
+}
+
+/// A GPX route, a planned path through points
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Route {
+    /// `name`
+    pub name: Option<String>,
+    /// Route points, in order
+    pub points: Vec<Point>,
+}
+
+/// A GPX track, a recorded path made of segments
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Track {
+    /// `name`
+    pub name: Option<String>,
+    /// Track points of each segment, in order
+    pub segments: Vec<Vec<Point>>,
+}
+
+impl Track {
+    /// Every point of every segment, in order
+    pub fn points(&self) -> impl Iterator<Item = &Point> {
+        self.segments.iter().flatten()
+    }
+}
+
+/// Waypoints, routes, and tracks from a GPX document
+///
+/// Elements are matched by local name, so GPX 1.0 and 1.1 are both read. Points with missing
+/// or invalid coordinates are skipped.
+///
+/// # Example
+/// ```rust
+/// # use soupy::geo::Gpx;
+/// let gpx = Gpx::parse(r#"<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1">
+///     <wpt lat="48.2" lon="16.37"><name>Start</name></wpt>
+///     <trk><name>Run</name><trkseg>
+///         <trkpt lat="48.20" lon="16.37"><ele>171.5</ele></trkpt>
+///         <trkpt lat="48.21" lon="16.38"/>
+///     </trkseg></trk>
+/// </gpx>"#).unwrap();
+///
+/// assert_eq!(gpx.waypoints[0].name.as_deref(), Some("Start"));
+///
+/// let track = &gpx.tracks[0];
+/// assert_eq!(track.points().map(|p| (p.lat, p.lon)).collect::<Vec<_>>(), [(48.20, 16.37), (48.21, 16.38)]);
+/// assert_eq!(track.segments[0][0].ele, Some(171.5));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gpx {
+    /// Waypoints, in order
+    pub waypoints: Vec<Point>,
+    /// Routes, in order
+    pub routes: Vec<Route>,
+    /// Tracks, in order
+    pub tracks: Vec<Track>,
+}
+
+impl Gpx {
+    /// Reads a parsed GPX document
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let points =
+            |node: &XMLNode, name| children(node, name).filter_map(Point::from_node).collect();
+
+        Self {
+            waypoints: soup
+                .tag("wpt")
+                .all()
+                .filter_map(|w| Point::from_node(&w))
+                .collect(),
+            routes: soup
+                .tag("rte")
+                .all()
+                .map(|r| Route {
+                    name: child_text(&r, "name"),
+                    points: points(&r, "rtept"),
+                })
+                .collect(),
+            tracks: soup
+                .tag("trk")
+                .all()
+                .map(|t| Track {
+                    name: child_text(&t, "name"),
+                    segments: children(&t, "trkseg").map(|s| points(s, "trkpt")).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Parses a GPX document
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+}
+
+/// A KML coordinate tuple
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinate {
+    /// Longitude, in degrees
+    pub lon: f64,
+    /// Latitude, in degrees
+    pub lat: f64,
+    /// Altitude, in meters
+    pub alt: Option<f64>,
+}
+
+/// Parses a KML `coordinates` string: `lon,lat[,alt]` tuples separated by whitespace
+///
+/// Returns `None` if any tuple is malformed.
+///
+/// # Example
+/// ```rust
+/// # use soupy::geo::{parse_coordinates, Coordinate};
+/// let coords = parse_coordinates("16.37,48.2 16.38,48.21,180").unwrap();
+/// assert_eq!(coords[1], Coordinate { lon: 16.38, lat: 48.21, alt: Some(180.0) });
+/// ```
+#[must_use]
+pub fn parse_coordinates(s: &str) -> Option<Vec<Coordinate>> {
+    s.split_ascii_whitespace()
+        .map(|tuple| {
+            let mut parts = tuple
+                .split(',')
+                .map(|p| p.parse::<f64>().ok().filter(|c| c.is_finite()));
+
+            let coordinate = Coordinate {
+                lon: parts.next()??,
+                lat: parts.next()??,
+                alt: match parts.next() {
+                    Some(alt) => Some(alt?),
+                    None => None,
+                },
+            };
+
+            parts.next().is_none().then_some(coordinate)
+        })
+        .collect()
+}
+
+/// The shape of a KML placemark
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    /// `Point`
+    Point(Coordinate),
+    /// `LineString`, or a `LinearRing` outside a polygon
+    LineString(Vec<Coordinate>),
+    /// `Polygon`
+    Polygon {
+        /// The `outerBoundaryIs` ring
+        outer: Vec<Coordinate>,
+        /// The `innerBoundaryIs` rings, which are holes
+        inner: Vec<Vec<Coordinate>>,
+    },
+    /// `MultiGeometry`
+    Multi(Vec<Geometry>),
+}
+
+impl Geometry {
+    /// Reads a geometry element, returning `None` for other elements or invalid coordinates
+    #[must_use]
+    pub fn from_node(node: &XMLNode) -> Option<Self> {
+        let coordinates =
+            |node: &XMLNode| parse_coordinates(&child(node, "coordinates")?.all_text());
+        let ring = |boundary: &XMLNode| coordinates(child(boundary, "LinearRing")?);
+
+        match node.name()?.as_str() {
+            "Point" => Some(Self::Point(*coordinates(node)?.first()?)),
+            "LineString" | "LinearRing" => Some(Self::LineString(coordinates(node)?)),
+            "Polygon" => Some(Self::Polygon {
+                outer: ring(child(node, "outerBoundaryIs")?)?,
+                inner: children(node, "innerBoundaryIs")
+                    .map(ring)
+                    .collect::<Option<_>>()?,
+            }),
+            "MultiGeometry" => Some(Self::Multi(
+                node.children().iter().filter_map(Self::from_node).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Every coordinate of the geometry, in order
+    #[must_use]
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        match self {
+            Self::Point(c) => vec![*c],
+            Self::LineString(line) => line.clone(),
+            Self::Polygon { outer, inner } => outer
+                .iter()
+                .chain(inner.iter().flatten())
+                .copied()
+                .collect(),
+            Self::Multi(parts) => parts.iter().flat_map(Self::coordinates).collect(),
+        }
+    }
+}
+
+/// A KML placemark
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placemark {
+    /// `name`
+    pub name: Option<String>,
+    /// `description`
+    pub description: Option<String>,
+    /// The first geometry of the placemark, if it has a supported one
+    pub geometry: Option<Geometry>,
+}
+
+/// Returns the placemarks of a KML document, in document order, including those in folders
+///
+/// # Example
+/// ```rust
+/// # use soupy::{geo::{self, Geometry}, prelude::*};
+/// let soup = Soup::xml(r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document><Folder>
+///     <Placemark><name>Office</name><Point><coordinates>16.37,48.2,0</coordinates></Point></Placemark>
+/// </Folder></Document></kml>"#.as_bytes()).unwrap();
+///
+/// let placemarks = geo::placemarks(&soup);
+/// assert_eq!(placemarks[0].name.as_deref(), Some("Office"));
+/// assert!(matches!(placemarks[0].geometry, Some(Geometry::Point(c)) if c.lat == 48.2));
+/// ```
+#[must_use]
+pub fn placemarks(soup: &Soup<XMLNode>) -> Vec<Placemark> {
+    soup.tag("Placemark")
+        .all()
+        .map(|p| Placemark {
+            name: child_text(&p, "name"),
+            description: child_text(&p, "description"),
+            geometry: p.children().iter().find_map(Geometry::from_node),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpx() {
+        let gpx = Gpx::parse(
+            r#"<?xml version="1.0"?>
+            <gpx version="1.0" xmlns="http://www.topografix.com/GPX/1/0">
+                <wpt lat="1" lon="2"><time>2024-01-01T00:00:00Z</time></wpt>
+                <wpt lat="north" lon="2"/>
+                <rte><name>Plan</name><rtept lat="1" lon="2"/><rtept lat="3" lon="4"/></rte>
+                <trk>
+                    <trkseg><trkpt lat="1" lon="1"/></trkseg>
+                    <trkseg><trkpt lat="2" lon="2"><ele>-3</ele></trkpt><trkpt lat="3" lon="3"/></trkseg>
+                </trk>
+            </gpx>"#,
+        )
+        .expect("Failed to parse GPX");
+
+        assert_eq!(gpx.waypoints.len(), 1);
+        assert_eq!(
+            gpx.waypoints[0].time.as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+        assert_eq!(gpx.routes[0].name.as_deref(), Some("Plan"));
+        assert_eq!(gpx.routes[0].points.len(), 2);
+        assert_eq!(gpx.tracks[0].name, None);
+        assert_eq!(gpx.tracks[0].segments.len(), 2);
+        assert_eq!(gpx.tracks[0].points().count(), 3);
+        assert_eq!(gpx.tracks[0].segments[1][0].ele, Some(-3.0));
+    }
+
+    #[test]
+    fn test_kml() {
+        let soup = Soup::xml(
+            r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document>
+                <Placemark>
+                    <name>Park</name>
+                    <description>Green</description>
+                    <Polygon>
+                        <outerBoundaryIs><LinearRing><coordinates>
+                            0,0 0,1 1,1 0,0
+                        </coordinates></LinearRing></outerBoundaryIs>
+                        <innerBoundaryIs><LinearRing><coordinates>0.2,0.2 0.3,0.3 0.2,0.2</coordinates></LinearRing></innerBoundaryIs>
+                    </Polygon>
+                </Placemark>
+                <Placemark><MultiGeometry>
+                    <Point><coordinates>1,2</coordinates></Point>
+                    <LineString><coordinates>1,2,3 4,5,6</coordinates></LineString>
+                </MultiGeometry></Placemark>
+                <Placemark><Point><coordinates>1,2,3,4</coordinates></Point></Placemark>
+            </Document></kml>"#
+                .as_bytes(),
+        )
+        .expect("Failed to parse KML");
+
+        let placemarks = placemarks(&soup);
+        assert_eq!(placemarks.len(), 3);
+        assert_eq!(placemarks[0].description.as_deref(), Some("Green"));
+
+        let Some(Geometry::Polygon { outer, inner }) = &placemarks[0].geometry else {
+            panic!("Expected a polygon");
+        };
+        assert_eq!(outer.len(), 4);
+        assert_eq!(inner.len(), 1);
+
+        let multi = placemarks[1].geometry.as_ref().expect("Missing geometry");
+        assert_eq!(multi.coordinates().len(), 3);
+        assert_eq!(multi.coordinates()[2], Coordinate {
+            lon: 4.0,
+            lat: 5.0,
+            alt: Some(6.0)
+        });
+
+        assert_eq!(placemarks[2].geometry, None);
+        assert_eq!(parse_coordinates("1,x"), None);
+        assert_eq!(parse_coordinates(""), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,276 @@
+use alloc::{
+    string::String,
+    vec::Vec,
+};
+
+use crate::{
+    Node,
+    Soup,
+};
+
+/// Structural hOCR element classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    /// `ocr_page`
+    Page,
+    /// `ocr_carea`, a block of text
+    Area,
+    /// `ocr_par`
+    Paragraph,
+    /// `ocr_line`, or a line-level `ocr_caption`, `ocr_header`, `ocr_footer` or `ocr_textfloat`
+    Line,
+    /// `ocrx_word`
+    Word,
+}
+
+impl Kind {
+    fn classes(self) -> &'static [&'static str] {
+        match self {
+            Self::Page => &["ocr_page"],
+            Self::Area => &["ocr_carea"],
+            Self::Paragraph => &["ocr_par"],
+            Self::Line => &[
+                "ocr_line",
+                "ocr_caption",
+                "ocr_header",
+                "ocr_footer",
+                "ocr_textfloat",
+            ],
+            Self::Word => &["ocrx_word"],
+        }
+    }
+
+    fn of<N>(node: &N) -> Option<Self>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let class = node.get_str("class")?;
+
+        [
+            Self::Page,
+            Self::Area,
+            Self::Paragraph,
+            Self::Line,
+            Self::Word,
+        ]
+        .into_iter()
+        .find(|kind| {
+            class
+                .split_ascii_whitespace()
+                .any(|c| kind.classes().contains(&c))
+        })
+    }
+}
+
+/// A bounding box in image pixels, from the top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BBox {
+    /// Left edge
+    pub x0: u32,
+    /// Top edge
+    pub y0: u32,
+    /// Right edge
+    pub x1: u32,
+    /// Bottom edge
+    pub y1: u32,
+}
+
+impl BBox {
+    /// Width of the box
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.x1.saturating_sub(self.x0)
+    }
+
+    /// Height of the box
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.y1.saturating_sub(self.y0)
+    }
+
+    /// Whether `other` lies entirely inside this box
+    #[must_use]
+    pub fn contains(&self, other: &BBox) -> bool {
+        self.x0 <= other.x0 && self.y0 <= other.y0 && self.x1 >= other.x1 && self.y1 >= other.y1
+    }
+}
+
+/// A page, area, paragraph, line or word in an hOCR document
+///
+/// # Example
+/// ```rust
+/// # use soupy::{hocr::{self, BBox, Kind}, prelude::*};
+/// let soup = Soup::html_strict(r#"<div class="ocr_page" title="image scan.png; bbox 0 0 800 600">
+///     <span class="ocr_line" title="bbox 10 10 200 30">
+///         <span class="ocrx_word" title="bbox 10 10 90 30; x_wconf 96">Hello</span>
+///         <span class="ocrx_word" title="bbox 100 10 200 30; x_wconf 71">world</span>
+///     </span>
+/// </div>"#).unwrap();
+///
+/// let page = &hocr::elements(&soup, Kind::Page)[0];
+/// assert_eq!(page.property("image"), Some("scan.png"));
+///
+/// let line = &page.elements(Kind::Line)[0];
+/// assert_eq!(line.text(), "Hello world");
+///
+/// let words = line.elements(Kind::Word);
+/// assert_eq!(words[1].bbox(), Some(BBox { x0: 100, y0: 10, x1: 200, y1: 30 }));
+/// assert_eq!(words[1].confidence(), Some(71.0));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Element<'x, N> {
+    /// The underlying node
+    pub node: &'x N,
+
+    /// The kind of element
+    pub kind: Kind,
+}
+
+impl<'x, N> Element<'x, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Wraps `node` if it is a structural hOCR element
+    #[must_use]
+    pub fn new(node: &'x N) -> Option<Self> {
+        Some(Self {
+            node,
+            kind: Kind::of(node)?,
+        })
+    }
+
+    /// The `id` attribute of the element
+    #[must_use]
+    pub fn id(&self) -> Option<&'x str> {
+        self.node.get_str("id")
+    }
+
+    /// Returns the arguments of the property `name` in the element's `title`
+    ///
+    /// Quoted arguments, as used for `image`, are unquoted.
+    #[must_use]
+    pub fn property(&self, name: &str) -> Option<&'x str> {
+        self.node.get_str("title")?.split(';').find_map(|p| {
+            let (key, value) = p.trim().split_once(char::is_whitespace)?;
+            (key == name).then(|| value.trim().trim_matches('"'))
+        })
+    }
+
+    /// The `bbox` property
+    #[must_use]
+    pub fn bbox(&self) -> Option<BBox> {
+        let mut coords = self
+            .property("bbox")?
+            .split_ascii_whitespace()
+            .map(str::parse);
+
+        let mut next = || coords.next()?.ok();
+
+        Some(BBox {
+            x0: next()?,
+            y0: next()?,
+            x1: next()?,
+            y1: next()?,
+        })
+    }
+
+    /// The word confidence from `x_wconf`, from 0 to 100
+    #[must_use]
+    pub fn confidence(&self) -> Option<f32> {
+        self.property("x_wconf")?.parse().ok()
+    }
+
+    /// The text of the element, with words separated by single spaces
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.node
+            .descendants()
+            .filter_map(Node::text)
+            .flat_map(|t| t.as_ref().split_whitespace())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the elements of `kind` inside this element, in document order
+    #[must_use]
+    pub fn elements(&self, kind: Kind) -> Vec<Element<'x, N>> {
+        find(self.node.children(), kind)
+    }
+}
+
+/// Returns the elements of `kind` in an hOCR document, in document order
+#[must_use]
+pub fn elements<N>(soup: &Soup<N>, kind: Kind) -> Vec<Element<'_, N>>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    find(&soup.nodes, kind)
+}
+
+fn find<N>(nodes: &[N], kind: Kind) -> Vec<Element<'_, N>>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    nodes
+        .iter()
+        .flat_map(Node::descendants)
+        .filter_map(Element::new)
+        .filter(|e| e.kind == kind)
+        .collect()
+}
+
+#[cfg(all(test, feature = "html-lenient"))]
+mod tests {
+    use super::*;
+
+    const HOCR: &str = r#"<html><body>
+        <div class="ocr_page" id="page_1" title='image "a b.png"; bbox 0 0 1000 800; ppageno 0'>
+            <div class="ocr_carea" title="bbox 10 10 500 100">
+                <p class="ocr_par" title="bbox 10 10 500 100">
+                    <span class="ocr_line" title="bbox 10 10 500 40; baseline 0 -5">
+                        <span class="ocrx_word" title="bbox 10 10 60 40; x_wconf 90"><strong>Big</strong></span>
+                        <span class="ocrx_word" title="bbox 70 10 160 40; x_wconf 88.5">news</span>
+                    </span>
+                    <span class="ocr_caption" title="bbox 10 60 300 100">
+                        <span class="ocrx_word" title="bbox 10 60 300 100">Caption</span>
+                    </span>
+                </p>
+            </div>
+        </div>
+        <div class="ocr_page" title="bbox 0 0 1000 800"></div>
+    </body></html>"#;
+
+    #[test]
+    fn test_elements() {
+        let soup = Soup::html(HOCR);
+
+        let pages = elements(&soup, Kind::Page);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].id(), Some("page_1"));
+        assert_eq!(pages[0].property("image"), Some("a b.png"));
+        assert_eq!(pages[0].property("ppageno"), Some("0"));
+        assert!(pages[1].elements(Kind::Word).is_empty());
+
+        let lines = pages[0].elements(Kind::Line);
+        let text = lines.iter().map(Element::text).collect::<Vec<_>>();
+        assert_eq!(text, ["Big news", "Caption"]);
+
+        let words = elements(&soup, Kind::Word);
+        assert_eq!(words.len(), 3);
+        assert_eq!(words[1].confidence(), Some(88.5));
+        assert_eq!(words[2].confidence(), None);
+
+        let area = pages[0].elements(Kind::Area)[0]
+            .bbox()
+            .expect("Missing bbox");
+        assert!(words
+            .iter()
+            .filter_map(Element::bbox)
+            .all(|b| area.contains(&b)));
+        assert_eq!((area.width(), area.height()), (490, 90));
+    }
+}
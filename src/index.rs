@@ -0,0 +1,280 @@
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    vec::Vec,
+};
+
+use crate::{
+    filter::{
+        And,
+        Attr,
+        Filter,
+        Or,
+        Tag,
+    },
+    query::{
+        NodeId,
+        Query,
+        QueryItem,
+        Root,
+        Selection,
+    },
+    Node,
+    Pattern,
+    Soup,
+};
+
+/// Lookup tables from element names and attributes to the nodes that have them
+///
+/// Built once per document, an `Index` answers queries for exact tag names and attribute
+/// values without testing every node. Use [`Index::select`] to run a query through it.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{index::Index, prelude::*};
+/// let soup = Soup::html_strict(r#"<ul><li id="a">A</li><li id="b">B</li></ul><p id="b">P</p>"#).unwrap();
+/// let index = Index::new(&soup);
+///
+/// let found = index.select(soup.tag("li").attr("id", "b"));
+/// assert_eq!(found.iter().map(|n| n.all_text()).collect::<Vec<_>>(), ["B"]);
+/// ```
+#[derive(Debug)]
+pub struct Index<'x, N: Node> {
+    root: Root<'x, N>,
+    nodes: Vec<&'x N>,
+    tags: BTreeMap<&'x N::Text, Vec<NodeId>>,
+    attr_names: BTreeMap<&'x N::Text, Vec<NodeId>>,
+    attrs: BTreeMap<&'x N::Text, BTreeMap<&'x N::Text, Vec<NodeId>>>,
+}
+
+impl<'x, N> Index<'x, N>
+where
+    N: Node,
+    N::Text: Ord,
+{
+    /// Indexes every node of `soup`
+    #[must_use]
+    pub fn new(soup: &'x Soup<N>) -> Self {
+        let mut index = Self {
+            root: soup.root(),
+            nodes: Vec::new(),
+            tags: BTreeMap::new(),
+            attr_names: BTreeMap::new(),
+            attrs: BTreeMap::new(),
+        };
+
+        for item in soup {
+            let (id, node) = (item.id(), item.node());
+            index.nodes.push(node);
+
+            if let Some(name) = node.name() {
+                index.tags.entry(name).or_default().push(id);
+            }
+
+            for (name, value) in node.attrs().into_iter().flatten() {
+                index.attr_names.entry(name).or_default().push(id);
+                index
+                    .attrs
+                    .entry(name)
+                    .or_default()
+                    .entry(value)
+                    .or_default()
+                    .push(id);
+            }
+        }
+
+        index
+    }
+
+    /// Number of indexed nodes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the document has no nodes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Elements named `name`, in document order
+    #[must_use]
+    pub fn tag(&self, name: &N::Text) -> &[NodeId] {
+        self.tags.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Elements with an attribute `name`, in document order
+    #[must_use]
+    pub fn attr_name(&self, name: &N::Text) -> &[NodeId] {
+        self.attr_names.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Elements with an attribute `name` set to `value`, in document order
+    #[must_use]
+    pub fn attr(&self, name: &N::Text, value: &N::Text) -> &[NodeId] {
+        self.attrs
+            .get(name)
+            .and_then(|values| values.get(value))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Executes `query`, looking up candidates in the index when its filter allows
+    ///
+    /// Candidates are still tested against the whole filter. Queries that are not recursive
+    /// searches of the indexed document, or whose filter has no [`IndexFilter::candidates`],
+    /// run as usual.
+    #[must_use]
+    pub fn select<F>(&self, query: Query<'x, N, F>) -> Selection<'x, N>
+    where
+        F: IndexFilter<N>,
+    {
+        let indexed = query.recursive && core::ptr::eq(query.nodes, self.root.nodes);
+
+        let Some(candidates) = indexed.then(|| query.filter.candidates(self)).flatten() else {
+            return query.selection();
+        };
+
+        candidates
+            .iter()
+            .map(|&id| (id, self.nodes[id.index()]))
+            .filter(|(_, node)| query.filter.matches(node))
+            .map(|(id, item)| QueryItem {
+                item,
+                id,
+                root: self.root,
+            })
+            .collect()
+    }
+}
+
+/// A [`Filter`] that can narrow its search using an [`Index`]
+///
+/// Custom filters implement this to take part in [`Index::select`]. The default keeps the
+/// filter usable with an index, testing every node.
+pub trait IndexFilter<N>: Filter<N>
+where
+    N: Node,
+{
+    /// Returns every node that might match, in document order, or `None` if the index can't
+    /// narrow the search
+    fn candidates<'i>(&self, index: &'i Index<'_, N>) -> Option<Cow<'i, [NodeId]>> {
+        let _ = index;
+        None
+    }
+}
+
+impl<N> IndexFilter<N> for () where N: Node {}
+
+impl<N, A, B> IndexFilter<N> for And<A, B>
+where
+    N: Node,
+    A: IndexFilter<N>,
+    B: IndexFilter<N>,
+{
+    fn candidates<'i>(&self, index: &'i Index<'_, N>) -> Option<Cow<'i, [NodeId]>> {
+        match (self.0.candidates(index), self.1.candidates(index)) {
+            (Some(a), Some(b)) => Some(Cow::Owned(intersect(&a, &b))),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+impl<N, A, B> IndexFilter<N> for Or<A, B>
+where
+    N: Node,
+    A: IndexFilter<N>,
+    B: IndexFilter<N>,
+{
+    fn candidates<'i>(&self, index: &'i Index<'_, N>) -> Option<Cow<'i, [NodeId]>> {
+        let (a, b) = (self.0.candidates(index)?, self.1.candidates(index)?);
+        Some(Cow::Owned(union(&a, &b)))
+    }
+}
+
+impl<N, P> IndexFilter<N> for Tag<P>
+where
+    N: Node,
+    N::Text: Ord,
+    P: Pattern<N::Text>,
+{
+    fn candidates<'i>(&self, index: &'i Index<'_, N>) -> Option<Cow<'i, [NodeId]>> {
+        Some(Cow::Borrowed(index.tag(&self.tag.value()?)))
+    }
+}
+
+impl<N, Q, V> IndexFilter<N> for Attr<Q, V>
+where
+    N: Node,
+    N::Text: Ord,
+    Q: Pattern<N::Text>,
+    V: Pattern<N::Text>,
+{
+    fn candidates<'i>(&self, index: &'i Index<'_, N>) -> Option<Cow<'i, [NodeId]>> {
+        let name = self.name.value()?;
+
+        Some(Cow::Borrowed(match self.value.value() {
+            Some(value) => index.attr(&name, &value),
+            None => index.attr_name(&name),
+        }))
+    }
+}
+
+fn intersect(a: &[NodeId], b: &[NodeId]) -> Vec<NodeId> {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    short
+        .iter()
+        .filter(|id| long.binary_search(id).is_ok())
+        .copied()
+        .collect()
+}
+
+fn union(a: &[NodeId], b: &[NodeId]) -> Vec<NodeId> {
+    let mut ids = a.iter().chain(b).copied().collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser,
+        Queryable,
+    };
+
+    #[test]
+    fn test_index() {
+        let soup = Soup::html_strict(
+            r#"<div class="a"><p class="b">1</p><p class="a">2</p></div><p class="a">3</p><span>4</span>"#,
+        )
+        .expect("Failed to parse HTML");
+        let index = Index::new(&soup);
+
+        let ids = |s: Selection<'_, parser::HTMLNode<&str>>| {
+            s.iter().map(QueryItem::id).collect::<Vec<_>>()
+        };
+
+        for (class, count) in [("a", 2), ("b", 1), ("c", 0)] {
+            let query = || soup.tag("p").attr("class", class);
+            let found = ids(index.select(query()));
+            assert_eq!(found.len(), count);
+            assert_eq!(found, ids(query().selection()));
+        }
+
+        let either = || {
+            soup.filter(Or(Tag { tag: "span" }, Attr {
+                name: "class",
+                value: "a",
+            }))
+        };
+        assert_eq!(either().filter.candidates(&index).map(|c| c.len()), Some(4));
+        assert_eq!(ids(index.select(either())), ids(either().selection()));
+
+        assert_eq!(index.select(soup.tag("p").attr("class", true)).len(), 3);
+        assert!(soup.strict().tag("p").filter.candidates(&index).is_some());
+        assert_eq!(index.select(soup.strict().tag("p")).len(), 1);
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::{
+    collections::BTreeSet,
+    sync::Arc,
+};
+
+/// A set of shared strings, so that equal text is stored once
+///
+/// Interning the text of a document, with [`Soup::intern`](crate::Soup::intern), turns every
+/// name, attribute, and text node into an [`Arc<str>`] from the pool. Documents with heavy
+/// repetition, like large tables where every cell has the same `class`, then keep one copy of
+/// each distinct string. A pool can be shared between documents with
+/// [`Soup::intern_with`](crate::Soup::intern_with).
+#[derive(Debug, Clone, Default)]
+pub struct TextPool {
+    strings: BTreeSet<Arc<str>>,
+}
+
+impl TextPool {
+    /// Creates an empty pool
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pooled copy of `s`, adding it if this is its first use
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(shared) = self.strings.get(s) {
+            return shared.clone();
+        }
+
+        let shared = Arc::<str>::from(s);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// Number of distinct strings in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if nothing has been interned
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Total length in bytes of the distinct strings in the pool
+    #[must_use]
+    pub fn bytes(&self) -> usize {
+        self.strings.iter().map(|s| s.len()).sum()
+    }
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl<S> crate::Soup<crate::parser::HTMLNode<S>>
+where
+    S: AsRef<str>,
+{
+    /// Converts every string in the document to a shared string, storing equal text once
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<tr><td class="cell">1</td><td class="cell">1</td></tr>"#).unwrap();
+    /// let soup = soup.intern();
+    ///
+    /// let cells = soup.tag("td").all().collect::<Vec<_>>();
+    /// let (a, b) = (cells[0].get_str("class"), cells[1].get_str("class"));
+    /// assert!(std::ptr::eq(a.unwrap(), b.unwrap()));
+    /// ```
+    #[must_use]
+    pub fn intern(self) -> crate::Soup<crate::parser::HTMLNode<Arc<str>>> {
+        self.intern_with(&mut TextPool::new())
+    }
+
+    /// Converts every string in the document to a shared string from `pool`
+    ///
+    /// Using the same pool for many documents shares text between them too.
+    #[must_use]
+    pub fn intern_with(
+        self,
+        pool: &mut TextPool,
+    ) -> crate::Soup<crate::parser::HTMLNode<Arc<str>>> {
+        crate::Soup::from_nodes(
+            self.nodes
+                .into_iter()
+                .map(|node| node.map_text(|s| pool.intern(s.as_ref())))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::HTMLNode,
+        Node,
+        Queryable,
+        Soup,
+    };
+
+    #[test]
+    fn test_intern() {
+        let row = r#"<tr><td class="num">1</td><td class="num">2</td></tr>"#;
+        let mut pool = TextPool::new();
+
+        let a = Soup::html_strict(row)
+            .expect("Failed to parse HTML")
+            .intern_with(&mut pool);
+        assert_eq!(pool.len(), 6);
+        assert_eq!(pool.bytes(), "trtdclassnum12".len());
+
+        let b = Soup::html_strict(&row.replace('2', "3"))
+            .expect("Failed to parse HTML")
+            .intern_with(&mut pool);
+        assert_eq!(pool.len(), 7);
+
+        let first = |soup: &Soup<HTMLNode<Arc<str>>>| {
+            soup.tag("td")
+                .first()
+                .and_then(|td| td.get("class").cloned())
+                .expect("Couldn't find td")
+        };
+        assert!(Arc::ptr_eq(&first(&a), &first(&b)));
+        assert_eq!(
+            a.tag("td")
+                .all()
+                .map(|td| td.all_text())
+                .collect::<alloc::vec::Vec<_>>(),
+            ["1", "2"]
+        );
+        assert!(TextPool::new().is_empty());
+    }
+}
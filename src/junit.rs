@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    parser::XMLNode,
+    Node,
+    Queryable,
+    Soup,
+};
+
+fn elements<'x>(node: &'x XMLNode, name: &'x str) -> impl Iterator<Item = &'x XMLNode> {
+    node.children()
+        .iter()
+        .filter(move |c| c.name().is_some_and(|n| n == name))
+}
+
+/// Parses a duration in seconds, allowing thousands separators like `1,024.5`
+fn seconds(node: &XMLNode) -> Option<f64> {
+    node.get("time")?.replace(',', "").trim().parse().ok()
+}
+
+fn output(node: &XMLNode, name: &str) -> Option<String> {
+    elements(node, name).next().map(Node::all_text)
+}
+
+/// Details of a failed or errored test case
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Failure {
+    /// `message` attribute, usually a one-line summary
+    pub message: Option<String>,
+    /// `type` attribute, like an exception or assertion class
+    pub kind: Option<String>,
+    /// Text content, usually a stack trace or diff
+    pub text: String,
+}
+
+impl Failure {
+    fn from_node(node: &XMLNode) -> Self {
+        Self {
+            message: node.get("message").cloned(),
+            kind: node.get("type").cloned(),
+            text: node.all_text().trim().to_string(),
+        }
+    }
+}
+
+/// Result of a [`TestCase`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The test passed
+    Passed,
+    /// An assertion failed
+    Failed(Failure),
+    /// The test could not run to completion, like an unexpected exception
+    Errored(Failure),
+    /// The test was skipped, with the `message` if any
+    Skipped(Option<String>),
+}
+
+/// A `testcase`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    /// `name`
+    pub name: String,
+    /// `classname`, often the module or class the test is in
+    pub class_name: Option<String>,
+    /// `time`, in seconds
+    pub time: Option<f64>,
+    /// Result, from the first `failure`, `error`, or `skipped` child
+    pub status: Status,
+    /// `system-out` text
+    pub stdout: Option<String>,
+    /// `system-err` text
+    pub stderr: Option<String>,
+}
+
+impl TestCase {
+    fn from_node(node: &XMLNode) -> Self {
+        let status = node
+            .children()
+            .iter()
+            .find_map(|c| match c.name()?.as_str() {
+                "failure" => Some(Status::Failed(Failure::from_node(c))),
+                "error" => Some(Status::Errored(Failure::from_node(c))),
+                "skipped" => Some(Status::Skipped(c.get("message").cloned())),
+                _ => None,
+            })
+            .unwrap_or(Status::Passed);
+
+        Self {
+            name: node.get("name").cloned().unwrap_or_default(),
+            class_name: node.get("classname").cloned(),
+            time: seconds(node),
+            status,
+            stdout: output(node, "system-out"),
+            stderr: output(node, "system-err"),
+        }
+    }
+
+    /// Returns `true` if the test failed or errored
+    #[must_use]
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, Status::Failed(_) | Status::Errored(_))
+    }
+}
+
+/// A `testsuite`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestSuite {
+    /// `name`
+    pub name: String,
+    /// `timestamp`, as written
+    pub timestamp: Option<String>,
+    /// `hostname`
+    pub hostname: Option<String>,
+    /// `time`, in seconds
+    pub time: Option<f64>,
+    /// `properties`, by name
+    pub properties: BTreeMap<String, String>,
+    /// Test cases directly in the suite, in order
+    pub cases: Vec<TestCase>,
+    /// Nested suites, in order
+    pub suites: Vec<TestSuite>,
+}
+
+impl TestSuite {
+    fn from_node(node: &XMLNode) -> Self {
+        Self {
+            name: node.get("name").cloned().unwrap_or_default(),
+            timestamp: node.get("timestamp").cloned(),
+            hostname: node.get("hostname").cloned(),
+            time: seconds(node),
+            properties: elements(node, "properties")
+                .flat_map(|p| elements(p, "property"))
+                .filter_map(|p| {
+                    let value = p.get("value").cloned().unwrap_or_else(|| p.all_text());
+                    Some((p.get("name")?.clone(), value))
+                })
+                .collect(),
+            cases: elements(node, "testcase")
+                .map(TestCase::from_node)
+                .collect(),
+            suites: elements(node, "testsuite")
+                .map(TestSuite::from_node)
+                .collect(),
+        }
+    }
+
+    /// Every test case in this suite and its nested suites, depth first
+    pub fn cases(&self) -> impl Iterator<Item = &TestCase> {
+        let mut stack = vec![self];
+
+        core::iter::from_fn(move || {
+            let suite = stack.pop()?;
+            stack.extend(suite.suites.iter().rev());
+            Some(suite)
+        })
+        .flat_map(|s| &s.cases)
+    }
+}
+
+/// Counts of test cases by status
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    /// Every test case
+    pub tests: usize,
+    /// Failed test cases
+    pub failures: usize,
+    /// Errored test cases
+    pub errors: usize,
+    /// Skipped test cases
+    pub skipped: usize,
+    /// Sum of the test case durations, in seconds
+    pub time: f64,
+}
+
+/// A JUnit-style XML test report, as written by most test runners and CI systems
+///
+/// Both a `testsuites` root and a single `testsuite` root are read.
+///
+/// # Example
+/// ```rust
+/// # use soupy::junit::{Report, Status};
+/// let report = Report::parse(r#"<testsuites>
+///     <testsuite name="parser" tests="2">
+///         <testcase name="parses_empty" classname="tests" time="0.01"/>
+///         <testcase name="parses_nested" classname="tests" time="0.25">
+///             <failure message="assertion failed" type="AssertionError">left: 1, right: 2</failure>
+///         </testcase>
+///     </testsuite>
+/// </testsuites>"#).unwrap();
+///
+/// let failed = report.failures().next().unwrap();
+/// assert_eq!(failed.name, "parses_nested");
+///
+/// let Status::Failed(failure) = &failed.status else { panic!() };
+/// assert_eq!(failure.message.as_deref(), Some("assertion failed"));
+///
+/// let totals = report.totals();
+/// assert_eq!((totals.tests, totals.failures), (2, 1));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// Top-level suites, in order
+    pub suites: Vec<TestSuite>,
+}
+
+impl Report {
+    /// Reads a parsed test report
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let suites = match soup.tag("testsuites").first() {
+            Some(root) => elements(&root, "testsuite")
+                .map(TestSuite::from_node)
+                .collect(),
+            None => soup
+                .strict()
+                .tag("testsuite")
+                .all()
+                .map(|s| TestSuite::from_node(&s))
+                .collect(),
+        };
+
+        Self { suites }
+    }
+
+    /// Parses a test report
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Every test case, depth first
+    pub fn cases(&self) -> impl Iterator<Item = &TestCase> {
+        self.suites.iter().flat_map(TestSuite::cases)
+    }
+
+    /// Every failed or errored test case, depth first
+    pub fn failures(&self) -> impl Iterator<Item = &TestCase> {
+        self.cases().filter(|c| c.is_failure())
+    }
+
+    /// Counts the test cases by status
+    ///
+    /// Counts are taken from the test cases themselves, not the `tests` and `failures`
+    /// attributes of the suites, which some tools leave out or get wrong.
+    #[must_use]
+    pub fn totals(&self) -> Totals {
+        self.cases().fold(Totals::default(), |mut totals, case| {
+            totals.tests += 1;
+            totals.time += case.time.unwrap_or_default();
+
+            match case.status {
+                Status::Passed => {}
+                Status::Failed(_) => totals.failures += 1,
+                Status::Errored(_) => totals.errors += 1,
+                Status::Skipped(_) => totals.skipped += 1,
+            }
+
+            totals
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let report = Report::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <testsuite name="root" timestamp="2024-01-02T03:04:05" hostname="ci" time="1,001.5">
+                <properties>
+                    <property name="java.version" value="21"/>
+                    <property name="note">multi
+line</property>
+                </properties>
+                <testcase name="a" time="1000">
+                    <system-out>hello</system-out>
+                </testcase>
+                <testcase name="b" time="1.5"><error type="NullPointerException"/></testcase>
+                <testsuite name="nested">
+                    <testcase name="c"><skipped message="flaky"/></testcase>
+                </testsuite>
+            </testsuite>"#,
+        )
+        .expect("Failed to parse report");
+
+        let suite = &report.suites[0];
+        assert_eq!(suite.time, Some(1001.5));
+        assert_eq!(suite.hostname.as_deref(), Some("ci"));
+        assert_eq!(
+            suite.properties.get("java.version").map(String::as_str),
+            Some("21")
+        );
+        assert_eq!(
+            suite.properties.get("note").map(String::as_str),
+            Some("multi\nline")
+        );
+
+        let cases = report.cases().collect::<Vec<_>>();
+        assert_eq!(cases[0].stdout.as_deref(), Some("hello"));
+        assert_eq!(cases[0].status, Status::Passed);
+        assert_eq!(
+            cases[1].status,
+            Status::Errored(Failure {
+                message: None,
+                kind: Some("NullPointerException".into()),
+                text: String::new(),
+            })
+        );
+        assert_eq!(cases[2].status, Status::Skipped(Some("flaky".into())));
+        assert_eq!(report.failures().count(), 1);
+
+        assert_eq!(report.totals(), Totals {
+            tests: 3,
+            failures: 0,
+            errors: 1,
+            skipped: 1,
+            time: 1001.5,
+        });
+    }
+}
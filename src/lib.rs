@@ -1,23 +1,141 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::pedantic)]
 #![warn(missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 #![doc = include_str!("../README.md")]
 
+extern crate alloc;
+
+/// Reading Android manifests and resource values
+#[cfg(feature = "xml")]
+pub mod android;
+/// Detecting regions repeated across pages, like navigation and footers
+pub mod boilerplate;
+/// Cooperative cancellation of long-running parses and queries
+pub mod cancel;
+/// Reading Checkstyle-format XML lint reports
+#[cfg(feature = "xml")]
+pub mod checkstyle;
+/// `BeautifulSoup`-compatible search API
+pub mod compat;
+mod corpus;
+/// Extracting style sheets and finding the rules that apply to elements
+pub mod css;
+/// Deserialize documents into Rust types with `serde`
+#[cfg(feature = "serde")]
+pub mod de;
+/// Changing documents in place, by the ids of query results
+pub mod edit;
+/// Parsing and cleaning HTML email
+#[cfg(feature = "html-lenient")]
+pub mod email;
+/// Decoding character references after parsing
+pub mod entities;
+/// Readers for EPUB container and package documents
+#[cfg(feature = "xml")]
+pub mod epub;
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict",
+    feature = "xml"
+))]
+mod escape;
+/// Writing query results as CSV rows or NDJSON records
+pub mod export;
 /// Filters for use in search queries
 pub mod filter;
+/// Stable structural hashes of documents and subtrees
+pub mod fingerprint;
+/// Struct-of-arrays document storage for bulk analysis
+pub mod flat;
+/// Typed waypoints, tracks, and placemarks from GPX and KML documents
+#[cfg(feature = "geo")]
+pub mod geo;
+/// Typed access to hOCR documents produced by OCR engines
+pub mod hocr;
+/// Lookup tables for answering queries without scanning every node
+pub mod index;
+/// Sharing equal strings between nodes to save memory
+pub mod intern;
+/// Reading JUnit-style XML test reports
+#[cfg(feature = "xml")]
+pub mod junit;
+/// Extracting outbound references from HTML documents
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub mod links;
+/// Checking HTML documents for common mistakes
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub mod lint;
+/// Reading dependencies from Maven `pom.xml` and .NET project files
+#[cfg(feature = "xml")]
+pub mod manifest;
+/// Hooks for reporting parse and query counters to monitoring systems
+pub mod metrics;
+/// Reading MHTML single-file web archives
+#[cfg(feature = "mhtml")]
+pub mod mhtml;
 mod node;
+/// Reading OPML outlines, like feed and podcast subscription lists
+#[cfg(feature = "xml")]
+pub mod opml;
 /// Parser traits allow you to search different formats.
 pub mod parser;
 mod pattern;
 /// Core functionality. Builds queries for searching
 pub mod query;
+/// Unwrapping SOAP and XML-RPC responses, and detecting faults
+#[cfg(feature = "xml")]
+pub mod rpc;
+/// Inferring which attributes and children a set of elements has
+pub mod schema;
+/// CSS selectors, for [`Queryable::select`]
+pub mod selector;
+/// Comparing the structure of documents and subtrees
+pub mod similarity;
 mod soup;
+/// Reading view boxes, lengths, path data, and references in SVG documents
+pub mod svg;
+/// Building new trees from existing ones, for sanitizers and rewriters
+pub mod transform;
+/// Visiting the nodes of a tree with callbacks, instead of recursing by hand
+pub mod visit;
+/// Reading HTML pages from WARC web archives
+#[cfg(feature = "warc")]
+pub mod warc;
+/// `XPath` expressions, for [`Soup::xpath`]
+pub mod xpath;
 
+#[cfg(all(feature = "serde", feature = "html-lenient"))]
+pub use crate::de::from_html;
+#[cfg(all(feature = "serde", feature = "xml"))]
+pub use crate::de::from_xml;
 pub use crate::{
-    node::Node,
-    pattern::Pattern,
+    corpus::Corpus,
+    node::{
+        AttrsIgnoreCase,
+        Node,
+        ParentIter,
+    },
+    pattern::{
+        Lookup,
+        Pattern,
+        TextPattern,
+    },
     query::Queryable,
-    soup::Soup,
+    soup::{
+        DepthError,
+        Soup,
+        SoupRef,
+    },
 };
 
 /// Prelude: convenient import for all the user-facing APIs provided by the crate
@@ -0,0 +1,200 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{
+    parser::HTMLNode,
+    query::NodeId,
+    Node,
+    Soup,
+};
+
+/// Attributes holding a single URL, and the elements they hold one on (any element if empty)
+const URL_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("href", &["a", "area", "base", "link"]),
+    ("src", &[
+        "audio", "embed", "frame", "iframe", "img", "input", "script", "source", "track", "video",
+    ]),
+    ("poster", &["video"]),
+    ("data", &["object"]),
+    ("action", &["form"]),
+    ("formaction", &["button", "input"]),
+    ("cite", &["blockquote", "del", "ins", "q"]),
+];
+
+/// Attributes holding a list of image candidates, like `a.png 1x, b.png 2x`
+const SRCSET_ATTRIBUTES: &[(&str, &[&str])] =
+    &[("srcset", &["img", "source"]), ("imagesrcset", &["link"])];
+
+/// An outbound reference found by [`links`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Link<'x> {
+    /// The element holding the reference, which can be resolved with [`Soup::get`]
+    pub node: NodeId,
+
+    /// Name of the element, like `img`
+    pub element: &'x str,
+
+    /// Name of the attribute, like `src`
+    pub attribute: &'x str,
+
+    /// The reference as written, with surrounding whitespace removed and not resolved against
+    /// any base URL
+    pub url: &'x str,
+}
+
+impl Link<'_> {
+    /// Returns the byte range of the URL within `source`
+    ///
+    /// Only available when the document borrows its text from `source`, as with
+    /// [`Soup::html_strict`] and [`Soup::html_loose`]. Returns `None` otherwise.
+    #[must_use]
+    pub fn span(&self, source: &str) -> Option<Range<usize>> {
+        let start = (self.url.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+        let end = start + self.url.len();
+
+        (end <= source.len()).then_some(start..end)
+    }
+}
+
+/// Returns every outbound reference in an HTML document, in document order
+///
+/// Covers `href`, `src`, `srcset`, `poster`, `data`, `action`, `formaction` and `cite` on the
+/// elements where they hold URLs. Each `srcset` candidate is a separate [`Link`].
+///
+/// # Example
+/// ```rust
+/// # use soupy::{links, prelude::*};
+/// let html = r#"<a href=" /about ">About</a><img src="a.png" srcset="a.png 1x, a@2x.png 2x">"#;
+/// let soup = Soup::html_strict(html).unwrap();
+///
+/// let found = links::links(&soup);
+/// let urls = found.iter().map(|l| (l.attribute, l.url)).collect::<Vec<_>>();
+/// assert_eq!(urls, [("href", "/about"), ("src", "a.png"), ("srcset", "a.png"), ("srcset", "a@2x.png")]);
+///
+/// let span = found[0].span(html).unwrap();
+/// assert_eq!(&html[span], "/about");
+/// ```
+#[must_use]
+pub fn links<'x, S>(soup: &'x Soup<HTMLNode<S>>) -> Vec<Link<'x>>
+where
+    S: AsRef<str>,
+{
+    let mut links = Vec::new();
+
+    for item in soup {
+        let node = item.node();
+
+        let (Some(element), Some(attrs)) = (node.name(), node.attrs()) else {
+            continue;
+        };
+        let element = element.as_ref();
+
+        for (attribute, value) in attrs {
+            let (attribute, value) = (attribute.as_ref(), value.as_ref());
+
+            let link = |url: &'x str| Link {
+                node: item.id(),
+                element,
+                attribute,
+                url,
+            };
+
+            if applies(URL_ATTRIBUTES, element, attribute) {
+                let url = value.trim_matches(is_space);
+
+                if !url.is_empty() {
+                    links.push(link(url));
+                }
+            } else if applies(SRCSET_ATTRIBUTES, element, attribute) {
+                links.extend(srcset(value).map(link));
+            }
+        }
+    }
+
+    links
+}
+
+fn applies(table: &[(&str, &[&str])], element: &str, attribute: &str) -> bool {
+    table.iter().any(|(a, elements)| {
+        a.eq_ignore_ascii_case(attribute)
+            && elements.iter().any(|e| e.eq_ignore_ascii_case(element))
+    })
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
+
+/// Iterates over the URLs of the candidates in a `srcset`, skipping their descriptors
+pub(crate) fn srcset(mut value: &str) -> impl Iterator<Item = &str> {
+    core::iter::from_fn(move || {
+        value = value.trim_start_matches(|c| is_space(c) || c == ',');
+
+        if value.is_empty() {
+            return None;
+        }
+
+        let end = value.find(is_space).unwrap_or(value.len());
+        let (url, rest) = value.split_at(end);
+
+        value = if url.ends_with(',') {
+            rest
+        } else {
+            rest.find(',').map_or("", |i| &rest[i..])
+        };
+
+        Some(url.trim_end_matches(','))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links() {
+        let html = r#"<HTML><body background="bg.png">
+            <A HREF="https://example.com/">Example</A>
+            <picture><source srcset="wide.webp 800w,narrow.webp 400w, data:image/png;base64,AA== 1x"></picture>
+            <video poster="p.jpg" src=" v.mp4 "><track src="subs.vtt"></video>
+            <object data="doc.pdf"></object><form action="/search"><button formaction="/go">Go</button></form>
+            <blockquote cite="/quote"></blockquote><a href="">Empty</a><link rel="icon" href="favicon.ico">
+        </body></HTML>"#;
+
+        let strict = Soup::html_strict(html).expect("Failed to parse HTML");
+        let found = links(&strict);
+
+        let urls = found.iter().map(|l| l.url).collect::<Vec<_>>();
+        assert_eq!(urls, [
+            "https://example.com/",
+            "wide.webp",
+            "narrow.webp",
+            "data:image/png;base64,AA==",
+            "p.jpg",
+            "v.mp4",
+            "subs.vtt",
+            "doc.pdf",
+            "/search",
+            "/go",
+            "/quote",
+            "favicon.ico",
+        ]);
+
+        for link in &found {
+            let span = link.span(html).expect("Missing span");
+            assert_eq!(&html[span], link.url);
+
+            let node = strict.get(link.node).expect("Invalid node id");
+            assert_eq!(node.name(), Some(&link.element));
+        }
+
+        #[cfg(feature = "html-lenient")]
+        {
+            let lenient = Soup::html(html);
+            let lenient_links = links(&lenient);
+            assert_eq!(lenient_links.len(), found.len());
+            assert_eq!(lenient_links[0].element, "a");
+            assert_eq!(lenient_links[0].span(html), None);
+        }
+    }
+}
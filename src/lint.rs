@@ -0,0 +1,611 @@
+use alloc::{
+    collections::BTreeSet,
+    format,
+    string::{
+        String,
+        ToString,
+    },
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    parser::{
+        html::node::is_custom_element_name,
+        HTMLNode,
+    },
+    query::NodeId,
+    Node,
+    Soup,
+};
+
+/// Elements defined by the HTML standard
+const KNOWN_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "math",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "svg",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+/// Elements the HTML standard lists as obsolete
+const OBSOLETE_ELEMENTS: &[&str] = &[
+    "acronym",
+    "applet",
+    "basefont",
+    "bgsound",
+    "big",
+    "blink",
+    "center",
+    "dir",
+    "font",
+    "frame",
+    "frameset",
+    "isindex",
+    "keygen",
+    "listing",
+    "marquee",
+    "menuitem",
+    "multicol",
+    "nextid",
+    "nobr",
+    "noembed",
+    "noframes",
+    "param",
+    "plaintext",
+    "rb",
+    "rtc",
+    "spacer",
+    "strike",
+    "tt",
+    "xmp",
+];
+
+/// Obsolete attributes, and the elements they are obsolete on (any element if empty)
+const DEPRECATED_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("align", &[]),
+    ("alink", &["body"]),
+    ("background", &[]),
+    ("bgcolor", &[]),
+    ("border", &["img", "object", "table"]),
+    ("cellpadding", &["table"]),
+    ("cellspacing", &["table"]),
+    ("char", &[]),
+    ("charoff", &[]),
+    ("charset", &["a", "link", "script"]),
+    ("clear", &["br"]),
+    ("compact", &["dl", "menu", "ol", "ul"]),
+    ("frameborder", &["iframe"]),
+    ("frame", &["table"]),
+    ("hspace", &["embed", "iframe", "img", "input", "object"]),
+    ("language", &["script"]),
+    ("link", &["body"]),
+    ("longdesc", &["iframe", "img"]),
+    ("marginheight", &["body", "iframe"]),
+    ("marginwidth", &["body", "iframe"]),
+    ("name", &["a", "embed", "img", "option"]),
+    ("nowrap", &["td", "th"]),
+    ("rev", &["a", "link"]),
+    ("rules", &["table"]),
+    ("scrolling", &["iframe"]),
+    ("summary", &["table"]),
+    ("text", &["body"]),
+    ("valign", &[]),
+    ("vlink", &["body"]),
+    ("vspace", &["embed", "iframe", "img", "input", "object"]),
+    ("width", &[
+        "col", "colgroup", "hr", "pre", "table", "td", "th",
+    ]),
+];
+
+/// Elements that close a `<p>`, and so can never be inside one
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "search",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Elements that are not allowed inside `<a>` or `<button>`
+const INTERACTIVE_ELEMENTS: &[&str] = &[
+    "a", "button", "details", "embed", "iframe", "input", "label", "select", "textarea",
+];
+
+/// Elements that may only appear inside specific parents
+const REQUIRED_PARENTS: &[(&str, &[&str])] = &[
+    ("li", &["ol", "ul", "menu"]),
+    ("dt", &["dl", "div"]),
+    ("dd", &["dl", "div"]),
+    ("tr", &["table", "thead", "tbody", "tfoot"]),
+    ("td", &["tr"]),
+    ("th", &["tr"]),
+    ("thead", &["table"]),
+    ("tbody", &["table"]),
+    ("tfoot", &["table"]),
+    ("caption", &["table"]),
+    ("option", &["select", "datalist", "optgroup"]),
+];
+
+/// A check performed by [`check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// Elements that are not defined by HTML, and are not custom elements
+    UnknownElement,
+    /// Elements that HTML lists as obsolete, like `<center>` and `<font>`
+    ObsoleteElement,
+    /// Presentational attributes that HTML lists as obsolete, like `align` and `bgcolor`
+    DeprecatedAttribute,
+    /// An `id` that is already used by an earlier element
+    DuplicateId,
+    /// Elements inside parents that cannot contain them, like `<div>` inside `<p>`
+    InvalidNesting,
+    /// Images without an `alt` attribute
+    MissingAlt,
+}
+
+impl Rule {
+    /// Every rule
+    pub const ALL: [Rule; 6] = [
+        Rule::UnknownElement,
+        Rule::ObsoleteElement,
+        Rule::DeprecatedAttribute,
+        Rule::DuplicateId,
+        Rule::InvalidNesting,
+        Rule::MissingAlt,
+    ];
+
+    /// Name of the rule, like `duplicate-id`
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UnknownElement => "unknown-element",
+            Self::ObsoleteElement => "obsolete-element",
+            Self::DeprecatedAttribute => "deprecated-attribute",
+            Self::DuplicateId => "duplicate-id",
+            Self::InvalidNesting => "invalid-nesting",
+            Self::MissingAlt => "missing-alt",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Rules to check, and elements to accept in addition to those defined by HTML
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintOptions {
+    /// Enabled rules
+    pub rules: BTreeSet<Rule>,
+
+    /// Additional element names that are not reported as unknown, in lowercase
+    pub elements: BTreeSet<String>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            rules: Rule::ALL.into_iter().collect(),
+            elements: BTreeSet::new(),
+        }
+    }
+}
+
+impl LintOptions {
+    /// Creates options with every rule enabled
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Disables `rule`
+    #[must_use]
+    pub fn without(mut self, rule: Rule) -> Self {
+        self.rules.remove(&rule);
+        self
+    }
+
+    /// Accepts the element `name`, like a framework component that is not a custom element
+    #[must_use]
+    pub fn with_element(mut self, name: &str) -> Self {
+        self.elements.insert(name.to_ascii_lowercase());
+        self
+    }
+}
+
+/// A problem found by [`check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The rule that was violated
+    pub rule: Rule,
+
+    /// The offending node, which can be resolved with [`Soup::get`]
+    pub node: NodeId,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
+
+/// Checks an HTML document against the enabled rules, returning diagnostics in document order
+///
+/// Elements inside `<svg>` and `<math>` are only checked for duplicate ids.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{lint::{self, LintOptions, Rule}, prelude::*};
+/// let soup = Soup::html_strict(r#"<p id="a"><div id="a"><img src="x.png"></div></p>"#).unwrap();
+///
+/// let diagnostics = lint::check(&soup, &LintOptions::new());
+/// let rules = diagnostics.iter().map(|d| d.rule).collect::<Vec<_>>();
+/// assert_eq!(rules, [Rule::DuplicateId, Rule::InvalidNesting, Rule::MissingAlt]);
+///
+/// let div = soup.get(diagnostics[1].node).unwrap();
+/// assert_eq!(div.name(), Some(&"div"));
+/// assert_eq!(diagnostics[1].to_string(), "invalid-nesting: <div> is not allowed inside <p>");
+/// ```
+#[must_use]
+pub fn check<S>(soup: &Soup<HTMLNode<S>>, options: &LintOptions) -> Vec<Diagnostic>
+where
+    S: AsRef<str>,
+{
+    let mut linter = Linter {
+        options,
+        index: 0,
+        ancestors: Vec::new(),
+        ids: BTreeSet::new(),
+        diagnostics: Vec::new(),
+    };
+
+    for node in &soup.nodes {
+        linter.visit(node);
+    }
+
+    linter.diagnostics
+}
+
+struct Linter<'a> {
+    options: &'a LintOptions,
+    /// Index of the next node in document order
+    index: usize,
+    /// Lowercase names of the open elements
+    ancestors: Vec<String>,
+    ids: BTreeSet<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn visit<S>(&mut self, node: &HTMLNode<S>)
+    where
+        S: AsRef<str>,
+    {
+        let id = NodeId(self.index);
+        self.index += 1;
+
+        let Some(name) = node.name() else {
+            return;
+        };
+        let name = name.as_ref().to_ascii_lowercase();
+
+        if let Some(value) = node.get_str("id") {
+            if !self.ids.insert(value.to_string()) {
+                self.report(
+                    Rule::DuplicateId,
+                    id,
+                    format!("id \"{value}\" is already used"),
+                );
+            }
+        }
+
+        if !self.in_foreign_content() {
+            self.check_element(node, id, &name);
+        }
+
+        self.ancestors.push(name);
+
+        for child in node.children() {
+            self.visit(child);
+        }
+
+        self.ancestors.pop();
+    }
+
+    fn check_element<S>(&mut self, node: &HTMLNode<S>, id: NodeId, name: &str)
+    where
+        S: AsRef<str>,
+    {
+        if OBSOLETE_ELEMENTS.contains(&name) {
+            self.report(Rule::ObsoleteElement, id, format!("<{name}> is obsolete"));
+        } else if !KNOWN_ELEMENTS.contains(&name)
+            && !is_custom_element_name(name)
+            && !self.options.elements.contains(name)
+        {
+            self.report(
+                Rule::UnknownElement,
+                id,
+                format!("<{name}> is not an HTML element"),
+            );
+        }
+
+        for (attr, _) in node.attrs().into_iter().flatten() {
+            let attr = attr.as_ref();
+
+            if DEPRECATED_ATTRIBUTES.iter().any(|(a, elements)| {
+                a.eq_ignore_ascii_case(attr) && (elements.is_empty() || elements.contains(&name))
+            }) {
+                self.report(
+                    Rule::DeprecatedAttribute,
+                    id,
+                    format!("the {attr} attribute on <{name}> is obsolete"),
+                );
+            }
+        }
+
+        if let Some(parent) = self.invalid_parent(name) {
+            self.report(
+                Rule::InvalidNesting,
+                id,
+                format!("<{name}> is not allowed inside {parent}"),
+            );
+        }
+
+        let needs_alt = match name {
+            "img" => true,
+            "area" => node.get_str("href").is_some(),
+            "input" => node
+                .get_str("type")
+                .is_some_and(|t| t.eq_ignore_ascii_case("image")),
+            _ => false,
+        };
+
+        if needs_alt && node.get_str("alt").is_none() {
+            self.report(
+                Rule::MissingAlt,
+                id,
+                format!("<{name}> has no alt attribute"),
+            );
+        }
+    }
+
+    /// Describes the ancestor that `name` is not allowed inside, like `<p>`
+    fn invalid_parent(&self, name: &str) -> Option<String> {
+        let parent = self.ancestors.last();
+
+        if let Some((_, parents)) = REQUIRED_PARENTS.iter().find(|(n, _)| *n == name) {
+            let allowed = parent.is_some_and(|p| parents.contains(&p.as_str()))
+                || parent.is_some_and(|p| p == "template");
+
+            if !allowed {
+                return Some(
+                    parent.map_or_else(|| "the document root".into(), |p| format!("<{p}>")),
+                );
+            }
+        }
+
+        if BLOCK_ELEMENTS.contains(&name) && parent.is_some_and(|p| p == "p") {
+            return Some("<p>".into());
+        }
+
+        if INTERACTIVE_ELEMENTS.contains(&name) {
+            if let Some(a) = self
+                .ancestors
+                .iter()
+                .rev()
+                .find(|a| *a == "a" || *a == "button")
+            {
+                return Some(format!("<{a}>"));
+            }
+        }
+
+        if name == "form" && self.ancestors.iter().any(|a| a == "form") {
+            return Some("<form>".into());
+        }
+
+        None
+    }
+
+    fn in_foreign_content(&self) -> bool {
+        self.ancestors.iter().any(|a| a == "svg" || a == "math")
+    }
+
+    fn report(&mut self, rule: Rule, node: NodeId, message: String) {
+        if self.options.rules.contains(&rule) {
+            self.diagnostics.push(Diagnostic {
+                rule,
+                node,
+                message,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_check() {
+        let soup = Soup::html_strict(
+            r#"<table align="center" width="100"><tr><td valign="top"><center>Hi</center></td></tr></table>
+            <ul><li><a href="/"><button>Go</button></a></li></ul>
+            <li>Stray</li>
+            <user-card></user-card><widget></widget><x-y/>
+            <svg><foo id="icon"/></svg><span id="icon"></span>
+            <input type="image" src="go.png"><area href="/" alt="Home"><img alt="">"#,
+        )
+        .expect("Failed to parse HTML");
+
+        let messages = check(&soup, &LintOptions::new())
+            .into_iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(messages, [
+            "deprecated-attribute: the align attribute on <table> is obsolete",
+            "deprecated-attribute: the width attribute on <table> is obsolete",
+            "deprecated-attribute: the valign attribute on <td> is obsolete",
+            "obsolete-element: <center> is obsolete",
+            "invalid-nesting: <button> is not allowed inside <a>",
+            "invalid-nesting: <li> is not allowed inside the document root",
+            "unknown-element: <widget> is not an HTML element",
+            "duplicate-id: id \"icon\" is already used",
+            "missing-alt: <input> has no alt attribute",
+        ]);
+
+        let options = LintOptions::new()
+            .without(Rule::DeprecatedAttribute)
+            .without(Rule::InvalidNesting)
+            .with_element("Widget");
+        let diagnostics = check(&soup, &options);
+        assert_eq!(diagnostics.len(), 3);
+
+        let span = soup.get(diagnostics[1].node).expect("Invalid node id");
+        assert_eq!(span.name(), Some(&"span"));
+        assert_eq!(soup.tag("span").first().map(|s| s.id()), Some(span.id()));
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    parser::XMLNode,
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// Returns the first child element of `node` named `name`
+fn child<'x>(node: &'x XMLNode, name: &str) -> Option<&'x XMLNode> {
+    node.children()
+        .iter()
+        .find(|c| c.name().is_some_and(|n| n == name))
+}
+
+/// Returns the trimmed text of the first child element of `node` named `name`
+fn child_text(node: &XMLNode, name: &str) -> Option<String> {
+    Some(child(node, name)?.all_text().trim().to_string())
+}
+
+/// Maven coordinates of a project or its parent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coordinates {
+    /// `groupId`
+    pub group_id: Option<String>,
+    /// `artifactId`
+    pub artifact_id: Option<String>,
+    /// `version`
+    pub version: Option<String>,
+}
+
+/// A `dependency` of a Maven project
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dependency {
+    /// `groupId`
+    pub group_id: String,
+    /// `artifactId`
+    pub artifact_id: String,
+    /// `version`, which may be missing if it is managed by a parent
+    pub version: Option<String>,
+    /// `scope`, like `test` or `provided`
+    pub scope: Option<String>,
+    /// `type`, like `pom` for imported bills of materials
+    pub kind: Option<String>,
+    /// `classifier`
+    pub classifier: Option<String>,
+    /// `true` if the dependency is `optional`
+    pub optional: bool,
+    /// `true` if the dependency is listed in `dependencyManagement`, which pins versions
+    /// without adding the dependency
+    pub managed: bool,
+}
+
+impl Dependency {
+    fn from_node(node: &XMLNode, managed: bool) -> Self {
+        let field = |name| child_text(node, name);
+
+        Self {
+            group_id: field("groupId").unwrap_or_default(),
+            artifact_id: field("artifactId").unwrap_or_default(),
+            version: field("version"),
+            scope: field("scope"),
+            kind: field("type"),
+            classifier: field("classifier"),
+            optional: field("optional").is_some_and(|o| o == "true"),
+            managed,
+        }
+    }
+}
+
+/// A Maven `pom.xml`
+///
+/// Property references like `${spring.version}` are kept as written, and can be expanded with
+/// [`Pom::resolve`].
+///
+/// # Example
+/// ```rust
+/// # use soupy::manifest::Pom;
+/// let pom = Pom::parse(r#"<project xmlns="http://maven.apache.org/POM/4.0.0">
+///     <groupId>com.example</groupId>
+///     <artifactId>app</artifactId>
+///     <version>1.0</version>
+///     <properties><junit.version>5.10.0</junit.version></properties>
+///     <dependencies>
+///         <dependency>
+///             <groupId>org.junit.jupiter</groupId>
+///             <artifactId>junit-jupiter</artifactId>
+///             <version>${junit.version}</version>
+///             <scope>test</scope>
+///         </dependency>
+///     </dependencies>
+/// </project>"#).unwrap();
+///
+/// let junit = &pom.dependencies[0];
+/// assert_eq!(junit.scope.as_deref(), Some("test"));
+/// assert_eq!(pom.resolve(junit.version.as_deref().unwrap()), "5.10.0");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pom {
+    /// Coordinates of the project, with the group and version inherited from the parent if
+    /// missing
+    pub coordinates: Coordinates,
+    /// `packaging`, like `jar` or `pom`
+    pub packaging: Option<String>,
+    /// Coordinates of the `parent` project
+    pub parent: Option<Coordinates>,
+    /// `properties`, by name
+    pub properties: BTreeMap<String, String>,
+    /// `dependencies`, then `dependencyManagement` dependencies, in order
+    pub dependencies: Vec<Dependency>,
+}
+
+impl Pom {
+    /// Reads a parsed `pom.xml`
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let Some(project) = soup.tag("project").first() else {
+            return Self::default();
+        };
+
+        let coordinates = |node: &XMLNode| Coordinates {
+            group_id: child_text(node, "groupId"),
+            artifact_id: child_text(node, "artifactId"),
+            version: child_text(node, "version"),
+        };
+
+        let parent = child(&project, "parent").map(coordinates);
+        let mut own = coordinates(&project);
+
+        if let Some(parent) = &parent {
+            own.group_id = own.group_id.or_else(|| parent.group_id.clone());
+            own.version = own.version.or_else(|| parent.version.clone());
+        }
+
+        let dependencies = |node: Option<&XMLNode>, managed| {
+            node.and_then(|n| child(n, "dependencies"))
+                .map(|deps| {
+                    deps.children()
+                        .iter()
+                        .filter(|d| d.name().is_some_and(|n| n == "dependency"))
+                        .map(|d| Dependency::from_node(d, managed))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+
+        Self {
+            packaging: child_text(&project, "packaging"),
+            properties: child(&project, "properties")
+                .map(|p| {
+                    p.children()
+                        .iter()
+                        .filter_map(|c| Some((c.name()?.clone(), c.all_text().trim().to_string())))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            dependencies: dependencies(Some(&project), false)
+                .into_iter()
+                .chain(dependencies(child(&project, "dependencyManagement"), true))
+                .collect(),
+            coordinates: own,
+            parent,
+        }
+    }
+
+    /// Parses a `pom.xml`
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Expands `${name}` references in `value` with the project's properties
+    ///
+    /// `project.groupId`, `project.artifactId`, and `project.version` are also expanded.
+    /// Unknown references are kept as written.
+    #[must_use]
+    pub fn resolve(&self, value: &str) -> String {
+        let mut out = String::new();
+        let mut rest = value;
+
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+
+            let name = &rest[start + 2..start + len];
+            out.push_str(&rest[..start]);
+
+            let project = match name {
+                "project.groupId" => self.coordinates.group_id.as_deref(),
+                "project.artifactId" => self.coordinates.artifact_id.as_deref(),
+                "project.version" => self.coordinates.version.as_deref(),
+                _ => None,
+            };
+
+            match project.or_else(|| self.properties.get(name).map(String::as_str)) {
+                Some(value) => out.push_str(value),
+                None => out.push_str(&rest[start..=start + len]),
+            }
+
+            rest = &rest[start + len + 1..];
+        }
+
+        out.push_str(rest);
+        out
+    }
+}
+
+/// A package used by a .NET project
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageReference {
+    /// Package id, from `Include` or `id`
+    pub name: String,
+    /// Version or version range, from a `Version` attribute or element
+    pub version: Option<String>,
+    /// `PrivateAssets`, like `all` for development-only packages
+    pub private_assets: Option<String>,
+    /// `targetFramework`, from `packages.config`
+    pub target_framework: Option<String>,
+}
+
+/// Returns the packages referenced by a .NET project file, central package versions
+/// file, or `packages.config`, in order
+///
+/// Reads `PackageReference` and `PackageVersion` items, and `package` elements.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{manifest, prelude::*};
+/// let soup = Soup::xml(r#"<Project Sdk="Microsoft.NET.Sdk">
+///     <ItemGroup>
+///         <PackageReference Include="Newtonsoft.Json" Version="13.0.3" />
+///         <PackageReference Include="coverlet.collector">
+///             <Version>6.0.0</Version>
+///             <PrivateAssets>all</PrivateAssets>
+///         </PackageReference>
+///     </ItemGroup>
+/// </Project>"#.as_bytes()).unwrap();
+///
+/// let packages = manifest::package_references(&soup);
+/// assert_eq!(packages[0].version.as_deref(), Some("13.0.3"));
+/// assert_eq!(packages[1].private_assets.as_deref(), Some("all"));
+/// ```
+#[must_use]
+pub fn package_references(soup: &Soup<XMLNode>) -> Vec<PackageReference> {
+    soup.iter()
+        .filter_map(|node| {
+            let name = node.name()?;
+
+            let (id, version) = match name.as_str() {
+                "PackageReference" | "PackageVersion" => ("Include", "Version"),
+                "package" => ("id", "version"),
+                _ => return None,
+            };
+
+            // Either an attribute or a child element, as MSBuild allows both
+            let field = |name: &str| node.get(name).cloned().or_else(|| child_text(&node, name));
+
+            Some(PackageReference {
+                name: node.get(id).or_else(|| node.get("Update"))?.clone(),
+                version: field(version),
+                private_assets: field("PrivateAssets"),
+                target_framework: node.get("targetFramework").cloned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pom() {
+        let pom = Pom::parse(
+            r#"<?xml version="1.0"?>
+            <project>
+                <parent>
+                    <groupId>org.example</groupId>
+                    <artifactId>parent</artifactId>
+                    <version>2.1</version>
+                </parent>
+                <artifactId>child</artifactId>
+                <packaging>jar</packaging>
+                <dependencyManagement><dependencies>
+                    <dependency><groupId>org.bom</groupId><artifactId>bom</artifactId><version>3</version><type>pom</type><scope>import</scope></dependency>
+                </dependencies></dependencyManagement>
+                <dependencies>
+                    <dependency><groupId>${project.groupId}</groupId><artifactId>core</artifactId><version>${project.version}</version></dependency>
+                    <dependency><groupId>org.lib</groupId><artifactId>lib</artifactId><optional>true</optional></dependency>
+                </dependencies>
+                <build><plugins><plugin><dependencies>
+                    <dependency><groupId>ignored</groupId><artifactId>ignored</artifactId></dependency>
+                </dependencies></plugin></plugins></build>
+            </project>"#,
+        )
+        .expect("Failed to parse POM");
+
+        assert_eq!(pom.coordinates, Coordinates {
+            group_id: Some("org.example".into()),
+            artifact_id: Some("child".into()),
+            version: Some("2.1".into()),
+        });
+        assert_eq!(pom.packaging.as_deref(), Some("jar"));
+        assert_eq!(
+            pom.dependencies
+                .iter()
+                .map(|d| (d.artifact_id.as_str(), d.managed))
+                .collect::<Vec<_>>(),
+            [("core", false), ("lib", false), ("bom", true)]
+        );
+        assert!(pom.dependencies[1].optional);
+        assert_eq!(pom.dependencies[1].version, None);
+        assert_eq!(pom.dependencies[2].kind.as_deref(), Some("pom"));
+        assert_eq!(
+            pom.resolve("${project.groupId}:core:${project.version}:${missing}"),
+            "org.example:core:2.1:${missing}"
+        );
+        assert_eq!(pom.resolve("${open"), "${open");
+    }
+
+    #[test]
+    fn test_package_references() {
+        let soup = Soup::xml(
+            br#"<packages>
+                <package id="jQuery" version="3.7.1" targetFramework="net48" />
+                <package version="1" />
+            </packages>"#
+                .as_slice(),
+        )
+        .expect("Failed to parse XML");
+
+        assert_eq!(package_references(&soup), [PackageReference {
+            name: "jQuery".into(),
+            version: Some("3.7.1".into()),
+            private_assets: None,
+            target_framework: Some("net48".into()),
+        }]);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    parser::Parser,
+    query::Stats,
+    Node,
+    Soup,
+};
+
+/// Receives counters for parses and queries, to be forwarded to a monitoring system
+///
+/// Every method does nothing by default, so implementations only handle the events they
+/// need. Methods take `&self` and may be called from many threads at once, so counters are
+/// usually atomics or handles from a metrics library.
+///
+/// # Example
+/// ```rust
+/// # use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+/// # use soupy::{metrics::Metrics, parser::StrictHTMLParser, prelude::*, query::Stats};
+/// #[derive(Default)]
+/// struct Counters {
+///     bytes: AtomicUsize,
+///     nodes: AtomicUsize,
+///     queries: AtomicUsize,
+///     matches: AtomicUsize,
+/// }
+///
+/// impl Metrics for Counters {
+///     fn parsed(&self, bytes: usize, nodes: usize) {
+///         self.bytes.fetch_add(bytes, Ordering::Relaxed);
+///         self.nodes.fetch_add(nodes, Ordering::Relaxed);
+///     }
+///
+///     fn query(&self, stats: Stats) {
+///         self.queries.fetch_add(1, Ordering::Relaxed);
+///         self.matches.fetch_add(stats.matched, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let counters = Arc::new(Counters::default());
+/// let soup = Soup::new_measured::<StrictHTMLParser>("<ul><li>1</li><li>2</li></ul>", &*counters).unwrap();
+/// assert_eq!(soup.tag("li").all().report_to(counters.clone()).count(), 2);
+///
+/// assert_eq!(counters.bytes.load(Ordering::Relaxed), 29);
+/// assert_eq!(counters.nodes.load(Ordering::Relaxed), 5);
+/// assert_eq!(counters.queries.load(Ordering::Relaxed), 1);
+/// assert_eq!(counters.matches.load(Ordering::Relaxed), 2);
+/// ```
+pub trait Metrics: Send + Sync {
+    /// Called after a document is parsed, with the length of the input and the number of nodes
+    fn parsed(&self, bytes: usize, nodes: usize) {
+        let _ = (bytes, nodes);
+    }
+
+    /// Called when a query reporting to this is dropped, with the work it did
+    ///
+    /// [`Stats::matched`] is the number of items the query returned.
+    fn query(&self, stats: Stats) {
+        let _ = stats;
+    }
+}
+
+impl Soup {
+    /// Uses the [`Parser`] to create a new `Soup`, reporting the input length and node count
+    /// to `metrics`
+    ///
+    /// Nothing is reported if parsing fails.
+    ///
+    /// # Errors
+    /// If the text has an invalid format.
+    pub fn new_measured<P>(
+        input: P::Input,
+        metrics: &dyn Metrics,
+    ) -> Result<Soup<P::Node>, P::Error>
+    where
+        P: Parser,
+        P::Input: AsRef<[u8]>,
+    {
+        let bytes = input.as_ref().len();
+        let soup = Soup::new::<P>(input)?;
+        let nodes = soup.nodes.iter().map(|n| n.descendants().count()).sum();
+
+        metrics.parsed(bytes, nodes);
+        Ok(soup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use core::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+
+    use super::*;
+    use crate::{
+        parser::StrictHTMLParser,
+        Queryable,
+    };
+
+    #[derive(Default)]
+    struct Visited(AtomicUsize);
+
+    impl Metrics for Visited {
+        fn query(&self, stats: Stats) {
+            self.0.fetch_add(stats.visited, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let visited = Arc::new(Visited::default());
+        let soup = Soup::new_measured::<StrictHTMLParser>("<p>1</p><p>2</p>", &*visited)
+            .expect("Failed to parse HTML");
+
+        let first = soup.tag("p").all().report_to(visited.clone()).next();
+        assert!(first.is_some());
+        assert_eq!(visited.0.load(Ordering::Relaxed), 1);
+
+        let _ = soup.tag("b").all().report_to(visited.clone()).count();
+        assert_eq!(visited.0.load(Ordering::Relaxed), 5);
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+
+use html5ever::tendril::StrTendril;
+
+use crate::{
+    parser::HTMLNode,
+    Soup,
+};
+
+/// Error returned when an MHTML document is malformed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MhtmlError(&'static str);
+
+impl fmt::Display for MhtmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid MHTML document: {}", self.0)
+    }
+}
+
+impl std::error::Error for MhtmlError {}
+
+/// One part of an MHTML document, with its body already decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// MIME header fields, in order
+    pub headers: Vec<(String, String)>,
+
+    /// Body, decoded from its `Content-Transfer-Encoding`
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    /// Returns the value of the header field `name`, ignoring case
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The MIME type of the part, without parameters
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("Content-Type")
+            .and_then(|t| t.split(';').next())
+            .map(str::trim)
+    }
+
+    /// The `Content-Location` of the part, usually the URL it was saved from
+    #[must_use]
+    pub fn content_location(&self) -> Option<&str> {
+        self.header("Content-Location")
+    }
+
+    /// The `Content-ID` of the part, without angle brackets
+    #[must_use]
+    pub fn content_id(&self) -> Option<&str> {
+        self.header("Content-ID")
+            .map(|id| id.trim_start_matches('<').trim_end_matches('>'))
+    }
+}
+
+/// A single-file web archive, as saved by browsers as `.mht` or `.mhtml`
+///
+/// # Example
+/// ```rust
+/// # use soupy::{mhtml::Mhtml, prelude::*};
+/// let mht = "MIME-Version: 1.0\r\n\
+///     Content-Type: multipart/related; boundary=\"b\"; type=\"text/html\"\r\n\
+///     \r\n\
+///     --b\r\n\
+///     Content-Type: text/html; charset=utf-8\r\n\
+///     Content-Location: https://example.com/\r\n\
+///     \r\n\
+///     <img src=\"https://example.com/a.png\">\r\n\
+///     --b\r\n\
+///     Content-Type: image/png\r\n\
+///     Content-Transfer-Encoding: base64\r\n\
+///     Content-Location: https://example.com/a.png\r\n\
+///     \r\n\
+///     iVBORw==\r\n\
+///     --b--\r\n";
+///
+/// let mhtml = Mhtml::parse(mht.as_bytes()).unwrap();
+/// let soup = mhtml.soup();
+///
+/// let src = soup.tag("img").first().and_then(|img| img.get("src").cloned()).unwrap();
+/// let image = mhtml.resource(&src).unwrap();
+/// assert_eq!(image.body, b"\x89PNG");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mhtml {
+    /// Every part, in order
+    pub parts: Vec<Part>,
+
+    root: usize,
+}
+
+impl Mhtml {
+    /// Splits an MHTML document into its parts
+    ///
+    /// The root part is the one named by the `start` parameter of the document's `Content-Type`,
+    /// or else the first HTML part.
+    ///
+    /// # Errors
+    /// If the document is not `multipart/related`, or contains no parts.
+    pub fn parse(bytes: &[u8]) -> Result<Self, MhtmlError> {
+        let (headers, body) = split_headers(bytes);
+
+        let content_type = header(&headers, "Content-Type").unwrap_or_default();
+
+        if !content_type
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with("multipart/related")
+        {
+            return Err(MhtmlError("expected a multipart/related document"));
+        }
+
+        let boundary =
+            param(content_type, "boundary").ok_or(MhtmlError("missing multipart boundary"))?;
+
+        let parts = split_parts(body, boundary.as_bytes())
+            .into_iter()
+            .map(|part| {
+                let (headers, body) = split_headers(part);
+
+                let body = match header(&headers, "Content-Transfer-Encoding") {
+                    Some(e) if e.eq_ignore_ascii_case("base64") => decode_base64(body)?,
+                    Some(e) if e.eq_ignore_ascii_case("quoted-printable") => {
+                        decode_quoted_printable(body)
+                    }
+                    _ => body.to_vec(),
+                };
+
+                Ok(Part { headers, body })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let start = param(content_type, "start");
+        let start = start
+            .as_deref()
+            .map(|s| s.trim_start_matches('<').trim_end_matches('>'));
+
+        let root = start
+            .and_then(|start| parts.iter().position(|p| p.content_id() == Some(start)))
+            .or_else(|| {
+                parts.iter().position(|p| {
+                    p.content_type()
+                        .is_some_and(|t| t.eq_ignore_ascii_case("text/html"))
+                })
+            })
+            .or((!parts.is_empty()).then_some(0))
+            .ok_or(MhtmlError("document contains no parts"))?;
+
+        Ok(Self { parts, root })
+    }
+
+    /// The root part, usually the saved HTML page
+    #[must_use]
+    pub fn root(&self) -> &Part {
+        &self.parts[self.root]
+    }
+
+    /// Parses the root part as HTML, decoded with the charset from its `Content-Type`
+    #[must_use]
+    pub fn soup(&self) -> Soup<HTMLNode<StrTendril>> {
+        let root = self.root();
+        Soup::html_with_content_type(&root.body, root.header("Content-Type"))
+    }
+
+    /// Returns the part saved from `location`, or with the given `cid:` URL
+    #[must_use]
+    pub fn resource(&self, location: &str) -> Option<&Part> {
+        match location.strip_prefix("cid:") {
+            Some(id) => self.parts.iter().find(|p| p.content_id() == Some(id)),
+            None => self
+                .parts
+                .iter()
+                .find(|p| p.content_location() == Some(location)),
+        }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Returns the value of the parameter `name` in a header value like `a/b; name="value"`
+fn param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|p| {
+        let (key, value) = p.split_once('=')?;
+
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Splits a MIME entity into its header fields and body
+///
+/// Folded header lines are joined to the field they continue.
+fn split_headers(bytes: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut pos = 0;
+
+    for line in bytes.split_inclusive(|&b| b == b'\n') {
+        pos += line.len();
+
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        if line.is_empty() {
+            return (headers, &bytes[pos..]);
+        }
+
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    (headers, &[])
+}
+
+/// Splits a multipart body on its boundary delimiter lines
+fn split_parts<'a>(body: &'a [u8], boundary: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut start = None;
+    let mut pos = 0;
+
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let end = pos;
+        pos += line.len();
+
+        let Some(rest) = line
+            .strip_prefix(b"--")
+            .and_then(|l| l.strip_prefix(boundary))
+        else {
+            continue;
+        };
+
+        let rest = rest.trim_ascii_end();
+
+        if !rest.is_empty() && rest != b"--" {
+            continue;
+        }
+
+        if let Some(start) = start {
+            // The line break before a delimiter belongs to the delimiter
+            let part = &body[start..end];
+            let part = part.strip_suffix(b"\n").unwrap_or(part);
+            parts.push(part.strip_suffix(b"\r").unwrap_or(part));
+        }
+
+        if rest == b"--" {
+            break;
+        }
+
+        start = Some(pos);
+    }
+
+    parts
+}
+
+fn decode_base64(body: &[u8]) -> Result<Vec<u8>, MhtmlError> {
+    let mut out = Vec::with_capacity(body.len() / 4 * 3);
+    let mut buf = 0u32;
+    let mut bits = 0;
+
+    for &byte in body {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            b if b.is_ascii_whitespace() => continue,
+            _ => return Err(MhtmlError("invalid base64")),
+        };
+
+        buf = buf << 6 | u32::from(value);
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push(u8::try_from(buf >> bits & 0xff).unwrap_or_default());
+        }
+    }
+
+    Ok(out)
+}
+
+fn decode_quoted_printable(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut i = 0;
+
+    while i < body.len() {
+        if body[i] != b'=' {
+            out.push(body[i]);
+            i += 1;
+            continue;
+        }
+
+        let rest = &body[i + 1..];
+
+        if let Some(len) = [&b"\r\n"[..], b"\n"]
+            .iter()
+            .find_map(|soft| rest.starts_with(soft).then_some(soft.len()))
+        {
+            // Soft line break
+            i += 1 + len;
+        } else if let Some(byte) = rest
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    const MHT: &str = "From: <Saved by Blink>\r\n\
+        Subject: Example\r\n\
+        MIME-Version: 1.0\r\n\
+        Content-Type: multipart/related;\r\n\
+        \ttype=\"text/html\";\r\n\
+        \tboundary=\"----MultipartBoundary--abc\"\r\n\
+        \r\n\
+        ------MultipartBoundary--abc\r\n\
+        Content-Type: text/css\r\n\
+        Content-Location: https://example.com/style.css\r\n\
+        \r\n\
+        p { color: red }\r\n\
+        ------MultipartBoundary--abc\r\n\
+        Content-Type: text/html; charset=windows-1252\r\n\
+        Content-ID: <frame-1@mhtml.blink>\r\n\
+        Content-Transfer-Encoding: quoted-printable\r\n\
+        Content-Location: https://example.com/\r\n\
+        \r\n\
+        <p class=3D\"intro\">Caf=E9 =\r\n\
+        au lait</p>\r\n\
+        ------MultipartBoundary--abc\r\n\
+        Content-Type: image/gif\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        Content-ID: <img-1>\r\n\
+        \r\n\
+        R0lG\r\n\
+        ODlh\r\n\
+        ------MultipartBoundary--abc--\r\n";
+
+    #[test]
+    fn test_parse() {
+        let mhtml = Mhtml::parse(MHT.as_bytes()).expect("Failed to parse MHTML");
+
+        assert_eq!(mhtml.parts.len(), 3);
+        assert_eq!(mhtml.root().content_id(), Some("frame-1@mhtml.blink"));
+        assert_eq!(mhtml.parts[0].body, b"p { color: red }");
+
+        let soup = mhtml.soup();
+        let intro = soup.class("intro").first().map(|p| p.all_text());
+        assert_eq!(intro, Some("Caf\u{e9} au lait".into()));
+
+        assert_eq!(
+            mhtml.resource("cid:img-1").map(|p| &p.body[..]),
+            Some(&b"GIF89a"[..])
+        );
+        assert_eq!(
+            mhtml
+                .resource("https://example.com/style.css")
+                .and_then(Part::content_type),
+            Some("text/css")
+        );
+        assert!(mhtml.resource("https://example.com/missing").is_none());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Mhtml::parse(b"Content-Type: text/html\r\n\r\n<p>").is_err());
+        assert!(Mhtml::parse(b"Content-Type: multipart/related\r\n\r\n").is_err());
+        assert!(Mhtml::parse(b"Content-Type: multipart/related; boundary=x\r\n\r\n").is_err());
+        assert_eq!(decode_base64(b"QQ=="), Ok(b"A".to_vec()));
+        assert!(decode_base64(b"Q!").is_err());
+    }
+}
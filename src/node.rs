@@ -1,4 +1,11 @@
-use std::collections::BTreeMap;
+use alloc::{
+    collections::BTreeMap,
+    string::{
+        String,
+        ToString,
+    },
+    vec::Vec,
+};
 
 /// Basic queryable unit of the data structure
 pub trait Node: Sized {
@@ -11,6 +18,13 @@ pub trait Node: Sized {
     /// Returns the direct text content of the node, if any
     fn text(&self) -> Option<&Self::Text>;
 
+    /// Returns the content of the node if it is a comment
+    ///
+    /// Formats without comments, or that drop them while parsing, return `None`.
+    fn comment(&self) -> Option<&Self::Text> {
+        None
+    }
+
     /// Returns the node's attributes as a [`BTreeMap`]
     #[must_use]
     fn attrs(&self) -> Option<&BTreeMap<Self::Text, Self::Text>>;
@@ -33,18 +47,83 @@ pub trait Node: Sized {
         self.attrs().and_then(|a| a.get(&name.into()))
     }
 
+    /// Looks for an attribute named `name` and returns its value as a string slice
+    ///
+    /// Unlike [`Node::get`], the name is compared as a `str` instead of being converted into
+    /// `Self::Text`, so it works the same for `&str`, `String` and `StrTendril` text without
+    /// allocating.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<a href="/about">About</a>"#).unwrap();
+    /// let a = soup.tag("a").first().expect("Couldn't find a");
+    /// assert_eq!(a.get_str("href"), Some("/about"));
+    /// ```
+    #[must_use]
+    fn get_str(&self, name: &str) -> Option<&str>
+    where
+        Self::Text: AsRef<str>,
+    {
+        self.attrs()?
+            .iter()
+            .find(|(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_ref())
+    }
+
+    /// Returns a view of the node's attributes that ignores ASCII case in names
+    ///
+    /// HTML attribute names are case-insensitive, so this finds attributes however the document
+    /// or parser spelled them, like `viewBox` on SVG elements.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<svg VIEWBOX="0 0 10 10"></svg><p DATA-id="1"></p>"#).unwrap();
+    ///
+    /// let svg = soup.tag("svg").first().unwrap();
+    /// assert_eq!(svg.get_str("viewbox"), None);
+    /// assert_eq!(svg.attrs_ignore_case().and_then(|a| a.get("viewbox")), Some("0 0 10 10"));
+    ///
+    /// let p = soup.tag("p").first().unwrap();
+    /// assert_eq!(p.attrs_ignore_case().and_then(|a| a.get("Data-ID")), Some("1"));
+    /// ```
+    fn attrs_ignore_case(&self) -> Option<AttrsIgnoreCase<'_, Self::Text>> {
+        self.attrs().map(|attrs| AttrsIgnoreCase { attrs })
+    }
+
     /// Direct children of the node
     fn children(&self) -> &[Self];
 
     /// Depth-first iterator over children of the node, including the root
-    fn descendants(&self) -> NodeIter<Self> {
+    fn descendants(&self) -> NodeIter<'_, Self> {
         NodeIter::tree(self)
     }
 
+    /// Depth-first iterator over the node and its descendants, each paired with its parent
+    ///
+    /// The node itself is paired with `None`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<ul><li>One</li></ul>").unwrap();
+    /// let ul = soup.tag("ul").first().unwrap();
+    ///
+    /// let pairs = ul
+    ///     .with_parents()
+    ///     .map(|(node, parent)| (node.name().copied(), parent.and_then(|p| p.name().copied())))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(pairs, [(Some("ul"), None), (Some("li"), Some("ul")), (None, Some("li"))]);
+    /// ```
+    fn with_parents(&self) -> ParentIter<'_, Self> {
+        ParentIter::new(core::slice::from_ref(self))
+    }
+
     /// Returns all text content contained within the node's tree
     fn all_text(&self) -> String
     where
-        Self::Text: std::fmt::Display,
+        Self::Text: core::fmt::Display,
     {
         self.descendants()
             .filter_map(|n| n.text())
@@ -54,14 +133,125 @@ pub trait Node: Sized {
     }
 }
 
+/// Attributes of a node, looked up ignoring ASCII case in names
+///
+/// Returned by [`Node::attrs_ignore_case`].
+#[derive(Debug)]
+pub struct AttrsIgnoreCase<'x, S> {
+    attrs: &'x BTreeMap<S, S>,
+}
+
+impl<S> Clone for AttrsIgnoreCase<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for AttrsIgnoreCase<'_, S> {}
+
+impl<'x, S> AttrsIgnoreCase<'x, S>
+where
+    S: AsRef<str>,
+{
+    /// Returns the value of the attribute `name`
+    ///
+    /// An attribute spelled exactly like `name` is preferred over other spellings.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&'x str> {
+        let mut folded = None;
+
+        for (k, v) in self.attrs {
+            let k = k.as_ref();
+
+            if k == name {
+                return Some(v.as_ref());
+            }
+
+            if folded.is_none() && k.eq_ignore_ascii_case(name) {
+                folded = Some(v.as_ref());
+            }
+        }
+
+        folded
+    }
+
+    /// Returns `true` if the node has an attribute `name`
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.attrs
+            .keys()
+            .any(|k| k.as_ref().eq_ignore_ascii_case(name))
+    }
+
+    /// Iterate over attribute names and values, as written in the document
+    pub fn iter(&self) -> impl Iterator<Item = (&'x str, &'x str)> {
+        self.attrs.iter().map(|(k, v)| (k.as_ref(), v.as_ref()))
+    }
+
+    /// Number of attributes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.attrs.len()
+    }
+
+    /// Returns `true` if the node has no attributes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.attrs.is_empty()
+    }
+}
+
+/// Depth-first iterator over nodes paired with their parents
+///
+/// Returned by [`Node::with_parents`] and [`Soup::with_parents`](crate::Soup::with_parents).
+#[derive(Debug, Clone)]
+pub struct ParentIter<'x, N> {
+    stack: Vec<(Option<&'x N>, core::slice::Iter<'x, N>)>,
+}
+
+impl<'x, N> ParentIter<'x, N> {
+    pub(crate) fn new(nodes: &'x [N]) -> Self {
+        Self {
+            stack: alloc::vec![(None, nodes.iter())],
+        }
+    }
+}
+
+impl<'x, N> Iterator for ParentIter<'x, N>
+where
+    N: Node,
+{
+    type Item = (&'x N, Option<&'x N>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (parent, iter) = self.stack.last_mut()?;
+
+            if let Some(node) = iter.next() {
+                let parent = *parent;
+
+                if !node.children().is_empty() {
+                    self.stack.push((Some(node), node.children().iter()));
+                }
+
+                return Some((node, parent));
+            }
+
+            self.stack.pop();
+        }
+    }
+}
+
 pub enum NodeIter<'x, N> {
     Direct {
-        iter: std::slice::Iter<'x, N>,
+        iter: core::slice::Iter<'x, N>,
     },
     Tree {
-        node: &'x N,
-        child: Option<Box<NodeIter<'x, N>>>,
-        next: Option<usize>,
+        root: Option<&'x N>,
+        /// Remaining siblings at each level below the root, deepest last
+        stack: Vec<core::slice::Iter<'x, N>>,
+        /// Whether the children of the node returned last are on top of the stack
+        entered: bool,
     },
 }
 
@@ -69,15 +259,25 @@ impl<'x, N> NodeIter<'x, N>
 where
     N: Node,
 {
-    pub(crate) fn direct(iter: std::slice::Iter<'x, N>) -> Self {
+    pub(crate) fn direct(iter: core::slice::Iter<'x, N>) -> Self {
         Self::Direct { iter }
     }
 
     pub(crate) fn tree(node: &'x N) -> Self {
         Self::Tree {
-            node,
-            child: None,
-            next: None,
+            root: Some(node),
+            stack: Vec::new(),
+            entered: false,
+        }
+    }
+
+    /// Skips the descendants of the node returned last
+    pub(crate) fn skip_children(&mut self) {
+        if let NodeIter::Tree { stack, entered, .. } = self {
+            if *entered {
+                stack.pop();
+                *entered = false;
+            }
         }
     }
 }
@@ -91,27 +291,32 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             NodeIter::Direct { iter } => iter.next(),
-            NodeIter::Tree { node, child, next } => loop {
-                if let Some(c) = child.as_mut() {
-                    if let Some(next) = c.next() {
-                        return Some(next);
-                    }
-
-                    *child = None;
-                } else if let Some(n) = next {
-                    let children = node.children();
-
-                    if let Some(c) = children.get(*n) {
-                        *child = Some(Box::new(Self::tree(c)));
-                        *next = Some(*n + 1);
-                    } else {
-                        return None;
-                    }
-                } else {
-                    *next = Some(0);
-                    return Some(*node);
+            NodeIter::Tree {
+                root,
+                stack,
+                entered,
+            } => {
+                let next = match root.take() {
+                    Some(root) => root,
+                    None => loop {
+                        let top = stack.last_mut()?;
+
+                        if let Some(next) = top.next() {
+                            break next;
+                        }
+
+                        stack.pop();
+                    },
+                };
+
+                *entered = !next.children().is_empty();
+
+                if *entered {
+                    stack.push(next.children().iter());
                 }
-            },
+
+                Some(next)
+            }
         }
     }
 }
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    parser::XMLNode,
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// The `head` of an OPML document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Head {
+    /// `title`
+    pub title: Option<String>,
+    /// `dateCreated`, as written
+    pub date_created: Option<String>,
+    /// `dateModified`, as written
+    pub date_modified: Option<String>,
+    /// `ownerName`
+    pub owner_name: Option<String>,
+    /// `ownerEmail`
+    pub owner_email: Option<String>,
+}
+
+/// An `outline` element, with its nested outlines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outline {
+    /// `text`, the label shown for the outline, or its `title` if `text` is missing
+    pub text: String,
+    /// `type`, like `rss` or `link`
+    pub kind: Option<String>,
+    /// `xmlUrl`, the address of the feed
+    pub xml_url: Option<String>,
+    /// `htmlUrl`, the address of the site the feed belongs to
+    pub html_url: Option<String>,
+    /// Every attribute, including the ones above
+    pub attrs: BTreeMap<String, String>,
+    /// Nested outlines, in order
+    pub children: Vec<Outline>,
+}
+
+impl Outline {
+    fn from_node(node: &XMLNode) -> Self {
+        let get = |name| node.get(name).cloned();
+
+        Self {
+            text: get("text").or_else(|| get("title")).unwrap_or_default(),
+            kind: get("type"),
+            xml_url: get("xmlUrl"),
+            html_url: get("htmlUrl"),
+            attrs: node.attrs().cloned().unwrap_or_default(),
+            children: outlines(node.children()),
+        }
+    }
+
+    /// Returns `true` if the outline subscribes to a feed
+    #[must_use]
+    pub fn is_feed(&self) -> bool {
+        self.xml_url.is_some()
+    }
+
+    /// This outline and every outline nested in it, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &Outline> {
+        let mut stack = vec![self];
+
+        core::iter::from_fn(move || {
+            let outline = stack.pop()?;
+            stack.extend(outline.children.iter().rev());
+            Some(outline)
+        })
+    }
+}
+
+fn outlines(nodes: &[XMLNode]) -> Vec<Outline> {
+    nodes
+        .iter()
+        .filter(|n| n.name().is_some_and(|name| name == "outline"))
+        .map(Outline::from_node)
+        .collect()
+}
+
+/// An OPML document, like a list of podcast or feed subscriptions
+///
+/// # Example
+/// ```rust
+/// # use soupy::opml::Opml;
+/// let opml = Opml::parse(r#"<opml version="2.0">
+///     <head><title>Subscriptions</title></head>
+///     <body>
+///         <outline text="News">
+///             <outline text="Example" type="rss" xmlUrl="https://example.com/feed.xml"/>
+///         </outline>
+///         <outline text="Podcast" type="rss" xmlUrl="https://example.com/podcast.xml"/>
+///     </body>
+/// </opml>"#).unwrap();
+///
+/// assert_eq!(opml.head.title.as_deref(), Some("Subscriptions"));
+/// assert_eq!(opml.outlines.len(), 2);
+///
+/// let feeds = opml.feeds().filter_map(|o| o.xml_url.as_deref()).collect::<Vec<_>>();
+/// assert_eq!(feeds, ["https://example.com/feed.xml", "https://example.com/podcast.xml"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Opml {
+    /// The `version` attribute, like `1.0` or `2.0`
+    pub version: Option<String>,
+    /// Metadata
+    pub head: Head,
+    /// Top-level outlines of the `body`, in order
+    pub outlines: Vec<Outline>,
+}
+
+impl Opml {
+    /// Reads a parsed OPML document
+    #[must_use]
+    pub fn from_soup(soup: &Soup<XMLNode>) -> Self {
+        let opml = soup.tag("opml").first();
+        let head = soup.tag("head").first();
+
+        let field = |name| {
+            head.as_ref()?
+                .children()
+                .iter()
+                .find(|c| c.name().is_some_and(|n| n == name))
+                .map(|e| e.all_text().trim().to_string())
+        };
+
+        Self {
+            version: opml.and_then(|o| o.get("version").cloned()),
+            head: Head {
+                title: field("title"),
+                date_created: field("dateCreated"),
+                date_modified: field("dateModified"),
+                owner_name: field("ownerName"),
+                owner_email: field("ownerEmail"),
+            },
+            outlines: soup
+                .tag("body")
+                .first()
+                .map(|b| outlines(b.children()))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Parses an OPML document
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse(xml: &str) -> Result<Self, xmltree::ParseError> {
+        Ok(Self::from_soup(&Soup::xml(xml.as_bytes())?))
+    }
+
+    /// Every outline, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &Outline> {
+        self.outlines.iter().flat_map(Outline::iter)
+    }
+
+    /// Every outline that subscribes to a feed, depth first
+    pub fn feeds(&self) -> impl Iterator<Item = &Outline> {
+        self.iter().filter(|o| o.is_feed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opml() {
+        let opml = Opml::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <opml version="1.0">
+                <head>
+                    <title> Feeds </title>
+                    <ownerName>Someone</ownerName>
+                </head>
+                <body>
+                    <outline title="Tech">
+                        <outline text="A" xmlUrl="https://a.example/rss" htmlUrl="https://a.example/" category="/x"/>
+                        <outline text="Sub">
+                            <outline text="B" type="rss" xmlUrl="https://b.example/rss"/>
+                        </outline>
+                    </outline>
+                    <outline text="Notes" type="link" url="https://c.example/"/>
+                </body>
+            </opml>"#,
+        )
+        .expect("Failed to parse OPML");
+
+        assert_eq!(opml.version.as_deref(), Some("1.0"));
+        assert_eq!(opml.head.title.as_deref(), Some("Feeds"));
+        assert_eq!(opml.head.owner_name.as_deref(), Some("Someone"));
+        assert_eq!(opml.head.owner_email, None);
+
+        assert_eq!(opml.iter().map(|o| o.text.as_str()).collect::<Vec<_>>(), [
+            "Tech", "A", "Sub", "B", "Notes"
+        ]);
+
+        let a = opml.feeds().next().expect("Couldn't find feed");
+        assert_eq!(a.html_url.as_deref(), Some("https://a.example/"));
+        assert_eq!(a.attrs.get("category").map(String::as_str), Some("/x"));
+        assert_eq!(opml.feeds().count(), 2);
+        assert_eq!(opml.outlines[1].kind.as_deref(), Some("link"));
+
+        assert!(Opml::parse("<opml>").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+
+use html5ever::tendril::StrTendril;
+
+use crate::{
+    parser::{
+        HTMLNode,
+        JSONError,
+        JSONNode,
+        XMLNode,
+    },
+    Soup,
+};
+
+/// Document formats recognized by [`Soup::auto`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Parsed with the [`LenientHTMLParser`](crate::parser::LenientHTMLParser)
+    Html,
+    /// Parsed with the [`XMLParser`](crate::parser::XMLParser)
+    Xml,
+    /// Parsed with the [`JSONParser`](crate::parser::JSONParser)
+    Json,
+}
+
+impl Format {
+    /// Guesses the format of `text`
+    ///
+    /// A recognized MIME type in `content_type` takes priority. Otherwise, leading `{` or `[`
+    /// means JSON, and an XML declaration or a root element with an `xmlns` attribute means XML.
+    /// Everything else, including doctypes, is HTML.
+    #[must_use]
+    pub fn sniff(text: &str, content_type: Option<&str>) -> Self {
+        content_type
+            .and_then(Self::from_mime)
+            .unwrap_or_else(|| Self::from_content(text))
+    }
+
+    fn from_mime(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+
+        match mime.as_str() {
+            "text/html" | "application/xhtml+xml" => Some(Self::Html),
+            "application/json" | "text/json" => Some(Self::Json),
+            "application/xml" | "text/xml" => Some(Self::Xml),
+            m if m.ends_with("+json") => Some(Self::Json),
+            m if m.ends_with("+xml") => Some(Self::Xml),
+            _ => None,
+        }
+    }
+
+    fn from_content(text: &str) -> Self {
+        let mut rest = text.trim_start_matches(['\u{feff}', ' ', '\t', '\n', '\r']);
+
+        if rest.starts_with(['{', '[']) {
+            return Self::Json;
+        }
+
+        if rest.starts_with("<?xml") {
+            return Self::Xml;
+        }
+
+        // Comments may precede the root element
+        while let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment
+                .split_once("-->")
+                .map_or("", |(_, r)| r)
+                .trim_start();
+        }
+
+        let root = rest.split_once('>').map_or(rest, |(tag, _)| tag);
+
+        if rest.starts_with('<')
+            && !rest.starts_with("<!")
+            && root
+                .split_ascii_whitespace()
+                .any(|a| a.starts_with("xmlns"))
+        {
+            Self::Xml
+        } else {
+            Self::Html
+        }
+    }
+}
+
+/// A document parsed by [`Soup::auto`], in whichever format it was detected as
+#[derive(Debug, Clone)]
+pub enum AutoSoup {
+    /// An HTML document
+    Html(Soup<HTMLNode<StrTendril>>),
+    /// An XML document
+    Xml(Soup<XMLNode>),
+    /// A JSON document
+    Json(Soup<JSONNode>),
+}
+
+impl AutoSoup {
+    /// The format the document was parsed as
+    #[must_use]
+    pub fn format(&self) -> Format {
+        match self {
+            Self::Html(_) => Format::Html,
+            Self::Xml(_) => Format::Xml,
+            Self::Json(_) => Format::Json,
+        }
+    }
+}
+
+/// Error returned when a detected format fails to parse
+#[derive(Debug)]
+pub enum AutoError {
+    /// The document was detected as XML, but is malformed
+    Xml(xmltree::ParseError),
+    /// The document was detected as JSON, but is malformed
+    Json(JSONError),
+}
+
+impl fmt::Display for AutoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(e) => write!(f, "invalid XML: {e}"),
+            Self::Json(e) => write!(f, "invalid JSON: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AutoError {}
+
+impl Soup {
+    /// Attempts to create a new `Soup` instance from a document in any supported format.
+    ///
+    /// The format is chosen by [`Format::sniff`], using the value of a `Content-Type` header
+    /// if one is available.
+    ///
+    /// # Errors
+    /// If the document is detected as XML or JSON, but fails to parse as such.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::{AutoSoup, Format}, prelude::*};
+    /// let soup = Soup::auto(r#"{"name": "Widget"}"#, None).unwrap();
+    /// assert_eq!(soup.format(), Format::Json);
+    ///
+    /// if let AutoSoup::Json(soup) = soup {
+    ///     assert_eq!(soup.tag("name").first().and_then(|n| n.text().cloned()), Some("Widget".into()));
+    /// }
+    /// ```
+    pub fn auto(text: &str, content_type: Option<&str>) -> Result<AutoSoup, AutoError> {
+        Ok(match Format::sniff(text, content_type) {
+            Format::Html => AutoSoup::Html(Soup::html(text)),
+            Format::Xml => AutoSoup::Xml(Soup::xml(text.as_bytes()).map_err(AutoError::Xml)?),
+            Format::Json => AutoSoup::Json(Soup::json(text).map_err(AutoError::Json)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        let cases = [
+            ("<!DOCTYPE html><p>Hi", None, Format::Html),
+            ("  <html><body></body></html>", None, Format::Html),
+            ("plain text", None, Format::Html),
+            ("\u{feff}<?xml version=\"1.0\"?><rss/>", None, Format::Xml),
+            (
+                "<!-- c --><feed xmlns=\"http://www.w3.org/2005/Atom\"/>",
+                None,
+                Format::Xml,
+            ),
+            ("\n [1, 2]", None, Format::Json),
+            ("{}", Some("text/html; charset=utf-8"), Format::Html),
+            ("<a/>", Some("application/ld+json"), Format::Json),
+            ("<a/>", Some("application/atom+xml"), Format::Xml),
+            ("{}", Some("application/octet-stream"), Format::Json),
+        ];
+
+        for (text, content_type, format) in cases {
+            assert_eq!(Format::sniff(text, content_type), format, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_auto() {
+        let xml = Soup::auto("<?xml version=\"1.0\"?><a><b/></a>", None);
+        assert_eq!(xml.map(|s| s.format()).ok(), Some(Format::Xml));
+
+        assert!(matches!(
+            Soup::auto("{\"a\": ", None),
+            Err(AutoError::Json(_))
+        ));
+        assert!(matches!(
+            Soup::auto("<a>", Some("text/xml")),
+            Err(AutoError::Xml(_))
+        ));
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+
+use crate::{
+    parser::{
+        html::node::is_void_element,
+        HTMLNode,
+        XMLElement,
+        XMLNode,
+    },
+    Soup,
+};
+
+/// Error returned when a node has no equivalent in the target format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// HTML doctypes cannot be represented in an XML tree
+    Doctype,
+    /// Processing instructions cannot be represented in an HTML tree
+    ProcessingInstruction(String),
+    /// The named void element has children, which HTML does not allow
+    VoidWithChildren(String),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Doctype => f.write_str("doctype cannot be converted to XML"),
+            Self::ProcessingInstruction(name) => {
+                write!(
+                    f,
+                    "processing instruction '{name}' cannot be converted to HTML"
+                )
+            }
+            Self::VoidWithChildren(name) => {
+                write!(f, "void element '{name}' cannot contain children in HTML")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Converts an HTML node into XML
+///
+/// Void elements become empty elements, and the content of raw elements becomes CDATA.
+///
+/// # Errors
+/// If the node is or contains a doctype.
+impl<S> TryFrom<HTMLNode<S>> for XMLNode
+where
+    S: AsRef<str>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: HTMLNode<S>) -> Result<Self, Self::Error> {
+        html_to_xml(value, true).map(|n| n.expect("strict conversion never drops nodes"))
+    }
+}
+
+/// Converts an XML node into HTML
+///
+/// Prefixed names are kept as `prefix:name`, CDATA becomes text, and the text content of
+/// `script` and `style` elements becomes raw content.
+///
+/// # Errors
+/// If the node is or contains a processing instruction, or a void element with children.
+impl TryFrom<XMLNode> for HTMLNode<String> {
+    type Error = ConversionError;
+
+    fn try_from(value: XMLNode) -> Result<Self, Self::Error> {
+        xml_to_html(value, true).map(|n| n.expect("strict conversion never drops nodes"))
+    }
+}
+
+impl XMLNode {
+    /// Converts an HTML node into XML, dropping anything that has no XML equivalent
+    ///
+    /// Returns `None` if the node itself was dropped.
+    pub fn from_html_lossy<S>(node: HTMLNode<S>) -> Option<Self>
+    where
+        S: AsRef<str>,
+    {
+        html_to_xml(node, false).ok().flatten()
+    }
+}
+
+impl HTMLNode<String> {
+    /// Converts an XML node into HTML, dropping anything that has no HTML equivalent
+    ///
+    /// Returns `None` if the node itself was dropped.
+    #[must_use]
+    pub fn from_xml_lossy(node: XMLNode) -> Option<Self> {
+        xml_to_html(node, false).ok().flatten()
+    }
+}
+
+impl<S> Soup<HTMLNode<S>>
+where
+    S: AsRef<str>,
+{
+    /// Converts the document into XML, dropping anything that has no XML equivalent
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<!DOCTYPE html><p>Line<br>break</p>").unwrap();
+    /// let xml = soup.into_xml_lossy();
+    /// assert_eq!(xml.tag("p").first().unwrap().to_string(), "<p>Line<br/>break</p>");
+    /// ```
+    #[must_use]
+    pub fn into_xml_lossy(self) -> Soup<XMLNode> {
+        Soup::from_nodes(
+            self.nodes
+                .into_iter()
+                .filter_map(XMLNode::from_html_lossy)
+                .collect(),
+        )
+    }
+
+    /// Converts the document into XML
+    ///
+    /// # Errors
+    /// If the document contains a doctype.
+    pub fn try_into_xml(self) -> Result<Soup<XMLNode>, ConversionError> {
+        Ok(Soup::from_nodes(
+            self.nodes
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+impl Soup<XMLNode> {
+    /// Converts the document into HTML, dropping anything that has no HTML equivalent
+    #[must_use]
+    pub fn into_html_lossy(self) -> Soup<HTMLNode<String>> {
+        Soup::from_nodes(
+            self.nodes
+                .into_iter()
+                .filter_map(HTMLNode::from_xml_lossy)
+                .collect(),
+        )
+    }
+
+    /// Converts the document into HTML
+    ///
+    /// # Errors
+    /// If the document contains a processing instruction, or a void element with children.
+    pub fn try_into_html(self) -> Result<Soup<HTMLNode<String>>, ConversionError> {
+        Ok(Soup::from_nodes(
+            self.nodes
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+fn html_to_xml<S>(node: HTMLNode<S>, strict: bool) -> Result<Option<XMLNode>, ConversionError>
+where
+    S: AsRef<str>,
+{
+    let element = |name: S, attrs: std::collections::BTreeMap<S, S>, children| {
+        XMLNode::Element(XMLElement {
+            name: name.as_ref().to_string(),
+            attributes: attrs
+                .iter()
+                .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+                .collect(),
+            children,
+            ..Default::default()
+        })
+    };
+
+    Ok(Some(match node {
+        HTMLNode::Comment(c) => XMLNode::Comment(c.as_ref().to_string()),
+        HTMLNode::Doctype(_) if strict => return Err(ConversionError::Doctype),
+        HTMLNode::Doctype(_) => return Ok(None),
+        HTMLNode::Element {
+            name,
+            attrs,
+            children,
+        } => {
+            let mut converted = Vec::with_capacity(children.len());
+
+            for child in children {
+                converted.extend(html_to_xml(child, strict)?);
+            }
+
+            element(name, attrs, converted)
+        }
+        HTMLNode::RawElement {
+            name,
+            attrs,
+            content,
+        } => {
+            let content = content.as_ref();
+
+            let children = if content.is_empty() {
+                vec![]
+            } else if content.contains("]]>") {
+                vec![XMLNode::Text(content.to_string())]
+            } else {
+                vec![XMLNode::CData(content.to_string())]
+            };
+
+            element(name, attrs, children)
+        }
+        HTMLNode::Void { name, attrs } => element(name, attrs, vec![]),
+        HTMLNode::Text(t) => XMLNode::Text(t.as_ref().to_string()),
+    }))
+}
+
+fn xml_to_html(node: XMLNode, strict: bool) -> Result<Option<HTMLNode<String>>, ConversionError> {
+    Ok(Some(match node {
+        XMLNode::Element(e) => {
+            let name = match e.prefix {
+                Some(prefix) => format!("{prefix}:{}", e.name),
+                None => e.name,
+            };
+            let attrs = e.attributes;
+
+            if is_void_element(&name) {
+                if strict && !e.children.is_empty() {
+                    return Err(ConversionError::VoidWithChildren(name));
+                }
+
+                HTMLNode::Void { name, attrs }
+            } else if name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style") {
+                let content = e
+                    .children
+                    .iter()
+                    .filter_map(|c| match c {
+                        XMLNode::Text(t) | XMLNode::CData(t) => Some(t.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+
+                HTMLNode::RawElement {
+                    name,
+                    attrs,
+                    content,
+                }
+            } else {
+                let mut children = Vec::with_capacity(e.children.len());
+
+                for child in e.children {
+                    children.extend(xml_to_html(child, strict)?);
+                }
+
+                HTMLNode::Element {
+                    name,
+                    attrs,
+                    children,
+                }
+            }
+        }
+        XMLNode::Comment(c) => HTMLNode::Comment(c),
+        XMLNode::CData(t) | XMLNode::Text(t) => HTMLNode::Text(t),
+        XMLNode::ProcessingInstruction(name, _) if strict => {
+            return Err(ConversionError::ProcessingInstruction(name))
+        }
+        XMLNode::ProcessingInstruction(..) => return Ok(None),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_html_to_xml() {
+        let soup = Soup::html_strict(
+            r#"<!DOCTYPE html><div id="a"><input disabled><script>if (a < b) {}</script>Text</div>"#,
+        )
+        .expect("Failed to parse HTML");
+
+        assert_eq!(
+            soup.clone().try_into_xml().map(|_| ()),
+            Err(ConversionError::Doctype)
+        );
+
+        let xml = soup.into_xml_lossy();
+
+        assert_eq!(
+            xml.nodes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>(),
+            r#"<div id="a"><input disabled=""/><script><![CDATA[if (a < b) {}]]></script>Text</div>"#
+        );
+    }
+
+    #[test]
+    fn test_xml_to_html() {
+        let soup = Soup::xml(
+            r#"<root><?pi data?><br><child/></br><style><![CDATA[a > b {}]]></style><x:y xmlns:x="urn:x">Text</x:y></root>"#
+                .as_bytes(),
+        )
+        .expect("Failed to parse XML");
+
+        assert_eq!(
+            soup.clone().try_into_html().map(|_| ()),
+            Err(ConversionError::ProcessingInstruction("pi".into()))
+        );
+
+        let html = soup.into_html_lossy();
+
+        assert_eq!(
+            html.nodes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>(),
+            "<root><br><style>a > b {}</style><x:y>Text</x:y></root>"
+        );
+    }
+}
@@ -0,0 +1,669 @@
+use std::{
+    borrow::Cow,
+    fmt,
+};
+
+#[cfg(feature = "html-lenient")]
+use crate::{
+    parser::Parser,
+    Soup,
+};
+
+/// Base lengths for length symbols 257..=285
+const LENGTH_BASE: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+/// Extra bits for length symbols 257..=285
+const LENGTH_EXTRA: [usize; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances for distance symbols 0..=29
+const DISTANCE_BASE: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// Extra bits for distance symbols 0..=29
+const DISTANCE_EXTRA: [usize; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which code length code lengths are stored in dynamic blocks
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Error returned when compressed input is malformed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecompressError {
+    /// The input ended before the end of the stream
+    UnexpectedEnd,
+    /// The stream is not valid, for the given reason
+    Invalid(&'static str),
+    /// The decompressed data does not match the stream's checksum
+    ChecksumMismatch,
+    /// The decompressed data is longer than [`DecompressOptions::max_size`]
+    TooLarge,
+}
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of compressed data"),
+            Self::Invalid(reason) => write!(f, "invalid compressed data: {reason}"),
+            Self::ChecksumMismatch => f.write_str("checksum mismatch in compressed data"),
+            Self::TooLarge => f.write_str("decompressed data is too large"),
+        }
+    }
+}
+
+impl std::error::Error for DecompressError {}
+
+/// Options for [`decompress_with`] and [`inflate_with`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecompressOptions {
+    /// Maximum number of decompressed bytes, 256 MiB by default
+    ///
+    /// A few kilobytes of deflate data can expand to gigabytes, so output past this limit fails
+    /// with [`DecompressError::TooLarge`] instead of being allocated.
+    pub max_size: usize,
+}
+
+impl Default for DecompressOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 256 << 20,
+        }
+    }
+}
+
+/// Decompresses gzip or zlib data, detected by their headers
+///
+/// Concatenated gzip members are decompressed in turn. Input without either header is returned
+/// unchanged, so uncompressed documents pass straight through. Use [`inflate`] for raw deflate
+/// streams, which have no header to detect.
+///
+/// Output is limited to the default [`DecompressOptions::max_size`]; use [`decompress_with`]
+/// to change it.
+///
+/// # Errors
+/// If the input has a gzip or zlib header, but is malformed or decompresses to too many bytes.
+///
+/// # Example
+/// ```rust
+/// # use soupy::parser::decompress;
+/// assert_eq!(&*decompress(b"<p>Plain</p>").unwrap(), b"<p>Plain</p>");
+/// ```
+pub fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, DecompressError> {
+    decompress_with(bytes, DecompressOptions::default())
+}
+
+/// Decompresses gzip or zlib data like [`decompress`], with the given options
+///
+/// # Errors
+/// If the input has a gzip or zlib header, but is malformed or decompresses to more than
+/// [`DecompressOptions::max_size`] bytes.
+///
+/// # Example
+/// ```rust
+/// # use soupy::parser::{decompress_with, DecompressError, DecompressOptions};
+/// let gzip = [
+///     0x1f, 0x8b, 0x08, 0, 0, 0, 0, 0, 0x02, 0x03, 0xb3, 0x29, 0xb0, 0xf3, 0x48, 0xcd, 0xc9,
+///     0xc9, 0xb7, 0xd1, 0x2f, 0xb0, 0x03, 0, 0xe7, 0x79, 0x5f, 0x50, 0x0c, 0, 0, 0,
+/// ];
+///
+/// let options = DecompressOptions { max_size: 8 };
+/// assert_eq!(decompress_with(&gzip, options), Err(DecompressError::TooLarge));
+/// ```
+pub fn decompress_with(
+    bytes: &[u8],
+    options: DecompressOptions,
+) -> Result<Cow<'_, [u8]>, DecompressError> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut out = Vec::new();
+        let mut rest = bytes;
+
+        while rest.starts_with(&GZIP_MAGIC) {
+            rest = gunzip(rest, &mut out, options)?;
+        }
+
+        Ok(Cow::Owned(out))
+    } else if is_zlib(bytes) {
+        unzlib(bytes, options).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(bytes))
+    }
+}
+
+/// Decompresses a raw deflate stream, as described in RFC 1951
+///
+/// Output is limited to the default [`DecompressOptions::max_size`]; use [`inflate_with`]
+/// to change it.
+///
+/// # Errors
+/// If the stream is malformed or decompresses to too many bytes.
+pub fn inflate(bytes: &[u8]) -> Result<Vec<u8>, DecompressError> {
+    inflate_with(bytes, DecompressOptions::default())
+}
+
+/// Decompresses a raw deflate stream like [`inflate`], with the given options
+///
+/// # Errors
+/// If the stream is malformed or decompresses to more than [`DecompressOptions::max_size`]
+/// bytes.
+pub fn inflate_with(bytes: &[u8], options: DecompressOptions) -> Result<Vec<u8>, DecompressError> {
+    let mut out = Vec::new();
+    Inflater::new(bytes, options).inflate(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(feature = "html-lenient")]
+impl Soup {
+    /// Attempts to create a new `Soup` instance from HTML that may be gzip or zlib compressed.
+    ///
+    /// The bytes are passed through [`decompress`] and then decoded as in
+    /// [`Soup::html_with_content_type`]. For XML, pass the output of [`decompress`] to
+    /// [`Soup::xml`].
+    ///
+    /// # Errors
+    /// If the input has a gzip or zlib header, but is malformed or decompresses to more than
+    /// the default [`DecompressOptions::max_size`].
+    pub fn html_compressed(
+        bytes: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<Soup<<crate::parser::LenientHTMLParser<String> as Parser>::Node>, DecompressError>
+    {
+        Ok(Soup::html_with_content_type(
+            &decompress(bytes)?,
+            content_type,
+        ))
+    }
+}
+
+/// Whether `bytes` starts with a zlib header using deflate without a preset dictionary
+fn is_zlib(bytes: &[u8]) -> bool {
+    match bytes {
+        [cmf, flg, ..] => {
+            cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && flg & 0x20 == 0
+                && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0
+        }
+        _ => false,
+    }
+}
+
+/// Decompresses a zlib stream, checking its Adler-32 checksum
+fn unzlib(bytes: &[u8], options: DecompressOptions) -> Result<Vec<u8>, DecompressError> {
+    let mut out = Vec::new();
+    let mut inflater = Inflater::new(bytes.get(2..).unwrap_or_default(), options);
+
+    inflater.inflate(&mut out)?;
+
+    let trailer = inflater.bytes(4)?;
+
+    if u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != adler32(&out) {
+        return Err(DecompressError::ChecksumMismatch);
+    }
+
+    Ok(out)
+}
+
+/// Decompresses one gzip member into `out`, returning the input after it
+fn gunzip<'a>(
+    bytes: &'a [u8],
+    out: &mut Vec<u8>,
+    options: DecompressOptions,
+) -> Result<&'a [u8], DecompressError> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let mut inflater = Inflater::new(bytes, options);
+    let header = inflater.bytes(10)?;
+
+    if header[2] != 8 {
+        return Err(DecompressError::Invalid("unknown gzip compression method"));
+    }
+
+    let flags = header[3];
+
+    if flags & FEXTRA != 0 {
+        let len = inflater.bytes(2)?;
+        inflater.bytes(usize::from(u16::from_le_bytes([len[0], len[1]])))?;
+    }
+
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            while inflater.bytes(1)? != [0] {}
+        }
+    }
+
+    if flags & FHCRC != 0 {
+        inflater.bytes(2)?;
+    }
+
+    let start = out.len();
+    inflater.inflate(out)?;
+
+    let trailer = inflater.bytes(8)?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+
+    // The size is stored modulo 2^32
+    #[allow(clippy::cast_possible_truncation)]
+    if crc != crc32(&out[start..]) || size != (out.len() - start) as u32 {
+        return Err(DecompressError::ChecksumMismatch);
+    }
+
+    Ok(&bytes[inflater.pos..])
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= u32::from(byte);
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xEDB8_8320
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for &byte in data {
+        a = (a + u32::from(byte)) % MOD;
+        b = (b + a) % MOD;
+    }
+
+    b << 16 | a
+}
+
+/// Canonical Huffman code, decoded one bit at a time
+struct Huffman {
+    /// Number of codes of each length
+    counts: [usize; 16],
+    /// Symbols ordered by code
+    symbols: Vec<usize>,
+}
+
+impl Huffman {
+    fn new(lengths: &[usize]) -> Result<Self, DecompressError> {
+        let mut counts = [0usize; 16];
+
+        for &len in lengths {
+            counts[len] += 1;
+        }
+
+        counts[0] = 0;
+
+        let mut left = 1isize;
+
+        for &count in &counts[1..] {
+            left = left * 2 - count.cast_signed();
+
+            if left < 0 {
+                return Err(DecompressError::Invalid("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0; 16];
+
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+
+        let mut symbols = vec![0; lengths.len()];
+
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len]] = symbol;
+                offsets[len] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, inflater: &mut Inflater) -> Result<usize, DecompressError> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+
+        for &count in &self.counts[1..] {
+            code |= inflater.bits(1)?;
+
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(DecompressError::Invalid("invalid Huffman code"))
+    }
+}
+
+/// Bit reader and block decoder for deflate streams
+struct Inflater<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: usize,
+    bit_count: usize,
+    /// Maximum length of the output, including anything already in it
+    max_size: usize,
+}
+
+impl<'a> Inflater<'a> {
+    fn new(data: &'a [u8], options: DecompressOptions) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_count: 0,
+            max_size: options.max_size,
+        }
+    }
+
+    /// Checks that `n` more bytes fit in `out`
+    fn reserve(&self, out: &[u8], n: usize) -> Result<(), DecompressError> {
+        if out.len().saturating_add(n) > self.max_size {
+            return Err(DecompressError::TooLarge);
+        }
+
+        Ok(())
+    }
+
+    /// Reads `n` bits, least significant first
+    fn bits(&mut self, n: usize) -> Result<usize, DecompressError> {
+        while self.bit_count < n {
+            let byte = self
+                .data
+                .get(self.pos)
+                .ok_or(DecompressError::UnexpectedEnd)?;
+
+            self.bit_buf |= usize::from(*byte) << self.bit_count;
+            self.bit_count += 8;
+            self.pos += 1;
+        }
+
+        let value = self.bit_buf & ((1 << n) - 1);
+
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+
+        Ok(value)
+    }
+
+    /// Discards any bits left in the current byte, then reads `n` whole bytes
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], DecompressError> {
+        self.bit_buf = 0;
+        self.bit_count = 0;
+
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or(DecompressError::UnexpectedEnd)?;
+
+        self.pos += n;
+
+        Ok(bytes)
+    }
+
+    fn inflate(&mut self, out: &mut Vec<u8>) -> Result<(), DecompressError> {
+        loop {
+            let last = self.bits(1)? == 1;
+
+            match self.bits(2)? {
+                0 => self.stored(out)?,
+                1 => {
+                    let mut lengths = [8; 288];
+                    lengths[144..256].fill(9);
+                    lengths[256..280].fill(7);
+
+                    self.codes(out, &Huffman::new(&lengths)?, &Huffman::new(&[5; 30])?)?;
+                }
+                2 => {
+                    let (literals, distances) = self.dynamic()?;
+                    self.codes(out, &literals, &distances)?;
+                }
+                _ => return Err(DecompressError::Invalid("invalid block type")),
+            }
+
+            if last {
+                return Ok(());
+            }
+        }
+    }
+
+    fn stored(&mut self, out: &mut Vec<u8>) -> Result<(), DecompressError> {
+        let header = self.bytes(4)?;
+        let len = u16::from_le_bytes([header[0], header[1]]);
+
+        if len != !u16::from_le_bytes([header[2], header[3]]) {
+            return Err(DecompressError::Invalid("stored block length mismatch"));
+        }
+
+        self.reserve(out, usize::from(len))?;
+        out.extend_from_slice(self.bytes(usize::from(len))?);
+
+        Ok(())
+    }
+
+    fn dynamic(&mut self) -> Result<(Huffman, Huffman), DecompressError> {
+        let literal_count = self.bits(5)? + 257;
+        let distance_count = self.bits(5)? + 1;
+        let code_count = self.bits(4)? + 4;
+
+        if literal_count > 286 || distance_count > 30 {
+            return Err(DecompressError::Invalid(
+                "too many length or distance codes",
+            ));
+        }
+
+        let mut code_lengths = [0; 19];
+
+        for &i in &CODE_LENGTH_ORDER[..code_count] {
+            code_lengths[i] = self.bits(3)?;
+        }
+
+        let code = Huffman::new(&code_lengths)?;
+
+        let total = literal_count + distance_count;
+        let mut lengths = Vec::with_capacity(total);
+
+        while lengths.len() < total {
+            let (len, repeat) = match code.decode(self)? {
+                len @ 0..=15 => (len, 1),
+                16 => {
+                    let prev = lengths
+                        .last()
+                        .copied()
+                        .ok_or(DecompressError::Invalid("repeat with no previous length"))?;
+
+                    (prev, 3 + self.bits(2)?)
+                }
+                17 => (0, 3 + self.bits(3)?),
+                _ => (0, 11 + self.bits(7)?),
+            };
+
+            if lengths.len() + repeat > total {
+                return Err(DecompressError::Invalid("too many code lengths"));
+            }
+
+            lengths.extend(std::iter::repeat_n(len, repeat));
+        }
+
+        if lengths[256] == 0 {
+            return Err(DecompressError::Invalid("missing end-of-block code"));
+        }
+
+        Ok((
+            Huffman::new(&lengths[..literal_count])?,
+            Huffman::new(&lengths[literal_count..])?,
+        ))
+    }
+
+    fn codes(
+        &mut self,
+        out: &mut Vec<u8>,
+        literals: &Huffman,
+        distances: &Huffman,
+    ) -> Result<(), DecompressError> {
+        loop {
+            let symbol = literals.decode(self)?;
+
+            if let Ok(byte) = u8::try_from(symbol) {
+                self.reserve(out, 1)?;
+                out.push(byte);
+                continue;
+            }
+
+            if symbol == 256 {
+                return Ok(());
+            }
+
+            let i = symbol - 257;
+
+            if i >= LENGTH_BASE.len() {
+                return Err(DecompressError::Invalid("invalid length symbol"));
+            }
+
+            let len = LENGTH_BASE[i] + self.bits(LENGTH_EXTRA[i])?;
+
+            let i = distances.decode(self)?;
+
+            if i >= DISTANCE_BASE.len() {
+                return Err(DecompressError::Invalid("invalid distance symbol"));
+            }
+
+            let distance = DISTANCE_BASE[i] + self.bits(DISTANCE_EXTRA[i])?;
+
+            if distance > out.len() {
+                return Err(DecompressError::Invalid("distance too far back"));
+            }
+
+            self.reserve(out, len)?;
+
+            for _ in 0..len {
+                out.push(out[out.len() - distance]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_decompress() {
+        let html = (0..40).fold(String::from("<html><body>"), |html, i| {
+            html + &format!("<p class=\"item\">Item {i}</p>")
+        }) + "</body></html>";
+
+        let dynamic = hex(
+            "78da7dd43b0ec2301045d1ad44d98099f7081fc9b867190490404a44a4a461f720d272dd4c73ab39b2273f\
+             967128b97fddde254fcd75b8ccf3a97d2ef7b12de7ef6c36394dff4b60111663d962e9b0ecb0ecb11cb01c\
+             79d30a022b0433043b0443044b0453045b0463046b8835547913ac21d6106b8835c41a620db18658c3ac61\
+             d670e58bb08659c3ac61d6306b9835bc6aa4f568a4df05f90069635aec",
+        );
+        let fixed = hex("1f8b0800000000000203b329b0f348cdc9c9b7d12fb00300e7795f500c000000");
+        let stored = hex("7801010900f6ff3c703e48693c2f703e0daf02b5");
+
+        assert_eq!(decompress(&dynamic).unwrap(), html.as_bytes());
+        assert_eq!(decompress(&fixed).unwrap(), &b"<p>Hello</p>"[..]);
+        assert_eq!(decompress(&stored).unwrap(), &b"<p>Hi</p>"[..]);
+
+        let members = [fixed.clone(), fixed.clone()].concat();
+        assert_eq!(
+            decompress(&members).unwrap(),
+            &b"<p>Hello</p><p>Hello</p>"[..]
+        );
+
+        let soup = Soup::html_compressed(&dynamic, None).unwrap();
+        assert_eq!(soup.class("item").all().count(), 40);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut corrupt = hex("1f8b0800000000000203b329b0f348cdc9c9b7d12fb00300e7795f500c000000");
+        corrupt[25] ^= 1;
+
+        assert_eq!(decompress(&corrupt), Err(DecompressError::ChecksumMismatch));
+        assert_eq!(
+            decompress(&corrupt[..20]),
+            Err(DecompressError::UnexpectedEnd)
+        );
+        assert_eq!(
+            inflate(&[0x07]),
+            Err(DecompressError::Invalid("invalid block type"))
+        );
+    }
+
+    #[test]
+    fn test_max_size() {
+        let fixed = hex("1f8b0800000000000203b329b0f348cdc9c9b7d12fb00300e7795f500c000000");
+        let stored = hex("7801010900f6ff3c703e48693c2f703e0daf02b5");
+        let options = |max_size| DecompressOptions { max_size };
+
+        assert_eq!(
+            decompress_with(&fixed, options(12)).unwrap(),
+            &b"<p>Hello</p>"[..]
+        );
+        assert_eq!(
+            decompress_with(&fixed, options(11)),
+            Err(DecompressError::TooLarge)
+        );
+        assert_eq!(
+            decompress_with(&stored, options(8)),
+            Err(DecompressError::TooLarge)
+        );
+
+        // The limit applies to all members together
+        let members = [fixed.clone(), fixed].concat();
+        assert_eq!(
+            decompress_with(&members, options(20)),
+            Err(DecompressError::TooLarge)
+        );
+
+        // About 1 KiB of deflate data expanding to 1 MiB of zeros
+        let bomb = [
+            hex("edc13101000000c2a0f54f6d085fa0"),
+            vec![0; 1016],
+            hex("3e03"),
+        ]
+        .concat();
+        assert_eq!(inflate(&bomb).unwrap(), vec![0; 1 << 20]);
+        assert_eq!(
+            inflate_with(&bomb, options(4096)),
+            Err(DecompressError::TooLarge)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::borrow::Cow;
+
+use crate::{
+    parser::{
+        Escaping,
+        HTMLNode,
+    },
+    Soup,
+};
+
+/// Number of bytes searched for a `<meta>` charset declaration
+const PRESCAN_LIMIT: usize = 1024;
+
+/// Character encodings that can be decoded without external dependencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8
+    Utf8,
+    /// UTF-16, little-endian
+    Utf16Le,
+    /// UTF-16, big-endian
+    Utf16Be,
+    /// windows-1252, also used for `iso-8859-1` and `us-ascii`, as browsers do
+    Windows1252,
+}
+
+/// Characters for bytes `0x80..=0x9F` in windows-1252; the rest match ISO-8859-1
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+impl Encoding {
+    /// Looks up an encoding by its WHATWG label, like `latin1`
+    #[must_use]
+    pub fn for_label(label: &str) -> Option<Self> {
+        let label = label
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .to_ascii_lowercase();
+
+        match label.as_str() {
+            "unicode-1-1-utf-8" | "unicode11utf8" | "unicode20utf8" | "utf-8" | "utf8"
+            | "x-unicode20utf8" => Some(Self::Utf8),
+            "csunicode" | "iso-10646-ucs-2" | "ucs-2" | "unicode" | "unicodefeff" | "utf-16"
+            | "utf-16le" => Some(Self::Utf16Le),
+            "unicodefffe" | "utf-16be" => Some(Self::Utf16Be),
+            "ansi_x3.4-1968" | "ascii" | "cp1252" | "cp819" | "csisolatin1" | "ibm819"
+            | "iso-8859-1" | "iso-ir-100" | "iso8859-1" | "iso88591" | "iso_8859-1"
+            | "iso_8859-1:1987" | "l1" | "latin1" | "us-ascii" | "windows-1252" | "x-cp1252" => {
+                Some(Self::Windows1252)
+            }
+            _ => None,
+        }
+    }
+
+    /// Picks the encoding of an HTML document the way [`Soup::html_with_content_type`] does
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::parser::Encoding;
+    /// let bytes = b"<meta charset=\"iso-8859-1\"><p>caf\xe9</p>";
+    /// assert_eq!(Encoding::detect(bytes, None), Encoding::Windows1252);
+    /// assert_eq!(Encoding::detect(bytes, Some("text/html; charset=utf-8")), Encoding::Utf8);
+    /// ```
+    #[must_use]
+    pub fn detect(bytes: &[u8], content_type: Option<&str>) -> Self {
+        if let Some((encoding, _)) = bom(bytes) {
+            return encoding;
+        }
+
+        content_type
+            .and_then(charset_param)
+            .and_then(Self::for_label)
+            .or_else(|| prescan(bytes))
+            .unwrap_or(Self::Utf8)
+    }
+
+    /// The encoding's canonical name, like `windows-1252`
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// Encodes `text`, writing characters the encoding can't represent as numeric character
+    /// references, like `&#9731;`
+    ///
+    /// No byte order mark is written.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::parser::Encoding;
+    /// assert_eq!(Encoding::Windows1252.encode("caf\u{e9} \u{20AC}5 \u{2603}"), b"caf\xe9 \x805 &#9731;");
+    /// assert_eq!(Encoding::Utf16Be.encode("hi"), b"\0h\0i");
+    /// ```
+    #[must_use]
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => text.as_bytes().to_vec(),
+            Self::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Self::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Self::Windows1252 => {
+                let mut bytes = Vec::with_capacity(text.len());
+
+                for c in text.chars() {
+                    match windows_1252_byte(c) {
+                        Some(b) => bytes.push(b),
+                        None => bytes.extend(format!("&#{};", u32::from(c)).bytes()),
+                    }
+                }
+
+                bytes
+            }
+        }
+    }
+
+    /// Decodes `bytes`, replacing malformed sequences with U+FFFD
+    pub(crate) fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes),
+            Self::Utf16Le | Self::Utf16Be => {
+                let units = bytes.chunks(2).map(|pair| match (pair, self) {
+                    ([a, b], Self::Utf16Le) => u16::from_le_bytes([*a, *b]),
+                    ([a, b], _) => u16::from_be_bytes([*a, *b]),
+                    _ => 0xFFFD,
+                });
+
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect::<String>()
+                    .into()
+            }
+            Self::Windows1252 => bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(b - 0x80)],
+                    _ => char::from(b),
+                })
+                .collect::<String>()
+                .into(),
+        }
+    }
+}
+
+/// Returns the windows-1252 byte for `c`, if there is one
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match u32::from(c) {
+        0..=0x7F | 0xA0..=0xFF => u8::try_from(c).ok(),
+        _ => WINDOWS_1252_HIGH
+            .iter()
+            .position(|&h| h == c)
+            .and_then(|i| u8::try_from(i + 0x80).ok()),
+    }
+}
+
+impl<S> Soup<HTMLNode<S>>
+where
+    S: AsRef<str>,
+{
+    /// Serializes the document like [`Soup::to_html`], then encodes it with `encoding`
+    ///
+    /// Use this with [`Encoding::detect`] to write a document back in the charset it was read
+    /// in. Characters the encoding can't represent are written as numeric character
+    /// references, which browsers decode in text and attribute values, but not in the content
+    /// of elements like `<script>`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::{Encoding, Escaping}, prelude::*};
+    /// let bytes = b"<html><head><meta charset=\"latin1\"></head><body><p>\x80 caf\xe9</p></body></html>";
+    ///
+    /// let encoding = Encoding::detect(bytes, None);
+    /// let soup = Soup::html_with_content_type(bytes, None);
+    /// assert_eq!(soup.to_html_encoded(encoding, Escaping::Decoded), bytes);
+    /// ```
+    #[must_use]
+    pub fn to_html_encoded(&self, encoding: Encoding, escaping: Escaping) -> Vec<u8> {
+        encoding.encode(&self.to_html(escaping))
+    }
+}
+
+/// Decodes an HTML document, picking the encoding in the order browsers do:
+///
+/// 1. A byte order mark
+/// 2. The `charset` parameter of the `Content-Type` header
+/// 3. A `<meta>` declaration within the first 1024 bytes
+/// 4. UTF-8
+///
+/// Unsupported or unknown labels are skipped.
+pub(crate) fn decode<'a>(bytes: &'a [u8], content_type: Option<&str>) -> Cow<'a, str> {
+    let start = bom(bytes).map_or(0, |(_, len)| len);
+    Encoding::detect(bytes, content_type).decode(&bytes[start..])
+}
+
+fn bom(bytes: &[u8]) -> Option<(Encoding, usize)> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Some((Encoding::Utf8, 3)),
+        [0xFF, 0xFE, ..] => Some((Encoding::Utf16Le, 2)),
+        [0xFE, 0xFF, ..] => Some((Encoding::Utf16Be, 2)),
+        _ => None,
+    }
+}
+
+/// Extracts the value of a `charset=` parameter, e.g. from `text/html; charset="utf-8"`
+fn charset_param(text: &str) -> Option<&str> {
+    let lower = text.to_ascii_lowercase();
+    let mut from = 0;
+
+    while let Some(i) = lower[from..].find("charset") {
+        let rest = text[from + i + "charset".len()..].trim_start();
+        from += i + "charset".len();
+
+        let Some(rest) = rest.strip_prefix('=') else {
+            continue;
+        };
+
+        let rest = rest.trim_start();
+
+        let value = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => rest[1..].split(q).next(),
+            _ => rest
+                .split(|c: char| matches!(c, ';' | '/' | '"' | '\'') || c.is_ascii_whitespace())
+                .next(),
+        };
+
+        return value.filter(|v| !v.is_empty());
+    }
+
+    None
+}
+
+/// Looks for `<meta charset>` or `<meta http-equiv content>` near the start of the document
+fn prescan(bytes: &[u8]) -> Option<Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(PRESCAN_LIMIT)]);
+    let lower = head.to_ascii_lowercase();
+
+    lower
+        .match_indices("<meta")
+        .filter_map(|(i, _)| {
+            let end = lower[i..].find('>').map_or(lower.len(), |e| i + e);
+            charset_param(&head[i..end])
+        })
+        .find_map(Encoding::for_label)
+        // A document that could be read as ASCII to find the declaration is not UTF-16
+        .map(|e| match e {
+            Encoding::Utf16Le | Encoding::Utf16Be => Encoding::Utf8,
+            e => e,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority() {
+        let latin1 = b"<p>caf\xe9</p>";
+        let meta = b"<meta charset=\"windows-1252\"><p>caf\xe9 \x80</p>";
+
+        assert_eq!(decode(latin1, None), "<p>caf\u{FFFD}</p>");
+        assert_eq!(
+            decode(latin1, Some("text/html; charset=ISO-8859-1")),
+            "<p>caf\u{e9}</p>"
+        );
+        assert_eq!(
+            decode(meta, None),
+            "<meta charset=\"windows-1252\"><p>caf\u{e9} \u{20AC}</p>"
+        );
+        assert_eq!(
+            decode(meta, Some("text/html; charset=utf-8")),
+            "<meta charset=\"windows-1252\"><p>caf\u{FFFD} \u{FFFD}</p>"
+        );
+        assert_eq!(
+            decode(
+                b"\xEF\xBB\xBFcaf\xC3\xA9",
+                Some("text/html; charset=latin1")
+            ),
+            "caf\u{e9}"
+        );
+        assert_eq!(decode(b"\xFF\xFEh\0i\0", None), "hi");
+        assert_eq!(
+            decode(latin1, Some("text/html; charset=x-unknown")),
+            "<p>caf\u{FFFD}</p>"
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let bytes = b"<p title=\"\x93quoted\x94\">caf\xe9 \x81</p>";
+        let text = decode(bytes, Some("text/html; charset=cp1252"));
+
+        assert_eq!(Encoding::Windows1252.encode(&text), bytes);
+        assert_eq!(
+            Encoding::Windows1252.encode("\u{80}\u{100}\u{1F600}"),
+            b"&#128;&#256;&#128512;"
+        );
+        assert_eq!(Encoding::Utf16Le.encode("\u{1F600}"), [
+            0x3D, 0xD8, 0x00, 0xDE
+        ]);
+        assert_eq!(
+            Encoding::for_label("UTF8").map(Encoding::name),
+            Some("UTF-8")
+        );
+    }
+
+    #[test]
+    fn test_charset_param() {
+        assert_eq!(charset_param("text/html; charset=UTF-8"), Some("UTF-8"));
+        assert_eq!(
+            charset_param("text/html;charset=\"latin1\"; x=y"),
+            Some("latin1")
+        );
+        assert_eq!(
+            charset_param(
+                "<meta http-equiv=\"Content-Type\" content=\"text/html; charset=cp1252\""
+            ),
+            Some("cp1252")
+        );
+        assert_eq!(charset_param("text/html"), None);
+    }
+}
@@ -0,0 +1,545 @@
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    slice,
+};
+
+use nom::character::complete::multispace0;
+
+use crate::parser::html::{
+    node::is_void_element,
+    strict::{
+        attr,
+        tag_name,
+        unquoted_value,
+        StrictHTMLError,
+        StrictHTMLOptions,
+        StrictHTMLParser,
+    },
+};
+
+/// Kind of a [`CstNode`]
+///
+/// Branches group the tokens of a construct, while tokens hold the text itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    /// An element, holding its start tag, its children, and its end tag if it has one
+    Element,
+    /// A start tag, like `<a href="/">`
+    StartTag,
+    /// An end tag, like `</a>`
+    EndTag,
+    /// An attribute inside a start tag, like `href="/"`
+    Attribute,
+    /// A comment, like `<!-- ... -->`
+    Comment,
+    /// A doctype, like `<!DOCTYPE html>`
+    Doctype,
+
+    /// Whitespace between or inside other tokens
+    Whitespace,
+    /// Text content, without surrounding whitespace
+    Text,
+    /// `<` opening a start tag
+    TagOpen,
+    /// `</` opening an end tag
+    EndTagOpen,
+    /// `>` closing a tag or doctype
+    TagClose,
+    /// `/>` closing a self-closing tag
+    SelfClose,
+    /// Name of an element
+    Name,
+    /// Name of an attribute
+    AttrName,
+    /// `=` between an attribute's name and value
+    Equals,
+    /// Quote around an attribute value
+    Quote,
+    /// Value of an attribute, without quotes
+    AttrValue,
+    /// Content of a `script` or `style` element
+    RawText,
+    /// `<!--` opening a comment
+    CommentOpen,
+    /// Content of a comment
+    CommentBody,
+    /// `-->` closing a comment
+    CommentClose,
+    /// `<!DOCTYPE` opening a doctype, in its original case
+    DoctypeOpen,
+    /// Content of a doctype, without surrounding whitespace
+    DoctypeBody,
+}
+
+/// A node of the concrete syntax tree produced by [`StrictHTMLParser::parse_cst`]
+///
+/// Every byte of the input belongs to exactly one token, so the text of the top-level nodes
+/// joined together is the original input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CstNode<'a> {
+    /// What the node represents
+    pub kind: SyntaxKind,
+
+    /// Text covered by the node, including all its children
+    pub text: &'a str,
+
+    /// Byte offset of the node in the input
+    pub offset: usize,
+
+    /// Child nodes and tokens, in order; empty for tokens
+    pub children: Vec<CstNode<'a>>,
+}
+
+impl<'a> CstNode<'a> {
+    fn token(kind: SyntaxKind, text: &'a str, offset: usize) -> Self {
+        Self {
+            kind,
+            text,
+            offset,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns `true` for tokens, which have no children
+    #[must_use]
+    pub fn is_token(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Iterates over the tokens of the node, in order
+    #[must_use]
+    pub fn tokens(&self) -> Tokens<'_, 'a> {
+        Tokens {
+            stack: alloc::vec![slice::from_ref(self).iter()],
+        }
+    }
+
+    /// Finds the first direct child of the given kind
+    #[must_use]
+    pub fn child(&self, kind: SyntaxKind) -> Option<&CstNode<'a>> {
+        self.children.iter().find(|c| c.kind == kind)
+    }
+}
+
+impl fmt::Display for CstNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+/// An [`Iterator`] over the tokens of a [`CstNode`], returned by [`CstNode::tokens`]
+#[derive(Clone, Debug)]
+pub struct Tokens<'c, 'a> {
+    stack: Vec<slice::Iter<'c, CstNode<'a>>>,
+}
+
+impl<'c, 'a> Iterator for Tokens<'c, 'a> {
+    type Item = &'c CstNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(node) = self.stack.last_mut()?.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            if node.is_token() {
+                return Some(node);
+            }
+
+            self.stack.push(node.children.iter());
+        }
+    }
+}
+
+impl<'a> StrictHTMLParser<'a> {
+    /// Parses `text` into a lossless concrete syntax tree
+    ///
+    /// Unlike [`StrictHTMLParser::parse_with`], every token is kept, including whitespace,
+    /// tag delimiters, and attribute quotes, so formatters and refactoring tools can edit the
+    /// source precisely. The tree parallels the regular one: each [`HTMLNode`] has a node of
+    /// kind [`Element`](SyntaxKind::Element), [`Comment`](SyntaxKind::Comment),
+    /// [`Doctype`](SyntaxKind::Doctype) or [`Text`](SyntaxKind::Text), in the same order.
+    ///
+    /// [`HTMLNode`]: crate::parser::HTMLNode
+    ///
+    /// # Errors
+    /// If the text is invalid HTML.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::parser::{StrictHTMLOptions, StrictHTMLParser, SyntaxKind};
+    /// let text = "<p class='intro'>\n  Hello\n</p>";
+    /// let cst = StrictHTMLParser::parse_cst(text, StrictHTMLOptions::default()).unwrap();
+    ///
+    /// let start = cst[0].child(SyntaxKind::StartTag).unwrap();
+    /// let quotes = start.tokens().filter(|t| t.kind == SyntaxKind::Quote).count();
+    /// assert_eq!(quotes, 2);
+    ///
+    /// let source = cst.iter().flat_map(|n| n.tokens()).map(|t| t.text).collect::<String>();
+    /// assert_eq!(source, text);
+    /// ```
+    pub fn parse_cst(
+        text: &'a str,
+        options: StrictHTMLOptions,
+    ) -> Result<Vec<CstNode<'a>>, StrictHTMLError<'a>> {
+        Self::parse_with(text, options)?;
+        Ok(build(text))
+    }
+}
+
+/// Splits `text` at `len`, returning a token for the first part and the rest
+fn split(kind: SyntaxKind, text: &str, offset: usize, len: usize) -> (CstNode<'_>, &str) {
+    let len = (0..=len.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or_default();
+    let (token, rest) = text.split_at(len);
+
+    (CstNode::token(kind, token, offset), rest)
+}
+
+/// Pushes `text` as a token of `kind`, with leading and trailing whitespace as their own tokens
+fn push_trimmed<'a>(out: &mut Vec<CstNode<'a>>, kind: SyntaxKind, text: &'a str, offset: usize) {
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len().max(start);
+
+    for (kind, range) in [
+        (SyntaxKind::Whitespace, 0..start),
+        (kind, start..end),
+        (SyntaxKind::Whitespace, end..text.len()),
+    ] {
+        if !range.is_empty() {
+            out.push(CstNode::token(
+                kind,
+                &text[range.clone()],
+                offset + range.start,
+            ));
+        }
+    }
+}
+
+/// Wraps `children` in a branch of `kind` covering their text
+fn branch<'a>(kind: SyntaxKind, source: &'a str, children: Vec<CstNode<'a>>) -> CstNode<'a> {
+    let start = children.first().map_or(0, |c| c.offset);
+    let end = children.last().map_or(start, |c| c.offset + c.text.len());
+
+    CstNode {
+        kind,
+        text: &source[start..end],
+        offset: start,
+        children,
+    }
+}
+
+/// Lexes a start tag at the beginning of `rest`, returning its name and whether it closed itself
+fn start_tag<'a>(
+    source: &'a str,
+    rest: &'a str,
+    out: &mut Vec<CstNode<'a>>,
+) -> (&'a str, bool, &'a str) {
+    let at = |rest: &str| source.len() - rest.len();
+    let mut tokens = Vec::new();
+
+    let (open, rest) = split(SyntaxKind::TagOpen, rest, at(rest), 1);
+    tokens.push(open);
+
+    let name = tag_name::<()>(rest).map_or("", |(_, name)| name);
+    let (token, mut rest) = split(SyntaxKind::Name, rest, at(rest), name.len());
+    tokens.push(token);
+
+    let closed = loop {
+        let (left, space) = multispace0::<_, ()>(rest).unwrap_or((rest, ""));
+        if !space.is_empty() {
+            tokens.push(CstNode::token(SyntaxKind::Whitespace, space, at(rest)));
+        }
+        rest = left;
+
+        if rest.starts_with("/>") || rest.starts_with('>') || rest.is_empty() {
+            let closed = rest.starts_with("/>");
+            let kind = if closed {
+                SyntaxKind::SelfClose
+            } else {
+                SyntaxKind::TagClose
+            };
+            let (token, left) = split(kind, rest, at(rest), if closed { 2 } else { 1 });
+            tokens.push(token);
+            rest = left;
+            break closed;
+        }
+
+        let (attribute, left) = attribute(source, rest);
+        tokens.push(attribute);
+        rest = left;
+    };
+
+    out.push(branch(SyntaxKind::StartTag, source, tokens));
+    (name, closed, rest)
+}
+
+/// Lexes an attribute at the beginning of `rest`
+fn attribute<'a>(source: &'a str, rest: &'a str) -> (CstNode<'a>, &'a str) {
+    let at = |rest: &str| source.len() - rest.len();
+    let mut tokens = Vec::new();
+
+    // Always take at least one character, so unexpected input can't stall the lexer
+    let name = attr::<()>(rest).map_or(1, |(_, name)| name.len());
+    let (token, mut rest) = split(SyntaxKind::AttrName, rest, at(rest), name);
+    tokens.push(token);
+
+    let (after, space) = multispace0::<_, ()>(rest).unwrap_or((rest, ""));
+    if let Some(after) = after.strip_prefix('=') {
+        if !space.is_empty() {
+            tokens.push(CstNode::token(SyntaxKind::Whitespace, space, at(rest)));
+        }
+        let equals = at(after) - 1;
+        tokens.push(CstNode::token(
+            SyntaxKind::Equals,
+            &source[equals..=equals],
+            equals,
+        ));
+
+        let (after, space) = multispace0::<_, ()>(after).unwrap_or((after, ""));
+        if !space.is_empty() {
+            tokens.push(CstNode::token(
+                SyntaxKind::Whitespace,
+                space,
+                at(after) - space.len(),
+            ));
+        }
+        rest = after;
+
+        if let Some(quote) = rest.chars().next().filter(|&c| c == '"' || c == '\'') {
+            let (token, left) = split(SyntaxKind::Quote, rest, at(rest), 1);
+            tokens.push(token);
+
+            let len = left.find(quote).unwrap_or(left.len());
+            let (token, left) = split(SyntaxKind::AttrValue, left, at(left), len);
+            tokens.push(token);
+
+            let (token, left) = split(SyntaxKind::Quote, left, at(left), 1);
+            tokens.push(token);
+            rest = left;
+        } else {
+            let len = unquoted_value::<()>(rest).map_or(0, |(_, value)| value.len());
+            let (token, left) = split(SyntaxKind::AttrValue, rest, at(rest), len);
+            tokens.push(token);
+            rest = left;
+        }
+    }
+
+    (branch(SyntaxKind::Attribute, source, tokens), rest)
+}
+
+/// Lexes an end tag at the beginning of `rest`
+fn end_tag<'a>(source: &'a str, rest: &'a str) -> (CstNode<'a>, &'a str) {
+    let at = |rest: &str| source.len() - rest.len();
+    let mut tokens = Vec::new();
+
+    let (token, rest) = split(SyntaxKind::EndTagOpen, rest, at(rest), 2);
+    tokens.push(token);
+
+    let name = tag_name::<()>(rest).map_or(0, |(_, name)| name.len());
+    let (token, rest) = split(SyntaxKind::Name, rest, at(rest), name);
+    tokens.push(token);
+
+    let close = rest.find('>').unwrap_or(rest.len());
+    let (space, rest) = (&rest[..close], &rest[close..]);
+    if !space.is_empty() {
+        tokens.push(CstNode::token(
+            SyntaxKind::Whitespace,
+            space,
+            at(rest) - close,
+        ));
+    }
+
+    let (token, rest) = split(SyntaxKind::TagClose, rest, at(rest), 1);
+    tokens.push(token);
+
+    (branch(SyntaxKind::EndTag, source, tokens), rest)
+}
+
+/// Builds the tree of text that has already been validated by the strict parser
+///
+/// Open elements are kept on an explicit stack, so deep nesting cannot overflow the call stack.
+fn build(source: &str) -> Vec<CstNode<'_>> {
+    let at = |rest: &str| source.len() - rest.len();
+
+    // Children of the document, and of each open element
+    let mut top = Vec::new();
+    let mut stack: Vec<Vec<CstNode<'_>>> = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let out = match stack.last_mut() {
+            Some(children) => children,
+            None => &mut top,
+        };
+
+        if rest.starts_with(char::is_whitespace) {
+            let len = rest.len() - rest.trim_start().len();
+            let (token, left) = split(SyntaxKind::Whitespace, rest, at(rest), len);
+            out.push(token);
+            rest = left;
+        } else if let Some(body) = rest.strip_prefix("<!--") {
+            let len = body.find("-->").unwrap_or(body.len());
+            let (open, body) = split(SyntaxKind::CommentOpen, rest, at(rest), 4);
+            let (body, left) = split(SyntaxKind::CommentBody, body, at(body), len);
+            let (close, left) = split(SyntaxKind::CommentClose, left, at(left), 3);
+
+            let children = if body.text.is_empty() {
+                alloc::vec![open, close]
+            } else {
+                alloc::vec![open, body, close]
+            };
+            out.push(branch(SyntaxKind::Comment, source, children));
+            rest = left;
+        } else if rest
+            .get(..9)
+            .is_some_and(|open| open.eq_ignore_ascii_case("<!doctype"))
+        {
+            let (open, body) = split(SyntaxKind::DoctypeOpen, rest, at(rest), 9);
+            let len = body.find('>').unwrap_or(body.len());
+            let mut children = alloc::vec![open];
+            push_trimmed(
+                &mut children,
+                SyntaxKind::DoctypeBody,
+                &body[..len],
+                at(body),
+            );
+            let (close, left) = split(SyntaxKind::TagClose, &body[len..], at(body) + len, 1);
+            children.push(close);
+
+            out.push(branch(SyntaxKind::Doctype, source, children));
+            rest = left;
+        } else if rest.starts_with("</") {
+            let (tag, left) = end_tag(source, rest);
+            rest = left;
+
+            let mut children = stack.pop().unwrap_or_default();
+            children.push(tag);
+            let element = branch(SyntaxKind::Element, source, children);
+            stack.last_mut().unwrap_or(&mut top).push(element);
+        } else if rest.starts_with('<') {
+            let mut children = Vec::new();
+            let (name, closed, left) = start_tag(source, rest, &mut children);
+            rest = left;
+
+            let raw = name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style");
+
+            if closed || is_void_element(name) {
+                out.push(branch(SyntaxKind::Element, source, children));
+            } else if raw {
+                let end = rest
+                    .match_indices("</")
+                    .map(|(i, _)| i)
+                    .find(|&i| {
+                        rest.get(i + 2..i + 2 + name.len())
+                            .is_some_and(|close| close.eq_ignore_ascii_case(name))
+                    })
+                    .unwrap_or(rest.len());
+
+                let (content, left) = split(SyntaxKind::RawText, rest, at(rest), end);
+                if !content.text.is_empty() {
+                    children.push(content);
+                }
+
+                let (tag, left) = end_tag(source, left);
+                children.push(tag);
+                rest = left;
+
+                out.push(branch(SyntaxKind::Element, source, children));
+            } else {
+                stack.push(children);
+            }
+        } else {
+            let len = rest.find('<').unwrap_or(rest.len());
+            push_trimmed(out, SyntaxKind::Text, &rest[..len], at(rest));
+            rest = &rest[len..];
+        }
+    }
+
+    // Only reached with unclosed elements if the text was not validated first
+    while let Some(children) = stack.pop() {
+        let element = branch(SyntaxKind::Element, source, children);
+        stack.last_mut().unwrap_or(&mut top).push(element);
+    }
+
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test]
+    fn test_cst() {
+        let text = concat!(
+            "<!DOCTYPE html>\n<html>\n  <head><style> p { color: red } </style></head>\n",
+            "  <body class = \"main\" hidden data-x=1>\n    <!-- note -->\n",
+            "    <p>Hello,\n  world! <br/><img src='a.png'></p>\n  </body >\n</html>\n",
+        );
+        let cst = StrictHTMLParser::parse_cst(text, StrictHTMLOptions::default())
+            .expect("Failed to parse HTML");
+
+        let source = cst
+            .iter()
+            .flat_map(CstNode::tokens)
+            .map(|t| t.text)
+            .collect::<String>();
+        assert_eq!(source, text);
+
+        for token in cst.iter().flat_map(CstNode::tokens) {
+            assert_eq!(
+                &text[token.offset..token.offset + token.text.len()],
+                token.text
+            );
+        }
+
+        let kinds = cst.iter().map(|n| n.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [
+            SyntaxKind::Doctype,
+            SyntaxKind::Whitespace,
+            SyntaxKind::Element,
+            SyntaxKind::Whitespace
+        ]);
+
+        let html = &cst[2];
+        assert_eq!(html.text, text[16..].trim_end());
+
+        let body = html
+            .children
+            .iter()
+            .find(|n| n.text.starts_with("<body"))
+            .expect("Couldn't find body");
+        let start = body.child(SyntaxKind::StartTag).expect("No start tag");
+        let attrs = start
+            .children
+            .iter()
+            .filter(|n| n.kind == SyntaxKind::Attribute)
+            .map(|n| n.text)
+            .collect::<Vec<_>>();
+        assert_eq!(attrs, ["class = \"main\"", "hidden", "data-x=1"]);
+        assert_eq!(
+            body.child(SyntaxKind::EndTag).map(|t| t.text),
+            Some("</body >")
+        );
+
+        let texts = cst
+            .iter()
+            .flat_map(CstNode::tokens)
+            .filter(|t| t.kind == SyntaxKind::Text)
+            .map(|t| t.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["Hello,\n  world!"]);
+
+        assert!(StrictHTMLParser::parse_cst("<p>", StrictHTMLOptions::default()).is_err());
+
+        let unclosed = build("<p><b>x");
+        assert_eq!(unclosed.len(), 1);
+        assert_eq!(unclosed[0].text, "<p><b>x");
+    }
+}
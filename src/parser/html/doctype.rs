@@ -0,0 +1,367 @@
+#[cfg(feature = "html-lenient")]
+use alloc::string::String;
+use core::fmt;
+
+use crate::{
+    parser::HTMLNode,
+    Soup,
+};
+
+/// Public identifiers that trigger quirks mode, matched as ASCII case-insensitive prefixes
+const QUIRKS_PUBLIC_PREFIXES: &[&str] = &[
+    "+//Silmaril//dtd html Pro v0r11 19970101//",
+    "-//AS//DTD HTML 3.0 asWedit + extensions//",
+    "-//AdvaSoft Ltd//DTD HTML 3.0 asWedit + extensions//",
+    "-//IETF//DTD HTML 2.0 Level 1//",
+    "-//IETF//DTD HTML 2.0 Level 2//",
+    "-//IETF//DTD HTML 2.0 Strict Level 1//",
+    "-//IETF//DTD HTML 2.0 Strict Level 2//",
+    "-//IETF//DTD HTML 2.0 Strict//",
+    "-//IETF//DTD HTML 2.0//",
+    "-//IETF//DTD HTML 2.1E//",
+    "-//IETF//DTD HTML 3.0//",
+    "-//IETF//DTD HTML 3.2 Final//",
+    "-//IETF//DTD HTML 3.2//",
+    "-//IETF//DTD HTML 3//",
+    "-//IETF//DTD HTML Level 0//",
+    "-//IETF//DTD HTML Level 1//",
+    "-//IETF//DTD HTML Level 2//",
+    "-//IETF//DTD HTML Level 3//",
+    "-//IETF//DTD HTML Strict Level 0//",
+    "-//IETF//DTD HTML Strict Level 1//",
+    "-//IETF//DTD HTML Strict Level 2//",
+    "-//IETF//DTD HTML Strict Level 3//",
+    "-//IETF//DTD HTML Strict//",
+    "-//IETF//DTD HTML//",
+    "-//Metrius//DTD Metrius Presentational//",
+    "-//Microsoft//DTD Internet Explorer 2.0 HTML Strict//",
+    "-//Microsoft//DTD Internet Explorer 2.0 HTML//",
+    "-//Microsoft//DTD Internet Explorer 2.0 Tables//",
+    "-//Microsoft//DTD Internet Explorer 3.0 HTML Strict//",
+    "-//Microsoft//DTD Internet Explorer 3.0 HTML//",
+    "-//Microsoft//DTD Internet Explorer 3.0 Tables//",
+    "-//Netscape Comm. Corp.//DTD HTML//",
+    "-//Netscape Comm. Corp.//DTD Strict HTML//",
+    "-//O'Reilly and Associates//DTD HTML 2.0//",
+    "-//O'Reilly and Associates//DTD HTML Extended 1.0//",
+    "-//O'Reilly and Associates//DTD HTML Extended Relaxed 1.0//",
+    "-//SQ//DTD HTML 2.0 HoTMetaL + extensions//",
+    "-//SoftQuad Software//DTD HoTMetaL PRO 6.0::19990601::extensions to HTML 4.0//",
+    "-//SoftQuad//DTD HoTMetaL PRO 4.0::19970916::extensions to HTML 4.0//",
+    "-//Spyglass//DTD HTML 2.0 Extended//",
+    "-//Sun Microsystems Corp.//DTD HotJava HTML//",
+    "-//Sun Microsystems Corp.//DTD HotJava Strict HTML//",
+    "-//W3C//DTD HTML 3 1995-03-24//",
+    "-//W3C//DTD HTML 3.2 Draft//",
+    "-//W3C//DTD HTML 3.2 Final//",
+    "-//W3C//DTD HTML 3.2//",
+    "-//W3C//DTD HTML 3.2S Draft//",
+    "-//W3C//DTD HTML 4.0 Frameset//",
+    "-//W3C//DTD HTML 4.0 Transitional//",
+    "-//W3C//DTD HTML Experimental 19960712//",
+    "-//W3C//DTD HTML Experimental 970421//",
+    "-//W3C//DTD W3 HTML//",
+    "-//W3O//DTD W3 HTML 3.0//",
+    "-//WebTechs//DTD Mozilla HTML 2.0//",
+    "-//WebTechs//DTD Mozilla HTML//",
+];
+
+/// Public identifiers that trigger quirks mode, matched exactly
+const QUIRKS_PUBLIC_IDS: &[&str] = &[
+    "-//W3O//DTD W3 HTML Strict 3.0//EN//",
+    "-/W3C/DTD HTML 4.0 Transitional/EN",
+    "HTML",
+];
+
+/// The system identifier that triggers quirks mode
+const QUIRKS_SYSTEM_ID: &str = "http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd";
+
+/// HTML 4.01 public identifiers, which trigger quirks mode without a system identifier
+/// and limited-quirks mode with one
+const HTML_4_PUBLIC_PREFIXES: &[&str] = &[
+    "-//W3C//DTD HTML 4.01 Frameset//",
+    "-//W3C//DTD HTML 4.01 Transitional//",
+];
+
+/// Public identifiers that trigger limited-quirks mode
+const LIMITED_QUIRKS_PUBLIC_PREFIXES: &[&str] = &[
+    "-//W3C//DTD XHTML 1.0 Frameset//",
+    "-//W3C//DTD XHTML 1.0 Transitional//",
+];
+
+/// The rendering mode a browser picks based on the doctype
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QuirksMode {
+    /// Standards mode
+    NoQuirks,
+    /// Almost standards mode, which only changes the layout of images in table cells
+    LimitedQuirks,
+    /// Emulates the layout bugs of legacy browsers
+    Quirks,
+}
+
+/// A parsed doctype, like `<!DOCTYPE html PUBLIC "..." "...">`
+///
+/// # Example
+/// ```rust
+/// # use soupy::{parser::{Doctype, QuirksMode}, prelude::*};
+/// let soup = Soup::html_strict(r#"<!DOCTYPE html PUBLIC "-//W3C//DTD HTML 4.01 Transitional//EN"><p>Hi</p>"#).unwrap();
+///
+/// let doctype = soup.doctype().expect("Missing doctype");
+/// assert_eq!(doctype.public_id, Some("-//W3C//DTD HTML 4.01 Transitional//EN"));
+/// assert_eq!(doctype.quirks_mode(), QuirksMode::Quirks);
+///
+/// assert_eq!(Doctype::HTML.to_string(), "<!DOCTYPE html>");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Doctype<'a> {
+    /// Name, `html` for HTML documents
+    pub name: &'a str,
+
+    /// Public identifier, from `PUBLIC "..."`
+    pub public_id: Option<&'a str>,
+
+    /// System identifier, the quoted string after the public identifier or `SYSTEM`
+    pub system_id: Option<&'a str>,
+}
+
+impl<'a> Doctype<'a> {
+    /// The HTML5 doctype, `<!DOCTYPE html>`
+    pub const HTML: Doctype<'static> = Doctype {
+        name: "html",
+        public_id: None,
+        system_id: None,
+    };
+
+    /// Parses the content of a doctype node, like `html PUBLIC "..." "..."`
+    ///
+    /// Identifiers that are not quoted are ignored.
+    #[must_use]
+    pub fn parse(text: &'a str) -> Self {
+        let text = text.trim_start_matches(is_space);
+        let (name, rest) = text.split_at(text.find(is_space).unwrap_or(text.len()));
+        let rest = rest.trim_start_matches(is_space);
+
+        let keyword = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (keyword, rest) = rest.split_at(keyword);
+
+        let mut doctype = Self {
+            name,
+            public_id: None,
+            system_id: None,
+        };
+
+        if keyword.eq_ignore_ascii_case("public") {
+            if let Some((public_id, rest)) = quoted(rest) {
+                doctype.public_id = Some(public_id);
+                doctype.system_id = quoted(rest).map(|(id, _)| id);
+            }
+        } else if keyword.eq_ignore_ascii_case("system") {
+            doctype.system_id = quoted(rest).map(|(id, _)| id);
+        }
+
+        doctype
+    }
+
+    /// Returns the rendering mode browsers use for a document with this doctype
+    ///
+    /// Documents without a doctype are rendered in [`QuirksMode::Quirks`].
+    #[must_use]
+    pub fn quirks_mode(&self) -> QuirksMode {
+        let public = self.public_id.unwrap_or_default();
+        let public_starts_with = |prefixes: &[&str]| {
+            prefixes.iter().any(|p| {
+                public
+                    .get(..p.len())
+                    .is_some_and(|s| s.eq_ignore_ascii_case(p))
+            })
+        };
+
+        if !self.name.eq_ignore_ascii_case("html")
+            || QUIRKS_PUBLIC_IDS
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(public))
+            || self
+                .system_id
+                .is_some_and(|id| id.eq_ignore_ascii_case(QUIRKS_SYSTEM_ID))
+            || public_starts_with(QUIRKS_PUBLIC_PREFIXES)
+            || (self.system_id.is_none() && public_starts_with(HTML_4_PUBLIC_PREFIXES))
+        {
+            QuirksMode::Quirks
+        } else if public_starts_with(LIMITED_QUIRKS_PUBLIC_PREFIXES)
+            || public_starts_with(HTML_4_PUBLIC_PREFIXES)
+        {
+            QuirksMode::LimitedQuirks
+        } else {
+            QuirksMode::NoQuirks
+        }
+    }
+
+    /// Returns `true` if this is the HTML5 doctype, optionally with the legacy system identifier
+    #[must_use]
+    pub fn is_html5(&self) -> bool {
+        self.name.eq_ignore_ascii_case("html")
+            && self.public_id.is_none()
+            && self.system_id.is_none_or(|id| id == "about:legacy-compat")
+    }
+
+    /// Writes the content of the doctype node, without the `<!DOCTYPE` and `>` delimiters
+    fn write_content(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let quote = |id: &str| if id.contains('"') { '\'' } else { '"' };
+
+        f.write_str(self.name)?;
+
+        match (self.public_id, self.system_id) {
+            (Some(public), system) => {
+                write!(f, " PUBLIC {q}{public}{q}", q = quote(public))?;
+
+                if let Some(system) = system {
+                    write!(f, " {q}{system}{q}", q = quote(system))?;
+                }
+            }
+            (None, Some(system)) => write!(f, " SYSTEM {q}{system}{q}", q = quote(system))?,
+            (None, None) => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes the doctype as markup, like `<!DOCTYPE html>`
+impl fmt::Display for Doctype<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<!DOCTYPE ")?;
+        self.write_content(f)?;
+        f.write_str(">")
+    }
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
+
+/// Splits a leading quoted string from `i`, returning the string without quotes and the rest
+fn quoted(i: &str) -> Option<(&str, &str)> {
+    let i = i.trim_start_matches(is_space);
+    let quote = i.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+
+    i[1..].split_once(quote)
+}
+
+/// Builds the content of a doctype node from the parts reported by `html5ever`,
+/// which uses empty strings for missing identifiers
+#[cfg(feature = "html-lenient")]
+pub(crate) fn content(name: &str, public_id: &str, system_id: &str) -> String {
+    let mut content = String::new();
+
+    let _ = Doctype {
+        name,
+        public_id: (!public_id.is_empty()).then_some(public_id),
+        system_id: (!system_id.is_empty()).then_some(system_id),
+    }
+    .write_content(&mut content);
+
+    content
+}
+
+impl<S> Soup<HTMLNode<S>>
+where
+    S: AsRef<str>,
+{
+    /// Returns the document's doctype, if it has one
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::QuirksMode, prelude::*};
+    /// # #[cfg(feature = "html-loose")] {
+    /// let soup = Soup::html_loose("<!DOCTYPE html><title>Hi</title>");
+    /// assert_eq!(soup.doctype().map(|d| d.quirks_mode()), Some(QuirksMode::NoQuirks));
+    ///
+    /// let soup = Soup::html_loose("<title>Hi</title>");
+    /// assert_eq!(soup.doctype(), None);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn doctype(&self) -> Option<Doctype<'_>> {
+        self.nodes.iter().find_map(|n| match n {
+            HTMLNode::Doctype(d) => Some(Doctype::parse(d.as_ref())),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Doctype::parse("html"), Doctype::HTML);
+        assert_eq!(
+            Doctype::parse(r#"HTML PUBLIC "-//W3C//DTD HTML 4.01//EN" 'strict.dtd'"#),
+            Doctype {
+                name: "HTML",
+                public_id: Some("-//W3C//DTD HTML 4.01//EN"),
+                system_id: Some("strict.dtd"),
+            }
+        );
+        assert_eq!(
+            Doctype::parse(r#"html system"about:legacy-compat""#).system_id,
+            Some("about:legacy-compat")
+        );
+        assert_eq!(
+            Doctype::parse("html public -//W3C//DTD HTML//EN"),
+            Doctype::HTML
+        );
+    }
+
+    #[cfg(any(feature = "html-lenient", feature = "html-loose"))]
+    #[test]
+    fn test_parse_document() {
+        use alloc::string::ToString;
+
+        let doctype = r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">"#;
+        let check = |parsed: Option<Doctype<'_>>| {
+            let parsed = parsed.expect("Missing doctype");
+            assert_eq!(parsed.to_string(), doctype);
+            assert_eq!(parsed.quirks_mode(), QuirksMode::LimitedQuirks);
+        };
+
+        #[cfg(feature = "html-lenient")]
+        check(Soup::html(doctype).doctype());
+        #[cfg(feature = "html-loose")]
+        check(Soup::html_loose(doctype).doctype());
+    }
+
+    #[test]
+    fn test_quirks_mode() {
+        let mode = |text| Doctype::parse(text).quirks_mode();
+
+        assert_eq!(mode("html"), QuirksMode::NoQuirks);
+        assert_eq!(
+            mode(r#"html SYSTEM "about:legacy-compat""#),
+            QuirksMode::NoQuirks
+        );
+        assert_eq!(
+            mode(r#"html PUBLIC "-//W3C//DTD HTML 4.01//EN""#),
+            QuirksMode::NoQuirks
+        );
+        assert_eq!(mode("svg"), QuirksMode::Quirks);
+        assert_eq!(mode(r#"html PUBLIC "html""#), QuirksMode::Quirks);
+        assert_eq!(
+            mode(r#"html PUBLIC "-//w3c//dtd html 3.2 final//en""#),
+            QuirksMode::Quirks
+        );
+        assert_eq!(
+            mode(r#"html PUBLIC "-//W3C//DTD HTML 4.01 Transitional//EN""#),
+            QuirksMode::Quirks
+        );
+        assert_eq!(
+            mode(r#"html PUBLIC "-//W3C//DTD HTML 4.01 Transitional//EN" "loose.dtd""#),
+            QuirksMode::LimitedQuirks
+        );
+        assert!(!Doctype::parse(r#"html PUBLIC "-//W3C//DTD HTML 4.01//EN""#).is_html5());
+    }
+}
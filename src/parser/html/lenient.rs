@@ -1,16 +1,34 @@
 use std::{
+    collections::BTreeMap,
     convert::Infallible,
     marker::PhantomData,
 };
 
-use crate::parser::{
-    html::HTMLNode,
-    Parser,
+use ego_tree::{
+    NodeId,
+    Tree,
+};
+use scraper::StrTendril;
+
+use crate::{
+    parser::{
+        html::{
+            doctype,
+            node::is_void_element,
+            sink,
+            HTMLNode,
+        },
+        Parser,
+    },
+    Soup,
 };
 
 /// Lenient HTML parser
 ///
 /// Attempts to work through invalid HTML.
+///
+/// Uses `html5ever` to build the tree directly. Unlike [`scraper::Html`], the contents of
+/// `<template>` elements are kept as the template's children.
 #[derive(Clone, Debug)]
 pub struct LenientHTMLParser<S> {
     _marker: PhantomData<S>,
@@ -21,21 +39,162 @@ where
     S: AsRef<str>,
 {
     type Input = S;
-    type Node = HTMLNode<scraper::StrTendril>;
+    type Node = HTMLNode<html5ever::tendril::StrTendril>;
     type Error = Infallible;
 
     fn parse(text: S) -> Result<Vec<Self::Node>, Self::Error> {
-        Ok(scraper::Html::parse_document(text.as_ref())
-            .tree
-            .root()
-            .children()
-            .filter_map(|n| n.try_into().ok())
-            .collect())
+        Ok(Self::parse_with(text, LenientHTMLOptions::default()))
+    }
+}
+
+impl<S> LenientHTMLParser<S>
+where
+    S: AsRef<str>,
+{
+    /// Parses `text` with the given options
+    #[must_use]
+    pub fn parse_with(text: S, options: LenientHTMLOptions) -> Vec<HTMLNode<StrTendril>> {
+        sink::parse(text.as_ref(), options)
     }
 }
 
+/// Options for the [`LenientHTMLParser`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LenientHTMLOptions {
+    /// Parse as if scripting were disabled, like a browser with JavaScript turned off
+    ///
+    /// The contents of `<noscript>` elements become real elements rather than a single text
+    /// node, which is usually what scrapers want from fallback markup.
+    pub disable_scripting: bool,
+}
+
+impl From<Tree<scraper::Node>> for Soup<HTMLNode<StrTendril>> {
+    fn from(mut tree: Tree<scraper::Node>) -> Self {
+        let root = tree.root().id();
+
+        Soup::from_nodes(take_children(&mut tree, root))
+    }
+}
+
+/// Converts an already-parsed [`scraper::Html`] document without re-parsing it
+///
+/// # Example
+/// ```rust
+/// # use soupy::prelude::*;
+/// let html = scraper::Html::parse_fragment("<p>Hello!</p>");
+/// let soup = Soup::from(html);
+/// assert_eq!(soup.tag("p").first().map(|p| p.all_text()), Some("Hello!".into()));
+/// ```
+impl From<scraper::Html> for Soup<HTMLNode<StrTendril>> {
+    fn from(html: scraper::Html) -> Self {
+        html.tree.into()
+    }
+}
+
+/// Moves the children of `id` out of the tree, converting them into [`HTMLNode`]s
+///
+/// Text, comments, and attribute values are moved rather than cloned,
+/// so the converted nodes share no storage with the (now hollow) tree.
+/// Uses an explicit stack of open elements, so deeply nested documents cannot overflow
+/// the call stack.
+fn take_children(tree: &mut Tree<scraper::Node>, id: NodeId) -> Vec<HTMLNode<StrTendril>> {
+    let mut root = Open::new(tree, id, None);
+    let mut stack = Vec::new();
+
+    loop {
+        let top = stack.last_mut().unwrap_or(&mut root);
+
+        if let Some(child) = top.rest.next() {
+            match take(tree, child) {
+                Taken::Leaf(node) => top.children.extend(node),
+                Taken::Parent(open) => stack.push(open),
+            }
+        } else if let Some(open) = stack.pop() {
+            let parent = stack.last_mut().unwrap_or(&mut root);
+
+            if let Some((name, attrs)) = open.element {
+                parent.children.push(HTMLNode::Element {
+                    name,
+                    attrs,
+                    children: open.children,
+                });
+            }
+        } else {
+            return root.children;
+        }
+    }
+}
+
+type Attrs = BTreeMap<StrTendril, StrTendril>;
+
+/// An element whose children are being taken
+struct Open {
+    /// Name and attributes of the element, or `None` for the node whose children are taken
+    element: Option<(StrTendril, Attrs)>,
+    children: Vec<HTMLNode<StrTendril>>,
+    /// Children not yet taken
+    rest: std::vec::IntoIter<NodeId>,
+}
+
+impl Open {
+    fn new(tree: &Tree<scraper::Node>, id: NodeId, element: Option<(StrTendril, Attrs)>) -> Self {
+        let ids = tree
+            .get(id)
+            .map(|n| n.children().map(|c| c.id()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        Self {
+            element,
+            children: Vec::new(),
+            rest: ids.into_iter(),
+        }
+    }
+}
+
+/// A node moved out of the tree by [`take`]
+enum Taken {
+    /// A node without children, or `None` if it has no [`HTMLNode`] equivalent
+    Leaf(Option<HTMLNode<StrTendril>>),
+    /// An element whose children still have to be taken
+    Parent(Open),
+}
+
 #[allow(clippy::mutable_key_type)]
-impl<'a> TryFrom<ego_tree::NodeRef<'a, scraper::Node>> for HTMLNode<scraper::StrTendril> {
+fn take(tree: &mut Tree<scraper::Node>, id: NodeId) -> Taken {
+    let Some(mut node) = tree.get_mut(id) else {
+        return Taken::Leaf(None);
+    };
+
+    let node = match std::mem::replace(node.value(), scraper::Node::Document) {
+        scraper::Node::Document
+        | scraper::Node::Fragment
+        | scraper::Node::ProcessingInstruction(_) => None,
+        scraper::Node::Doctype(d) => Some(HTMLNode::Doctype(
+            doctype::content(&d.name, &d.public_id, &d.system_id).into(),
+        )),
+        scraper::Node::Comment(comment) => Some(HTMLNode::Comment(comment.comment)),
+        scraper::Node::Text(text) => Some(HTMLNode::Text(text.text)),
+        scraper::Node::Element(element) => {
+            let name = StrTendril::from(&*element.name.local);
+            let attrs = element
+                .attrs
+                .into_iter()
+                .map(|(k, v)| (StrTendril::from(&*k.local), v))
+                .collect();
+
+            if !is_void_element(&name) {
+                return Taken::Parent(Open::new(tree, id, Some((name, attrs))));
+            }
+
+            Some(HTMLNode::Void { name, attrs })
+        }
+    };
+
+    Taken::Leaf(node)
+}
+
+#[allow(clippy::mutable_key_type)]
+impl<'a> TryFrom<ego_tree::NodeRef<'a, scraper::Node>> for HTMLNode<StrTendril> {
     type Error = ();
 
     fn try_from(node: ego_tree::NodeRef<'a, scraper::Node>) -> Result<Self, Self::Error> {
@@ -43,21 +202,23 @@ impl<'a> TryFrom<ego_tree::NodeRef<'a, scraper::Node>> for HTMLNode<scraper::Str
             scraper::Node::Document
             | scraper::Node::Fragment
             | scraper::Node::ProcessingInstruction(_) => Err(()),
-            scraper::Node::Doctype(doctype) => Ok(HTMLNode::Doctype(doctype.name.clone())),
+            scraper::Node::Doctype(d) => Ok(HTMLNode::Doctype(
+                doctype::content(&d.name, &d.public_id, &d.system_id).into(),
+            )),
             scraper::Node::Comment(comment) => Ok(HTMLNode::Comment(comment.comment.clone())),
             scraper::Node::Text(text) => Ok(HTMLNode::Text(text.text.clone())),
             scraper::Node::Element(element) => {
                 let name = element.name().into();
                 let attrs = element.attrs().map(|(k, v)| (k.into(), v.into())).collect();
 
-                Ok(match element.name() {
-                    "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link"
-                    | "meta" | "source" | "track" | "wbr" => HTMLNode::Void { name, attrs },
-                    _ => HTMLNode::Element {
+                Ok(if is_void_element(element.name()) {
+                    HTMLNode::Void { name, attrs }
+                } else {
+                    HTMLNode::Element {
                         name,
                         attrs,
                         children: node.children().filter_map(|e| e.try_into().ok()).collect(),
-                    },
+                    }
                 })
             }
         }
@@ -139,4 +300,75 @@ mod tests {
         );
         assert_eq!(headings.next().and_then(|h| h.name().cloned()), None);
     }
+
+    #[test]
+    fn test_take_matches_clone() {
+        let html = scraper::Html::parse_document(HELLO);
+
+        let cloned = html
+            .tree
+            .root()
+            .children()
+            .filter_map(|n| n.try_into().ok())
+            .collect::<Vec<parser::HTMLNode<_>>>();
+
+        assert_eq!(Soup::html(HELLO).nodes, cloned);
+    }
+
+    #[test]
+    fn test_from_scraper() {
+        let html = scraper::Html::parse_fragment(r#"<div id="a"><p>Text</p></div>"#);
+        let soup = Soup::from(html.tree.clone());
+
+        assert_eq!(soup.nodes, Soup::from(html).nodes);
+        assert_eq!(
+            soup.attr("id", "a").first().map(|d| d.all_text()),
+            Some("Text".into())
+        );
+    }
+
+    #[test]
+    fn test_disable_scripting() {
+        const PAGE: &str = r#"<body><noscript><img src="pixel.png"></noscript></body>"#;
+
+        let soup = Soup::html(PAGE);
+        assert!(soup.tag("img").first().is_none());
+        assert_eq!(
+            soup.tag("noscript").first().map(|n| n.all_text()),
+            Some(r#"<img src="pixel.png">"#.into())
+        );
+
+        let soup = Soup::html_with(PAGE, parser::LenientHTMLOptions {
+            disable_scripting: true,
+        });
+        assert_eq!(
+            soup.tag("noscript").first().map(|n| n
+                .children()
+                .iter()
+                .filter_map(Node::name)
+                .count()),
+            Some(1)
+        );
+        assert_eq!(
+            soup.tag("img").first().and_then(|i| i.get("src").cloned()),
+            Some("pixel.png".into())
+        );
+    }
+
+    #[test]
+    fn test_from_scraper_deep() {
+        let html = scraper::Html::parse_document(&"<div>".repeat(10_000));
+        let soup = Soup::from(html);
+
+        let mut depth = 0;
+        let mut node = soup.nodes.last();
+
+        while let Some(n) = node {
+            depth += 1;
+            node = n.children().last();
+        }
+
+        // `<html>` and `<body>` enclose the divs
+        assert_eq!(depth, 10_002);
+    }
 }
@@ -0,0 +1,511 @@
+use alloc::{
+    collections::BTreeMap,
+    vec::Vec,
+};
+use core::{
+    convert::Infallible,
+    marker::PhantomData,
+};
+
+use crate::{
+    cancel::{
+        CancelToken,
+        Cancelled,
+    },
+    parser::{
+        html::{
+            node::{
+                is_raw_element,
+                is_void_element,
+            },
+            HTMLNode,
+        },
+        Parser,
+    },
+};
+
+/// Fast, loose HTML parser
+///
+/// A single-pass recovering tokenizer with no dependencies.
+/// Unclosed elements are closed implicitly, stray end tags are ignored,
+/// and stray `<` characters are kept as text.
+///
+/// Unlike [`LenientHTMLParser`](crate::parser::LenientHTMLParser), this parser does not implement the
+/// full HTML5 tree construction algorithm and does not decode entities, but it borrows
+/// all text from the input and is considerably faster.
+#[derive(Clone, Debug)]
+pub struct LooseHTMLParser<'a> {
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> Parser for LooseHTMLParser<'a> {
+    type Input = &'a str;
+    type Node = HTMLNode<&'a str>;
+    type Error = Infallible;
+
+    fn parse(text: &'a str) -> Result<Vec<Self::Node>, Self::Error> {
+        // Without a token, building can't fail
+        Ok(Builder::new(text, None).build().unwrap_or_default())
+    }
+}
+
+impl<'a> LooseHTMLParser<'a> {
+    /// Parses `text`, stopping early if `token` is cancelled
+    ///
+    /// # Errors
+    /// If `token` is cancelled before parsing finishes.
+    pub fn parse_cancellable(
+        text: &'a str,
+        token: &CancelToken,
+    ) -> Result<Vec<HTMLNode<&'a str>>, Cancelled> {
+        Builder::new(text, Some(token)).build()
+    }
+}
+
+/// An element that has been opened but not yet closed
+struct Frame<'a> {
+    name: &'a str,
+    attrs: BTreeMap<&'a str, &'a str>,
+    children: Vec<HTMLNode<&'a str>>,
+}
+
+struct Builder<'a, 'c> {
+    input: &'a str,
+    cancel: Option<&'c CancelToken>,
+    pos: usize,
+    root: Vec<HTMLNode<&'a str>>,
+    stack: Vec<Frame<'a>>,
+}
+
+impl<'a, 'c> Builder<'a, 'c> {
+    fn new(input: &'a str, cancel: Option<&'c CancelToken>) -> Self {
+        Self {
+            input,
+            cancel,
+            pos: 0,
+            root: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn build(mut self) -> Result<Vec<HTMLNode<&'a str>>, Cancelled> {
+        while self.pos < self.input.len() {
+            if self.cancel.is_some_and(CancelToken::is_cancelled) {
+                return Err(Cancelled);
+            }
+
+            let rest = &self.input[self.pos..];
+
+            if let Some(r) = rest.strip_prefix("<!--") {
+                let (comment, len) = until(r, "-->");
+                self.pos += 4 + len;
+                self.push(HTMLNode::Comment(comment));
+            } else if starts_with_ignore_case(rest, "<!doctype") {
+                let (doctype, len) = until(&rest[9..], ">");
+                self.pos += 9 + len;
+                self.push(HTMLNode::Doctype(doctype.trim()));
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                // Bogus comment, like `<![CDATA[ ... ]]>` or `<?xml ... ?>`
+                let (comment, len) = until(&rest[2..], ">");
+                self.pos += 2 + len;
+                self.push(HTMLNode::Comment(comment));
+            } else if let Some(r) = rest.strip_prefix("</").filter(|r| starts_with_alpha(r)) {
+                let name = take_name(r);
+                let (_, len) = until(&r[name.len()..], ">");
+                self.pos += 2 + name.len() + len;
+                self.close(name);
+            } else if let Some(r) = rest.strip_prefix('<').filter(|r| starts_with_alpha(r)) {
+                self.pos += 1;
+                self.start_tag(r);
+            } else {
+                self.text(rest);
+            }
+        }
+
+        while !self.stack.is_empty() {
+            self.close_top();
+        }
+
+        Ok(self.root)
+    }
+
+    fn push(&mut self, node: HTMLNode<&'a str>) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.children.push(node);
+        } else {
+            self.root.push(node);
+        }
+    }
+
+    fn close_top(&mut self) {
+        if let Some(frame) = self.stack.pop() {
+            self.push(HTMLNode::Element {
+                name: frame.name,
+                attrs: frame.attrs,
+                children: frame.children,
+            });
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if let Some(i) = self
+            .stack
+            .iter()
+            .rposition(|f| f.name.eq_ignore_ascii_case(name))
+        {
+            while self.stack.len() > i {
+                self.close_top();
+            }
+        }
+    }
+
+    fn text(&mut self, rest: &'a str) {
+        let mut end = rest.len();
+
+        for (i, _) in rest.match_indices('<').filter(|(i, _)| *i > 0) {
+            if is_markup(&rest[i..]) {
+                end = i;
+                break;
+            }
+        }
+
+        self.pos += end;
+
+        let text = rest[..end].trim();
+
+        if !text.is_empty() {
+            self.push(HTMLNode::Text(text));
+        }
+    }
+
+    fn start_tag(&mut self, rest: &'a str) {
+        let name = take_name(rest);
+        let mut attrs = BTreeMap::new();
+        let mut closed = false;
+        let mut i = name.len();
+
+        loop {
+            i += whitespace(&rest[i..]);
+
+            let r = &rest[i..];
+
+            if r.is_empty() {
+                break;
+            } else if r.starts_with("/>") {
+                i += 2;
+                closed = true;
+                break;
+            } else if r.starts_with('>') {
+                i += 1;
+                break;
+            }
+
+            let attr = take_until(r, |c| {
+                c.is_ascii_whitespace() || matches!(c, '/' | '>' | '=')
+            });
+
+            if attr.is_empty() {
+                // Stray `/` or `=`
+                i += 1;
+                continue;
+            }
+
+            i += attr.len();
+            i += whitespace(&rest[i..]);
+
+            let mut value = "";
+
+            if let Some(r) = rest[i..].strip_prefix('=') {
+                i += 1 + whitespace(r);
+
+                let r = &rest[i..];
+
+                if let Some(quote) = r.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+                    let (v, len) = until(&r[1..], if quote == '"' { "\"" } else { "'" });
+                    value = v;
+                    i += 1 + len;
+                } else {
+                    value = take_until(r, |c| c.is_ascii_whitespace() || c == '>');
+                    i += value.len();
+                }
+            }
+
+            attrs.entry(attr).or_insert(value);
+        }
+
+        self.pos += i;
+
+        while self
+            .stack
+            .last()
+            .is_some_and(|f| closes_implicitly(f.name, name))
+        {
+            self.close_top();
+        }
+
+        if is_void_element(name) {
+            self.push(HTMLNode::Void { name, attrs });
+        } else if is_raw_element(name) {
+            let content = if closed { "" } else { self.raw_content(name) };
+
+            self.push(HTMLNode::RawElement {
+                name,
+                attrs,
+                content,
+            });
+        } else if closed {
+            self.push(HTMLNode::Element {
+                name,
+                attrs,
+                children: Vec::new(),
+            });
+        } else {
+            self.stack.push(Frame {
+                name,
+                attrs,
+                children: Vec::new(),
+            });
+        }
+    }
+
+    fn raw_content(&mut self, name: &str) -> &'a str {
+        let rest = &self.input[self.pos..];
+
+        let end = rest.match_indices("</").map(|(i, _)| i).find(|i| {
+            rest.get(i + 2..i + 2 + name.len())
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        });
+
+        if let Some(end) = end {
+            let (_, len) = until(&rest[end..], ">");
+            self.pos += end + len;
+            rest[..end].trim()
+        } else {
+            self.pos = self.input.len();
+            rest.trim()
+        }
+    }
+}
+
+/// Returns the text before `pat` and the length consumed, including `pat`
+///
+/// If `pat` is not found, the rest of the input is consumed.
+fn until<'a>(i: &'a str, pat: &str) -> (&'a str, usize) {
+    match i.find(pat) {
+        Some(end) => (&i[..end], end + pat.len()),
+        None => (i, i.len()),
+    }
+}
+
+fn take_until(i: &str, f: impl Fn(char) -> bool) -> &str {
+    &i[..i.find(f).unwrap_or(i.len())]
+}
+
+pub(super) fn take_name(i: &str) -> &str {
+    take_until(i, |c| c.is_ascii_whitespace() || matches!(c, '/' | '>'))
+}
+
+fn whitespace(i: &str) -> usize {
+    i.len()
+        - i.trim_start_matches(|c: char| c.is_ascii_whitespace())
+            .len()
+}
+
+pub(super) fn starts_with_alpha(i: &str) -> bool {
+    i.starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+fn starts_with_ignore_case(i: &str, prefix: &str) -> bool {
+    i.get(..prefix.len())
+        .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+}
+
+/// Returns `true` if the `<` at the start of `i` opens a tag, comment, or declaration
+fn is_markup(i: &str) -> bool {
+    i.strip_prefix('<').is_some_and(|r| {
+        starts_with_alpha(r)
+            || r.starts_with('!')
+            || r.starts_with('?')
+            || r.strip_prefix('/').is_some_and(starts_with_alpha)
+    })
+}
+
+/// Returns `true` if opening the tag `new` implicitly closes the open element `open`
+pub(super) fn closes_implicitly(open: &str, new: &str) -> bool {
+    let is = |name: &str, names: &[&str]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+    if open.eq_ignore_ascii_case("p") {
+        is(new, &[
+            "address",
+            "article",
+            "aside",
+            "blockquote",
+            "details",
+            "div",
+            "dl",
+            "fieldset",
+            "figcaption",
+            "figure",
+            "footer",
+            "form",
+            "h1",
+            "h2",
+            "h3",
+            "h4",
+            "h5",
+            "h6",
+            "header",
+            "hr",
+            "li",
+            "main",
+            "menu",
+            "nav",
+            "ol",
+            "p",
+            "pre",
+            "section",
+            "table",
+            "ul",
+        ])
+    } else if open.eq_ignore_ascii_case("li") {
+        is(new, &["li"])
+    } else if is(open, &["dt", "dd"]) {
+        is(new, &["dt", "dd"])
+    } else if open.eq_ignore_ascii_case("option") {
+        is(new, &["option", "optgroup"])
+    } else if open.eq_ignore_ascii_case("tr") {
+        is(new, &["tr", "tbody", "tfoot"])
+    } else if is(open, &["td", "th"]) {
+        is(new, &["td", "th", "tr", "tbody", "tfoot"])
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::*;
+
+    fn parse(text: &str) -> Vec<HTMLNode<&str>> {
+        LooseHTMLParser::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_well_formed() {
+        assert_eq!(
+            parse(r#"<!DOCTYPE html><!-- hi --><div class="a"><br/><p>Text</p></div>"#),
+            vec![
+                HTMLNode::Doctype("html"),
+                HTMLNode::Comment(" hi "),
+                HTMLNode::Element {
+                    name: "div",
+                    attrs: [("class", "a")].into(),
+                    children: vec![
+                        HTMLNode::Void {
+                            name: "br",
+                            attrs: [].into(),
+                        },
+                        HTMLNode::Element {
+                            name: "p",
+                            attrs: [].into(),
+                            children: vec![HTMLNode::Text("Text")],
+                        },
+                    ],
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        assert_eq!(
+            parse(r#"<input value=yes disabled name = 'n' id="x" id="y" / >"#),
+            vec![HTMLNode::Void {
+                name: "input",
+                attrs: [
+                    ("value", "yes"),
+                    ("disabled", ""),
+                    ("name", "n"),
+                    ("id", "x")
+                ]
+                .into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_recovery() {
+        assert_eq!(
+            parse("<ul><li>One<li>Two</ul></span><p>1 < 2<p>Last"),
+            vec![
+                HTMLNode::Element {
+                    name: "ul",
+                    attrs: [].into(),
+                    children: vec![
+                        HTMLNode::Element {
+                            name: "li",
+                            attrs: [].into(),
+                            children: vec![HTMLNode::Text("One")],
+                        },
+                        HTMLNode::Element {
+                            name: "li",
+                            attrs: [].into(),
+                            children: vec![HTMLNode::Text("Two")],
+                        },
+                    ],
+                },
+                HTMLNode::Element {
+                    name: "p",
+                    attrs: [].into(),
+                    children: vec![HTMLNode::Text("1 < 2")],
+                },
+                HTMLNode::Element {
+                    name: "p",
+                    attrs: [].into(),
+                    children: vec![HTMLNode::Text("Last")],
+                },
+            ]
+        );
+
+        assert_eq!(parse("<!-- unterminated"), vec![HTMLNode::Comment(
+            " unterminated"
+        )]);
+        assert_eq!(parse("<a href=\"x"), vec![HTMLNode::Element {
+            name: "a",
+            attrs: [("href", "x")].into(),
+            children: vec![],
+        }]);
+    }
+
+    #[test]
+    fn test_raw_element() {
+        assert_eq!(parse("<script>if (a </b) {}</SCRIPT ><style>"), vec![
+            HTMLNode::RawElement {
+                name: "script",
+                attrs: [].into(),
+                content: "if (a </b) {}",
+            },
+            HTMLNode::RawElement {
+                name: "style",
+                attrs: [].into(),
+                content: "",
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_soup() {
+        let soup = Soup::html_loose("<div><a href='/one'>One<a href='/two'>Two</div>");
+
+        let hrefs = soup
+            .tag("a")
+            .all()
+            .filter_map(|a| a.get("href").copied())
+            .collect::<Vec<_>>();
+
+        assert_eq!(hrefs, ["/one", "/two"]);
+    }
+}
@@ -1,13 +1,77 @@
 #[cfg(feature = "html-lenient")]
+pub(crate) mod charset;
+#[cfg(feature = "html-strict")]
+mod cst;
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+mod doctype;
+#[cfg(feature = "html-lenient")]
 mod lenient;
-#[cfg(feature = "html")]
-mod node;
+#[cfg(feature = "html-loose")]
+mod loose;
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub(crate) mod node;
+#[cfg(feature = "html-loose")]
+mod rewrite;
+#[cfg(feature = "html-lenient")]
+mod scraper_node;
+#[cfg(feature = "html-lenient")]
+mod sink;
 #[cfg(feature = "html-strict")]
 mod strict;
 
 #[cfg(feature = "html-lenient")]
-pub use lenient::LenientHTMLParser;
-#[cfg(feature = "html")]
-pub use node::HTMLNode;
+pub use charset::Encoding;
+#[cfg(feature = "html-strict")]
+pub use cst::{
+    CstNode,
+    SyntaxKind,
+    Tokens,
+};
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub use doctype::{
+    Doctype,
+    QuirksMode,
+};
+#[cfg(feature = "html-lenient")]
+pub use lenient::{
+    LenientHTMLOptions,
+    LenientHTMLParser,
+};
+#[cfg(feature = "html-loose")]
+pub use loose::LooseHTMLParser;
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub use node::{
+    Escaping,
+    HTMLNode,
+};
+#[cfg(feature = "html-loose")]
+pub use rewrite::{
+    HTMLRewriter,
+    Rewrite,
+};
+#[cfg(feature = "html-lenient")]
+pub use scraper_node::ScraperNode;
 #[cfg(feature = "html-strict")]
-pub use strict::StrictHTMLParser;
+pub use strict::{
+    OwnedStrictHTMLParser,
+    PartialParse,
+    StrictHTMLError,
+    StrictHTMLOptions,
+    StrictHTMLParser,
+};
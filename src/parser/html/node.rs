@@ -1,6 +1,29 @@
-use std::collections::BTreeMap;
-
-use crate::node::Node;
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    escape,
+    node::Node,
+    Soup,
+};
+
+/// How text and attribute values are written by [`HTMLNode::serialize`]
+///
+/// The lenient parser and [`decode_entities`](crate::entities::decode_entities) produce decoded
+/// values, so `&` in the text stands for itself. The strict and loose parsers keep values as
+/// they appear in the source, so `&amp;` is still encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Escaping {
+    /// Values are decoded, so `&` and `<` are escaped when written
+    #[default]
+    Decoded,
+    /// Values are source text and are written as they are, except for `"` in attribute values
+    Raw,
+}
 
 /// An HTML node
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -38,6 +61,76 @@ pub enum HTMLNode<S> {
     Text(S),
 }
 
+/// Elements that are unable to contain children
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Returns `true` if `name` is a void element, ignoring ASCII case
+pub(crate) fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS.iter().any(|v| v.eq_ignore_ascii_case(name))
+}
+
+/// Elements whose text content is written without escaping
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "script",
+    "style",
+    "xmp",
+];
+
+/// Returns `true` if `name` is an element whose content is not parsed as HTML, ignoring ASCII case
+pub(crate) fn is_raw_element(name: &str) -> bool {
+    name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style")
+}
+
+/// Names reserved by SVG and `MathML` that would otherwise be valid custom element names
+const RESERVED_CUSTOM_NAMES: &[&str] = &[
+    "annotation-xml",
+    "color-profile",
+    "font-face",
+    "font-face-src",
+    "font-face-uri",
+    "font-face-format",
+    "font-face-name",
+    "missing-glyph",
+];
+
+/// Returns `true` if `name` is a valid custom element name, like `my-widget`
+///
+/// Custom element names start with a lowercase ASCII letter, contain a hyphen, and contain no
+/// uppercase ASCII letters.
+pub(crate) fn is_custom_element_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && name.contains('-')
+        && chars.all(|c| {
+            matches!(c,
+                '-' | '.' | '_' | '\u{B7}'
+                | '0'..='9'
+                | 'a'..='z'
+                | '\u{C0}'..='\u{D6}'
+                | '\u{D8}'..='\u{F6}'
+                | '\u{F8}'..='\u{37D}'
+                | '\u{37F}'..='\u{1FFF}'
+                | '\u{200C}'..='\u{200D}'
+                | '\u{203F}'..='\u{2040}'
+                | '\u{2070}'..='\u{218F}'
+                | '\u{2C00}'..='\u{2FEF}'
+                | '\u{3001}'..='\u{D7FF}'
+                | '\u{F900}'..='\u{FDCF}'
+                | '\u{FDF0}'..='\u{FFFD}'
+                | '\u{10000}'..='\u{EFFFF}'
+            )
+        })
+        && !RESERVED_CUSTOM_NAMES.contains(&name)
+}
+
 impl<S> Node for HTMLNode<S> {
     type Text = S;
 
@@ -57,6 +150,13 @@ impl<S> Node for HTMLNode<S> {
         }
     }
 
+    fn comment(&self) -> Option<&S> {
+        match self {
+            Self::Comment(c) => Some(c),
+            _ => None,
+        }
+    }
+
     fn attrs(&self) -> Option<&BTreeMap<S, S>> {
         match self {
             Self::Element { attrs, .. }
@@ -77,14 +177,340 @@ impl<S> Node for HTMLNode<S> {
 
 impl<S> HTMLNode<S> {
     /// Iterate over direct children
-    pub fn iter(&self) -> std::slice::Iter<Self> {
+    pub fn iter(&self) -> core::slice::Iter<'_, Self> {
         self.children().iter()
     }
+
+    /// Recursively shrinks the capacity of every child list as much as possible
+    pub fn shrink_to_fit(&mut self) {
+        if let Self::Element { children, .. } = self {
+            children.shrink_to_fit();
+            children.iter_mut().for_each(Self::shrink_to_fit);
+        }
+    }
+
+    /// Converts every text value in the tree (names, attributes, and content) with `f`
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use soupy::{parser::HTMLNode, prelude::*};
+    /// let soup = Soup::html_strict(r#"<a href="/">Home</a>"#).unwrap();
+    /// let a = (*soup.tag("a").first().unwrap()).clone();
+    /// let a: HTMLNode<Arc<str>> = a.map_text(Arc::from);
+    /// assert_eq!(a.get("href").map(AsRef::as_ref), Some("/"));
+    /// ```
+    #[must_use]
+    pub fn map_text<T, F>(self, mut f: F) -> HTMLNode<T>
+    where
+        T: Ord,
+        F: FnMut(S) -> T,
+    {
+        self.map_text_with(&mut f)
+    }
+
+    fn map_text_with<T, F>(self, f: &mut F) -> HTMLNode<T>
+    where
+        T: Ord,
+        F: FnMut(S) -> T,
+    {
+        let mut attrs = |attrs: BTreeMap<S, S>| -> BTreeMap<T, T> {
+            attrs.into_iter().map(|(k, v)| (f(k), f(v))).collect()
+        };
+
+        match self {
+            Self::Comment(c) => HTMLNode::Comment(f(c)),
+            Self::Doctype(d) => HTMLNode::Doctype(f(d)),
+            Self::Element {
+                name,
+                attrs: a,
+                children,
+            } => {
+                let attrs = attrs(a);
+
+                HTMLNode::Element {
+                    name: f(name),
+                    attrs,
+                    children: children.into_iter().map(|c| c.map_text_with(f)).collect(),
+                }
+            }
+            Self::RawElement {
+                name,
+                attrs: a,
+                content,
+            } => {
+                let attrs = attrs(a);
+
+                HTMLNode::RawElement {
+                    name: f(name),
+                    attrs,
+                    content: f(content),
+                }
+            }
+            Self::Void { name, attrs: a } => {
+                let attrs = attrs(a);
+
+                HTMLNode::Void {
+                    name: f(name),
+                    attrs,
+                }
+            }
+            Self::Text(t) => HTMLNode::Text(f(t)),
+        }
+    }
+}
+
+impl<S> HTMLNode<S>
+where
+    S: AsRef<str>,
+{
+    /// Returns the unparsed content of a raw element, like the body of a `<script>` or `<style>`
+    ///
+    /// Parsers that build raw elements as regular elements, like the lenient parser, store the
+    /// content as a single text child, which is returned instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<script>window.__DATA__ = {};</script>").unwrap();
+    /// let script = soup.tag("script").first().expect("Couldn't find script");
+    /// assert_eq!(script.raw_content(), Some(&"window.__DATA__ = {};"));
+    /// ```
+    #[must_use]
+    pub fn raw_content(&self) -> Option<&S> {
+        match self {
+            Self::RawElement { content, .. } => Some(content),
+            Self::Element { name, children, .. } if is_raw_element(name.as_ref()) => {
+                match children.as_slice() {
+                    [Self::Text(content)] => Some(content),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is an autonomous custom element, like `<my-widget>`
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<user-card><p>Hi</p></user-card>").unwrap();
+    /// let custom = soup.all().filter(|n| n.is_custom_element()).count();
+    /// assert_eq!(custom, 1);
+    /// ```
+    #[must_use]
+    pub fn is_custom_element(&self) -> bool {
+        self.name()
+            .is_some_and(|name| is_custom_element_name(name.as_ref()))
+    }
+
+    /// Writes the node as HTML
+    ///
+    /// Text and attribute values are written according to `escaping`, except inside elements
+    /// like `<script>` and `<style>` whose content is not parsed as HTML. Attribute values are
+    /// always double-quoted, and void elements have no end tag.
+    ///
+    /// # Errors
+    /// If writing to `w` fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::parser::{Escaping, HTMLNode};
+    /// let p = HTMLNode::Element {
+    ///     name: "p",
+    ///     attrs: [("title", r#""hi""#)].into(),
+    ///     children: vec![HTMLNode::Text("a < b"), HTMLNode::Void { name: "br", attrs: Default::default() }],
+    /// };
+    /// let script = HTMLNode::Element {
+    ///     name: "script",
+    ///     attrs: Default::default(),
+    ///     children: vec![HTMLNode::Text("if (a < b) {}")],
+    /// };
+    ///
+    /// let mut html = String::new();
+    /// p.serialize(&mut html, Escaping::Decoded).unwrap();
+    /// script.serialize(&mut html, Escaping::Decoded).unwrap();
+    /// assert_eq!(html, r#"<p title="&quot;hi&quot;">a &lt; b<br></p><script>if (a < b) {}</script>"#);
+    ///
+    /// let mut html = String::new();
+    /// HTMLNode::Text("Fish &amp; Chips").serialize(&mut html, Escaping::Raw).unwrap();
+    /// assert_eq!(html, "Fish &amp; Chips");
+    /// ```
+    pub fn serialize<W>(&self, w: &mut W, escaping: Escaping) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match self {
+            Self::Comment(c) => write!(w, "<!--{}-->", c.as_ref()),
+            Self::Doctype(d) => write!(w, "<!DOCTYPE {}>", d.as_ref()),
+            Self::Element {
+                name,
+                attrs,
+                children,
+            } => {
+                write_start_tag(w, name, attrs, escaping)?;
+
+                if children.is_empty() && is_void_element(name.as_ref()) {
+                    return Ok(());
+                }
+
+                let raw = RAW_TEXT_ELEMENTS
+                    .iter()
+                    .any(|r| r.eq_ignore_ascii_case(name.as_ref()));
+
+                for child in children {
+                    match child {
+                        Self::Text(t) if raw => w.write_str(t.as_ref())?,
+                        child => child.serialize(w, escaping)?,
+                    }
+                }
+
+                write!(w, "</{}>", name.as_ref())
+            }
+            Self::RawElement {
+                name,
+                attrs,
+                content,
+            } => {
+                write_start_tag(w, name, attrs, escaping)?;
+                write!(w, "{}</{}>", content.as_ref(), name.as_ref())
+            }
+            Self::Void { name, attrs } => write_start_tag(w, name, attrs, escaping),
+            Self::Text(t) => match escaping {
+                Escaping::Decoded => escape::text(w, t.as_ref()),
+                Escaping::Raw => w.write_str(t.as_ref()),
+            },
+        }
+    }
+
+    /// Formats the node as HTML with the given escaping, as with [`HTMLNode::serialize`]
+    ///
+    /// Formatting the node directly treats its values as decoded, which is only right for nodes
+    /// from the lenient parser or [`decode_entities`](crate::entities::decode_entities).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::Escaping, prelude::*};
+    /// let soup = Soup::html_strict(r#"<p title="Fish &amp; Chips">Fish &amp; <b>Chips</b></p>"#).unwrap();
+    /// let p = soup.tag("p").first().expect("Couldn't find p");
+    ///
+    /// let html = p.display(Escaping::Raw).to_string();
+    /// assert_eq!(html, r#"<p title="Fish &amp; Chips">Fish &amp;<b>Chips</b></p>"#);
+    /// ```
+    #[must_use]
+    pub fn display(&self, escaping: Escaping) -> impl fmt::Display + '_ {
+        HTMLDisplay {
+            node: self,
+            escaping,
+        }
+    }
+}
+
+struct HTMLDisplay<'a, S> {
+    node: &'a HTMLNode<S>,
+    escaping: Escaping,
+}
+
+impl<S> fmt::Display for HTMLDisplay<'_, S>
+where
+    S: AsRef<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.node.serialize(f, self.escaping)
+    }
+}
+
+impl<S> Soup<HTMLNode<S>> {
+    /// Shrinks the capacity of every child list in the document as much as possible
+    ///
+    /// Useful for documents that are kept around for a long time after parsing. Text and
+    /// attributes are left as they are; [`HTMLNode::map_text`] can convert them to a more
+    /// compact type.
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.nodes.iter_mut().for_each(HTMLNode::shrink_to_fit);
+    }
+}
+
+impl<S> Soup<HTMLNode<S>>
+where
+    S: AsRef<str>,
+{
+    /// Serializes the whole document back into HTML, as with [`HTMLNode::serialize`]
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::Escaping, prelude::*};
+    /// # #[cfg(feature = "html-loose")] {
+    /// let html = r#"<!DOCTYPE html><html><head></head><body><a href="/?a=1&amp;b=2">Fish &amp; Chips</a><br></body></html>"#;
+    /// assert_eq!(Soup::html_loose(html).to_html(Escaping::Raw), html);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn to_html(&self, escaping: Escaping) -> String {
+        let mut html = String::new();
+
+        for node in &self.nodes {
+            let _ = node.serialize(&mut html, escaping);
+        }
+
+        html
+    }
+}
+
+/// Serializes the node as HTML, with [`HTMLNode::serialize`] and [`Escaping::Decoded`]
+///
+/// Text and attribute values are escaped, while the content of raw elements is written as-is.
+/// For nodes from the strict or loose parsers, whose values are still encoded, use
+/// [`HTMLNode::display`] with [`Escaping::Raw`] instead.
+///
+/// # Example
+/// ```rust
+/// # use soupy::parser::HTMLNode;
+/// let p = HTMLNode::Element {
+///     name: "p",
+///     attrs: [("class", "intro")].into(),
+///     children: vec![HTMLNode::Text("Fish & Chips")],
+/// };
+/// assert_eq!(p.to_string(), r#"<p class="intro">Fish &amp; Chips</p>"#);
+/// ```
+impl<S> fmt::Display for HTMLNode<S>
+where
+    S: AsRef<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.serialize(f, Escaping::Decoded)
+    }
+}
+
+fn write_start_tag<S, W>(
+    f: &mut W,
+    name: &S,
+    attrs: &BTreeMap<S, S>,
+    escaping: Escaping,
+) -> fmt::Result
+where
+    S: AsRef<str>,
+    W: fmt::Write,
+{
+    write!(f, "<{}", name.as_ref())?;
+
+    for (k, v) in attrs {
+        write!(f, " {}=\"", k.as_ref())?;
+        match escaping {
+            Escaping::Decoded => escape::attr(f, v.as_ref())?,
+            Escaping::Raw => escape::quote(f, v.as_ref())?,
+        }
+        f.write_str("\"")?;
+    }
+
+    f.write_str(">")
 }
 
 impl<'a, S> IntoIterator for &'a HTMLNode<S> {
     type Item = &'a HTMLNode<S>;
-    type IntoIter = std::slice::Iter<'a, HTMLNode<S>>;
+    type IntoIter = core::slice::Iter<'a, HTMLNode<S>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
@@ -93,7 +519,11 @@ impl<'a, S> IntoIterator for &'a HTMLNode<S> {
 
 #[cfg(test)]
 mod tests {
-    use std::ops::Deref;
+    use alloc::{
+        string::ToString,
+        vec,
+    };
+    use core::ops::Deref;
 
     use super::*;
     use crate::*;
@@ -208,4 +638,141 @@ mod tests {
             Some("Other Link".into())
         );
     }
+
+    #[test]
+    fn test_display_round_trip() {
+        let soup = Soup::html_strict(HELLO).expect("Failed to parse HTML");
+
+        let html = soup
+            .nodes
+            .iter()
+            .map(|n| n.display(Escaping::Raw).to_string())
+            .collect::<String>();
+
+        assert!(html.starts_with(r#"<!DOCTYPE html><html lang="en"><head><meta charset="UTF-8">"#));
+
+        let reparsed = Soup::html_strict(&html).expect("Failed to parse serialized HTML");
+
+        assert_eq!(reparsed.nodes, soup.nodes);
+    }
+
+    #[cfg(feature = "html-lenient")]
+    #[test]
+    fn test_display_round_trip_decoded() {
+        let soup = Soup::html(
+            r#"<p title="Fish &amp; &quot;Chips&quot;">1 &lt; 2 &amp;&amp; <b>&amp;lt;</b></p>
+            <script>if (a < b && c) {}</script>"#,
+        );
+
+        let html = soup
+            .nodes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<String>();
+
+        assert_eq!(Soup::html(html.as_str()).nodes, soup.nodes);
+    }
+
+    #[test]
+    fn test_serialize_raw_entities() {
+        let html =
+            r#"<p title="Fish &amp; &quot;Chips&quot;">1 &lt; 2 &amp;&amp; <b>&#x3E;</b></p>"#;
+
+        let soup = Soup::html_strict(html).expect("Failed to parse HTML");
+        let serialized = soup.to_html(Escaping::Raw);
+        assert_eq!(
+            serialized,
+            r#"<p title="Fish &amp; &quot;Chips&quot;">1 &lt; 2 &amp;&amp;<b>&#x3E;</b></p>"#
+        );
+
+        let reparsed = Soup::html_strict(&serialized).expect("Failed to parse serialized HTML");
+        assert_eq!(reparsed.nodes, soup.nodes);
+
+        #[cfg(feature = "html-loose")]
+        {
+            let soup = Soup::html_loose(html);
+            assert_eq!(soup.to_html(Escaping::Raw), serialized);
+            assert_eq!(
+                Soup::html_loose(&soup.to_html(Escaping::Raw)).nodes,
+                soup.nodes
+            );
+        }
+
+        let quoted =
+            Soup::html_strict(r#"<a title='say "hi"'>x</a>"#).expect("Failed to parse HTML");
+        assert_eq!(
+            quoted.to_html(Escaping::Raw),
+            r#"<a title="say &quot;hi&quot;">x</a>"#
+        );
+    }
+
+    #[cfg(feature = "html-lenient")]
+    #[test]
+    fn test_display_escaping() {
+        let soup = Soup::html(
+            r#"<p title="&quot;quoted&quot; &amp; more">1 &lt; 2 &amp;&amp; 3 &gt; 2</p>"#,
+        );
+
+        let p = soup.tag("p").first().expect("Could not find p tag");
+
+        assert_eq!(
+            p.to_string(),
+            r#"<p title="&quot;quoted&quot; &amp; more">1 &lt; 2 &amp;&amp; 3 &gt; 2</p>"#
+        );
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut soup = Soup::html_strict(HELLO).expect("Failed to parse HTML");
+
+        soup.nodes.reserve(64);
+        soup.shrink_to_fit();
+
+        assert_eq!(soup.nodes.capacity(), soup.nodes.len());
+
+        for node in &soup {
+            if let HTMLNode::Element { children, .. } = &*node {
+                assert_eq!(children.capacity(), children.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_element_name() {
+        assert!(is_custom_element_name("my-widget"));
+        assert!(is_custom_element_name("x-"));
+        assert!(is_custom_element_name("math-α"));
+        assert!(is_custom_element_name("emotion-😍"));
+
+        assert!(!is_custom_element_name("widget"));
+        assert!(!is_custom_element_name("My-widget"));
+        assert!(!is_custom_element_name("my-Widget"));
+        assert!(!is_custom_element_name("-widget"));
+        assert!(!is_custom_element_name("font-face"));
+        assert!(!is_custom_element_name("svg:my-use"));
+    }
+
+    #[cfg(feature = "html-lenient")]
+    #[test]
+    fn test_raw_content() {
+        let soup = Soup::html(
+            r#"<head><style>p { color: red }</style><script type="application/json">{"a": 1}</script></head>
+            <p>{"a": 1}</p>"#,
+        );
+
+        let json = regex::Regex::new(r"^\{").expect("Failed to compile regex");
+        let found = soup
+            .filter(filter::RawContent { content: json })
+            .all()
+            .map(|n| n.get_str("type").map(String::from))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [Some("application/json".into())]);
+
+        let style = soup.tag("style").first().expect("Couldn't find style");
+        assert_eq!(style.raw_content().map(|c| &**c), Some("p { color: red }"));
+        assert_eq!(
+            soup.tag("p").first().and_then(|p| p.raw_content().cloned()),
+            None
+        );
+    }
 }
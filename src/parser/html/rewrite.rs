@@ -0,0 +1,429 @@
+use alloc::{
+    boxed::Box,
+    string::String,
+    vec::Vec,
+};
+
+use crate::{
+    filter::Filter,
+    parser::{
+        html::{
+            loose::{
+                closes_implicitly,
+                starts_with_alpha,
+                take_name,
+            },
+            node::{
+                is_raw_element,
+                is_void_element,
+            },
+            HTMLNode,
+            LooseHTMLParser,
+        },
+        Parser,
+    },
+};
+
+/// What to do with an element selected by an [`HTMLRewriter`] handler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rewrite {
+    /// Write the element's original source unchanged
+    Keep,
+    /// Drop the element and everything inside it
+    Remove,
+    /// Write the given markup in place of the element
+    Replace(String),
+}
+
+type Handler<'h> = (
+    Box<dyn Filter<HTMLNode<String>> + 'h>,
+    Box<dyn FnMut(&HTMLNode<String>) -> Rewrite + 'h>,
+);
+
+/// An element selected by a handler, buffered until it is closed
+struct Capture {
+    handler: usize,
+    /// Position of the element in the stack of open elements
+    depth: usize,
+    source: String,
+}
+
+/// Streaming HTML rewriter
+///
+/// Input is fed in chunks with [`write`](HTMLRewriter::write) and passed through to the output sink
+/// as soon as each piece of markup is complete. Only elements selected by a handler are buffered,
+/// so large documents can be rewritten without building a tree.
+///
+/// Elements are matched against their start tag, so filters only see the name and attributes.
+/// The handler then receives the complete element, parsed with the [`LooseHTMLParser`].
+/// Content inside a selected element is not matched against other handlers.
+///
+/// # Example
+/// ```rust
+/// use soupy::{
+///     filter::{Attr, Tag},
+///     parser::{HTMLRewriter, Rewrite},
+///     prelude::*,
+/// };
+///
+/// let mut output = String::new();
+///
+/// let mut rewriter = HTMLRewriter::new(|chunk: &str| output.push_str(chunk))
+///     .on(Tag { tag: "script" }, |_| Rewrite::Remove)
+///     .on(Attr { name: "class", value: "price" }, |el| {
+///         Rewrite::Replace(format!("<b>{}</b>", el.all_text()))
+///     });
+///
+/// rewriter.write("<p>Only <span class=price>$5</span></p><scr");
+/// rewriter.write("ipt>track()</script>");
+/// rewriter.end();
+///
+/// assert_eq!(output, "<p>Only <b>$5</b></p>");
+/// ```
+pub struct HTMLRewriter<'h, O> {
+    output: O,
+    handlers: Vec<Handler<'h>>,
+    buffer: String,
+    /// Names of the currently open elements
+    stack: Vec<String>,
+    /// Name of the open `script` or `style` element, whose content is not markup
+    raw: Option<String>,
+    capture: Option<Capture>,
+}
+
+enum Token {
+    Start { name: String, closed: bool },
+    End { name: String },
+    Other,
+}
+
+impl<'h, O> HTMLRewriter<'h, O>
+where
+    O: FnMut(&str),
+{
+    /// Creates a rewriter that passes every chunk of output to `output`
+    pub fn new(output: O) -> Self {
+        Self {
+            output,
+            handlers: Vec::new(),
+            buffer: String::new(),
+            stack: Vec::new(),
+            raw: None,
+            capture: None,
+        }
+    }
+
+    /// Registers a handler for elements matching `filter`
+    ///
+    /// When several handlers match, the first one registered wins.
+    #[must_use]
+    pub fn on<F, H>(mut self, filter: F, handler: H) -> Self
+    where
+        F: Filter<HTMLNode<String>> + 'h,
+        H: FnMut(&HTMLNode<String>) -> Rewrite + 'h,
+    {
+        self.handlers.push((Box::new(filter), Box::new(handler)));
+        self
+    }
+
+    /// Feeds a chunk of input, writing any output that is ready
+    pub fn write(&mut self, chunk: &str) {
+        self.buffer.push_str(chunk);
+        self.process(false);
+    }
+
+    /// Finishes the document, flushing all remaining output
+    pub fn end(mut self) {
+        self.process(true);
+
+        self.stack.clear();
+        self.finish_capture();
+    }
+
+    fn process(&mut self, ended: bool) {
+        let mut pos = 0;
+
+        while let Some((len, token)) = self.next_token(&self.buffer[pos..], ended) {
+            let source = String::from(&self.buffer[pos..pos + len]);
+            pos += len;
+
+            self.token(&source, token);
+        }
+
+        self.buffer.drain(..pos);
+    }
+
+    /// Returns the length of the next complete token, or `None` if more input is needed
+    fn next_token(&self, rest: &str, ended: bool) -> Option<(usize, Token)> {
+        if rest.is_empty() {
+            return None;
+        }
+
+        if let Some(raw) = &self.raw {
+            let end = rest.match_indices("</").map(|(i, _)| i).find(|i| {
+                rest.get(i + 2..i + 2 + raw.len())
+                    .is_some_and(|n| n.eq_ignore_ascii_case(raw))
+            });
+
+            match end {
+                Some(0) => {}
+                Some(end) => return Some((end, Token::Other)),
+                // Hold back a possible partial end tag
+                None if !ended => {
+                    return rest
+                        .rfind('<')
+                        .or(Some(rest.len()))
+                        .filter(|&i| i > 0)
+                        .map(|i| (i, Token::Other));
+                }
+                None => return Some((rest.len(), Token::Other)),
+            }
+        }
+
+        if !rest.starts_with('<') {
+            return Some((rest.find('<').unwrap_or(rest.len()), Token::Other));
+        }
+
+        let complete = |end: Option<usize>| match end {
+            Some(end) => Some(end),
+            None if ended => Some(rest.len()),
+            None => None,
+        };
+
+        if rest.len() < 4 && !ended && "<!--".starts_with(rest) {
+            None
+        } else if let Some(r) = rest.strip_prefix("<!--") {
+            complete(r.find("-->").map(|i| 4 + i + 3)).map(|len| (len, Token::Other))
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            complete(rest.find('>').map(|i| i + 1)).map(|len| (len, Token::Other))
+        } else if let Some(r) = rest.strip_prefix("</").filter(|r| starts_with_alpha(r)) {
+            let name = take_name(r).into();
+            complete(rest.find('>').map(|i| i + 1)).map(|len| (len, Token::End { name }))
+        } else if let Some(r) = rest.strip_prefix('<').filter(|r| starts_with_alpha(r)) {
+            let len = complete(tag_end(rest))?;
+            let name = take_name(r).into();
+            let closed = rest[..len].ends_with("/>");
+
+            Some((len, Token::Start { name, closed }))
+        } else if (rest == "<" || rest == "</") && !ended {
+            None
+        } else {
+            Some((1, Token::Other))
+        }
+    }
+
+    fn token(&mut self, source: &str, token: Token) {
+        match token {
+            Token::Start { name, closed } => {
+                while self
+                    .stack
+                    .last()
+                    .is_some_and(|open| closes_implicitly(open, &name))
+                {
+                    self.pop();
+                }
+
+                if self.capture.is_none() {
+                    if let Some(handler) = self.matching_handler(source) {
+                        self.capture = Some(Capture {
+                            handler,
+                            depth: self.stack.len(),
+                            source: String::new(),
+                        });
+                    }
+                }
+
+                self.emit(source);
+
+                if !closed && !is_void_element(&name) {
+                    if is_raw_element(&name) {
+                        self.raw = Some(name.clone());
+                    }
+
+                    self.stack.push(name);
+                } else if self
+                    .capture
+                    .as_ref()
+                    .is_some_and(|c| c.depth == self.stack.len())
+                {
+                    self.finish_capture();
+                }
+            }
+            Token::End { name } => {
+                let Some(i) = self
+                    .stack
+                    .iter()
+                    .rposition(|open| open.eq_ignore_ascii_case(&name))
+                else {
+                    self.emit(source);
+                    return;
+                };
+
+                if self.capture.as_ref().is_some_and(|c| c.depth > i) {
+                    self.stack.truncate(i + 1);
+                    self.finish_capture();
+                }
+
+                self.emit(source);
+                self.raw = None;
+
+                while self.stack.len() > i {
+                    self.pop();
+                }
+            }
+            Token::Other => self.emit(source),
+        }
+    }
+
+    fn pop(&mut self) {
+        self.stack.pop();
+
+        if self
+            .capture
+            .as_ref()
+            .is_some_and(|c| c.depth >= self.stack.len())
+        {
+            self.finish_capture();
+        }
+    }
+
+    fn emit(&mut self, source: &str) {
+        if let Some(capture) = &mut self.capture {
+            capture.source.push_str(source);
+        } else {
+            (self.output)(source);
+        }
+    }
+
+    fn matching_handler(&self, start_tag: &str) -> Option<usize> {
+        let node = parse_element(start_tag)?;
+
+        self.handlers
+            .iter()
+            .position(|(filter, _)| filter.matches(&node))
+    }
+
+    fn finish_capture(&mut self) {
+        let Some(capture) = self.capture.take() else {
+            return;
+        };
+
+        let rewrite = parse_element(&capture.source).map_or(Rewrite::Keep, |node| {
+            (self.handlers[capture.handler].1)(&node)
+        });
+
+        match rewrite {
+            Rewrite::Keep => (self.output)(&capture.source),
+            Rewrite::Remove => {}
+            Rewrite::Replace(markup) => (self.output)(&markup),
+        }
+    }
+}
+
+fn parse_element(source: &str) -> Option<HTMLNode<String>> {
+    LooseHTMLParser::parse(source)
+        .ok()?
+        .into_iter()
+        .next()
+        .map(|node| node.map_text(String::from))
+}
+
+/// Returns the length of a start tag, skipping `>` inside quoted attribute values
+fn tag_end(tag: &str) -> Option<usize> {
+    let bytes = tag.as_bytes();
+    let mut after_eq = false;
+    let mut i = 1;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'>' => return Some(i + 1),
+            b'=' => after_eq = true,
+            q @ (b'"' | b'\'') if after_eq => {
+                i += 1 + bytes[i + 1..].iter().position(|&c| c == q)?;
+                after_eq = false;
+            }
+            c if c.is_ascii_whitespace() => {}
+            _ => after_eq = false,
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{
+        filter::{
+            Attr,
+            Tag,
+        },
+        Node,
+    };
+
+    /// Splits `html` into every possible chunk size
+    fn chunkings(html: &str) -> impl Iterator<Item = Vec<&str>> {
+        (1..=html.len()).map(|size| {
+            html.as_bytes()
+                .chunks(size)
+                .map(|c| core::str::from_utf8(c).unwrap())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let html = r#"<!DOCTYPE html><!-- a > b --><p title="1 > 0">x < y</p><br/><script>if (a<b) {}</script>"#;
+
+        for chunks in chunkings(html) {
+            let mut output = String::new();
+            let mut rewriter = HTMLRewriter::new(|chunk: &str| output.push_str(chunk));
+
+            for chunk in chunks {
+                rewriter.write(chunk);
+            }
+
+            rewriter.end();
+
+            assert_eq!(output, html);
+        }
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let html = "<ul><li class=ad>Buy<li>One</ul><img src=x.png><script>a</b></script><p>End";
+
+        for chunks in chunkings(html) {
+            let mut output = String::new();
+            let mut rewriter = HTMLRewriter::new(|chunk: &str| output.push_str(chunk))
+                .on(
+                    Attr {
+                        name: "class",
+                        value: "ad",
+                    },
+                    |_| Rewrite::Remove,
+                )
+                .on(Tag { tag: "img" }, |el| {
+                    Rewrite::Replace(el.get("src").map(ToString::to_string).unwrap_or_default())
+                })
+                .on(Tag { tag: "script" }, |el| {
+                    assert!(
+                        matches!(el, HTMLNode::RawElement { content, .. } if content == "a</b>")
+                    );
+                    Rewrite::Keep
+                });
+
+            for chunk in chunks {
+                rewriter.write(chunk);
+            }
+
+            rewriter.end();
+
+            assert_eq!(output, "<ul><li>One</ul>x.png<script>a</b></script><p>End");
+        }
+    }
+}
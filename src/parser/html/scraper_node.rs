@@ -0,0 +1,157 @@
+use std::{
+    cell::OnceCell,
+    collections::BTreeMap,
+    fmt,
+};
+
+use ego_tree::NodeRef;
+
+use crate::{
+    Node,
+    SoupRef,
+};
+
+/// A [`Node`] borrowing from a [`scraper::Html`] tree, so it can be queried without converting
+///
+/// Names, attributes, and text are borrowed from the tree, never copied. The [`Node`] trait
+/// hands out children as slices and attributes as maps, so each node builds those the first
+/// time they are asked for, and keeps them for later queries.
+///
+/// Matched nodes can be turned back into scraper's types with [`ScraperNode::node_ref`], for
+/// example to run a CSS selector on them.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{parser::ScraperNode, prelude::*};
+/// let html = scraper::Html::parse_fragment(r#"<ul><li class="a">One</li><li>Two</li></ul>"#);
+/// let root = ScraperNode::from(&html);
+///
+/// let li = root.view().attr("class", "a").first().unwrap();
+/// assert_eq!(li.all_text(), "One");
+///
+/// let element = scraper::ElementRef::wrap(li.node_ref()).unwrap();
+/// assert_eq!(element.inner_html(), "One");
+/// ```
+pub struct ScraperNode<'a> {
+    node: NodeRef<'a, scraper::Node>,
+    /// Name, text, or comment, depending on the kind of node
+    value: Option<&'a str>,
+    attrs: OnceCell<Option<BTreeMap<&'a str, &'a str>>>,
+    children: OnceCell<Vec<ScraperNode<'a>>>,
+}
+
+impl<'a> ScraperNode<'a> {
+    /// Wraps a node of a scraper tree
+    #[must_use]
+    pub fn new(node: NodeRef<'a, scraper::Node>) -> Self {
+        let value = match node.value() {
+            scraper::Node::Element(element) => Some(element.name()),
+            scraper::Node::Text(text) => Some(&**text),
+            scraper::Node::Comment(comment) => Some(&**comment),
+            _ => None,
+        };
+
+        Self {
+            node,
+            value,
+            attrs: OnceCell::new(),
+            children: OnceCell::new(),
+        }
+    }
+
+    /// The wrapped node
+    #[must_use]
+    pub fn node_ref(&self) -> NodeRef<'a, scraper::Node> {
+        self.node
+    }
+
+    /// Borrows the node's children as a queryable view
+    #[must_use]
+    pub fn view(&self) -> SoupRef<'_, Self> {
+        SoupRef::from(self.children())
+    }
+}
+
+impl<'a> From<&'a scraper::Html> for ScraperNode<'a> {
+    /// Wraps the root of the document, whose children are the top-level nodes
+    fn from(html: &'a scraper::Html) -> Self {
+        Self::new(html.tree.root())
+    }
+}
+
+impl Clone for ScraperNode<'_> {
+    fn clone(&self) -> Self {
+        Self::new(self.node)
+    }
+}
+
+impl fmt::Debug for ScraperNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScraperNode")
+            .field("id", &self.node.id())
+            .field("value", self.node.value())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Node for ScraperNode<'a> {
+    type Text = &'a str;
+
+    fn name(&self) -> Option<&&'a str> {
+        self.value
+            .as_ref()
+            .filter(|_| self.node.value().is_element())
+    }
+
+    fn text(&self) -> Option<&&'a str> {
+        self.value.as_ref().filter(|_| self.node.value().is_text())
+    }
+
+    fn comment(&self) -> Option<&&'a str> {
+        self.value
+            .as_ref()
+            .filter(|_| self.node.value().is_comment())
+    }
+
+    fn attrs(&self) -> Option<&BTreeMap<&'a str, &'a str>> {
+        self.attrs
+            .get_or_init(|| {
+                let element = self.node.value().as_element()?;
+                Some(element.attrs().collect())
+            })
+            .as_ref()
+    }
+
+    fn children(&self) -> &[Self] {
+        self.children
+            .get_or_init(|| self.node.children().map(Self::new).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_scraper_node() {
+        let html = scraper::Html::parse_document(
+            r#"<!DOCTYPE html><title>T</title><!-- note --><p id="x">Hello <b>world</b></p>"#,
+        );
+        let root = ScraperNode::from(&html);
+
+        let p = root.view().tag("p").first().expect("Couldn't find p");
+        assert_eq!(p.get("id"), Some(&"x"));
+        assert_eq!(p.all_text(), "Hello \nworld");
+        assert_eq!(root.view().tag("b").all().count(), 1);
+
+        let comment = root
+            .descendants()
+            .find_map(Node::comment)
+            .expect("Couldn't find comment");
+        assert_eq!(*comment, " note ");
+
+        let converted = crate::Soup::from(html.clone());
+        assert_eq!(root.view().iter().count(), converted.iter().count());
+    }
+}
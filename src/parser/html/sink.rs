@@ -0,0 +1,415 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+};
+
+use html5ever::{
+    expanded_name,
+    local_name,
+    namespace_url,
+    ns,
+    tendril::{
+        StrTendril,
+        TendrilSink,
+    },
+    tree_builder::{
+        ElementFlags,
+        NodeOrText,
+        QuirksMode,
+        TreeBuilderOpts,
+        TreeSink,
+    },
+    Attribute,
+    ExpandedName,
+    QualName,
+};
+
+use crate::parser::html::{
+    doctype,
+    node::is_void_element,
+    HTMLNode,
+    LenientHTMLOptions,
+};
+
+/// Parses a document with `html5ever`, building [`HTMLNode`]s directly
+pub(crate) fn parse(text: &str, options: LenientHTMLOptions) -> Vec<HTMLNode<StrTendril>> {
+    let opts = html5ever::ParseOpts {
+        tree_builder: TreeBuilderOpts {
+            scripting_enabled: !options.disable_scripting,
+            ..TreeBuilderOpts::default()
+        },
+        ..html5ever::ParseOpts::default()
+    };
+
+    html5ever::parse_document(Sink::default(), opts).one(text)
+}
+
+enum Data {
+    Document,
+    /// Contents of a `<template>`, stored as the template's first child
+    Fragment,
+    Doctype(StrTendril),
+    Comment(StrTendril),
+    Text(StrTendril),
+    Element {
+        name: QualName,
+        attrs: Vec<Attribute>,
+    },
+    ProcessingInstruction,
+}
+
+struct SinkNode {
+    data: Data,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// Arena of nodes under construction, addressed by index
+///
+/// The document is always at index 0.
+struct Sink {
+    nodes: Vec<SinkNode>,
+}
+
+impl Default for Sink {
+    fn default() -> Self {
+        Self {
+            nodes: vec![SinkNode {
+                data: Data::Document,
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl Sink {
+    fn create(&mut self, data: Data) -> usize {
+        self.nodes.push(SinkNode {
+            data,
+            parent: None,
+            children: Vec::new(),
+        });
+
+        self.nodes.len() - 1
+    }
+
+    fn detach(&mut self, id: usize) {
+        if let Some(parent) = self.nodes[id].parent.take() {
+            self.nodes[parent].children.retain(|&c| c != id);
+        }
+    }
+
+    fn append_child(&mut self, parent: usize, child: usize) {
+        self.detach(child);
+        self.nodes[child].parent = Some(parent);
+        self.nodes[parent].children.push(child);
+    }
+
+    /// Appends `text` to the node at `id` if it is a text node
+    fn concat(&mut self, id: Option<usize>, text: &StrTendril) -> bool {
+        if let Some(Data::Text(t)) = id.map(|id| &mut self.nodes[id].data) {
+            t.push_tendril(text);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves the children of `id` out of the arena, converting them into [`HTMLNode`]s
+    ///
+    /// Uses an explicit stack of open elements, so deeply nested documents cannot overflow
+    /// the call stack.
+    fn take_children(&mut self, id: usize) -> Vec<HTMLNode<StrTendril>> {
+        let mut root = self.open(id, None);
+        let mut stack = Vec::new();
+
+        loop {
+            let top = stack.last_mut().unwrap_or(&mut root);
+
+            if let Some(child) = top.rest.next() {
+                match self.take(child) {
+                    Taken::Leaf(node) => top.children.extend(node),
+                    Taken::Parent(open) => stack.push(open),
+                }
+            } else if let Some(open) = stack.pop() {
+                let parent = stack.last_mut().unwrap_or(&mut root);
+
+                match open.element {
+                    Some((name, attrs)) => parent.children.push(HTMLNode::Element {
+                        name,
+                        attrs,
+                        children: open.children,
+                    }),
+                    None => parent.children.extend(open.children),
+                }
+            } else {
+                return root.children;
+            }
+        }
+    }
+
+    fn open(&mut self, id: usize, element: Option<(StrTendril, Attrs)>) -> Open {
+        Open {
+            element,
+            children: Vec::new(),
+            rest: std::mem::take(&mut self.nodes[id].children).into_iter(),
+        }
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn take(&mut self, id: usize) -> Taken {
+        let node = match std::mem::replace(&mut self.nodes[id].data, Data::Document) {
+            Data::Document | Data::ProcessingInstruction => None,
+            Data::Fragment => return Taken::Parent(self.open(id, None)),
+            Data::Doctype(name) => Some(HTMLNode::Doctype(name)),
+            Data::Comment(comment) => Some(HTMLNode::Comment(comment)),
+            Data::Text(text) => Some(HTMLNode::Text(text)),
+            Data::Element { name, attrs } => {
+                let name = StrTendril::from(&*name.local);
+
+                let mut map = BTreeMap::new();
+
+                for attr in attrs {
+                    map.entry(StrTendril::from(&*attr.name.local))
+                        .or_insert(attr.value);
+                }
+
+                if !is_void_element(&name) {
+                    return Taken::Parent(self.open(id, Some((name, map))));
+                }
+
+                Some(HTMLNode::Void { name, attrs: map })
+            }
+        };
+
+        Taken::Leaf(node)
+    }
+}
+
+type Attrs = BTreeMap<StrTendril, StrTendril>;
+
+/// An element, or the contents of a `<template>`, whose children are being taken
+struct Open {
+    /// Name and attributes of the element, or `None` if the children replace it in its parent
+    element: Option<(StrTendril, Attrs)>,
+    children: Vec<HTMLNode<StrTendril>>,
+    /// Children not yet taken
+    rest: std::vec::IntoIter<usize>,
+}
+
+/// A node moved out of the arena by [`Sink::take`]
+enum Taken {
+    /// A node without children, or `None` if it has no [`HTMLNode`] equivalent
+    Leaf(Option<HTMLNode<StrTendril>>),
+    /// A node whose children still have to be taken
+    Parent(Open),
+}
+
+impl TreeSink for Sink {
+    type Handle = usize;
+    type Output = Vec<HTMLNode<StrTendril>>;
+
+    fn finish(mut self) -> Self::Output {
+        self.take_children(0)
+    }
+
+    fn parse_error(&mut self, _msg: Cow<'static, str>) {}
+
+    fn get_document(&mut self) -> usize {
+        0
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a usize) -> ExpandedName<'a> {
+        match &self.nodes[*target].data {
+            Data::Element { name, .. } => name.expanded(),
+            _ => unreachable!("elem_name called on a non-element"),
+        }
+    }
+
+    fn create_element(&mut self, name: QualName, attrs: Vec<Attribute>, _: ElementFlags) -> usize {
+        let template = name.expanded() == expanded_name!(html "template");
+        let id = self.create(Data::Element { name, attrs });
+
+        if template {
+            let contents = self.create(Data::Fragment);
+            self.append_child(id, contents);
+        }
+
+        id
+    }
+
+    fn create_comment(&mut self, text: StrTendril) -> usize {
+        self.create(Data::Comment(text))
+    }
+
+    fn create_pi(&mut self, _target: StrTendril, _data: StrTendril) -> usize {
+        self.create(Data::ProcessingInstruction)
+    }
+
+    fn append(&mut self, parent: &usize, child: NodeOrText<usize>) {
+        match child {
+            NodeOrText::AppendNode(id) => self.append_child(*parent, id),
+            NodeOrText::AppendText(text) => {
+                let last = self.nodes[*parent].children.last().copied();
+
+                if !self.concat(last, &text) {
+                    let id = self.create(Data::Text(text));
+                    self.append_child(*parent, id);
+                }
+            }
+        }
+    }
+
+    fn append_based_on_parent_node(
+        &mut self,
+        element: &usize,
+        prev_element: &usize,
+        child: NodeOrText<usize>,
+    ) {
+        if self.nodes[*element].parent.is_some() {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
+    }
+
+    fn append_doctype_to_document(
+        &mut self,
+        name: StrTendril,
+        public_id: StrTendril,
+        system_id: StrTendril,
+    ) {
+        let content = doctype::content(&name, &public_id, &system_id);
+        let id = self.create(Data::Doctype(content.into()));
+        self.append_child(0, id);
+    }
+
+    fn get_template_contents(&mut self, target: &usize) -> usize {
+        self.nodes[*target].children[0]
+    }
+
+    fn same_node(&self, x: &usize, y: &usize) -> bool {
+        x == y
+    }
+
+    fn set_quirks_mode(&mut self, _mode: QuirksMode) {}
+
+    fn append_before_sibling(&mut self, sibling: &usize, new_node: NodeOrText<usize>) {
+        if let NodeOrText::AppendNode(id) = new_node {
+            self.detach(id);
+        }
+
+        let Some(parent) = self.nodes[*sibling].parent else {
+            return;
+        };
+
+        let index = self.nodes[parent]
+            .children
+            .iter()
+            .position(|c| c == sibling)
+            .unwrap_or_default();
+
+        let id = match new_node {
+            NodeOrText::AppendNode(id) => id,
+            NodeOrText::AppendText(text) => {
+                let prev = index.checked_sub(1).map(|i| self.nodes[parent].children[i]);
+
+                if self.concat(prev, &text) {
+                    return;
+                }
+
+                self.create(Data::Text(text))
+            }
+        };
+
+        self.nodes[id].parent = Some(parent);
+        self.nodes[parent].children.insert(index, id);
+    }
+
+    fn add_attrs_if_missing(&mut self, target: &usize, attrs: Vec<Attribute>) {
+        if let Data::Element {
+            attrs: existing, ..
+        } = &mut self.nodes[*target].data
+        {
+            for attr in attrs {
+                if !existing.iter().any(|a| a.name == attr.name) {
+                    existing.push(attr);
+                }
+            }
+        }
+    }
+
+    fn remove_from_parent(&mut self, target: &usize) {
+        self.detach(*target);
+    }
+
+    fn reparent_children(&mut self, node: &usize, new_parent: &usize) {
+        for child in std::mem::take(&mut self.nodes[*node].children) {
+            self.nodes[child].parent = Some(*new_parent);
+            self.nodes[*new_parent].children.push(child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Node;
+
+    #[test]
+    fn test_matches_scraper() {
+        let html = r"<!DOCTYPE html><title>T</title><table><tr><td>1</td></tr>stray</table>
+            <p>One<b>Two<p>Three</b> <a href=x href=y>Link</a><?pi x?><!-- c --><br>";
+
+        let scraper = scraper::Html::parse_document(html)
+            .tree
+            .root()
+            .children()
+            .filter_map(|n| n.try_into().ok())
+            .collect::<Vec<HTMLNode<StrTendril>>>();
+
+        assert_eq!(parse(html, LenientHTMLOptions::default()), scraper);
+    }
+
+    #[test]
+    fn test_template_contents() {
+        let nodes = parse(
+            "<template><p>Inside</p></template>",
+            LenientHTMLOptions::default(),
+        );
+
+        let template = nodes
+            .iter()
+            .flat_map(HTMLNode::iter)
+            .flat_map(HTMLNode::iter)
+            .find(|n| matches!(n, HTMLNode::Element { name, .. } if &**name == "template"));
+
+        assert_eq!(
+            template.map(HTMLNode::children),
+            Some(
+                &[HTMLNode::Element {
+                    name: "p".into(),
+                    attrs: BTreeMap::new(),
+                    children: vec![HTMLNode::Text("Inside".into())],
+                }][..]
+            )
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let nodes = parse(&"<div>".repeat(10_000), LenientHTMLOptions::default());
+
+        let mut depth = 0;
+        let mut node = nodes.last();
+
+        while let Some(n) = node {
+            depth += 1;
+            node = n.children().last();
+        }
+
+        // `<html>` and `<body>` enclose the divs
+        assert_eq!(depth, 10_002);
+    }
+}
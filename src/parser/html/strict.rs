@@ -1,4 +1,15 @@
-use std::marker::PhantomData;
+use alloc::{
+    collections::BTreeMap,
+    string::{
+        String,
+        ToString,
+    },
+    vec::Vec,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+};
 
 use nom::{
     branch::alt,
@@ -7,14 +18,22 @@ use nom::{
         tag,
         tag_no_case,
         take_until,
+        take_while,
     },
     character::complete::{
-        alphanumeric1,
         char,
         multispace0,
+        multispace1,
+        satisfy,
+    },
+    combinator::{
+        map,
+        opt,
+        recognize,
+        verify,
     },
-    combinator::map,
-    multi::many0,
+    error::ErrorKind,
+    multi::fold_many0,
     sequence::{
         delimited,
         pair,
@@ -27,7 +46,10 @@ use nom::{
     Parser,
 };
 
-use crate::parser::html::HTMLNode;
+use crate::parser::html::{
+    node::is_void_element,
+    HTMLNode,
+};
 
 /// Simple, strict HTML parser
 ///
@@ -43,17 +65,282 @@ impl<'a> crate::parser::Parser for StrictHTMLParser<'a> {
     type Error = nom::Err<nom::error::Error<&'a str>>;
 
     fn parse(text: &'a str) -> Result<Vec<Self::Node>, Self::Error> {
-        nom::combinator::all_consuming(parse)(text).map(|r| r.1)
+        parse_all(text, StrictHTMLOptions::default())
+    }
+}
+
+impl<'a> StrictHTMLParser<'a> {
+    /// Parses `text` with the given options
+    ///
+    /// # Errors
+    /// If the text is invalid HTML, or nested deeper than [`StrictHTMLOptions::max_depth`].
+    pub fn parse_with(
+        text: &'a str,
+        options: StrictHTMLOptions,
+    ) -> Result<Vec<HTMLNode<&'a str>>, StrictHTMLError<'a>> {
+        parse_all(text, options).map_err(|e| match (e, options.max_depth) {
+            (nom::Err::Failure(e), Some(max_depth)) if e.code == ErrorKind::TooLarge => {
+                StrictHTMLError::TooDeep {
+                    offset: text.len() - e.input.len(),
+                    max_depth,
+                }
+            }
+            (e, _) => StrictHTMLError::Syntax(e),
+        })
+    }
+
+    /// Parses text from an untrusted source, like a page fetched from the web
+    ///
+    /// This never panics and, unless [`StrictHTMLOptions::max_depth`] is raised, uses bounded
+    /// stack space whatever the input: hostile input, like deeply nested elements or
+    /// unterminated comments and attributes, results in an error. Without a `max_depth`, the
+    /// limit is 256 levels, which fits the stack of a small thread. Parsing time grows linearly
+    /// with the input, so callers should still limit the length of the text they accept.
+    ///
+    /// # Errors
+    /// If the text is invalid HTML or nested too deeply.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::parser::{StrictHTMLError, StrictHTMLOptions, StrictHTMLParser};
+    /// let options = StrictHTMLOptions::default();
+    /// let hostile = "<div>".repeat(100_000);
+    ///
+    /// let error = StrictHTMLParser::parse_untrusted(&hostile, options).unwrap_err();
+    /// assert_eq!(error, StrictHTMLError::TooDeep { offset: 1280, max_depth: 256 });
+    /// assert!(StrictHTMLParser::parse_untrusted("<p>Hello</p>", options).is_ok());
+    /// ```
+    pub fn parse_untrusted(
+        text: &'a str,
+        options: StrictHTMLOptions,
+    ) -> Result<Vec<HTMLNode<&'a str>>, StrictHTMLError<'a>> {
+        Self::parse_with(text, StrictHTMLOptions {
+            max_depth: Some(options.max_depth.unwrap_or(UNTRUSTED_MAX_DEPTH)),
+            ..options
+        })
+    }
+
+    /// Parses `text`, keeping everything parsed before a failure
+    ///
+    /// # Errors
+    /// If the text is invalid HTML. The error holds the partial tree, in which elements that
+    /// were still open at the failure hold the children parsed so far.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::parser::{HTMLNode, StrictHTMLOptions, StrictHTMLParser};
+    /// let text = "<ul>\n  <li>One</li>\n  <li>Two</b>\n</ul>";
+    /// let partial = StrictHTMLParser::parse_partial(text, StrictHTMLOptions::default()).unwrap_err();
+    ///
+    /// assert_eq!((partial.line, partial.column), (3, 10));
+    /// assert!(partial.remaining.starts_with("</b>"));
+    /// assert_eq!(partial.to_string(), "unexpected input at line 3, column 10");
+    /// ```
+    pub fn parse_partial(
+        text: &'a str,
+        options: StrictHTMLOptions,
+    ) -> Result<Vec<HTMLNode<&'a str>>, PartialParse<'a>> {
+        if let Ok(nodes) = Self::parse_with(text, options) {
+            return Ok(nodes);
+        }
+
+        let (nodes, remaining) = partial(text, options, 0);
+
+        let offset = text.len() - remaining.len();
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Err(PartialParse {
+            nodes,
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            remaining,
+        })
+    }
+}
+
+/// Nesting limit of [`StrictHTMLParser::parse_untrusted`] when none is given
+const UNTRUSTED_MAX_DEPTH: usize = 256;
+
+fn parse_all(
+    text: &str,
+    options: StrictHTMLOptions,
+) -> Result<Vec<HTMLNode<&str>>, nom::Err<nom::error::Error<&str>>> {
+    nom::combinator::all_consuming(|i| nodes(i, options, 0))(text).map(|r| r.1)
+}
+
+/// Error returned by [`StrictHTMLParser::parse_with`]
+#[derive(Debug, PartialEq)]
+pub enum StrictHTMLError<'a> {
+    /// The text is not valid HTML
+    Syntax(nom::Err<nom::error::Error<&'a str>>),
+
+    /// An element is nested deeper than [`StrictHTMLOptions::max_depth`]
+    TooDeep {
+        /// Byte offset of the element's start tag
+        offset: usize,
+
+        /// The limit that was exceeded
+        max_depth: usize,
+    },
+}
+
+impl fmt::Display for StrictHTMLError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(e) => e.fmt(f),
+            Self::TooDeep { offset, max_depth } => write!(
+                f,
+                "element at {offset} is nested deeper than {max_depth} levels"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StrictHTMLError<'_> {}
+
+/// A failed strict parse, with the nodes parsed before the failure
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialParse<'a> {
+    /// Top-level nodes parsed before the failure
+    ///
+    /// Elements that were still open at the failure are included, with the children parsed so far.
+    pub nodes: Vec<HTMLNode<&'a str>>,
+
+    /// Byte offset of the failure
+    pub offset: usize,
+
+    /// Line of the failure, starting at 1
+    pub line: usize,
+
+    /// Column of the failure in characters, starting at 1
+    pub column: usize,
+
+    /// The input from the failure onwards
+    pub remaining: &'a str,
+}
+
+impl fmt::Display for PartialParse<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.remaining.trim().is_empty() {
+            "unexpected end of input"
+        } else {
+            "unexpected input"
+        };
+
+        write!(f, "{kind} at line {}, column {}", self.line, self.column)
     }
 }
 
-fn attr<'a, E>(i: &'a str) -> IResult<&'a str, &'a str, E>
+#[cfg(feature = "std")]
+impl std::error::Error for PartialParse<'_> {}
+
+/// Options for the [`StrictHTMLParser`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrictHTMLOptions {
+    /// Accept any doctype, like `<!DOCTYPE>` or `<!doctype html public -//W3C//DTD HTML//EN>`
+    ///
+    /// Enabled by default. When disabled, doctypes must follow the grammar of the HTML
+    /// specification: a name followed by an optional quoted `PUBLIC` or `SYSTEM` identifier.
+    pub sloppy_doctype: bool,
+
+    /// Maximum number of nested elements, unlimited by default
+    ///
+    /// The parser recurses once per level, so this bounds its stack usage. Deeper elements fail
+    /// with [`StrictHTMLError::TooDeep`] at their start tag.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for StrictHTMLOptions {
+    fn default() -> Self {
+        Self {
+            sloppy_doctype: true,
+            max_depth: None,
+        }
+    }
+}
+
+/// Simple, strict HTML parser producing owned text
+///
+/// Equivalent to [`StrictHTMLParser`], but the resulting tree does not borrow from the input,
+/// so it can outlive the source text or be handed to other threads.
+#[derive(Clone, Debug)]
+pub struct OwnedStrictHTMLParser<S> {
+    _marker: PhantomData<S>,
+}
+
+impl<S> crate::parser::Parser for OwnedStrictHTMLParser<S>
+where
+    S: AsRef<str>,
+{
+    type Input = S;
+    type Node = HTMLNode<String>;
+    type Error = nom::Err<nom::error::Error<String>>;
+
+    fn parse(text: S) -> Result<Vec<Self::Node>, Self::Error> {
+        <StrictHTMLParser as crate::parser::Parser>::parse(text.as_ref())
+            .map(|nodes| {
+                nodes
+                    .into_iter()
+                    .map(|n| n.map_text(ToString::to_string))
+                    .collect()
+            })
+            .map_err(nom::Err::<nom::error::Error<&str>>::to_owned)
+    }
+}
+
+/// Whether `c` may appear in a tag name after the first character
+///
+/// Follows the `NameChar` production of XML, so prefixed names like `svg:use` and names with
+/// dashes or non-ASCII letters are accepted.
+fn is_name_char(c: char) -> bool {
+    matches!(c,
+        ':' | '-' | '.' | '_' | '\u{B7}'
+        | '0'..='9'
+        | 'A'..='Z'
+        | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{203F}'..='\u{2040}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+/// Tag names start with an ASCII letter, like in the HTML tokenizer
+pub(super) fn tag_name<'a, E>(i: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic()),
+        take_while(is_name_char),
+    ))(i)
+}
+
+pub(super) fn attr<'a, E>(i: &'a str) -> IResult<&'a str, &'a str, E>
 where
     E: nom::error::ParseError<&'a str>,
 {
     is_not(r#" "'>/="#)(i)
 }
 
+pub(super) fn unquoted_value<'a, E>(i: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    is_not(r#"\t\n\f\r "'=<>`"#)(i)
+}
+
 fn ws<'a, F, O, E: nom::error::ParseError<&'a str>>(
     inner: F,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
@@ -73,16 +360,51 @@ fn comment(i: &str) -> IResult<&str, HTMLNode<&str>> {
     map(preceded(tag("<!--"), take_to("-->")), HTMLNode::Comment)(i)
 }
 
+fn quoted(i: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('\''), take_until("'"), char('\'')),
+        delimited(char('"'), take_until("\""), char('"')),
+    ))(i)
+}
+
+/// A doctype following the specification, like `<!DOCTYPE html PUBLIC "..." "...">`
 fn doctype(i: &str) -> IResult<&str, HTMLNode<&str>> {
     map(
-        preceded(tag_no_case("<!doctype "), take_to(">")),
+        delimited(
+            pair(tag_no_case("<!doctype"), multispace1),
+            recognize(pair(
+                is_not(" \t\n\r\x0C>"),
+                opt(preceded(
+                    multispace1,
+                    alt((
+                        recognize(tuple((
+                            tag_no_case("public"),
+                            multispace0,
+                            quoted,
+                            opt(preceded(multispace0, quoted)),
+                        ))),
+                        recognize(tuple((tag_no_case("system"), multispace0, quoted))),
+                    )),
+                )),
+            )),
+            pair(multispace0, char('>')),
+        ),
         HTMLNode::Doctype,
     )(i)
 }
 
-fn start_tag<'a, F, E>(
-    inner: F,
-) -> impl FnMut(&'a str) -> IResult<&'a str, (&'a str, Vec<(&'a str, &'a str)>, bool), E>
+/// Any doctype, with surrounding whitespace trimmed
+fn sloppy_doctype(i: &str) -> IResult<&str, HTMLNode<&str>> {
+    map(
+        preceded(tag_no_case("<!doctype"), take_to(">")),
+        |d: &str| HTMLNode::Doctype(d.trim()),
+    )(i)
+}
+
+/// Tag name, attributes, and whether the tag was self-closing
+type StartTag<'a> = (&'a str, BTreeMap<&'a str, &'a str>, bool);
+
+fn start_tag<'a, F, E>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, StartTag<'a>, E>
 where
     F: Parser<&'a str, &'a str, E>,
     E: nom::error::ParseError<&'a str>,
@@ -91,24 +413,31 @@ where
         tag("<"),
         tuple((
             inner,
-            many0(preceded(
-                multispace0,
-                alt((
-                    // unquoted
-                    separated_pair(attr, ws(char('=')), is_not(r#"\t\n\f\r "'=<>`"#)),
-                    // quoted
-                    separated_pair(
-                        attr,
-                        ws(char('=')),
-                        alt((
-                            delimited(char('\''), take_until("'"), char('\'')),
-                            delimited(char('"'), take_until("\""), char('"')),
-                        )),
-                    ),
-                    // boolean
-                    pair(attr, |i| Ok((i, ""))),
-                )),
-            )),
+            fold_many0(
+                preceded(
+                    multispace0,
+                    alt((
+                        // unquoted
+                        separated_pair(attr, ws(char('=')), unquoted_value),
+                        // quoted
+                        separated_pair(
+                            attr,
+                            ws(char('=')),
+                            alt((
+                                delimited(char('\''), take_until("'"), char('\'')),
+                                delimited(char('"'), take_until("\""), char('"')),
+                            )),
+                        ),
+                        // boolean
+                        pair(attr, |i| Ok((i, ""))),
+                    )),
+                ),
+                BTreeMap::new,
+                |mut attrs, (name, value)| {
+                    attrs.insert(name, value);
+                    attrs
+                },
+            ),
             preceded(
                 multispace0,
                 alt((map(tag("/>"), |_| true), map(tag(">"), |_| false))),
@@ -119,72 +448,72 @@ where
 
 fn void(i: &str) -> IResult<&str, HTMLNode<&str>> {
     map(
-        start_tag(alt((
-            tag_no_case("area"),
-            tag_no_case("base"),
-            tag_no_case("br"),
-            tag_no_case("col"),
-            tag_no_case("embed"),
-            tag_no_case("hr"),
-            tag_no_case("img"),
-            tag_no_case("input"),
-            tag_no_case("link"),
-            tag_no_case("meta"),
-            tag_no_case("source"),
-            tag_no_case("track"),
-            tag_no_case("wbr"),
-        ))),
-        |(name, attrs, _)| HTMLNode::Void {
-            name,
-            attrs: attrs.into_iter().collect(),
-        },
+        start_tag(verify(tag_name, |name: &str| is_void_element(name))),
+        |(name, attrs, _)| HTMLNode::Void { name, attrs },
     )(i)
 }
 
 fn raw_element(i: &str) -> IResult<&str, HTMLNode<&str>> {
-    let start = start_tag(alt((tag_no_case("script"), tag_no_case("style"))))(i)?;
+    let start = start_tag(verify(tag_name, |name: &str| {
+        name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style")
+    }))(i)?;
 
     let (left, (name, attrs, closed)) = start;
 
     if closed {
         return Ok((left, HTMLNode::RawElement {
             name,
-            attrs: attrs.into_iter().collect(),
+            attrs,
             content: "",
         }));
     }
 
-    let (left, content) = terminated(
-        take_until(&*format!("</{name}")),
-        delimited(
-            tag("</"),
-            tag_no_case(name),
-            preceded(multispace0, char('>')),
-        ),
+    // Closing tags are matched without allocating or slicing at possibly invalid offsets
+    let end = left
+        .match_indices("</")
+        .map(|(i, _)| i)
+        .find(|&i| {
+            left.get(i + 2..i + 2 + name.len())
+                .is_some_and(|close| close.eq_ignore_ascii_case(name))
+        })
+        .ok_or_else(|| nom::Err::Error(nom::error::Error::new(left, ErrorKind::TakeUntil)))?;
+    let (content, left) = left.split_at(end);
+
+    let (left, _) = delimited(
+        tag("</"),
+        tag_no_case(name),
+        preceded(multispace0, char('>')),
     )(left)?;
 
     Ok((left, HTMLNode::RawElement {
         name,
-        attrs: attrs.into_iter().collect(),
+        attrs,
         content: content.trim(),
     }))
 }
 
-fn element(i: &str) -> IResult<&str, HTMLNode<&str>> {
-    let start = start_tag(alphanumeric1)(i)?;
+fn element(i: &str, options: StrictHTMLOptions, depth: usize) -> IResult<&str, HTMLNode<&str>> {
+    let start = start_tag(tag_name)(i)?;
 
     let (left, (name, attrs, closed)) = start;
 
+    if options.max_depth.is_some_and(|max| depth >= max) {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            i,
+            ErrorKind::TooLarge,
+        )));
+    }
+
     if closed {
         return Ok((left, HTMLNode::Element {
             name,
-            attrs: attrs.into_iter().collect(),
-            children: vec![],
+            attrs,
+            children: Vec::new(),
         }));
     }
 
     let (left, children) = terminated(
-        parse,
+        |i| nodes(i, options, depth + 1),
         delimited(
             tag("</"),
             tag_no_case(name),
@@ -194,7 +523,7 @@ fn element(i: &str) -> IResult<&str, HTMLNode<&str>> {
 
     Ok((left, HTMLNode::Element {
         name,
-        attrs: attrs.into_iter().collect(),
+        attrs,
         children,
     }))
 }
@@ -203,21 +532,79 @@ fn text(i: &str) -> IResult<&str, HTMLNode<&str>> {
     map(map(is_not("<"), str::trim), HTMLNode::Text)(i)
 }
 
-fn single(i: &str) -> IResult<&str, HTMLNode<&str>> {
-    alt((comment, doctype, void, raw_element, element, text))(i)
+fn single(i: &str, options: StrictHTMLOptions, depth: usize) -> IResult<&str, HTMLNode<&str>> {
+    let doctype = if options.sloppy_doctype {
+        sloppy_doctype
+    } else {
+        doctype
+    };
+
+    alt((
+        comment,
+        doctype,
+        void,
+        raw_element,
+        |i| element(i, options, depth),
+        text,
+    ))(i)
 }
 
-pub(crate) fn parse(i: &str) -> IResult<&str, Vec<HTMLNode<&str>>> {
-    many0(ws(single))(i)
+/// Parses a list of nodes inside `depth` elements
+///
+/// Lists start empty and only allocate when a node is found, so tiny fragments with empty
+/// elements make few allocations.
+fn nodes(i: &str, options: StrictHTMLOptions, depth: usize) -> IResult<&str, Vec<HTMLNode<&str>>> {
+    fold_many0(
+        ws(|i| single(i, options, depth)),
+        Vec::new,
+        |mut nodes, node| {
+            nodes.push(node);
+            nodes
+        },
+    )(i)
+}
+
+/// Parses as much as possible, descending into the element that failed to parse
+///
+/// Returns the nodes and the input at the failure.
+fn partial(i: &str, options: StrictHTMLOptions, depth: usize) -> (Vec<HTMLNode<&str>>, &str) {
+    let (rest, mut nodes) = nodes(i, options, depth).unwrap_or((i, Vec::new()));
+
+    if let Ok((inner, (name, attrs, false))) = start_tag::<_, ()>(tag_name)(rest) {
+        if !is_void_element(name) && options.max_depth.is_none_or(|max| depth < max) {
+            let (children, rest) = partial(inner, options, depth + 1);
+
+            nodes.push(HTMLNode::Element {
+                name,
+                attrs,
+                children,
+            });
+
+            return (nodes, rest);
+        }
+    }
+
+    (nodes, rest)
 }
 
 #[allow(clippy::too_many_lines)]
 #[cfg(test)]
 mod test {
-    use std::collections::BTreeMap;
+    use alloc::{
+        collections::BTreeMap,
+        vec,
+    };
 
     use super::*;
 
+    fn element(i: &str) -> IResult<&str, HTMLNode<&str>> {
+        super::element(i, StrictHTMLOptions::default(), 0)
+    }
+
+    fn parse(i: &str) -> IResult<&str, Vec<HTMLNode<&str>>> {
+        nodes(i, StrictHTMLOptions::default(), 0)
+    }
+
     #[test]
     fn test_comment() {
         assert_eq!(
@@ -545,4 +932,273 @@ if (1 < 2) {
             ])),
         );
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_owned() {
+        use crate::Soup;
+
+        let text = String::from(r#"<div id="a"><p>Hello</p><!-- c --></div>"#);
+        let nodes = Soup::html_strict_owned(&text).unwrap().nodes;
+        drop(text);
+
+        let handle = std::thread::spawn(move || nodes);
+
+        assert_eq!(handle.join().unwrap(), vec![HTMLNode::Element {
+            name: "div".to_string(),
+            attrs: [("id".to_string(), "a".to_string())].into(),
+            children: vec![
+                HTMLNode::Element {
+                    name: "p".to_string(),
+                    attrs: [].into(),
+                    children: vec![HTMLNode::Text("Hello".to_string())],
+                },
+                HTMLNode::Comment(" c ".to_string()),
+            ],
+        }]);
+
+        assert_eq!(
+            Soup::html_strict_owned("<div>").map(|s| s.nodes),
+            Err(nom::Err::Error(nom::error::Error::new(
+                "<div>".to_string(),
+                nom::error::ErrorKind::Eof
+            )))
+        );
+    }
+
+    #[test]
+    fn test_void_prefix() {
+        assert!(void("<colgroup>").is_err());
+
+        assert_eq!(
+            parse("<colgroup><col span=2></colgroup>"),
+            Ok(("", vec![HTMLNode::Element {
+                name: "colgroup",
+                attrs: [].into(),
+                children: vec![HTMLNode::Void {
+                    name: "col",
+                    attrs: [("span", "2")].into()
+                }]
+            }]))
+        );
+    }
+
+    #[test]
+    fn test_prefixed_names() {
+        assert_eq!(
+            parse(r#"<fb:like data-href="/"></fb:like><svg:use/><título>Olá</título>"#),
+            Ok(("", vec![
+                HTMLNode::Element {
+                    name: "fb:like",
+                    attrs: [("data-href", "/")].into(),
+                    children: vec![]
+                },
+                HTMLNode::Element {
+                    name: "svg:use",
+                    attrs: [].into(),
+                    children: vec![]
+                },
+                HTMLNode::Element {
+                    name: "título",
+                    attrs: [].into(),
+                    children: vec![HTMLNode::Text("Olá")]
+                }
+            ]))
+        );
+
+        assert!(element("<1a></1a>").is_err());
+        assert!(element("<:a></:a>").is_err());
+    }
+
+    #[test]
+    fn test_custom_elements() {
+        assert_eq!(
+            parse(r#"<my-widget size=2><script-loader src="a.js"></script-loader></my-widget>"#),
+            Ok(("", vec![HTMLNode::Element {
+                name: "my-widget",
+                attrs: [("size", "2")].into(),
+                children: vec![HTMLNode::Element {
+                    name: "script-loader",
+                    attrs: [("src", "a.js")].into(),
+                    children: vec![]
+                }]
+            }]))
+        );
+
+        assert!(void("<col-group>").is_err());
+    }
+
+    #[test]
+    fn test_doctype_options() {
+        assert_eq!(
+            doctype(r#"<!DOCTYPE  html PUBLIC "-//W3C//DTD HTML 4.01//EN" 'strict.dtd' >"#),
+            Ok((
+                "",
+                HTMLNode::Doctype(r#"html PUBLIC "-//W3C//DTD HTML 4.01//EN" 'strict.dtd'"#)
+            ))
+        );
+
+        let strict = StrictHTMLOptions {
+            sloppy_doctype: false,
+            ..StrictHTMLOptions::default()
+        };
+
+        for text in ["<!DOCTYPE>", "<!doctype html public -//W3C//DTD HTML//EN>"] {
+            assert!(StrictHTMLParser::parse_with(text, strict).is_err());
+        }
+
+        // Doctypes outside the grammar are still accepted by default
+        let sloppy = StrictHTMLOptions::default();
+
+        assert_eq!(
+            StrictHTMLParser::parse_with("<!DOCTYPE>", sloppy),
+            Ok(vec![HTMLNode::Doctype("")])
+        );
+        assert_eq!(
+            StrictHTMLParser::parse_with("<!doctype html public -//W3C//DTD HTML//EN>", sloppy),
+            Ok(vec![HTMLNode::Doctype("html public -//W3C//DTD HTML//EN")])
+        );
+        assert_eq!(
+            StrictHTMLParser::parse_with("<!DOCTYPE html PUBLIC><p>Legacy</p>", sloppy)
+                .map(|nodes| nodes.len()),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn test_partial() {
+        let options = StrictHTMLOptions::default();
+
+        assert_eq!(
+            StrictHTMLParser::parse_partial("<p>Fine</p>", options),
+            Ok(vec![HTMLNode::Element {
+                name: "p",
+                attrs: [].into(),
+                children: vec![HTMLNode::Text("Fine")]
+            }])
+        );
+
+        let text = "<a>1</a>\n<div id=x>\n<p>2</p><p>3";
+        let partial = StrictHTMLParser::parse_partial(text, options).unwrap_err();
+
+        assert_eq!(partial.nodes, vec![
+            HTMLNode::Element {
+                name: "a",
+                attrs: [].into(),
+                children: vec![HTMLNode::Text("1")]
+            },
+            HTMLNode::Element {
+                name: "div",
+                attrs: [("id", "x")].into(),
+                children: vec![
+                    HTMLNode::Element {
+                        name: "p",
+                        attrs: [].into(),
+                        children: vec![HTMLNode::Text("2")]
+                    },
+                    HTMLNode::Element {
+                        name: "p",
+                        attrs: [].into(),
+                        children: vec![HTMLNode::Text("3")]
+                    }
+                ]
+            }
+        ]);
+        assert_eq!(partial.offset, text.len());
+        assert_eq!((partial.line, partial.column), (3, 13));
+        assert_eq!(
+            partial.to_string(),
+            "unexpected end of input at line 3, column 13"
+        );
+    }
+
+    #[test]
+    fn test_empty_allocations() {
+        let (_, nodes) = parse("<p><br><b></b> <i/></p><script>a</b></script>").unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        let HTMLNode::Element { children, .. } = &nodes[0] else {
+            panic!("Expected element, got {:?}", nodes[0]);
+        };
+        assert_eq!(children.len(), 3);
+
+        for child in children {
+            if let HTMLNode::Element { children, .. } = child {
+                assert_eq!(children.capacity(), 0);
+            }
+        }
+
+        assert_eq!(nodes[1], HTMLNode::RawElement {
+            name: "script",
+            attrs: BTreeMap::new(),
+            content: "a</b>"
+        });
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = |n| "<b>".repeat(n) + "x" + &"</b>".repeat(n);
+        let options = |max_depth| StrictHTMLOptions {
+            max_depth: Some(max_depth),
+            ..StrictHTMLOptions::default()
+        };
+
+        let text = nested(10);
+        assert!(StrictHTMLParser::parse_with(&text, options(10)).is_ok());
+
+        let error = StrictHTMLParser::parse_with(&text, options(9)).unwrap_err();
+        assert_eq!(error, StrictHTMLError::TooDeep {
+            offset: 27,
+            max_depth: 9
+        });
+        assert_eq!(
+            error.to_string(),
+            "element at 27 is nested deeper than 9 levels"
+        );
+
+        let partial = StrictHTMLParser::parse_partial(&text, options(9)).unwrap_err();
+        assert_eq!(partial.offset, 27);
+
+        // Unlimited by default, and the limit can be raised past the untrusted default
+        let default = StrictHTMLOptions::default();
+        assert!(StrictHTMLParser::parse_with(&nested(300), default).is_ok());
+        assert!(StrictHTMLParser::parse_untrusted(&nested(300), default).is_err());
+        assert!(StrictHTMLParser::parse_untrusted(&nested(300), options(300)).is_ok());
+
+        assert!(matches!(
+            StrictHTMLParser::parse_with("<b>", default),
+            Err(StrictHTMLError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_untrusted() {
+        let options = StrictHTMLOptions::default();
+        let bounded = StrictHTMLOptions {
+            max_depth: Some(256),
+            ..StrictHTMLOptions::default()
+        };
+
+        for hostile in [
+            "<b>".repeat(100_000),
+            "<!--".repeat(10_000),
+            r#"<a x=""#.repeat(10_000),
+            "<script>é</scrip".into(),
+            "<style></st\u{e9}".into(),
+            "</".repeat(10_000),
+            "<!doctype".into(),
+        ] {
+            assert!(StrictHTMLParser::parse_untrusted(&hostile, options).is_err());
+            assert!(StrictHTMLParser::parse_partial(&hostile, bounded).is_err());
+        }
+
+        assert_eq!(
+            StrictHTMLParser::parse_untrusted("<SCRIPT>a</b></script>", options),
+            Ok(vec![HTMLNode::RawElement {
+                name: "SCRIPT",
+                attrs: BTreeMap::new(),
+                content: "a</b>",
+            }])
+        );
+    }
 }
@@ -0,0 +1,511 @@
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+};
+
+use crate::{
+    parser::Parser,
+    Node,
+};
+
+/// JSON parser
+///
+/// Maps JSON values onto [`Node`]s: object members are named by their key,
+/// and arrays and objects contain their items as children.
+///
+/// Errors on malformed JSON.
+#[derive(Clone, Debug)]
+pub struct JSONParser<S> {
+    _marker: PhantomData<S>,
+}
+
+impl<S> Parser for JSONParser<S>
+where
+    S: AsRef<str>,
+{
+    type Input = S;
+    type Node = JSONNode;
+    type Error = JSONError;
+
+    fn parse(text: S) -> Result<Vec<Self::Node>, Self::Error> {
+        let mut reader = Reader {
+            input: text.as_ref().as_bytes(),
+            pos: 0,
+        };
+
+        let value = reader.value(0)?;
+        reader.whitespace();
+
+        if reader.pos < reader.input.len() {
+            return Err(reader.error(JSONErrorKind::TrailingCharacters));
+        }
+
+        Ok(vec![JSONNode { key: None, value }])
+    }
+}
+
+/// A JSON value, along with the key it is stored under in its parent object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSONNode {
+    /// Key of the value within its parent object, if any
+    pub key: Option<String>,
+
+    /// The value
+    pub value: JSONValue,
+}
+
+/// A JSON value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JSONValue {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// A number, as written in the source
+    Number(String),
+    /// A string
+    String(String),
+    /// An array of values
+    Array(Vec<JSONNode>),
+    /// An object, with members in source order
+    Object(Vec<JSONNode>),
+}
+
+impl Node for JSONNode {
+    type Text = String;
+
+    fn name(&self) -> Option<&String> {
+        self.key.as_ref()
+    }
+
+    /// Returns the text of strings and numbers
+    fn text(&self) -> Option<&String> {
+        match &self.value {
+            JSONValue::Number(s) | JSONValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn attrs(&self) -> Option<&BTreeMap<String, String>> {
+        None
+    }
+
+    fn children(&self) -> &[Self] {
+        match &self.value {
+            JSONValue::Array(c) | JSONValue::Object(c) => c,
+            _ => &[],
+        }
+    }
+}
+
+impl JSONNode {
+    /// Iterate over direct children
+    pub fn iter(&self) -> core::slice::Iter<'_, Self> {
+        self.children().iter()
+    }
+
+    /// Returns the value if it is a boolean
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            JSONValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is `null`
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        self.value == JSONValue::Null
+    }
+}
+
+impl<'a> IntoIterator for &'a JSONNode {
+    type Item = &'a JSONNode;
+    type IntoIter = core::slice::Iter<'a, JSONNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Error returned when parsing malformed JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSONError {
+    /// Byte offset of the error in the input
+    pub position: usize,
+
+    /// What went wrong
+    pub kind: JSONErrorKind,
+}
+
+/// Kind of [`JSONError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JSONErrorKind {
+    /// The input ended in the middle of a value
+    UnexpectedEnd,
+    /// A character that cannot start or continue a value
+    UnexpectedCharacter,
+    /// An invalid escape sequence in a string
+    InvalidEscape,
+    /// A malformed number
+    InvalidNumber,
+    /// Values are nested deeper than [`JSON_MAX_DEPTH`]
+    TooDeep,
+    /// Characters after the end of the top-level value
+    TrailingCharacters,
+}
+
+/// Maximum nesting depth of arrays and objects, for the JSON parser and the formats built on it
+pub const JSON_MAX_DEPTH: usize = 512;
+
+impl fmt::Display for JSONError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            JSONErrorKind::UnexpectedEnd => "unexpected end of input",
+            JSONErrorKind::UnexpectedCharacter => "unexpected character",
+            JSONErrorKind::InvalidEscape => "invalid escape sequence",
+            JSONErrorKind::InvalidNumber => "invalid number",
+            JSONErrorKind::TooDeep => "nesting too deep",
+            JSONErrorKind::TrailingCharacters => "trailing characters",
+        };
+
+        write!(f, "{kind} at byte {}", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JSONError {}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, kind: JSONErrorKind) -> JSONError {
+        JSONError {
+            position: self.pos,
+            kind,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JSONError> {
+        match self.peek() {
+            Some(b) if b == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.error(JSONErrorKind::UnexpectedCharacter)),
+            None => Err(self.error(JSONErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    fn literal(&mut self, literal: &[u8], value: JSONValue) -> Result<JSONValue, JSONError> {
+        if self.input[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error(JSONErrorKind::UnexpectedCharacter))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JSONValue, JSONError> {
+        if depth > JSON_MAX_DEPTH {
+            return Err(self.error(JSONErrorKind::TooDeep));
+        }
+
+        self.whitespace();
+
+        match self.peek() {
+            None => Err(self.error(JSONErrorKind::UnexpectedEnd)),
+            Some(b'n') => self.literal(b"null", JSONValue::Null),
+            Some(b't') => self.literal(b"true", JSONValue::Bool(true)),
+            Some(b'f') => self.literal(b"false", JSONValue::Bool(false)),
+            Some(b'"') => self.string().map(JSONValue::String),
+            Some(b'-' | b'0'..=b'9') => self.number().map(JSONValue::Number),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+
+                self.whitespace();
+
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(JSONValue::Array(items));
+                }
+
+                loop {
+                    let value = self.value(depth + 1)?;
+                    items.push(JSONNode { key: None, value });
+
+                    self.whitespace();
+
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    } else {
+                        self.expect(b']')?;
+                        return Ok(JSONValue::Array(items));
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+
+                self.whitespace();
+
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(JSONValue::Object(members));
+                }
+
+                loop {
+                    self.whitespace();
+
+                    if self.peek() != Some(b'"') {
+                        self.expect(b'"')?;
+                    }
+
+                    let key = self.string()?;
+
+                    self.whitespace();
+                    self.expect(b':')?;
+
+                    let value = self.value(depth + 1)?;
+                    members.push(JSONNode {
+                        key: Some(key),
+                        value,
+                    });
+
+                    self.whitespace();
+
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    } else {
+                        self.expect(b'}')?;
+                        return Ok(JSONValue::Object(members));
+                    }
+                }
+            }
+            Some(_) => Err(self.error(JSONErrorKind::UnexpectedCharacter)),
+        }
+    }
+
+    fn number(&mut self) -> Result<String, JSONError> {
+        let start = self.pos;
+
+        let digits = |r: &mut Self| {
+            let start = r.pos;
+
+            while matches!(r.peek(), Some(b'0'..=b'9')) {
+                r.pos += 1;
+            }
+
+            r.pos > start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        if self.peek() == Some(b'0') {
+            self.pos += 1;
+        } else if !digits(self) {
+            return Err(self.error(JSONErrorKind::InvalidNumber));
+        }
+
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+
+            if !digits(self) {
+                return Err(self.error(JSONErrorKind::InvalidNumber));
+            }
+        }
+
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+
+            if !digits(self) {
+                return Err(self.error(JSONErrorKind::InvalidNumber));
+            }
+        }
+
+        // Only ASCII was consumed
+        Ok(String::from_utf8_lossy(&self.input[start..self.pos]).into_owned())
+    }
+
+    fn string(&mut self) -> Result<String, JSONError> {
+        self.expect(b'"')?;
+
+        let mut out = Vec::new();
+
+        loop {
+            let Some(b) = self.peek() else {
+                return Err(self.error(JSONErrorKind::UnexpectedEnd));
+            };
+
+            self.pos += 1;
+
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let Some(e) = self.peek() else {
+                        return Err(self.error(JSONErrorKind::UnexpectedEnd));
+                    };
+
+                    self.pos += 1;
+
+                    let c = match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error(JSONErrorKind::InvalidEscape)),
+                    };
+
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                0..=0x1f => return Err(self.error(JSONErrorKind::UnexpectedCharacter)),
+                _ => out.push(b),
+            }
+        }
+
+        // The input is a `str` and escapes are encoded as UTF-8, so this never replaces anything
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    fn hex4(&mut self) -> Result<u32, JSONError> {
+        let hex = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| core::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .ok_or_else(|| self.error(JSONErrorKind::InvalidEscape))?;
+
+        self.pos += 4;
+
+        Ok(hex)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, JSONError> {
+        let high = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.input[self.pos..].starts_with(b"\\u") {
+                return Err(self.error(JSONErrorKind::InvalidEscape));
+            }
+
+            self.pos += 2;
+
+            let low = self.hex4()?;
+
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error(JSONErrorKind::InvalidEscape));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error(JSONErrorKind::InvalidEscape))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::*;
+
+    const HELLO: &str = r#"{
+        "name": "Widget",
+        "price": 12.50,
+        "tags": ["a", "bé\n"],
+        "stock": {"count": 3, "available": true, "note": null}
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let soup = Soup::json(HELLO).expect("Failed to parse JSON");
+
+        assert_eq!(
+            soup.tag("name").first().and_then(|n| n.text().cloned()),
+            Some("Widget".into())
+        );
+        assert_eq!(
+            soup.tag("price").first().and_then(|n| n.text().cloned()),
+            Some("12.50".into())
+        );
+        assert_eq!(
+            soup.tag("tags").first().map(|n| n.all_text()),
+            Some("a\nb\u{e9}\n".into())
+        );
+        assert_eq!(
+            soup.tag("available").first().and_then(|n| n.as_bool()),
+            Some(true)
+        );
+        assert!(soup.tag("note").first().is_some_and(|n| n.is_null()));
+        assert_eq!(
+            soup.tag("stock").first().map(|n| n.children().len()),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |s: &str| Soup::json(s).map(|_| ()).unwrap_err();
+
+        assert_eq!(err("").kind, JSONErrorKind::UnexpectedEnd);
+        assert_eq!(err("[1,]").kind, JSONErrorKind::UnexpectedCharacter);
+        assert_eq!(err("01").kind, JSONErrorKind::TrailingCharacters);
+        assert_eq!(err("-").kind, JSONErrorKind::InvalidNumber);
+        assert_eq!(err(r#""\x""#).kind, JSONErrorKind::InvalidEscape);
+        assert_eq!(err(r#""\ud800""#).kind, JSONErrorKind::InvalidEscape);
+        assert_eq!(err(r#""\u+041""#).kind, JSONErrorKind::InvalidEscape);
+        assert_eq!(
+            err(&"[".repeat(JSON_MAX_DEPTH + 2)).kind,
+            JSONErrorKind::TooDeep
+        );
+        assert_eq!(
+            err(r#"{"a" 1}"#).to_string(),
+            "unexpected character at byte 5"
+        );
+
+        assert_eq!(
+            Soup::json(r#""😀""#)
+                .ok()
+                .and_then(|s| s.iter().next().and_then(|n| n.text().cloned())),
+            Some("\u{1F600}".into())
+        );
+    }
+}
@@ -1,14 +1,107 @@
-mod html;
+#[cfg(all(feature = "html-lenient", feature = "xml", feature = "json"))]
+mod auto;
+#[cfg(all(
+    feature = "xml",
+    any(
+        feature = "html-lenient",
+        feature = "html-loose",
+        feature = "html-strict"
+    )
+))]
+mod convert;
+#[cfg(feature = "compression")]
+mod decompress;
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+pub(crate) mod html;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(feature = "toml")]
+mod toml;
 #[cfg(feature = "xml")]
 mod xml;
+#[cfg(feature = "yaml")]
+mod yaml;
 
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "html-lenient", feature = "xml", feature = "json"))]
+pub use auto::{
+    AutoError,
+    AutoSoup,
+    Format,
+};
+#[cfg(all(
+    feature = "xml",
+    any(
+        feature = "html-lenient",
+        feature = "html-loose",
+        feature = "html-strict"
+    )
+))]
+pub use convert::ConversionError;
+#[cfg(feature = "compression")]
+pub use decompress::{
+    decompress,
+    decompress_with,
+    inflate,
+    inflate_with,
+    DecompressError,
+    DecompressOptions,
+};
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
 pub use html::*;
+#[cfg(feature = "json")]
+pub use json::*;
+#[cfg(feature = "std")]
+pub use registry::{
+    Registry,
+    RegistryError,
+};
+#[cfg(feature = "toml")]
+pub use toml::*;
 #[cfg(feature = "xml")]
 pub use xml::*;
+#[cfg(feature = "yaml")]
+pub use yaml::*;
 
 use crate::Node;
 
 /// Used to convert a string into a [`Vec`] of nodes.
+///
+/// This is the only trait a custom backend needs to implement in order to be used with
+/// [`Soup::new`](crate::Soup::new). Parsers that borrow from their input carry the input's
+/// lifetime as a type parameter, like [`StrictHTMLParser`].
+///
+/// # Example
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # use soupy::{parser::{HTMLNode, Parser}, prelude::*};
+/// /// Parses each line as a text node
+/// struct LineParser<'a>(std::marker::PhantomData<&'a ()>);
+///
+/// impl<'a> Parser for LineParser<'a> {
+///     type Input = &'a str;
+///     type Node = HTMLNode<&'a str>;
+///     type Error = std::convert::Infallible;
+///
+///     fn parse(input: &'a str) -> Result<Vec<Self::Node>, Self::Error> {
+///         Ok(input.lines().map(HTMLNode::Text).collect())
+///     }
+/// }
+///
+/// let soup = Soup::new::<LineParser>("one\ntwo").unwrap();
+/// assert_eq!(soup.iter().count(), 2);
+/// ```
 pub trait Parser {
     /// Input type.
     type Input;
@@ -0,0 +1,234 @@
+use std::{
+    boxed::Box,
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    string::String,
+    vec::Vec,
+};
+
+use crate::{
+    parser::Parser,
+    Soup,
+};
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+type Backend<'r, I, N> = Box<dyn Fn(I) -> Result<Vec<N>, BoxError> + Send + Sync + 'r>;
+
+/// A set of parsers chosen by name at runtime
+///
+/// All the parsers of a registry take the same input and produce the same kind of node, so
+/// documents from any of them can be queried by the same code. Each parser keeps its own error
+/// type, which is boxed in a [`RegistryError`].
+///
+/// Backends only need to implement [`Parser`] to be registered. To make a backend look built in,
+/// add constructors to [`Soup`] with an extension trait:
+///
+/// ```rust
+/// # use soupy::{parser::{HTMLNode, Parser}, prelude::*};
+/// /// Parses `key=value` lines into `<key>value</key>` elements
+/// pub struct IniParser<'a>(std::marker::PhantomData<&'a ()>);
+///
+/// impl<'a> Parser for IniParser<'a> {
+///     type Input = &'a str;
+///     type Node = HTMLNode<&'a str>;
+///     type Error = std::fmt::Error;
+///
+///     fn parse(text: &'a str) -> Result<Vec<Self::Node>, Self::Error> {
+///         text.lines()
+///             .map(|line| {
+///                 let (name, value) = line.split_once('=').ok_or(std::fmt::Error)?;
+///                 Ok(HTMLNode::Element {
+///                     name: name.trim(),
+///                     attrs: Default::default(),
+///                     children: vec![HTMLNode::Text(value.trim())],
+///                 })
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// /// Adds `Soup::ini`
+/// pub trait IniSoup {
+///     fn ini(text: &str) -> Result<Soup<HTMLNode<&str>>, std::fmt::Error>;
+/// }
+///
+/// impl IniSoup for Soup {
+///     fn ini(text: &str) -> Result<Soup<HTMLNode<&str>>, std::fmt::Error> {
+///         Soup::new::<IniParser>(text)
+///     }
+/// }
+///
+/// let soup = Soup::ini("name = soupy\nkind = parser").unwrap();
+/// assert_eq!(soup.tag("kind").first().map(|k| k.all_text()), Some("parser".into()));
+/// ```
+///
+/// # Example
+/// ```rust
+/// # use soupy::parser::{HTMLNode, Registry, StrictHTMLParser};
+/// let mut registry = Registry::<&str, HTMLNode<&str>>::new();
+/// registry.register::<StrictHTMLParser>("html");
+/// registry.register_fn("lines", |text: &str| {
+///     Ok::<_, std::convert::Infallible>(text.lines().map(HTMLNode::Text).collect())
+/// });
+///
+/// assert_eq!(registry.formats().collect::<Vec<_>>(), ["html", "lines"]);
+/// assert!(registry.parse("lines", "a\nb").is_ok());
+/// assert!(registry.parse("html", "<p>").is_err());
+/// assert_eq!(registry.parse("pdf", "").unwrap_err().to_string(), "no parser registered for pdf");
+/// ```
+pub struct Registry<'r, I, N> {
+    backends: BTreeMap<String, Backend<'r, I, N>>,
+}
+
+impl<'r, I, N> Registry<'r, I, N> {
+    /// Creates an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            backends: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the parser `P` as `name`, replacing any parser already registered as `name`
+    pub fn register<P>(&mut self, name: &str)
+    where
+        P: Parser<Input = I, Node = N> + 'r,
+        P::Error: Error + Send + Sync + 'static,
+    {
+        self.register_fn(name, P::parse);
+    }
+
+    /// Registers a parsing function as `name`, replacing any parser already registered as
+    /// `name`
+    pub fn register_fn<F, E>(&mut self, name: &str, parse: F)
+    where
+        F: Fn(I) -> Result<Vec<N>, E> + Send + Sync + 'r,
+        E: Error + Send + Sync + 'static,
+    {
+        self.backends.insert(
+            name.into(),
+            Box::new(move |input| parse(input).map_err(Into::into)),
+        );
+    }
+
+    /// Returns `true` if a parser is registered as `name`
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.backends.contains_key(name)
+    }
+
+    /// Names of the registered parsers, in alphabetical order
+    pub fn formats(&self) -> impl Iterator<Item = &str> {
+        self.backends.keys().map(String::as_str)
+    }
+
+    /// Parses `input` with the parser registered as `name`
+    ///
+    /// # Errors
+    /// If no parser is registered as `name`, or the parser fails.
+    pub fn parse(&self, name: &str, input: I) -> Result<Soup<N>, RegistryError> {
+        let backend = self
+            .backends
+            .get(name)
+            .ok_or_else(|| RegistryError::Unknown(name.into()))?;
+
+        Ok(Soup::from_nodes(backend(input).map_err(|error| {
+            RegistryError::Parse {
+                format: name.into(),
+                error,
+            }
+        })?))
+    }
+}
+
+impl<I, N> Default for Registry<'_, I, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, N> fmt::Debug for Registry<'_, I, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("formats", &self.backends.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Error returned by [`Registry::parse`]
+#[derive(Debug)]
+pub enum RegistryError {
+    /// No parser is registered under the given name
+    Unknown(String),
+
+    /// The parser failed
+    Parse {
+        /// Name of the parser
+        format: String,
+
+        /// The parser's own error
+        error: Box<dyn Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(format) => write!(f, "no parser registered for {format}"),
+            Self::Parse { format, error } => write!(f, "invalid {format}: {error}"),
+        }
+    }
+}
+
+impl Error for RegistryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Unknown(_) => None,
+            Self::Parse { error, .. } => Some(&**error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::{
+            JSONNode,
+            JSONParser,
+        },
+        Queryable,
+    };
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::<&str, JSONNode>::default();
+        registry.register::<JSONParser<&str>>("json");
+        registry.register_fn("csv", |text: &str| {
+            JSONParser::parse(&alloc::format!("[{text}]"))
+        });
+        assert!(registry.contains("csv"));
+
+        let soup = registry.parse("csv", "1, 2, 3").expect("Failed to parse");
+        assert_eq!(soup.iter().count(), 4);
+        assert_eq!(
+            registry
+                .parse("json", r#"{"a": 1}"#)
+                .map(|soup| soup.tag("a").all().count())
+                .ok(),
+            Some(1)
+        );
+
+        let error = registry
+            .parse("json", "{")
+            .expect_err("Parsed invalid JSON");
+        assert!(error.to_string().starts_with("invalid json: "));
+        assert!(error.source().is_some());
+        assert_eq!(
+            alloc::format!("{registry:?}"),
+            r#"Registry { formats: ["csv", "json"] }"#
+        );
+    }
+}
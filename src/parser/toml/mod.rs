@@ -0,0 +1,693 @@
+use alloc::{
+    string::{
+        String,
+        ToString,
+    },
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+};
+
+use crate::parser::{
+    JSONNode,
+    JSONValue,
+    Parser,
+    JSON_MAX_DEPTH,
+};
+
+/// TOML parser
+///
+/// Maps TOML documents onto the same nodes as [`JSONParser`](crate::parser::JSONParser):
+/// tables are objects whose members are named by their key, and arrays contain their items as
+/// children. An array of tables, like `[[item]]`, is an array of unnamed tables.
+///
+/// Integers are written in decimal without underscores, so their text can be parsed with
+/// [`str::parse`]. Dates and times are kept as strings.
+///
+/// Errors on malformed TOML.
+#[derive(Clone, Debug)]
+pub struct TOMLParser<S> {
+    _marker: PhantomData<S>,
+}
+
+impl<S> Parser for TOMLParser<S>
+where
+    S: AsRef<str>,
+{
+    type Input = S;
+    type Node = JSONNode;
+    type Error = TOMLError;
+
+    fn parse(text: S) -> Result<Vec<Self::Node>, Self::Error> {
+        let mut reader = Reader {
+            input: text.as_ref().as_bytes(),
+            pos: 0,
+        };
+
+        let mut root = Vec::new();
+        let mut path = Vec::new();
+
+        loop {
+            reader.blank();
+
+            match reader.peek() {
+                None => break,
+                Some(b'[') => {
+                    let array = reader.input[reader.pos..].starts_with(b"[[");
+                    reader.pos += if array { 2 } else { 1 };
+
+                    let keys = reader.key()?;
+                    reader.expect(b']')?;
+
+                    let defined = if array {
+                        reader.expect(b']')?;
+                        push_table(&mut root, &keys)
+                    } else {
+                        table(&mut root, &keys).map(|_| ())
+                    };
+
+                    if defined.is_none() {
+                        return Err(reader.error(TOMLErrorKind::DuplicateKey));
+                    }
+
+                    path = keys;
+                }
+                Some(_) => {
+                    let keys = reader.key()?;
+                    reader.expect(b'=')?;
+                    reader.spaces();
+
+                    let value = reader.value(0)?;
+
+                    table(&mut root, &path)
+                        .ok_or(TOMLErrorKind::DuplicateKey)
+                        .and_then(|table| insert(table, &keys, value))
+                        .map_err(|kind| reader.error(kind))?;
+                }
+            }
+
+            reader.end_of_line()?;
+        }
+
+        Ok(vec![JSONNode {
+            key: None,
+            value: JSONValue::Object(root),
+        }])
+    }
+}
+
+/// Finds the table at `keys` under `table`, creating missing tables
+///
+/// Arrays of tables lead to their last table. Returns `None` if a key holds some other value.
+fn table<'t>(mut table: &'t mut Vec<JSONNode>, keys: &[String]) -> Option<&'t mut Vec<JSONNode>> {
+    for key in keys {
+        let i = if let Some(i) = table.iter().position(|n| n.key.as_ref() == Some(key)) {
+            i
+        } else {
+            table.push(JSONNode {
+                key: Some(key.clone()),
+                value: JSONValue::Object(Vec::new()),
+            });
+            table.len() - 1
+        };
+
+        table = match &mut table[i].value {
+            JSONValue::Object(members) => members,
+            JSONValue::Array(items) => match items.last_mut() {
+                Some(JSONNode {
+                    value: JSONValue::Object(members),
+                    ..
+                }) => members,
+                _ => return None,
+            },
+            _ => return None,
+        };
+    }
+
+    Some(table)
+}
+
+/// Appends an empty table to the array of tables at `keys`, creating the array if missing
+fn push_table(root: &mut Vec<JSONNode>, keys: &[String]) -> Option<()> {
+    let (key, parents) = keys.split_last()?;
+    let parent = table(root, parents)?;
+
+    let i = if let Some(i) = parent.iter().position(|n| n.key.as_ref() == Some(key)) {
+        i
+    } else {
+        parent.push(JSONNode {
+            key: Some(key.clone()),
+            value: JSONValue::Array(Vec::new()),
+        });
+        parent.len() - 1
+    };
+
+    let JSONValue::Array(items) = &mut parent[i].value else {
+        return None;
+    };
+
+    items.push(JSONNode {
+        key: None,
+        value: JSONValue::Object(Vec::new()),
+    });
+
+    Some(())
+}
+
+/// Stores `value` under the dotted key `keys` in `table`
+fn insert(
+    table: &mut Vec<JSONNode>,
+    keys: &[String],
+    value: JSONValue,
+) -> Result<(), TOMLErrorKind> {
+    let Some((key, parents)) = keys.split_last() else {
+        return Err(TOMLErrorKind::UnexpectedCharacter);
+    };
+
+    let table = self::table(table, parents).ok_or(TOMLErrorKind::DuplicateKey)?;
+
+    if table.iter().any(|n| n.key.as_ref() == Some(key)) {
+        return Err(TOMLErrorKind::DuplicateKey);
+    }
+
+    table.push(JSONNode {
+        key: Some(key.clone()),
+        value,
+    });
+
+    Ok(())
+}
+
+/// Error returned when parsing malformed TOML
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TOMLError {
+    /// Line of the error in the input, starting at 1
+    pub line: usize,
+
+    /// What went wrong
+    pub kind: TOMLErrorKind,
+}
+
+/// Kind of [`TOMLError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TOMLErrorKind {
+    /// The input ended in the middle of a value
+    UnexpectedEnd,
+    /// A character that cannot start or continue a key or value
+    UnexpectedCharacter,
+    /// An invalid escape sequence in a string
+    InvalidEscape,
+    /// A malformed number
+    InvalidNumber,
+    /// A key is defined twice, or used as a table when it holds some other value
+    DuplicateKey,
+    /// Values are nested deeper than [`JSON_MAX_DEPTH`]
+    TooDeep,
+}
+
+impl fmt::Display for TOMLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            TOMLErrorKind::UnexpectedEnd => "unexpected end of input",
+            TOMLErrorKind::UnexpectedCharacter => "unexpected character",
+            TOMLErrorKind::InvalidEscape => "invalid escape sequence",
+            TOMLErrorKind::InvalidNumber => "invalid number",
+            TOMLErrorKind::DuplicateKey => "duplicate key",
+            TOMLErrorKind::TooDeep => "nesting too deep",
+        };
+
+        write!(f, "{kind} on line {}", self.line)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TOMLError {}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, kind: TOMLErrorKind) -> TOMLError {
+        let end = self.pos.min(self.input.len());
+
+        TOMLError {
+            line: self.input[..end].split(|&b| b == b'\n').count(),
+            kind,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), TOMLError> {
+        self.spaces();
+
+        match self.peek() {
+            Some(b) if b == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.error(TOMLErrorKind::UnexpectedCharacter)),
+            None => Err(self.error(TOMLErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    fn spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn comment(&mut self) {
+        if self.peek() == Some(b'#') {
+            while !matches!(self.peek(), None | Some(b'\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skips whitespace, comments, and line breaks
+    fn blank(&mut self) {
+        loop {
+            self.spaces();
+            self.comment();
+
+            match self.peek() {
+                Some(b'\n' | b'\r') => self.pos += 1,
+                _ => return,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), TOMLError> {
+        self.spaces();
+        self.comment();
+
+        if self.input[self.pos..].starts_with(b"\r\n") {
+            self.pos += 2;
+            return Ok(());
+        }
+
+        match self.peek() {
+            None => Ok(()),
+            Some(b'\n') => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.error(TOMLErrorKind::UnexpectedCharacter)),
+        }
+    }
+
+    /// Reads a possibly dotted key, like `server."host name".port`
+    fn key(&mut self) -> Result<Vec<String>, TOMLError> {
+        let mut keys = Vec::new();
+
+        loop {
+            self.spaces();
+
+            let key = match self.peek() {
+                Some(b'"' | b'\'') => self.string()?,
+                Some(b) if b.is_ascii_alphanumeric() || b == b'_' || b == b'-' => {
+                    let start = self.pos;
+
+                    while self
+                        .peek()
+                        .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+                    {
+                        self.pos += 1;
+                    }
+
+                    // Only ASCII was consumed
+                    String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()
+                }
+                Some(_) => return Err(self.error(TOMLErrorKind::UnexpectedCharacter)),
+                None => return Err(self.error(TOMLErrorKind::UnexpectedEnd)),
+            };
+
+            keys.push(key);
+            self.spaces();
+
+            if self.peek() == Some(b'.') {
+                self.pos += 1;
+            } else {
+                return Ok(keys);
+            }
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JSONValue, TOMLError> {
+        if depth > JSON_MAX_DEPTH {
+            return Err(self.error(TOMLErrorKind::TooDeep));
+        }
+
+        match self.peek() {
+            None => Err(self.error(TOMLErrorKind::UnexpectedEnd)),
+            Some(b'"' | b'\'') => self.string().map(JSONValue::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+
+                loop {
+                    self.blank();
+
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        return Ok(JSONValue::Array(items));
+                    }
+
+                    let value = self.value(depth + 1)?;
+                    items.push(JSONNode { key: None, value });
+
+                    self.blank();
+
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    } else {
+                        self.expect(b']')?;
+                        return Ok(JSONValue::Array(items));
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+
+                self.spaces();
+
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(JSONValue::Object(members));
+                }
+
+                loop {
+                    let keys = self.key()?;
+                    self.expect(b'=')?;
+                    self.spaces();
+
+                    let value = self.value(depth + 1)?;
+                    insert(&mut members, &keys, value).map_err(|kind| self.error(kind))?;
+
+                    self.spaces();
+
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    } else {
+                        self.expect(b'}')?;
+                        return Ok(JSONValue::Object(members));
+                    }
+                }
+            }
+            Some(_) => self.scalar(),
+        }
+    }
+
+    /// Reads a boolean, number, or date and time
+    fn scalar(&mut self) -> Result<JSONValue, TOMLError> {
+        let start = self.pos;
+
+        let token = |r: &mut Self| {
+            while r
+                .peek()
+                .is_some_and(|b| b.is_ascii_alphanumeric() || b"_+-.:".contains(&b))
+            {
+                r.pos += 1;
+            }
+        };
+
+        token(self);
+
+        let is_date =
+            |s: &[u8]| s.len() >= 10 && s[..4].iter().all(u8::is_ascii_digit) && s[4] == b'-';
+
+        // A date and time may be separated by a space
+        if is_date(&self.input[start..self.pos])
+            && self.peek() == Some(b' ')
+            && self.input.get(self.pos + 1).is_some_and(u8::is_ascii_digit)
+        {
+            self.pos += 1;
+            token(self);
+        }
+
+        // Only ASCII was consumed
+        let text = String::from_utf8_lossy(&self.input[start..self.pos]).into_owned();
+
+        match &*text {
+            "" => Err(self.error(TOMLErrorKind::UnexpectedCharacter)),
+            "true" => Ok(JSONValue::Bool(true)),
+            "false" => Ok(JSONValue::Bool(false)),
+            _ if is_date(text.as_bytes()) || text.contains(':') => Ok(JSONValue::String(text)),
+            _ => number(&text)
+                .map(JSONValue::Number)
+                .ok_or_else(|| self.error(TOMLErrorKind::InvalidNumber)),
+        }
+    }
+
+    /// Reads a basic or literal string, on one line or many
+    fn string(&mut self) -> Result<String, TOMLError> {
+        let Some(quote) = self.peek() else {
+            return Err(self.error(TOMLErrorKind::UnexpectedEnd));
+        };
+
+        let basic = quote == b'"';
+        let delimiter = [quote; 3];
+        let multiline = self.input[self.pos..].starts_with(&delimiter);
+
+        let mut out = Vec::new();
+
+        if multiline {
+            self.pos += 3;
+
+            // A line break right after the opening delimiter is trimmed
+            if self.input[self.pos..].starts_with(b"\r\n") {
+                self.pos += 2;
+            } else if self.peek() == Some(b'\n') {
+                self.pos += 1;
+            }
+        } else {
+            self.pos += 1;
+        }
+
+        loop {
+            let Some(b) = self.peek() else {
+                return Err(self.error(TOMLErrorKind::UnexpectedEnd));
+            };
+
+            if b == quote && (!multiline || self.input[self.pos..].starts_with(&delimiter)) {
+                if multiline {
+                    // Up to two quotes may come right before the closing delimiter
+                    let extra = self.input[self.pos + 3..]
+                        .iter()
+                        .take(2)
+                        .take_while(|&&c| c == quote)
+                        .count();
+
+                    out.resize(out.len() + extra, quote);
+                    self.pos += 3 + extra;
+                } else {
+                    self.pos += 1;
+                }
+
+                break;
+            }
+
+            if b == b'\n' && !multiline {
+                return Err(self.error(TOMLErrorKind::UnexpectedCharacter));
+            }
+
+            self.pos += 1;
+
+            match b {
+                b'\\' if basic => {
+                    if multiline && self.line_ending_backslash() {
+                        continue;
+                    }
+
+                    let c = self.escape()?;
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                _ => out.push(b),
+            }
+        }
+
+        // The input is a `str` and escapes are encoded as UTF-8, so this never replaces anything
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    /// Skips the whitespace after a `\` that ends a line, returning `false` if it doesn't
+    fn line_ending_backslash(&mut self) -> bool {
+        let start = self.pos;
+        self.spaces();
+
+        if !matches!(self.peek(), Some(b'\n' | b'\r')) {
+            self.pos = start;
+            return false;
+        }
+
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+
+        true
+    }
+
+    fn escape(&mut self) -> Result<char, TOMLError> {
+        let Some(e) = self.peek() else {
+            return Err(self.error(TOMLErrorKind::UnexpectedEnd));
+        };
+
+        self.pos += 1;
+
+        match e {
+            b'b' => Ok('\u{8}'),
+            b't' => Ok('\t'),
+            b'n' => Ok('\n'),
+            b'f' => Ok('\u{c}'),
+            b'r' => Ok('\r'),
+            b'e' => Ok('\u{1b}'),
+            b'"' => Ok('"'),
+            b'\\' => Ok('\\'),
+            b'u' => self.hex(4),
+            b'U' => self.hex(8),
+            _ => Err(self.error(TOMLErrorKind::InvalidEscape)),
+        }
+    }
+
+    fn hex(&mut self, len: usize) -> Result<char, TOMLError> {
+        let c = self
+            .input
+            .get(self.pos..self.pos + len)
+            .and_then(|h| core::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(TOMLErrorKind::InvalidEscape))?;
+
+        self.pos += len;
+
+        Ok(c)
+    }
+}
+
+/// Normalizes a TOML number, returning `None` if it is malformed
+fn number(text: &str) -> Option<String> {
+    let digits = text.replace('_', "");
+
+    let radix = match digits.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+
+    if radix != 10 {
+        return Some(&digits[2..])
+            .filter(|d| !d.starts_with(['+', '-']))
+            .and_then(|d| i64::from_str_radix(d, radix).ok())
+            .map(|n| n.to_string());
+    }
+
+    let unsigned = digits.strip_prefix(['+', '-']).unwrap_or(&digits);
+
+    let valid = matches!(unsigned, "inf" | "nan")
+        || (unsigned.starts_with(|c: char| c.is_ascii_digit()) && digits.parse::<f64>().is_ok());
+
+    valid.then_some(digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::*;
+
+    const CONFIG: &str = r#"
+# Deployment settings
+title = "Shop"
+version = 1_024
+mask = 0xff
+
+[server]
+host = "example.com"   # public name
+port = 8_080
+started = 1979-05-27 07:32:00Z
+tags = [
+    "web",
+    'raw\path',  # trailing comma
+]
+
+[server.tls]
+enabled = true
+ratio = 6.5e-1
+
+[[service]]
+name = "api"
+limits = { cpu.max = 2, memory = "1G" }
+
+[[service]]
+name = """
+multi\
+   line"""
+"#;
+
+    #[test]
+    fn test_parse() {
+        let soup = Soup::toml(CONFIG).expect("Failed to parse TOML");
+        let text = |name: &str| soup.tag(name).first().and_then(|n| n.text().cloned());
+
+        assert_eq!(text("title"), Some("Shop".into()));
+        assert_eq!(text("version"), Some("1024".into()));
+        assert_eq!(text("mask"), Some("255".into()));
+        assert_eq!(text("port"), Some("8080".into()));
+        assert_eq!(text("started"), Some("1979-05-27 07:32:00Z".into()));
+        assert_eq!(text("ratio"), Some("6.5e-1".into()));
+        assert_eq!(text("max"), Some("2".into()));
+        assert_eq!(
+            soup.tag("tags").first().map(|n| n.all_text()),
+            Some("web\nraw\\path".into())
+        );
+        assert_eq!(
+            soup.tag("enabled").first().and_then(|n| n.as_bool()),
+            Some(true)
+        );
+        assert_eq!(
+            soup.tag("service")
+                .first()
+                .map(|s| s.iter().map(|t| t.children().len()).collect::<Vec<_>>()),
+            Some(vec![2, 1])
+        );
+        assert_eq!(
+            soup.tag("name")
+                .all()
+                .map(|n| n.all_text())
+                .collect::<Vec<_>>(),
+            ["api", "multiline"]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |s: &str| Soup::toml(s).map(|_| ()).unwrap_err();
+
+        assert_eq!(err("a = ").kind, TOMLErrorKind::UnexpectedEnd);
+        assert_eq!(err("a = 1 2").kind, TOMLErrorKind::UnexpectedCharacter);
+        assert_eq!(err("a = 1x").kind, TOMLErrorKind::InvalidNumber);
+        assert_eq!(err(r#"a = "\q""#).kind, TOMLErrorKind::InvalidEscape);
+        assert_eq!(err("a = 1\na = 2").kind, TOMLErrorKind::DuplicateKey);
+        assert_eq!(err("a = 1\n[a.b]").kind, TOMLErrorKind::DuplicateKey);
+        assert_eq!(
+            err(&alloc::format!("a = {}", "[".repeat(JSON_MAX_DEPTH + 2))).kind,
+            TOMLErrorKind::TooDeep
+        );
+        assert_eq!(
+            err("\n\nname = \"open\n").to_string(),
+            "unexpected character on line 3"
+        );
+    }
+}
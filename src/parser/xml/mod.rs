@@ -1,14 +1,28 @@
+pub mod ns;
+
 use std::{
     collections::BTreeMap,
+    fmt,
     io::Read,
     marker::PhantomData,
 };
 
+use ::xml::{
+    attribute::OwnedAttribute,
+    name::OwnedName,
+    reader::{
+        EventReader,
+        ParserConfig,
+        XmlEvent,
+    },
+};
 use xmltree::Namespace;
 
 use crate::{
+    escape,
     parser::Parser,
     Node,
+    Soup,
 };
 
 /// Default XML parser
@@ -28,10 +42,106 @@ where
     type Error = xmltree::ParseError;
 
     fn parse(reader: R) -> Result<Vec<Self::Node>, Self::Error> {
-        Ok(xmltree::Element::parse_all(reader)?
+        Self::parse_with(reader, &XMLOptions::default())
+    }
+}
+
+impl<R> XMLParser<R>
+where
+    R: Read,
+{
+    /// Parses the document with the given options
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    pub fn parse_with(
+        reader: R,
+        options: &XMLOptions,
+    ) -> Result<Vec<XMLNode>, xmltree::ParseError> {
+        let config = options.entities.iter().fold(
+            ParserConfig::new().ignore_comments(false),
+            |config, (name, value)| config.add_entity(name.as_str(), value.as_str()),
+        );
+
+        let mut reader = EventReader::new_with_config(reader, config);
+        let mut nodes = Vec::new();
+        // Elements whose end tag has not been read yet, innermost last, so deeply nested
+        // documents cannot overflow the call stack
+        let mut open: Vec<XMLElement> = Vec::new();
+
+        loop {
+            let node = match reader.next().map_err(xmltree::ParseError::MalformedXml)? {
+                XmlEvent::StartElement {
+                    name,
+                    attributes,
+                    namespace,
+                } => {
+                    open.push(element(name, attributes, namespace));
+                    continue;
+                }
+                // The reader checks that end tags match their start tags
+                XmlEvent::EndElement { .. } => match open.pop() {
+                    Some(element) => XMLNode::Element(element),
+                    None => continue,
+                },
+                XmlEvent::Comment(c) => XMLNode::Comment(c),
+                XmlEvent::Characters(t) => XMLNode::Text(t),
+                // Like `xmltree`, CDATA sections inside elements are read as text
+                XmlEvent::CData(d) if open.is_empty() => XMLNode::CData(d),
+                XmlEvent::CData(d) => XMLNode::Text(d),
+                XmlEvent::ProcessingInstruction { name, data } => {
+                    XMLNode::ProcessingInstruction(name, data)
+                }
+                XmlEvent::EndDocument if open.is_empty() => return Ok(nodes),
+                XmlEvent::Whitespace(_) => continue,
+                XmlEvent::StartDocument { .. } if open.is_empty() => continue,
+                _ => return Err(xmltree::ParseError::CannotParse),
+            };
+
+            match open.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => nodes.push(node),
+            }
+        }
+    }
+}
+
+/// Options for the [`XMLParser`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XMLOptions {
+    /// Extra entities, so `&name;` is replaced by the value instead of failing the parse
+    ///
+    /// Entities declared in the document's internal DTD subset are always recognized, but those
+    /// from external DTDs, like the ones `DocBook` and DITA documents rely on, have to be given here.
+    pub entities: BTreeMap<String, String>,
+}
+
+impl XMLOptions {
+    /// Creates the default options
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entity, so `&name;` is replaced by `value`
+    #[must_use]
+    pub fn with_entity(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.entities.insert(name.into(), value.into());
+        self
+    }
+}
+
+fn element(name: OwnedName, attributes: Vec<OwnedAttribute>, namespace: Namespace) -> XMLElement {
+    XMLElement {
+        prefix: name.prefix,
+        namespace: name.namespace,
+        namespaces: (!namespace.is_essentially_empty()).then_some(namespace),
+        name: name.local_name,
+        attributes: attributes
             .into_iter()
-            .map(Into::into)
-            .collect())
+            .map(|a| (a.name.local_name, a.value))
+            .collect(),
+        children: Vec::new(),
     }
 }
 
@@ -70,6 +180,19 @@ impl From<xmltree::Element> for XMLElement {
     }
 }
 
+impl From<XMLElement> for xmltree::Element {
+    fn from(value: XMLElement) -> Self {
+        Self {
+            prefix: value.prefix,
+            namespace: value.namespace,
+            namespaces: value.namespaces,
+            name: value.name,
+            attributes: value.attributes.into_iter().collect(),
+            children: value.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 /// Represents an XML node
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum XMLNode {
@@ -101,6 +224,41 @@ impl From<xmltree::XMLNode> for XMLNode {
     }
 }
 
+impl From<XMLNode> for xmltree::XMLNode {
+    fn from(value: XMLNode) -> Self {
+        match value {
+            XMLNode::Element(e) => xmltree::XMLNode::Element(e.into()),
+            XMLNode::Comment(c) => xmltree::XMLNode::Comment(c),
+            XMLNode::CData(d) => xmltree::XMLNode::CData(d),
+            XMLNode::Text(t) => xmltree::XMLNode::Text(t),
+            XMLNode::ProcessingInstruction(a, b) => xmltree::XMLNode::ProcessingInstruction(a, b),
+        }
+    }
+}
+
+/// Converts an element node back into an [`xmltree::Element`], so it can be written with xmltree
+///
+/// Returns the node unchanged if it is not an element.
+///
+/// # Example
+/// ```rust
+/// # use soupy::prelude::*;
+/// let soup = Soup::xml(r#"<root><child id="1"/></root>"#.as_bytes()).unwrap();
+/// let root = soup.tag("root").first().unwrap();
+/// let element = xmltree::Element::try_from((*root).clone()).unwrap();
+/// assert_eq!(element.get_child("child").and_then(|c| c.attributes.get("id")), Some(&"1".into()));
+/// ```
+impl TryFrom<XMLNode> for xmltree::Element {
+    type Error = XMLNode;
+
+    fn try_from(value: XMLNode) -> Result<Self, Self::Error> {
+        match value {
+            XMLNode::Element(e) => Ok(e.into()),
+            other => Err(other),
+        }
+    }
+}
+
 impl Node for XMLNode {
     type Text = String;
 
@@ -118,6 +276,13 @@ impl Node for XMLNode {
         }
     }
 
+    fn comment(&self) -> Option<&String> {
+        match self {
+            XMLNode::Comment(c) => Some(c),
+            _ => None,
+        }
+    }
+
     fn attrs(&self) -> Option<&BTreeMap<String, String>> {
         match self {
             XMLNode::Element(e) => Some(&e.attributes),
@@ -136,9 +301,202 @@ impl Node for XMLNode {
 
 impl XMLNode {
     /// Iterate over direct children
-    pub fn iter(&self) -> std::slice::Iter<Self> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Self> {
         self.children().iter()
     }
+
+    /// Recursively shrinks the capacity of every string and child list as much as possible
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            XMLNode::Element(e) => e.shrink_to_fit(),
+            XMLNode::Comment(s) | XMLNode::CData(s) | XMLNode::Text(s) => s.shrink_to_fit(),
+            XMLNode::ProcessingInstruction(name, data) => {
+                name.shrink_to_fit();
+                data.iter_mut().for_each(String::shrink_to_fit);
+            }
+        }
+    }
+
+    /// Converts every text value in the tree (names, attributes, and content) with `f`
+    ///
+    /// Namespace prefixes and URIs are left untouched.
+    #[must_use]
+    pub fn map_text<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(String) -> String,
+    {
+        self.map_text_with(&mut f)
+    }
+
+    fn map_text_with<F>(self, f: &mut F) -> Self
+    where
+        F: FnMut(String) -> String,
+    {
+        match self {
+            XMLNode::Element(e) => XMLNode::Element(e.map_text_with(f)),
+            XMLNode::Comment(c) => XMLNode::Comment(f(c)),
+            XMLNode::CData(d) => XMLNode::CData(f(d)),
+            XMLNode::Text(t) => XMLNode::Text(f(t)),
+            XMLNode::ProcessingInstruction(name, data) => {
+                XMLNode::ProcessingInstruction(f(name), data.map(&mut *f))
+            }
+        }
+    }
+}
+
+impl XMLElement {
+    /// Recursively shrinks the capacity of every string and child list as much as possible
+    pub fn shrink_to_fit(&mut self) {
+        self.name.shrink_to_fit();
+        self.prefix.iter_mut().for_each(String::shrink_to_fit);
+        self.namespace.iter_mut().for_each(String::shrink_to_fit);
+        self.children.shrink_to_fit();
+        self.children.iter_mut().for_each(XMLNode::shrink_to_fit);
+
+        // Keys cannot be mutated in place, so the map has to be rebuilt
+        self.attributes = core::mem::take(&mut self.attributes)
+            .into_iter()
+            .map(|(mut k, mut v)| {
+                k.shrink_to_fit();
+                v.shrink_to_fit();
+                (k, v)
+            })
+            .collect();
+    }
+
+    /// Converts every text value in the tree (names, attributes, and content) with `f`
+    ///
+    /// Namespace prefixes and URIs are left untouched.
+    #[must_use]
+    pub fn map_text<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(String) -> String,
+    {
+        self.map_text_with(&mut f)
+    }
+
+    fn map_text_with<F>(self, f: &mut F) -> Self
+    where
+        F: FnMut(String) -> String,
+    {
+        Self {
+            name: f(self.name),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(|(k, v)| (f(k), f(v)))
+                .collect(),
+            children: self
+                .children
+                .into_iter()
+                .map(|c| c.map_text_with(f))
+                .collect(),
+            ..self
+        }
+    }
+}
+
+impl Soup<XMLNode> {
+    /// Shrinks the capacity of every string and child list in the document as much as possible
+    ///
+    /// Useful for documents that are kept around for a long time after parsing.
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.nodes.iter_mut().for_each(XMLNode::shrink_to_fit);
+    }
+}
+
+/// Serializes the node as XML
+///
+/// Namespace declarations are only written where they differ from the enclosing element.
+impl fmt::Display for XMLNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node(f, self, None)
+    }
+}
+
+/// Serializes the element as XML
+impl fmt::Display for XMLElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_element(f, self, None)
+    }
+}
+
+fn write_node(
+    f: &mut fmt::Formatter<'_>,
+    node: &XMLNode,
+    parent: Option<&Namespace>,
+) -> fmt::Result {
+    match node {
+        XMLNode::Element(e) => write_element(f, e, parent),
+        XMLNode::Comment(c) => write!(f, "<!--{c}-->"),
+        XMLNode::CData(d) => write!(f, "<![CDATA[{d}]]>"),
+        XMLNode::Text(t) => escape::text(f, t),
+        XMLNode::ProcessingInstruction(name, Some(data)) => write!(f, "<?{name} {data}?>"),
+        XMLNode::ProcessingInstruction(name, None) => write!(f, "<?{name}?>"),
+    }
+}
+
+fn write_element(
+    f: &mut fmt::Formatter<'_>,
+    element: &XMLElement,
+    parent: Option<&Namespace>,
+) -> fmt::Result {
+    f.write_str("<")?;
+    write_qualified_name(f, element)?;
+
+    if let Some(namespaces) = &element.namespaces {
+        for (prefix, uri) in namespaces {
+            let inherited = parent
+                .and_then(|p| p.get(prefix))
+                .map_or(prefix.is_empty() && uri.is_empty(), |parent_uri| {
+                    parent_uri == uri
+                });
+
+            if inherited || prefix == "xml" || prefix == "xmlns" {
+                continue;
+            }
+
+            if prefix.is_empty() {
+                f.write_str(" xmlns=\"")?;
+            } else {
+                write!(f, " xmlns:{prefix}=\"")?;
+            }
+
+            escape::attr(f, uri)?;
+            f.write_str("\"")?;
+        }
+    }
+
+    for (k, v) in &element.attributes {
+        write!(f, " {k}=\"")?;
+        escape::attr(f, v)?;
+        f.write_str("\"")?;
+    }
+
+    if element.children.is_empty() {
+        return f.write_str("/>");
+    }
+
+    f.write_str(">")?;
+
+    let scope = element.namespaces.as_ref().or(parent);
+
+    for child in &element.children {
+        write_node(f, child, scope)?;
+    }
+
+    f.write_str("</")?;
+    write_qualified_name(f, element)?;
+    f.write_str(">")
+}
+
+fn write_qualified_name(f: &mut fmt::Formatter<'_>, element: &XMLElement) -> fmt::Result {
+    if let Some(prefix) = &element.prefix {
+        write!(f, "{prefix}:")?;
+    }
+
+    f.write_str(&element.name)
 }
 
 impl<'a> IntoIterator for &'a XMLNode {
@@ -295,4 +653,154 @@ mod tests {
             Some("Outer text".into())
         );
     }
+
+    #[test]
+    fn test_display_round_trip() {
+        let soup = Soup::xml(HELLO.as_bytes()).expect("Failed to parse XML");
+
+        let root = soup.tag("root").first().expect("Could not find 'root' tag");
+        let xml = root.to_string();
+
+        assert!(xml.starts_with("<root><simple>Here's some text</simple><complex id=\"hello\">"));
+
+        let reparsed = Soup::xml(xml.as_bytes()).expect("Failed to parse serialized XML");
+
+        assert_eq!(reparsed.nodes, soup.nodes);
+    }
+
+    #[test]
+    fn test_display_escaping() {
+        let soup = Soup::xml(
+            r#"<a:root xmlns:a="urn:a" note="&quot;x&quot; &amp; y"><a:b>1 &lt; 2</a:b><!--c--></a:root>"#
+                .as_bytes(),
+        )
+        .expect("Failed to parse XML");
+
+        let xml = soup.nodes[0].to_string();
+
+        assert_eq!(
+            xml,
+            r#"<a:root xmlns:a="urn:a" note="&quot;x&quot; &amp; y"><a:b>1 &lt; 2</a:b><!--c--></a:root>"#
+        );
+    }
+
+    #[test]
+    fn test_map_text() {
+        let soup =
+            Soup::xml(r#"<a:root xmlns:a="urn:a" id="x"><b>text</b><!--c--></a:root>"#.as_bytes())
+                .expect("Failed to parse XML");
+
+        let root = soup.nodes[0].clone().map_text(|s| s.to_uppercase());
+
+        assert_eq!(
+            root.to_string(),
+            r#"<a:ROOT xmlns:a="urn:a" ID="X"><B>TEXT</B><!--C--></a:ROOT>"#
+        );
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut soup = Soup::xml(HELLO.as_bytes()).expect("Failed to parse XML");
+        let before = soup.nodes.clone();
+
+        if let XMLNode::Element(root) = &mut soup.nodes[0] {
+            root.name.reserve(64);
+            root.children.reserve(64);
+        }
+
+        soup.shrink_to_fit();
+
+        if let XMLNode::Element(root) = &soup.nodes[0] {
+            assert_eq!(root.name.capacity(), root.name.len());
+            assert_eq!(root.children.capacity(), root.children.len());
+        }
+
+        assert_eq!(soup.nodes, before);
+    }
+
+    #[test]
+    fn test_into_xmltree() {
+        let soup = Soup::xml(HELLO.as_bytes()).expect("Failed to parse XML");
+
+        let element = xmltree::Element::try_from(soup.nodes[0].clone())
+            .expect("Failed to convert root element");
+
+        let mut xml = Vec::new();
+        element.write(&mut xml).expect("Failed to write XML");
+
+        let reparsed = Soup::xml(xml.as_slice()).expect("Failed to parse written XML");
+        assert_eq!(reparsed.nodes, soup.nodes);
+
+        assert_eq!(
+            xmltree::Element::try_from(XMLNode::Text("text".into())),
+            Err(XMLNode::Text("text".into()))
+        );
+    }
+
+    #[test]
+    fn test_entities() {
+        let docbook = r#"<?xml version="1.0"?>
+<!DOCTYPE book PUBLIC "-//OASIS//DTD DocBook XML V4.5//EN" "docbookx.dtd" [
+    <!ENTITY product "Soupy">
+]>
+<book><title>&product; &mdash; &version;</title><para>&amp;<![CDATA[&mdash;]]></para></book>"#;
+
+        assert!(Soup::xml(docbook.as_bytes()).is_err());
+
+        let options = XMLOptions::new()
+            .with_entity("mdash", "\u{2014}")
+            .with_entity("version", "1.0");
+        let soup = Soup::xml_with(docbook.as_bytes(), &options).expect("Failed to parse XML");
+
+        assert_eq!(
+            soup.tag("title").first().map(|t| t.all_text()),
+            Some("Soupy \u{2014} 1.0".into())
+        );
+        assert_eq!(
+            soup.tag("para").first().map(|t| t.all_text()),
+            Some("&\n&mdash;".into())
+        );
+
+        let plain = "<?x y?><!--c--><a b=\"1\"><![CDATA[d]]><e/>f</a>";
+        let expected = xmltree::Element::parse_all(plain.as_bytes())
+            .expect("Failed to parse XML")
+            .into_iter()
+            .map(XMLNode::from)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Soup::xml(plain.as_bytes())
+                .expect("Failed to parse XML")
+                .nodes,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_comment_filter() {
+        let xml = "<feed><!-- BEGIN items --><item/><!-- END items --><!-- generated --></feed>";
+        let soup = Soup::xml(xml.as_bytes()).expect("Failed to parse XML");
+
+        let markers = regex::Regex::new(r"^ (BEGIN|END) ").expect("Failed to compile regex");
+        let found = soup
+            .filter(filter::Comment { content: markers })
+            .all()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(found, ["<!-- BEGIN items -->", "<!-- END items -->"]);
+        assert!(soup
+            .filter(filter::Comment {
+                content: "generated"
+            })
+            .first()
+            .is_none());
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let xml = "<a>".repeat(5_000) + &"</a>".repeat(5_000);
+        let soup = Soup::xml(xml.as_bytes()).expect("Failed to parse XML");
+
+        assert_eq!(soup.depth(), 5_000);
+    }
 }
@@ -0,0 +1,197 @@
+//! Common XML namespaces, and filters for namespace-aware queries
+//!
+//! Elements are matched by namespace URI, never by the prefix a document happens to use.
+//!
+//! # Example
+//! ```rust
+//! # use soupy::{parser::ns::{self, Namespaces}, prelude::*};
+//! let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:m="http://search.yahoo.com/mrss/">
+//!     <entry><title>One</title><m:thumbnail url="a.png"/></entry>
+//! </feed>"#;
+//! let soup = Soup::xml(feed.as_bytes()).unwrap();
+//!
+//! let ns = Namespaces::common();
+//! let thumbnail = soup.filter(ns.name("media:thumbnail").unwrap()).first();
+//! assert_eq!(thumbnail.and_then(|t| t.get("url").cloned()), Some("a.png".into()));
+//!
+//! assert_eq!(soup.filter(ns::QName::new(ns::ATOM, "*")).all().count(), 3);
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::{
+    filter::Filter,
+    parser::XMLNode,
+};
+
+/// XHTML
+pub const XHTML: &str = "http://www.w3.org/1999/xhtml";
+/// SVG
+pub const SVG: &str = "http://www.w3.org/2000/svg";
+/// `XLink`, used for links in SVG
+pub const XLINK: &str = "http://www.w3.org/1999/xlink";
+/// `MathML`
+pub const MATHML: &str = "http://www.w3.org/1998/Math/MathML";
+/// The reserved `xml:` prefix
+pub const XML: &str = "http://www.w3.org/XML/1998/namespace";
+/// The reserved `xmlns:` prefix
+pub const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+/// XML Schema
+pub const XSD: &str = "http://www.w3.org/2001/XMLSchema";
+/// XML Schema instance attributes, like `xsi:type`
+pub const XSI: &str = "http://www.w3.org/2001/XMLSchema-instance";
+/// Atom feeds
+pub const ATOM: &str = "http://www.w3.org/2005/Atom";
+/// RSS 1.0
+pub const RSS_1: &str = "http://purl.org/rss/1.0/";
+/// RDF, the root of RSS 1.0 feeds
+pub const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+/// RSS content module, for `content:encoded`
+pub const RSS_CONTENT: &str = "http://purl.org/rss/1.0/modules/content/";
+/// Dublin Core, for `dc:creator` and `dc:date`
+pub const DUBLIN_CORE: &str = "http://purl.org/dc/elements/1.1/";
+/// Media RSS, for `media:content` and `media:thumbnail`
+pub const MEDIA_RSS: &str = "http://search.yahoo.com/mrss/";
+/// iTunes podcast tags
+pub const ITUNES: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+/// EPUB OPF package documents
+pub const OPF: &str = "http://www.idpf.org/2007/opf";
+/// EPUB `META-INF/container.xml`
+pub const OCF_CONTAINER: &str = "urn:oasis:names:tc:opendocument:xmlns:container";
+/// SOAP 1.1 envelopes
+pub const SOAP: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+/// SOAP 1.2 envelopes
+pub const SOAP_12: &str = "http://www.w3.org/2003/05/soap-envelope";
+/// Android attributes, like `android:name` in `AndroidManifest.xml`
+pub const ANDROID: &str = "http://schemas.android.com/apk/res/android";
+
+/// Filters elements by namespace URI and local name
+///
+/// A local name of `*` matches every element in the namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QName<'a> {
+    /// Namespace URI
+    pub namespace: &'a str,
+
+    /// Local name, without a prefix
+    pub local: &'a str,
+}
+
+impl<'a> QName<'a> {
+    /// Creates a new `QName` filter
+    #[must_use]
+    pub fn new(namespace: &'a str, local: &'a str) -> Self {
+        Self { namespace, local }
+    }
+}
+
+impl Filter<XMLNode> for QName<'_> {
+    fn matches(&self, node: &XMLNode) -> bool {
+        match node {
+            XMLNode::Element(e) => {
+                e.namespace.as_deref() == Some(self.namespace)
+                    && (self.local == "*" || e.name == self.local)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Prefixes for writing namespace-aware queries
+///
+/// These prefixes belong to the query, and are independent of those declared in documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Namespaces {
+    prefixes: BTreeMap<String, String>,
+}
+
+impl Namespaces {
+    /// Creates an empty set of prefixes
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates prefixes for every namespace in this module, named by convention
+    ///
+    /// `xhtml`, `svg`, `xlink`, `mathml`, `xml`, `xsd`, `xsi`, `atom`, `rss`, `rdf`, `content`,
+    /// `dc`, `media`, `itunes`, `opf`, `soap`, `soap12` and `android`.
+    #[must_use]
+    pub fn common() -> Self {
+        [
+            ("xhtml", XHTML),
+            ("svg", SVG),
+            ("xlink", XLINK),
+            ("mathml", MATHML),
+            ("xml", XML),
+            ("xsd", XSD),
+            ("xsi", XSI),
+            ("atom", ATOM),
+            ("rss", RSS_1),
+            ("rdf", RDF),
+            ("content", RSS_CONTENT),
+            ("dc", DUBLIN_CORE),
+            ("media", MEDIA_RSS),
+            ("itunes", ITUNES),
+            ("opf", OPF),
+            ("soap", SOAP),
+            ("soap12", SOAP_12),
+            ("android", ANDROID),
+        ]
+        .into_iter()
+        .fold(Self::new(), |ns, (prefix, uri)| ns.with(prefix, uri))
+    }
+
+    /// Binds `prefix` to the namespace `uri`, replacing any previous binding
+    #[must_use]
+    pub fn with(mut self, prefix: impl Into<String>, uri: impl Into<String>) -> Self {
+        self.prefixes.insert(prefix.into(), uri.into());
+        self
+    }
+
+    /// Returns the namespace URI bound to `prefix`
+    #[must_use]
+    pub fn uri(&self, prefix: &str) -> Option<&str> {
+        self.prefixes.get(prefix).map(String::as_str)
+    }
+
+    /// Resolves a prefixed name like `atom:entry` into a [`QName`] filter
+    ///
+    /// Returns `None` if the name has no prefix, or the prefix is not bound.
+    #[must_use]
+    pub fn name<'a>(&'a self, name: &'a str) -> Option<QName<'a>> {
+        let (prefix, local) = name.split_once(':')?;
+
+        Some(QName::new(self.uri(prefix)?, local))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_namespaces() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:l="http://www.w3.org/1999/xlink">
+            <a l:href="/"><title>Home</title></a>
+            <foreignObject><p xmlns="http://www.w3.org/1999/xhtml"><title>Other</title></p></foreignObject>
+        </svg>"#;
+
+        let soup = Soup::xml(svg.as_bytes()).expect("Failed to parse XML");
+        let ns = Namespaces::new().with("s", SVG).with("h", XHTML);
+
+        let titles = |name| {
+            soup.filter(ns.name(name).expect("Unbound prefix"))
+                .all()
+                .map(|t| t.all_text())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(titles("s:title"), ["Home"]);
+        assert_eq!(titles("h:title"), ["Other"]);
+        assert!(ns.name("x:title").is_none());
+        assert!(ns.name("title").is_none());
+        assert_eq!(Namespaces::common().uri("dc"), Some(DUBLIN_CORE));
+    }
+}
@@ -0,0 +1,884 @@
+use alloc::{
+    string::{
+        String,
+        ToString,
+    },
+    vec::Vec,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+};
+
+use crate::parser::{
+    JSONNode,
+    JSONValue,
+    Parser,
+    JSON_MAX_DEPTH,
+};
+
+/// YAML parser
+///
+/// Maps YAML documents onto the same nodes as [`JSONParser`](crate::parser::JSONParser):
+/// mapping entries are named by their key, and sequences and mappings contain their items as
+/// children. Each document of a stream is a top-level node.
+///
+/// Supports block and flow collections, plain and quoted scalars, and literal (`|`) and folded
+/// (`>`) block scalars. Plain scalars are resolved with the core schema, so `~` is null and
+/// `yes` is a string. Hexadecimal and octal integers are written in decimal.
+///
+/// Errors on malformed YAML, and on anchors, aliases, and tags, which are not supported.
+#[derive(Clone, Debug)]
+pub struct YAMLParser<S> {
+    _marker: PhantomData<S>,
+}
+
+impl<S> Parser for YAMLParser<S>
+where
+    S: AsRef<str>,
+{
+    type Input = S;
+    type Node = JSONNode;
+    type Error = YAMLError;
+
+    fn parse(text: S) -> Result<Vec<Self::Node>, Self::Error> {
+        let mut reader = Reader {
+            lines: text
+                .as_ref()
+                .lines()
+                .enumerate()
+                .map(|(i, line)| Line::new(i + 1, line))
+                .collect(),
+            pos: 0,
+            line: 1,
+        };
+
+        let mut documents = Vec::new();
+        // Whether a `---` has started a document that has no content yet
+        let mut started = false;
+
+        loop {
+            let Some(line) = reader.next_line() else {
+                if started {
+                    documents.push(JSONValue::Null);
+                }
+                break;
+            };
+
+            if line.is_marker("---") {
+                if started {
+                    documents.push(JSONValue::Null);
+                }
+                started = true;
+                reader.advance();
+            } else if line.is_marker("...") {
+                if started {
+                    documents.push(JSONValue::Null);
+                }
+                started = false;
+                reader.advance();
+            } else if line.indent == 0 && line.text.starts_with('%') {
+                // Directives only affect tags, which are not supported
+                reader.advance();
+            } else {
+                documents.push(reader.block(0)?);
+                started = false;
+
+                if let Some(line) = reader.next_line() {
+                    if !line.is_marker("---") && !line.is_marker("...") {
+                        return Err(line.error(YAMLErrorKind::Indentation));
+                    }
+                }
+            }
+        }
+
+        Ok(documents
+            .into_iter()
+            .map(|value| JSONNode { key: None, value })
+            .collect())
+    }
+}
+
+/// Error returned when parsing malformed or unsupported YAML
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YAMLError {
+    /// Line of the error in the input, starting at 1
+    pub line: usize,
+
+    /// What went wrong
+    pub kind: YAMLErrorKind,
+}
+
+/// Kind of [`YAMLError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YAMLErrorKind {
+    /// The input ended in the middle of a quoted scalar or flow collection
+    UnexpectedEnd,
+    /// A character that cannot start or continue a value
+    UnexpectedCharacter,
+    /// An invalid escape sequence in a double-quoted scalar
+    InvalidEscape,
+    /// A line is indented more than its siblings, or does not fit its parent
+    Indentation,
+    /// A key appears twice in the same mapping
+    DuplicateKey,
+    /// An anchor, alias, tag, or complex key
+    Unsupported,
+    /// Values are nested deeper than [`JSON_MAX_DEPTH`]
+    TooDeep,
+}
+
+impl fmt::Display for YAMLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            YAMLErrorKind::UnexpectedEnd => "unexpected end of input",
+            YAMLErrorKind::UnexpectedCharacter => "unexpected character",
+            YAMLErrorKind::InvalidEscape => "invalid escape sequence",
+            YAMLErrorKind::Indentation => "bad indentation",
+            YAMLErrorKind::DuplicateKey => "duplicate key",
+            YAMLErrorKind::Unsupported => "unsupported feature",
+            YAMLErrorKind::TooDeep => "nesting too deep",
+        };
+
+        write!(f, "{kind} on line {}", self.line)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for YAMLError {}
+
+/// A line of input, without its indentation or trailing whitespace
+#[derive(Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn new(number: usize, line: &'a str) -> Self {
+        let text = line.trim_start_matches(' ');
+
+        Self {
+            number,
+            indent: line.len() - text.len(),
+            text: text.trim_end(),
+        }
+    }
+
+    fn error(&self, kind: YAMLErrorKind) -> YAMLError {
+        YAMLError {
+            line: self.number,
+            kind,
+        }
+    }
+
+    fn is_marker(&self, marker: &str) -> bool {
+        self.indent == 0
+            && self.text.strip_prefix(marker).is_some_and(|rest| {
+                rest.is_empty() || (rest.starts_with([' ', '\t']) && is_blank(rest.trim_start()))
+            })
+    }
+}
+
+/// Returns `true` for empty text and comments
+fn is_blank(text: &str) -> bool {
+    text.is_empty() || text.starts_with('#')
+}
+
+/// Returns `true` if `text` starts a block sequence item
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ") || text.starts_with("-\t")
+}
+
+/// Splits a block mapping entry into its key and the rest of the line
+fn entry(text: &str) -> Option<(String, &str)> {
+    if text.starts_with(['"', '\'']) {
+        let mut flow = Flow {
+            input: text.as_bytes(),
+            pos: 0,
+        };
+
+        let key = flow.quoted().ok()?;
+        flow.spaces();
+
+        let rest = text[flow.pos..].strip_prefix(':')?;
+
+        return (rest.is_empty() || rest.starts_with([' ', '\t']))
+            .then(|| (key, rest.trim_start()));
+    }
+
+    if is_item(text) || text.starts_with(['[', '{', '#', '|', '>']) {
+        return None;
+    }
+
+    let colon = text
+        .match_indices(':')
+        .map(|(i, _)| i)
+        .find(|&i| text[i + 1..].is_empty() || text[i + 1..].starts_with([' ', '\t']))?;
+
+    let key = text[..colon].trim_end();
+
+    if key.is_empty() || key.contains(" #") {
+        return None;
+    }
+
+    Some((key.into(), text[colon + 1..].trim_start()))
+}
+
+struct Reader<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+    /// Number of the line being parsed, for errors
+    line: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, kind: YAMLErrorKind) -> YAMLError {
+        YAMLError {
+            line: self.line,
+            kind,
+        }
+    }
+
+    /// Skips blank lines and comments, returning the next line with content
+    fn next_line(&mut self) -> Option<Line<'a>> {
+        while self.lines.get(self.pos).is_some_and(|l| is_blank(l.text)) {
+            self.pos += 1;
+        }
+
+        self.lines.get(self.pos).copied()
+    }
+
+    fn advance(&mut self) {
+        if let Some(line) = self.lines.get(self.pos) {
+            self.line = line.number;
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the node starting at the current line, and any lines that belong to it
+    fn block(&mut self, depth: usize) -> Result<JSONValue, YAMLError> {
+        let Some(line) = self.next_line() else {
+            return Ok(JSONValue::Null);
+        };
+
+        if depth > JSON_MAX_DEPTH {
+            return Err(line.error(YAMLErrorKind::TooDeep));
+        }
+
+        if is_item(line.text) {
+            self.sequence(line.indent, depth)
+        } else if entry(line.text).is_some() {
+            self.mapping(line.indent, depth)
+        } else {
+            self.advance();
+            self.scalar(line.text, line.indent, depth)
+        }
+    }
+
+    /// Reads the value of an entry or item whose own line has nothing after it
+    fn nested(
+        &mut self,
+        indent: usize,
+        depth: usize,
+        in_mapping: bool,
+    ) -> Result<JSONValue, YAMLError> {
+        match self.next_line() {
+            Some(line) if line.indent > indent => self.block(depth + 1),
+            // Sequences may be indented as much as the key they belong to
+            Some(line) if in_mapping && line.indent == indent && is_item(line.text) => {
+                self.sequence(indent, depth + 1)
+            }
+            _ => Ok(JSONValue::Null),
+        }
+    }
+
+    fn sequence(&mut self, indent: usize, depth: usize) -> Result<JSONValue, YAMLError> {
+        let mut items = Vec::new();
+
+        while let Some(line) = self.next_line() {
+            if line.indent < indent || !is_item(line.text) {
+                break;
+            }
+
+            if line.indent > indent {
+                return Err(line.error(YAMLErrorKind::Indentation));
+            }
+
+            let rest = &line.text[1..];
+            let content = rest.trim_start_matches([' ', '\t']);
+
+            let value = if is_blank(content) {
+                self.advance();
+                self.nested(indent, depth, false)?
+            } else if is_item(content) || entry(content).is_some() {
+                // A collection can start on the same line as its item, indented past the dash
+                let current = &mut self.lines[self.pos];
+                current.indent += 1 + rest.len() - content.len();
+                current.text = content;
+
+                self.block(depth + 1)?
+            } else {
+                self.advance();
+                self.scalar(content, indent, depth + 1)?
+            };
+
+            items.push(JSONNode { key: None, value });
+        }
+
+        Ok(JSONValue::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize, depth: usize) -> Result<JSONValue, YAMLError> {
+        let mut members = Vec::<JSONNode>::new();
+
+        while let Some(line) = self.next_line() {
+            if line.indent < indent {
+                break;
+            }
+
+            if line.indent > indent {
+                return Err(line.error(YAMLErrorKind::Indentation));
+            }
+
+            if line.text.starts_with("? ") {
+                return Err(line.error(YAMLErrorKind::Unsupported));
+            }
+
+            let Some((key, rest)) = entry(line.text) else {
+                break;
+            };
+
+            if members.iter().any(|m| m.key.as_ref() == Some(&key)) {
+                return Err(line.error(YAMLErrorKind::DuplicateKey));
+            }
+
+            self.advance();
+
+            let value = if is_blank(rest) {
+                self.nested(indent, depth, true)?
+            } else {
+                self.scalar(rest, indent, depth + 1)?
+            };
+
+            members.push(JSONNode {
+                key: Some(key),
+                value,
+            });
+        }
+
+        Ok(JSONValue::Object(members))
+    }
+
+    /// Reads a value written after a key or dash, which may continue on more indented lines
+    fn scalar(&mut self, text: &str, indent: usize, depth: usize) -> Result<JSONValue, YAMLError> {
+        if text.starts_with(['|', '>']) {
+            return self.block_scalar(text, indent);
+        }
+
+        let mut text = String::from(text);
+
+        // Plain scalars are folded with the more indented lines that follow
+        if !text.starts_with(['[', '{', '"', '\'']) && !text.contains(" #") {
+            while let Some(line) = self
+                .lines
+                .get(self.pos)
+                .filter(|l| l.indent > indent && !is_blank(l.text) && entry(l.text).is_none())
+            {
+                text.push(' ');
+                text.push_str(line.text);
+                self.advance();
+            }
+        }
+
+        loop {
+            let mut flow = Flow {
+                input: text.as_bytes(),
+                pos: 0,
+            };
+
+            match flow.document(depth) {
+                Ok(value) => return Ok(value),
+                // Quoted scalars and flow collections may span lines
+                Err(YAMLErrorKind::UnexpectedEnd) => {
+                    let Some(line) = self.next_line().filter(|l| l.indent > indent) else {
+                        return Err(self.error(YAMLErrorKind::UnexpectedEnd));
+                    };
+
+                    text.push(' ');
+                    text.push_str(line.text);
+                    self.advance();
+                }
+                Err(kind) => return Err(self.error(kind)),
+            }
+        }
+    }
+
+    /// Reads a literal (`|`) or folded (`>`) block scalar, whose lines are indented past `indent`
+    fn block_scalar(&mut self, header: &str, indent: usize) -> Result<JSONValue, YAMLError> {
+        let folded = header.starts_with('>');
+        let mut keep = None;
+        let mut block_indent = None;
+
+        let indicators = &header[1..];
+        let comment = indicators.find([' ', '\t']).unwrap_or(indicators.len());
+
+        if !is_blank(indicators[comment..].trim_start()) {
+            return Err(self.error(YAMLErrorKind::UnexpectedCharacter));
+        }
+
+        for c in indicators[..comment].chars() {
+            match c {
+                '-' if keep.is_none() => keep = Some(false),
+                '+' if keep.is_none() => keep = Some(true),
+                '1'..='9' if block_indent.is_none() => {
+                    block_indent = c.to_digit(10).map(|d| indent + d as usize);
+                }
+                _ => return Err(self.error(YAMLErrorKind::UnexpectedCharacter)),
+            }
+        }
+
+        let mut content = Vec::new();
+
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.text.is_empty() {
+                content.push(String::new());
+            } else {
+                let block_indent = *block_indent.get_or_insert(line.indent);
+
+                if line.indent <= indent || line.indent < block_indent {
+                    break;
+                }
+
+                let mut text = " ".repeat(line.indent - block_indent);
+                text.push_str(line.text);
+                content.push(text);
+            }
+
+            self.advance();
+        }
+
+        let trailing = content.iter().rev().take_while(|l| l.is_empty()).count();
+        content.truncate(content.len() - trailing);
+
+        let mut out = String::new();
+
+        for (i, line) in content.iter().enumerate() {
+            if i > 0 {
+                let prev = &content[i - 1];
+
+                // Folding joins lines with a space, except around blank and more indented lines
+                out.push_str(
+                    if !folded || prev.is_empty() || prev.starts_with(' ') || line.starts_with(' ')
+                    {
+                        "\n"
+                    } else if line.is_empty() {
+                        ""
+                    } else {
+                        " "
+                    },
+                );
+            }
+
+            out.push_str(line);
+        }
+
+        if keep == Some(true) {
+            out.push_str(&"\n".repeat(trailing + usize::from(!content.is_empty())));
+        } else if keep.is_none() && !content.is_empty() {
+            out.push('\n');
+        }
+
+        Ok(JSONValue::String(out))
+    }
+}
+
+/// Reads values written on one line, including flow collections like `[a, {b: c}]`
+struct Flow<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Flow<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads a whole value, followed by nothing but a comment
+    fn document(&mut self, depth: usize) -> Result<JSONValue, YAMLErrorKind> {
+        let value = self.value(depth, false)?;
+        self.spaces();
+
+        if matches!(self.peek(), None | Some(b'#')) {
+            Ok(value)
+        } else {
+            Err(YAMLErrorKind::UnexpectedCharacter)
+        }
+    }
+
+    fn value(&mut self, depth: usize, in_flow: bool) -> Result<JSONValue, YAMLErrorKind> {
+        if depth > JSON_MAX_DEPTH {
+            return Err(YAMLErrorKind::TooDeep);
+        }
+
+        self.spaces();
+
+        match self.peek() {
+            Some(b'"' | b'\'') => self.quoted().map(JSONValue::String),
+            Some(b'&' | b'*' | b'!') => Err(YAMLErrorKind::Unsupported),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+
+                loop {
+                    self.spaces();
+
+                    match self.peek() {
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(JSONValue::Array(items));
+                        }
+                        None => return Err(YAMLErrorKind::UnexpectedEnd),
+                        _ => {}
+                    }
+
+                    let value = self.value(depth + 1, true)?;
+                    items.push(JSONNode { key: None, value });
+
+                    if !self.separator(b']')? {
+                        return Ok(JSONValue::Array(items));
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::<JSONNode>::new();
+
+                loop {
+                    self.spaces();
+
+                    match self.peek() {
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(JSONValue::Object(members));
+                        }
+                        None => return Err(YAMLErrorKind::UnexpectedEnd),
+                        _ => {}
+                    }
+
+                    let key = match self.value(depth + 1, true)? {
+                        JSONValue::Number(s) | JSONValue::String(s) => s,
+                        JSONValue::Bool(b) => b.to_string(),
+                        JSONValue::Null => "null".into(),
+                        JSONValue::Array(_) | JSONValue::Object(_) => {
+                            return Err(YAMLErrorKind::Unsupported)
+                        }
+                    };
+
+                    if members.iter().any(|m| m.key.as_ref() == Some(&key)) {
+                        return Err(YAMLErrorKind::DuplicateKey);
+                    }
+
+                    self.spaces();
+
+                    let value = if self.peek() == Some(b':') {
+                        self.pos += 1;
+                        self.value(depth + 1, true)?
+                    } else {
+                        JSONValue::Null
+                    };
+
+                    members.push(JSONNode {
+                        key: Some(key),
+                        value,
+                    });
+
+                    if !self.separator(b'}')? {
+                        return Ok(JSONValue::Object(members));
+                    }
+                }
+            }
+            _ => Ok(self.plain(in_flow)),
+        }
+    }
+
+    /// Reads the `,` between flow items, returning `false` at the `close` bracket instead
+    fn separator(&mut self, close: u8) -> Result<bool, YAMLErrorKind> {
+        self.spaces();
+
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(b) if b == close => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(_) => Err(YAMLErrorKind::UnexpectedCharacter),
+            None => Err(YAMLErrorKind::UnexpectedEnd),
+        }
+    }
+
+    /// Reads a plain scalar, which ends at a comment, or at flow punctuation inside a collection
+    fn plain(&mut self, in_flow: bool) -> JSONValue {
+        let start = self.pos;
+
+        while let Some(b) = self.peek() {
+            let next = self.input.get(self.pos + 1).copied();
+
+            let end = match b {
+                b'#' => self.pos > start && self.input[self.pos - 1].is_ascii_whitespace(),
+                b',' | b'[' | b']' | b'{' | b'}' => in_flow,
+                b':' => in_flow && matches!(next, None | Some(b' ' | b'\t' | b',' | b']' | b'}')),
+                _ => false,
+            };
+
+            if end {
+                break;
+            }
+
+            self.pos += 1;
+        }
+
+        // Only whole characters were consumed, as all delimiters are ASCII
+        let text = String::from_utf8_lossy(&self.input[start..self.pos]);
+
+        resolve(text.trim())
+    }
+
+    /// Reads a single- or double-quoted scalar
+    fn quoted(&mut self) -> Result<String, YAMLErrorKind> {
+        let Some(quote) = self.peek() else {
+            return Err(YAMLErrorKind::UnexpectedEnd);
+        };
+
+        self.pos += 1;
+        let mut out = Vec::new();
+
+        loop {
+            let Some(b) = self.peek() else {
+                return Err(YAMLErrorKind::UnexpectedEnd);
+            };
+
+            self.pos += 1;
+
+            match b {
+                b'\'' if quote == b'\'' => {
+                    if self.peek() == Some(b'\'') {
+                        self.pos += 1;
+                        out.push(b'\'');
+                    } else {
+                        break;
+                    }
+                }
+                b'"' if quote == b'"' => break,
+                b'\\' if quote == b'"' => {
+                    let c = self.escape()?;
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                _ => out.push(b),
+            }
+        }
+
+        // The input is a `str` and escapes are encoded as UTF-8, so this never replaces anything
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    fn escape(&mut self) -> Result<char, YAMLErrorKind> {
+        let Some(e) = self.peek() else {
+            return Err(YAMLErrorKind::UnexpectedEnd);
+        };
+
+        self.pos += 1;
+
+        match e {
+            b'0' => Ok('\0'),
+            b'a' => Ok('\u{7}'),
+            b'b' => Ok('\u{8}'),
+            b't' | b'\t' => Ok('\t'),
+            b'n' => Ok('\n'),
+            b'v' => Ok('\u{b}'),
+            b'f' => Ok('\u{c}'),
+            b'r' => Ok('\r'),
+            b'e' => Ok('\u{1b}'),
+            b' ' => Ok(' '),
+            b'"' => Ok('"'),
+            b'/' => Ok('/'),
+            b'\\' => Ok('\\'),
+            b'N' => Ok('\u{85}'),
+            b'_' => Ok('\u{a0}'),
+            b'L' => Ok('\u{2028}'),
+            b'P' => Ok('\u{2029}'),
+            b'x' => self.hex(2),
+            b'u' => self.hex(4),
+            b'U' => self.hex(8),
+            _ => Err(YAMLErrorKind::InvalidEscape),
+        }
+    }
+
+    fn hex(&mut self, len: usize) -> Result<char, YAMLErrorKind> {
+        let c = self
+            .input
+            .get(self.pos..self.pos + len)
+            .and_then(|h| core::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or(YAMLErrorKind::InvalidEscape)?;
+
+        self.pos += len;
+
+        Ok(c)
+    }
+}
+
+/// Resolves a plain scalar to a null, boolean, number, or string
+fn resolve(text: &str) -> JSONValue {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => JSONValue::Null,
+        "true" | "True" | "TRUE" => JSONValue::Bool(true),
+        "false" | "False" | "FALSE" => JSONValue::Bool(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => JSONValue::Number("inf".into()),
+        "-.inf" | "-.Inf" | "-.INF" => JSONValue::Number("-inf".into()),
+        ".nan" | ".NaN" | ".NAN" => JSONValue::Number("NaN".into()),
+        _ => {
+            let radix = match text.get(..2) {
+                Some("0x") => 16,
+                Some("0o") => 8,
+                _ => 10,
+            };
+
+            let number = if radix == 10 {
+                let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+                let digit = |s: &str| s.starts_with(|c: char| c.is_ascii_digit());
+
+                ((digit(unsigned) || unsigned.strip_prefix('.').is_some_and(digit))
+                    && text.parse::<f64>().is_ok())
+                .then(|| text.into())
+            } else {
+                Some(&text[2..])
+                    .filter(|d| !d.starts_with(['+', '-']))
+                    .and_then(|d| i64::from_str_radix(d, radix).ok())
+                    .map(|n| n.to_string())
+            };
+
+            number.map_or_else(|| JSONValue::String(text.into()), JSONValue::Number)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::ToString,
+        vec,
+    };
+
+    use super::*;
+    use crate::*;
+
+    const CONFIG: &str = r#"
+# Deployment settings
+name: shop
+replicas: 3
+mask: 0x1f
+enabled: yes
+debug: false
+owner: ~
+services:
+  - name: api
+    ports: [8080, 8443]
+    env: {LEVEL: "warn", "quoted key": 'it''s'}
+  - name: worker
+    command:
+    - run
+    - --fast
+description: >
+  Folded
+  text
+
+  here
+script: |-
+  echo "hi"
+    indented
+summary: a plain
+  scalar # comment
+"#;
+
+    #[test]
+    fn test_parse() {
+        let soup = Soup::yaml(CONFIG).expect("Failed to parse YAML");
+        let text = |name: &str| soup.tag(name).first().and_then(|n| n.text().cloned());
+
+        assert_eq!(text("replicas"), Some("3".into()));
+        assert_eq!(text("mask"), Some("31".into()));
+        assert_eq!(text("enabled"), Some("yes".into()));
+        assert_eq!(
+            soup.tag("debug").first().and_then(|n| n.as_bool()),
+            Some(false)
+        );
+        assert!(soup.tag("owner").first().is_some_and(|n| n.is_null()));
+        assert_eq!(
+            soup.tag("name")
+                .all()
+                .map(|n| n.all_text())
+                .collect::<Vec<_>>(),
+            ["shop", "api", "worker"]
+        );
+        assert_eq!(
+            soup.tag("ports").first().map(|n| n.all_text()),
+            Some("8080\n8443".into())
+        );
+        assert_eq!(text("quoted key"), Some("it's".into()));
+        assert_eq!(text("LEVEL"), Some("warn".into()));
+        assert_eq!(
+            soup.tag("command").first().map(|n| n.all_text()),
+            Some("run\n--fast".into())
+        );
+        assert_eq!(text("description"), Some("Folded text\nhere\n".into()));
+        assert_eq!(text("script"), Some("echo \"hi\"\n  indented".into()));
+        assert_eq!(text("summary"), Some("a plain scalar".into()));
+
+        let docs = Soup::yaml("a\n---\n- \"line\\none\n  two\"\n...\n").map(|s| s.nodes);
+        assert_eq!(
+            docs.as_ref().map(Vec::len),
+            Ok(2),
+            "documents are split by markers"
+        );
+        assert_eq!(
+            docs.ok()
+                .and_then(|d| d[1].iter().next().and_then(|n| n.text().cloned())),
+            Some("line\none two".into())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |s: &str| Soup::yaml(s).map(|_| ()).unwrap_err();
+
+        assert_eq!(err("a: [1, 2").kind, YAMLErrorKind::UnexpectedEnd);
+        assert_eq!(err("a: \"b\" c").kind, YAMLErrorKind::UnexpectedCharacter);
+        assert_eq!(err(r#"a: "\q""#).kind, YAMLErrorKind::InvalidEscape);
+        assert_eq!(err("a: 1\n  b: 2").kind, YAMLErrorKind::Indentation);
+        assert_eq!(err("a: 1\na: 2").kind, YAMLErrorKind::DuplicateKey);
+        assert_eq!(err("a: &x 1").kind, YAMLErrorKind::Unsupported);
+        assert_eq!(
+            err(&"[".repeat(JSON_MAX_DEPTH + 2)).kind,
+            YAMLErrorKind::TooDeep
+        );
+        assert_eq!(
+            err("a:\n  - 1\n  b: 2").to_string(),
+            "bad indentation on line 3"
+        );
+
+        assert_eq!(Soup::yaml("# nothing\n").map(|s| s.nodes), Ok(vec![]));
+    }
+}
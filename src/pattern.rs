@@ -1,3 +1,13 @@
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    ops::Range,
+};
+
 /// A trait used to indicate a type which can be used to match a value
 ///
 /// Any type that implements this trait can be passed to the various
@@ -32,6 +42,28 @@ pub trait Pattern<S> {
     fn value(&self) -> Option<S> {
         None
     }
+
+    /// Looks up the entry of `map` whose key is the one value matched by the pattern
+    ///
+    /// Used by [`Attr`](crate::filter::Attr) to find attributes by name. The default converts
+    /// the pattern with [`Pattern::value`] on every call, so patterns that can compare against
+    /// keys without converting should override it.
+    fn lookup<'m, V>(&self, map: &'m BTreeMap<S, V>) -> Lookup<'m, V>
+    where
+        S: Ord,
+    {
+        self.value()
+            .map_or(Lookup::AnyKey, |key| Lookup::Key(map.get(&key)))
+    }
+}
+
+/// Result of [`Pattern::lookup`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup<'m, V> {
+    /// The pattern matches a single key, and this is the value of its entry, if there is one
+    Key(Option<&'m V>),
+    /// The pattern is not a single key, so every entry has to be tested
+    AnyKey,
 }
 
 impl<S> Pattern<S> for bool {
@@ -51,6 +83,13 @@ where
     fn value(&self) -> Option<S> {
         Some((*self).into())
     }
+
+    fn lookup<'m, V>(&self, map: &'m BTreeMap<S, V>) -> Lookup<'m, V>
+    where
+        S: Ord,
+    {
+        Lookup::Key(lookup_str(map, self))
+    }
 }
 
 impl<S> Pattern<S> for String
@@ -64,6 +103,32 @@ where
     fn value(&self) -> Option<S> {
         Some(self.as_str().into())
     }
+
+    fn lookup<'m, V>(&self, map: &'m BTreeMap<S, V>) -> Lookup<'m, V>
+    where
+        S: Ord,
+    {
+        Lookup::Key(lookup_str(map, self))
+    }
+}
+
+/// Finds the entry with key `key`
+///
+/// The map is scanned in order comparing keys as strings, stopping at the first key past `key`.
+/// This never converts `key`, which would allocate for most text types on every node.
+fn lookup_str<'m, S, V>(map: &'m BTreeMap<S, V>, key: &str) -> Option<&'m V>
+where
+    S: AsRef<str>,
+{
+    for (k, v) in map {
+        match k.as_ref().cmp(key) {
+            Ordering::Less => {}
+            Ordering::Equal => return Some(v),
+            Ordering::Greater => return None,
+        }
+    }
+
+    None
 }
 
 #[cfg(feature = "regex")]
@@ -76,7 +141,51 @@ where
     }
 }
 
-#[cfg(test)]
+/// A pattern that can locate its matches within text, used by [`Soup::find_text`](crate::Soup::find_text)
+pub trait TextPattern {
+    /// Returns the byte ranges of the non-overlapping, non-empty matches in `haystack`, in order
+    fn find_in(&self, haystack: &str) -> Vec<Range<usize>>;
+}
+
+impl TextPattern for str {
+    fn find_in(&self, haystack: &str) -> Vec<Range<usize>> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        haystack
+            .match_indices(self)
+            .map(|(i, m)| i..i + m.len())
+            .collect()
+    }
+}
+
+impl TextPattern for String {
+    fn find_in(&self, haystack: &str) -> Vec<Range<usize>> {
+        self.as_str().find_in(haystack)
+    }
+}
+
+impl TextPattern for char {
+    fn find_in(&self, haystack: &str) -> Vec<Range<usize>> {
+        haystack
+            .match_indices(*self)
+            .map(|(i, m)| i..i + m.len())
+            .collect()
+    }
+}
+
+#[cfg(feature = "regex")]
+impl TextPattern for regex::Regex {
+    fn find_in(&self, haystack: &str) -> Vec<Range<usize>> {
+        self.find_iter(haystack)
+            .map(|m| m.range())
+            .filter(|r| !r.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "xml"))]
 mod tests {
     use crate::*;
 
@@ -101,6 +210,7 @@ mod tests {
     <a>Outer text</a>
 </root>"#;
 
+    #[cfg(feature = "regex")]
     #[test]
     fn test_regex() {
         let soup = Soup::xml(HELLO.as_bytes()).expect("Failed to parse XML");
@@ -173,4 +283,94 @@ mod tests {
         );
         assert_eq!(tags.next().map(|t| t.all_text()), None);
     }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_find_text() {
+        let soup = Soup::xml(HELLO.as_bytes()).expect("Failed to parse XML");
+
+        let words = regex::Regex::new(r"\b\w+ text\b").expect("Failed to compile regex");
+        let found = soup
+            .find_text(&words)
+            .into_iter()
+            .map(|m| (m.text, m.offset))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [
+            ("some text", 7),
+            ("Nested text", 0),
+            ("More text", 0),
+            ("Tree text", 0),
+            ("Inner text", 0),
+            ("Outer text", 0),
+        ]);
+
+        let t = soup.find_text(&'t');
+        assert_eq!(t.iter().filter(|m| m.node == t[0].node).count(), 2);
+        assert!(soup.find_text("").is_empty());
+    }
+
+    #[test]
+    fn test_lookup() {
+        use alloc::collections::BTreeMap;
+
+        let attrs = BTreeMap::from([(String::from("id"), 1), (String::from("class"), 2)]);
+        assert_eq!(
+            Pattern::<String>::lookup(&"id", &attrs),
+            Lookup::Key(Some(&1))
+        );
+        assert_eq!(
+            Pattern::<String>::lookup(&"href", &attrs),
+            Lookup::Key(None)
+        );
+        assert_eq!(Pattern::<String>::lookup(&"a", &attrs), Lookup::Key(None));
+        assert_eq!(
+            Pattern::<String>::lookup(&String::from("class"), &attrs),
+            Lookup::Key(Some(&2))
+        );
+        assert_eq!(Pattern::<String>::lookup(&true, &attrs), Lookup::AnyKey);
+
+        let many = (0..40)
+            .map(|i| (format!("k{i}"), i))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            Pattern::<String>::lookup(&"k17", &many),
+            Lookup::Key(Some(&17))
+        );
+        assert_eq!(Pattern::<String>::lookup(&"k", &many), Lookup::Key(None));
+
+        let soup = Soup::html_strict_owned(r#"<a id="x" href="/">Home</a>"#)
+            .expect("Failed to parse HTML");
+        assert!(soup.attr("href", "/").first().is_some());
+        assert!(soup.attr(String::from("id"), "y").first().is_none());
+    }
+
+    #[test]
+    fn test_lookup_without_conversion() {
+        use alloc::collections::BTreeMap;
+
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Key(String);
+
+        impl AsRef<str> for Key {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<&str> for Key {
+            fn from(_: &str) -> Self {
+                panic!("Converted the pattern");
+            }
+        }
+
+        // Keys are compared without converting the pattern, whatever the size of the map
+        let many = (0..40)
+            .map(|i| (Key(format!("k{i}")), i))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            Pattern::<Key>::lookup(&"k17", &many),
+            Lookup::Key(Some(&17))
+        );
+        assert_eq!(Pattern::<Key>::lookup(&"k", &many), Lookup::Key(None));
+    }
 }
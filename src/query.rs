@@ -1,39 +1,268 @@
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::String,
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt::{
+        self,
+        Write,
+    },
+    ops::Range,
+};
+
 use crate::{
+    cancel::CancelToken,
     filter::{
         And,
         Attr,
+        DynFilter,
         Filter,
         Tag,
     },
+    metrics::Metrics,
     node::NodeIter,
+    selector::{
+        Selector,
+        SelectorError,
+    },
+    soup::Sizes,
     Node,
     Pattern,
     Soup,
+    SoupRef,
 };
 
 /// A query for elements in [`Soup`](`crate::Soup`) matching the [`Filter`](`crate::filter::Filter`) `F`
 #[derive(Debug)]
 pub struct Query<'x, N, F> {
-    soup: &'x Soup<N>,
-    recursive: bool,
-    filter: F,
+    pub(crate) nodes: &'x [N],
+    /// The whole document, which ids index into
+    pub(crate) root: Root<'x, N>,
+    /// Document index of the first of `nodes`
+    pub(crate) offset: usize,
+    pub(crate) recursive: bool,
+    pub(crate) filter: F,
 }
 
-impl<'x, N, F> Copy for Query<'x, N, F> where F: Copy {}
+impl<N, F> Copy for Query<'_, N, F> where F: Copy {}
 
-impl<'x, N, F> Clone for Query<'x, N, F>
+impl<N, F> Clone for Query<'_, N, F>
 where
     F: Clone,
 {
     fn clone(&self) -> Self {
         Self {
-            soup: self.soup,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: self.recursive,
             filter: self.filter.clone(),
         }
     }
 }
 
+impl<'x, N, F> Query<'x, N, F>
+where
+    N: Node,
+    F: Filter<N>,
+{
+    /// Executes the query, and returns an iterator of the results along with their [`Position`]s
+    ///
+    /// Positions are absolute within the [`Soup`], so results from different queries can be
+    /// merged and sorted into document order.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<h1>One</h1><p>Text</p><h2>Two</h2>").unwrap();
+    /// let mut headings = soup.tag("h2").with_positions().chain(soup.tag("h1").with_positions()).collect::<Vec<_>>();
+    /// headings.sort_by(|a, b| a.0.cmp(&b.0));
+    /// assert_eq!(headings[0].1.name(), Some(&"h1"));
+    /// assert_eq!(headings[1].0.path, [2]);
+    /// ```
+    #[must_use]
+    pub fn with_positions(self) -> PositionIter<'x, N, F> {
+        PositionIter::new(
+            self.nodes,
+            self.root,
+            self.offset,
+            self.recursive,
+            self.filter,
+        )
+    }
+
+    /// Executes the query, and describes how it ran
+    ///
+    /// Queries scan the nodes in scope, testing each against the filter, so the work done is
+    /// shown by [`Stats::visited`]. Use an [`Index`](crate::index::Index) to avoid the scan.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<div><p>One</p><p>Two</p></div><p>Three</p>").unwrap();
+    ///
+    /// let explain = soup.strict().tag("p").explain();
+    /// assert_eq!((explain.stats.visited, explain.stats.matched), (2, 1));
+    /// assert_eq!(explain.to_string(), r#"scan: direct children of 2 nodes
+    /// filter: And((), Tag { tag: "p" })
+    /// visited: 2 nodes
+    /// matched: 1 node"#);
+    /// ```
+    #[must_use]
+    pub fn explain(self) -> Explain
+    where
+        F: fmt::Debug,
+    {
+        let filter = format!("{:?}", self.filter);
+        let (roots, recursive) = (self.nodes.len(), self.recursive);
+
+        let mut iter = self.into_iter();
+        iter.by_ref().for_each(drop);
+
+        Explain {
+            recursive,
+            roots,
+            filter,
+            stats: iter.stats(),
+        }
+    }
+
+    /// Executes the query, and collects the results into a [`Selection`]
+    #[must_use]
+    pub fn selection(self) -> Selection<'x, N> {
+        self.into_iter().collect()
+    }
+
+    /// Executes the query, and returns the only result
+    ///
+    /// Like [`Queryable::one`], but the error also describes the filter.
+    ///
+    /// # Errors
+    /// If there is not exactly one result.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<p class="price">12</p><p class="price">15</p>"#).unwrap();
+    ///
+    /// let error = soup.class("price").expect_one().unwrap_err();
+    /// assert_eq!(error.count, 2);
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     r#"expected exactly one match, found 2 for And((), Attr { name: "class", value: "price" })"#
+    /// );
+    /// ```
+    pub fn expect_one(self) -> Result<QueryItem<'x, N>, MatchCountError>
+    where
+        F: fmt::Debug,
+    {
+        let filter = format!("{:?}", self.filter);
+
+        self.one().map_err(|e| MatchCountError {
+            filter: Some(filter),
+            ..e
+        })
+    }
+
+    /// Executes the query, and groups the results by the key returned by `f`
+    ///
+    /// Groups are ordered by key, and items within a group are in document order.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<ul>
+    ///     <li data-section="b">Two</li>
+    ///     <li data-section="a">One</li>
+    ///     <li data-section="b">Three</li>
+    /// </ul>"#).unwrap();
+    ///
+    /// let groups = soup.tag("li").group_by(|li| li.get("data-section").copied());
+    /// assert_eq!(groups.keys().collect::<Vec<_>>(), [&Some("a"), &Some("b")]);
+    ///
+    /// let b = groups[&Some("b")].iter().map(|li| li.all_text()).collect::<Vec<_>>();
+    /// assert_eq!(b, ["Two", "Three"]);
+    /// ```
+    pub fn group_by<K, G>(self, mut f: G) -> BTreeMap<K, Vec<QueryItem<'x, N>>>
+    where
+        K: Ord,
+        G: FnMut(&QueryItem<'x, N>) -> K,
+    {
+        let mut groups = BTreeMap::<K, Vec<_>>::new();
+
+        for item in self {
+            groups.entry(f(&item)).or_default().push(item);
+        }
+
+        groups
+    }
+}
+
+impl<N> Query<'_, N, ()>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Parses a query string into a filter, for queries built at runtime from configuration
+    ///
+    /// Query strings are CSS selectors that match each element on its own, as accepted by
+    /// [`Selector::parse_isolated`]. The filter is boxed, so queries of any shape have the same
+    /// type.
+    ///
+    /// # Errors
+    /// If the query is not a valid selector, or uses combinators or structural pseudo-classes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{filter::DynFilter, parser::HTMLNode, prelude::*, query::Query};
+    /// let soup = Soup::html_strict(r#"<a href="https://a.example">A</a><a href="/b">B</a><img src="c.png">"#).unwrap();
+    ///
+    /// let config = ["a[href^=https]", "img[src$='.png']"];
+    /// let filters = config
+    ///     .iter()
+    ///     .map(|q| Query::parse(q))
+    ///     .collect::<Result<Vec<DynFilter<HTMLNode<&str>>>, _>>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(soup.filter(&filters[0]).all().count(), 1);
+    /// assert_eq!(soup.filter(&filters[1]).first().and_then(|i| i.get("src").copied()), Some("c.png"));
+    /// assert!(Query::<HTMLNode<&str>, ()>::parse("nav > a").is_err());
+    /// ```
+    pub fn parse(query: &str) -> Result<DynFilter<N>, SelectorError> {
+        Ok(Box::new(Selector::parse_isolated(query)?))
+    }
+}
+
+/// Error returned by [`Queryable::one`] and [`Query::expect_one`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchCountError {
+    /// Number of matching nodes
+    pub count: usize,
+
+    /// Description of the query's filter, if known
+    pub filter: Option<String>,
+}
+
+impl fmt::Display for MatchCountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected exactly one match, found {}", self.count)?;
+
+        if let Some(filter) = &self.filter {
+            write!(f, " for {filter}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MatchCountError {}
+
 /// Allows you to query for sub-elements matching the given [`Filter`](`crate::filter::Filter`)
 #[allow(clippy::type_complexity)]
 pub trait Queryable<'x>: Sized {
@@ -63,6 +292,68 @@ pub trait Queryable<'x>: Sized {
         T: Pattern<<Self::Node as Node>::Text>,
         Tag<T>: Filter<Self::Node>;
 
+    /// Adds an arbitrary [`Filter`](`crate::filter::Filter`) to the query
+    ///
+    /// By default the query searches recursively, as after [`Queryable::recursive`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{filter::Filter, parser::HTMLNode, prelude::*};
+    /// struct Empty;
+    ///
+    /// impl<S> Filter<HTMLNode<S>> for Empty {
+    ///     fn matches(&self, node: &HTMLNode<S>) -> bool {
+    ///         matches!(node, HTMLNode::Element { children, .. } if children.is_empty())
+    ///     }
+    /// }
+    ///
+    /// let soup = Soup::html_strict(r#"<p>Text</p><p id="empty"></p>"#).unwrap();
+    /// let result = soup.tag("p").filter(Empty).first().expect("Couldn't find empty p");
+    /// assert_eq!(result.get("id"), Some(&"empty"));
+    /// ```
+    fn filter<G>(self, filter: G) -> Query<'x, Self::Node, And<Self::Filter, G>>
+    where
+        G: Filter<Self::Node>,
+    {
+        let Query {
+            nodes,
+            root,
+            offset,
+            recursive,
+            filter: applied,
+        } = self.recursive();
+
+        Query {
+            nodes,
+            root,
+            offset,
+            recursive,
+            filter: And(applied, filter),
+        }
+    }
+
+    /// Searches with a CSS selector, like `div.content > a[href]`
+    ///
+    /// Results also match any filters already applied. See [`Selector`] for the supported
+    /// syntax, and to compile a selector once for use in many queries.
+    ///
+    /// # Errors
+    /// If the selector is invalid, or uses an unsupported pseudo-class.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<div class="content"><a href="/one">One</a><p><a href="/two">Two</a></p><a>Three</a></div>"#).unwrap();
+    /// let links = soup.select("div.content > a[href]").unwrap();
+    /// assert_eq!(links.iter().map(|a| a.all_text()).collect::<Vec<_>>(), ["One"]);
+    /// ```
+    fn select(self, selector: &str) -> Result<Selection<'x, Self::Node>, SelectorError>
+    where
+        <Self::Node as Node>::Text: AsRef<str>,
+    {
+        Ok(Selector::parse(selector)?.select(self.filter(())))
+    }
+
     /// Specifies an attribute name/value pair for which to search
     ///
     /// # Example
@@ -147,6 +438,37 @@ pub trait Queryable<'x>: Sized {
         self.into_iter().next()
     }
 
+    /// Executes the query, and returns the only result
+    ///
+    /// Unlike [`Queryable::first`], this guards against a page layout change silently matching
+    /// the wrong element.
+    ///
+    /// # Errors
+    /// If there is not exactly one result.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<h1>Title</h1><p>One</p><p>Two</p>"#).unwrap();
+    /// assert_eq!(soup.tag("h1").one().map(|h| h.all_text()), Ok("Title".into()));
+    /// assert_eq!(soup.tag("p").one().err().map(|e| e.count), Some(2));
+    /// assert_eq!(soup.tag("h2").one().err().map(|e| e.count), Some(0));
+    /// ```
+    fn one<T>(self) -> Result<T, MatchCountError>
+    where
+        Self: IntoIterator<Item = T>,
+    {
+        let mut iter = self.into_iter();
+
+        match (iter.next(), iter.next()) {
+            (Some(item), None) => Ok(item),
+            (first, second) => Err(MatchCountError {
+                count: usize::from(first.is_some()) + usize::from(second.is_some()) + iter.count(),
+                filter: None,
+            }),
+        }
+    }
+
     /// Executes the query, and returns an iterator of the results
     ///
     /// Equivalent to calling `self.into_iter()`
@@ -178,7 +500,9 @@ where
 
     fn recursive(self) -> Query<'x, N, F> {
         Query {
-            soup: self.soup,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: true,
             filter: self.filter,
         }
@@ -186,7 +510,9 @@ where
 
     fn strict(self) -> Query<'x, N, F> {
         Query {
-            soup: self.soup,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: false,
             filter: self.filter,
         }
@@ -198,12 +524,27 @@ where
         Tag<T>: Filter<N>,
     {
         Query {
-            soup: self.soup,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: self.recursive,
             filter: And(self.filter, Tag { tag }),
         }
     }
 
+    fn filter<G>(self, filter: G) -> Query<'x, N, And<F, G>>
+    where
+        G: Filter<N>,
+    {
+        Query {
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
+            recursive: self.recursive,
+            filter: And(self.filter, filter),
+        }
+    }
+
     fn attr<Q, V>(self, name: Q, value: V) -> Query<'x, N, And<F, Attr<Q, V>>>
     where
         Q: Pattern<N::Text>,
@@ -211,14 +552,16 @@ where
         Attr<Q, V>: Filter<N>,
     {
         Query {
-            soup: self.soup,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: self.recursive,
             filter: And(self.filter, Attr { name, value }),
         }
     }
 }
 
-impl<'x, N> Queryable<'x> for &'x Soup<N>
+impl<'x, N> Queryable<'x> for SoupRef<'x, N>
 where
     N: Node,
 {
@@ -227,7 +570,9 @@ where
 
     fn recursive(self) -> Query<'x, N, ()> {
         Query {
-            soup: self,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: true,
             filter: (),
         }
@@ -235,7 +580,9 @@ where
 
     fn strict(self) -> Query<'x, N, ()> {
         Query {
-            soup: self,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: false,
             filter: (),
         }
@@ -247,7 +594,9 @@ where
         Tag<T>: Filter<N>,
     {
         Query {
-            soup: self,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: true,
             filter: And((), Tag { tag }),
         }
@@ -260,117 +609,1063 @@ where
         Attr<Q, V>: Filter<N>,
     {
         Query {
-            soup: self,
+            nodes: self.nodes,
+            root: self.root,
+            offset: self.offset,
             recursive: true,
             filter: And((), Attr { name, value }),
         }
     }
 }
 
-/// Item returned by a [`Query`]
-#[derive(Debug, Copy, Clone)]
-pub struct QueryItem<'x, N> {
-    item: &'x N,
-}
-
-impl<'x, N> QueryItem<'x, N>
+impl<'x, N> Queryable<'x> for &'x Soup<N>
 where
-    N: Node + Clone,
+    N: Node,
 {
-    /// Convert the item into one that can be queried
-    #[must_use]
-    pub fn query(&self) -> Soup<N> {
-        Soup {
-            nodes: self.item.children().to_vec(),
-        }
-    }
-}
+    type Node = N;
+    type Filter = ();
 
-impl<'x, N> std::ops::Deref for QueryItem<'x, N> {
-    type Target = N;
+    fn recursive(self) -> Query<'x, N, ()> {
+        self.view().recursive()
+    }
 
-    fn deref(&self) -> &Self::Target {
-        self.item
+    fn strict(self) -> Query<'x, N, ()> {
+        self.view().strict()
     }
-}
 
-struct MapNodeIter<'x, N> {
-    iter: Option<std::slice::Iter<'x, N>>,
-    recursive: bool,
-}
+    fn tag<T>(self, tag: T) -> Query<'x, N, And<(), Tag<T>>>
+    where
+        T: Pattern<N::Text>,
+        Tag<T>: Filter<N>,
+    {
+        self.view().tag(tag)
+    }
 
-impl<'x, N> MapNodeIter<'x, N> {
-    fn new(nodes: &'x [N], recursive: bool) -> Self {
-        Self {
-            iter: Some(nodes.iter()),
-            recursive,
-        }
+    fn attr<Q, V>(self, name: Q, value: V) -> Query<'x, N, And<(), Attr<Q, V>>>
+    where
+        Q: Pattern<N::Text>,
+        V: Pattern<N::Text>,
+        Attr<Q, V>: Filter<N>,
+    {
+        self.view().attr(name, value)
     }
 }
 
-impl<'x, N> Iterator for MapNodeIter<'x, N>
+/// Queries a list of nodes, like the children of a node, without wrapping them in a [`Soup`]
+///
+/// # Example
+/// ```rust
+/// # use soupy::prelude::*;
+/// let soup = Soup::html_strict("<ul><li>One</li><li class=\"x\">Two</li></ul>").unwrap();
+/// let ul = soup.tag("ul").first().unwrap();
+///
+/// let children = ul.node().children();
+/// assert_eq!(children.class("x").first().map(|li| li.all_text()), Some("Two".into()));
+/// ```
+impl<'x, N> Queryable<'x> for &'x [N]
 where
     N: Node,
 {
-    type Item = NodeIter<'x, N>;
+    type Node = N;
+    type Filter = ();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.recursive {
-            self.iter
-                .as_mut()
-                .and_then(|i| Some(NodeIter::tree(i.next()?)))
-        } else {
-            self.iter.take().map(|i| NodeIter::direct(i))
-        }
+    fn recursive(self) -> Query<'x, N, ()> {
+        SoupRef::from(self).recursive()
     }
-}
 
-/// An [`Iterator`] over matching elements
-pub struct QueryIter<'x, N: Node + 'x, F> {
-    iter: std::iter::Flatten<MapNodeIter<'x, N>>,
-    filter: F,
-}
+    fn strict(self) -> Query<'x, N, ()> {
+        SoupRef::from(self).strict()
+    }
 
-impl<'x, N, F> QueryIter<'x, N, F>
-where
-    N: Node,
-{
-    pub(crate) fn new(nodes: &'x [N], recursive: bool, filter: F) -> Self {
-        Self {
-            iter: MapNodeIter::new(nodes, recursive).flatten(),
-            filter,
-        }
+    fn tag<T>(self, tag: T) -> Query<'x, N, And<(), Tag<T>>>
+    where
+        T: Pattern<N::Text>,
+        Tag<T>: Filter<N>,
+    {
+        SoupRef::from(self).tag(tag)
+    }
+
+    fn attr<Q, V>(self, name: Q, value: V) -> Query<'x, N, And<(), Attr<Q, V>>>
+    where
+        Q: Pattern<N::Text>,
+        V: Pattern<N::Text>,
+        Attr<Q, V>: Filter<N>,
+    {
+        SoupRef::from(self).attr(name, value)
     }
 }
 
-impl<'x, N, F> Iterator for QueryIter<'x, N, F>
+impl<'x, N> Queryable<'x> for &'x Vec<N>
 where
     N: Node,
-    F: Filter<N>,
 {
-    type Item = QueryItem<'x, N>;
+    type Node = N;
+    type Filter = ();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            let next = self.iter.next()?;
+    fn recursive(self) -> Query<'x, N, ()> {
+        self.as_slice().recursive()
+    }
 
-            if self.filter.matches(next) {
-                return Some(QueryItem { item: next });
-            }
-        }
+    fn strict(self) -> Query<'x, N, ()> {
+        self.as_slice().strict()
+    }
+
+    fn tag<T>(self, tag: T) -> Query<'x, N, And<(), Tag<T>>>
+    where
+        T: Pattern<N::Text>,
+        Tag<T>: Filter<N>,
+    {
+        self.as_slice().tag(tag)
+    }
+
+    fn attr<Q, V>(self, name: Q, value: V) -> Query<'x, N, And<(), Attr<Q, V>>>
+    where
+        Q: Pattern<N::Text>,
+        V: Pattern<N::Text>,
+        Attr<Q, V>: Filter<N>,
+    {
+        self.as_slice().attr(name, value)
     }
 }
 
-impl<'x, N, F> IntoIterator for Query<'x, N, F>
+/// Queries within the item's tree, borrowing rather than cloning like [`QueryItem::query`]
+///
+/// The item itself is never matched. [`NodeId`]s of the results are those of the whole document,
+/// so they can be resolved with [`Soup::get`].
+///
+/// # Example
+/// ```rust
+/// # use soupy::prelude::*;
+/// let soup = Soup::html_strict(r#"<tr><td class="name">Widget</td><td class="price">12</td></tr>"#).unwrap();
+///
+/// for row in soup.tag("tr").all() {
+///     let name = row.class("name").first().map(|td| td.all_text());
+///     let price = row.class("price").first().map(|td| td.all_text());
+///     assert_eq!((name, price), (Some("Widget".into()), Some("12".into())));
+/// }
+/// ```
+impl<'x, N> Queryable<'x> for &QueryItem<'x, N>
 where
     N: Node,
-    F: Filter<N>,
 {
-    type Item = QueryItem<'x, N>;
-    type IntoIter = QueryIter<'x, N, F>;
+    type Node = N;
+    type Filter = ();
+
+    fn recursive(self) -> Query<'x, N, ()> {
+        self.query_ref().recursive()
+    }
+
+    fn strict(self) -> Query<'x, N, ()> {
+        self.query_ref().strict()
+    }
+
+    fn tag<T>(self, tag: T) -> Query<'x, N, And<(), Tag<T>>>
+    where
+        T: Pattern<N::Text>,
+        Tag<T>: Filter<N>,
+    {
+        self.query_ref().tag(tag)
+    }
+
+    fn attr<Q, V>(self, name: Q, value: V) -> Query<'x, N, And<(), Attr<Q, V>>>
+    where
+        Q: Pattern<N::Text>,
+        V: Pattern<N::Text>,
+        Attr<Q, V>: Filter<N>,
+    {
+        self.query_ref().attr(name, value)
+    }
+}
+
+/// Lightweight handle to a node within a [`Soup`]
+///
+/// Handles are the node's index in document order, so they remain valid for as long as the
+/// `Soup` they were obtained from, and can be resolved with [`Soup::get`].
+/// Comparing handles compares document order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub(crate) usize);
+
+impl NodeId {
+    /// Index of the node in depth-first, pre-order traversal of the whole document
+    #[must_use]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Item returned by a [`Query`]
+#[derive(Debug, Copy, Clone)]
+pub struct QueryItem<'x, N> {
+    pub(crate) item: &'x N,
+    pub(crate) id: NodeId,
+    /// The queried nodes, which `id` indexes into
+    pub(crate) root: Root<'x, N>,
+}
+
+impl<N> QueryItem<'_, N> {
+    /// Returns a handle to the item that can be resolved with [`Soup::get`]
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict(r#"<a id="one">One</a><a id="two">Two</a>"#).unwrap();
+    /// let id = soup.attr("id", "two").first().unwrap().id();
+    /// assert_eq!(soup.get(id).and_then(|a| a.get("id").copied()), Some("two"));
+    /// ```
+    #[must_use]
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+}
+
+impl<'x, N> QueryItem<'x, N> {
+    /// Returns the node, borrowed for as long as the queried data rather than the item
+    #[must_use]
+    pub fn node(&self) -> &'x N {
+        self.item
+    }
+
+    /// Borrow the item's children as a view that can be queried
+    ///
+    /// Unlike [`QueryItem::query`], this does not clone the children, and the [`NodeId`]s of
+    /// results are those of the whole document, so they can be resolved with [`Soup::get`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<ul><li>One</li></ul><li>Two</li>").unwrap();
+    /// let ul = soup.tag("ul").first().unwrap();
+    /// let items = ul.query_ref().tag("li").all().map(|li| li.all_text()).collect::<Vec<_>>();
+    /// assert_eq!(items, ["One"]);
+    /// ```
+    #[must_use]
+    pub fn query_ref(&self) -> SoupRef<'x, N>
+    where
+        N: Node,
+    {
+        SoupRef {
+            nodes: self.item.children(),
+            root: self.root,
+            offset: self.id.0 + 1,
+        }
+    }
+
+    /// Elements containing the item within the queried data, outermost first
+    pub(crate) fn ancestors(&self) -> Vec<&'x N>
+    where
+        N: Node,
+    {
+        let mut steps = self.root.steps(self.id.0).unwrap_or_default();
+        steps.pop();
+
+        steps
+            .into_iter()
+            .map(|(siblings, pos)| &siblings[pos])
+            .collect()
+    }
+}
+
+/// The whole document that [`NodeId`]s index into
+pub(crate) struct Root<'x, N> {
+    pub(crate) nodes: &'x [N],
+    /// Subtree sizes, when the document is a [`Soup`] that caches them
+    sizes: Option<&'x Sizes>,
+}
+
+impl<N> Copy for Root<'_, N> {}
+
+impl<N> Clone for Root<'_, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N> fmt::Debug for Root<'_, N>
+where
+    N: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Root")
+            .field("nodes", &self.nodes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'x, N> Root<'x, N> {
+    pub(crate) fn new(nodes: &'x [N], sizes: Option<&'x Sizes>) -> Self {
+        Self { nodes, sizes }
+    }
+}
+
+impl<'x, N> Root<'x, N>
+where
+    N: Node,
+{
+    /// Number of nodes in the subtree of `node`, which is at `index` in document order
+    pub(crate) fn size(&self, index: usize, node: &N) -> usize {
+        match self.sizes.and_then(|s| s.get(self.nodes)) {
+            Some(sizes) => sizes[index],
+            None => node.descendants().count(),
+        }
+    }
+
+    /// Resolves the node at `index` in document order
+    pub(crate) fn get(self, id: NodeId) -> Option<QueryItem<'x, N>> {
+        let (siblings, pos) = self.steps(id.0)?.pop()?;
+
+        Some(QueryItem {
+            item: &siblings[pos],
+            id,
+            root: self,
+        })
+    }
+
+    /// Sibling lists leading from the top level to the node at `index` in document order, each
+    /// with the position of the next step in the list
+    ///
+    /// Whole subtrees before the node are skipped by their size, so with cached sizes this
+    /// only looks at the siblings of the node and its ancestors.
+    pub(crate) fn steps(&self, mut index: usize) -> Option<Vec<(&'x [N], usize)>> {
+        let mut steps = Vec::new();
+        let mut siblings = self.nodes;
+        // Document index of the first of `siblings`
+        let mut start = 0;
+
+        'outer: loop {
+            for (pos, node) in siblings.iter().enumerate() {
+                let size = self.size(start, node);
+
+                if index >= size {
+                    index -= size;
+                    start += size;
+                    continue;
+                }
+
+                steps.push((siblings, pos));
+
+                if index == 0 {
+                    return Some(steps);
+                }
+
+                index -= 1;
+                start += 1;
+                siblings = node.children();
+                continue 'outer;
+            }
+
+            return None;
+        }
+    }
+}
+
+/// Child indices leading from the top level of `root` to the node at `index` in document order
+fn path_to<N>(root: Root<'_, N>, index: usize) -> Vec<usize>
+where
+    N: Node,
+{
+    root.steps(index)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(_, pos)| pos)
+        .collect()
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node,
+{
+    /// Returns `true` if this item is inside `other`'s tree, not counting `other` itself
+    ///
+    /// Works for items from any queries over the same data.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<nav><a>Home</a></nav><a>Other</a>").unwrap();
+    /// let nav = soup.tag("nav").first().unwrap();
+    ///
+    /// let links = soup.tag("a").all().filter(|a| a.descendant_of(&nav)).collect::<Vec<_>>();
+    /// assert_eq!(links.len(), 1);
+    /// assert!(!nav.descendant_of(&nav));
+    /// ```
+    #[must_use]
+    pub fn descendant_of(&self, other: &QueryItem<'_, N>) -> bool {
+        other
+            .item
+            .descendants()
+            .skip(1)
+            .any(|n| core::ptr::eq(n, self.item))
+    }
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Returns an absolute `XPath` to the item, like `/html/body/div[2]/a[5]`
+    ///
+    /// Positions are only given for steps with siblings of the same name, matching the paths
+    /// copied from browser devtools. Text nodes are written as `text()`, and other nodes that
+    /// are not elements, like comments, as `node()`.
+    ///
+    /// Paths start at the top of the document, including for items found within another item.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<html><body><div></div><div><a>1</a>text<a>2</a></div></body></html>").unwrap();
+    /// let paths = soup.tag("a").all().map(|a| a.xpath()).collect::<Vec<_>>();
+    /// assert_eq!(paths, ["/html/body/div[2]/a[1]", "/html/body/div[2]/a[2]"]);
+    ///
+    /// let text = soup.all().find(|n| n.text() == Some(&"text")).unwrap();
+    /// assert_eq!(text.xpath(), "/html/body/div[2]/text()");
+    /// ```
+    #[must_use]
+    pub fn xpath(&self) -> String {
+        let mut path = String::new();
+
+        for (siblings, pos) in self.root.steps(self.id.0).unwrap_or_default() {
+            let node = &siblings[pos];
+            let name = node.name().map(AsRef::as_ref);
+            let same = |n: &N| match name {
+                Some(name) => n.name().is_some_and(|m| m.as_ref() == name),
+                None => n.name().is_none() && n.text().is_some() == node.text().is_some(),
+            };
+
+            let position = siblings[..pos].iter().filter(|n| same(n)).count() + 1;
+            let count = position + siblings[pos + 1..].iter().filter(|n| same(n)).count();
+
+            path.push('/');
+            match name {
+                Some(name) => path.push_str(name),
+                None if node.text().is_some() => path.push_str("text()"),
+                None => path.push_str("node()"),
+            }
+
+            if count > 1 {
+                let _ = write!(path, "[{position}]");
+            }
+        }
+
+        path
+    }
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node + Clone,
+{
+    /// Convert the item into one that can be queried
+    #[must_use]
+    pub fn query(&self) -> Soup<N> {
+        Soup::from_nodes(self.item.children().to_vec())
+    }
+}
+
+impl<N> core::ops::Deref for QueryItem<'_, N> {
+    type Target = N;
+
+    fn deref(&self) -> &Self::Target {
+        self.item
+    }
+}
+
+/// Counters for the work done by a [`QueryIter`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Nodes tested against the filter
+    pub visited: usize,
+
+    /// Nodes that matched the filter
+    pub matched: usize,
+}
+
+/// Description of an executed [`Query`], returned by [`Query::explain`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explain {
+    /// `true` if the query searched every descendant, `false` if only the top-level nodes
+    pub recursive: bool,
+
+    /// Number of top-level nodes the query started from
+    pub roots: usize,
+
+    /// The filter tree, in its [`Debug`](fmt::Debug) form
+    pub filter: String,
+
+    /// Work done while executing the query
+    pub stats: Stats,
+}
+
+impl fmt::Display for Explain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "node" } else { "nodes" };
+        let scan = if self.recursive {
+            "every descendant"
+        } else {
+            "direct children"
+        };
+
+        writeln!(f, "scan: {scan} of {} {}", self.roots, plural(self.roots))?;
+        writeln!(f, "filter: {}", self.filter)?;
+        writeln!(
+            f,
+            "visited: {} {}",
+            self.stats.visited,
+            plural(self.stats.visited)
+        )?;
+        write!(
+            f,
+            "matched: {} {}",
+            self.stats.matched,
+            plural(self.stats.matched)
+        )
+    }
+}
+
+/// A set of [`QueryItem`]s, kept in document order without duplicates
+///
+/// Items are identified by their [`NodeId`], so only combine selections from queries over the
+/// same [`Soup`].
+///
+/// # Example
+/// ```rust
+/// # use soupy::{parser::HTMLNode, prelude::*, query::Selection};
+/// let soup = Soup::html_strict(r#"<a class="nav" href="/">Home</a><a href="/about">About</a><b class="nav">Menu</b>"#).unwrap();
+///
+/// let links = soup.tag("a").selection();
+/// let nav = soup.attr("class", "nav").selection();
+///
+/// fn text(s: Selection<HTMLNode<&str>>) -> Vec<String> {
+///     s.into_iter().map(|n| n.all_text()).collect()
+/// }
+///
+/// assert_eq!(text(links.clone().intersection(nav.clone())), ["Home"]);
+/// assert_eq!(text(links.clone().difference(nav.clone())), ["About"]);
+/// assert_eq!(text(links.union(nav)), ["Home", "About", "Menu"]);
+/// ```
+#[derive(Debug)]
+pub struct Selection<'x, N> {
+    items: Vec<QueryItem<'x, N>>,
+}
+
+impl<N> Clone for Selection<'_, N> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.iter().map(|i| QueryItem { ..*i }).collect(),
+        }
+    }
+}
+
+impl<N> Default for Selection<'_, N> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<'x, N> Selection<'x, N> {
+    /// Creates an empty selection
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of items in the selection
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the selection has no items
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns `true` if the node with handle `id` is in the selection
+    #[must_use]
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.position(id).is_ok()
+    }
+
+    /// Adds an item, returning `false` if it was already in the selection
+    pub fn insert(&mut self, item: QueryItem<'x, N>) -> bool {
+        match self.position(item.id) {
+            Ok(_) => false,
+            Err(index) => {
+                self.items.insert(index, item);
+                true
+            }
+        }
+    }
+
+    /// Iterate over the items in document order
+    pub fn iter(&self) -> core::slice::Iter<'_, QueryItem<'x, N>> {
+        self.items.iter()
+    }
+
+    /// Items in either selection
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        self.merge(other, true, true, true)
+    }
+
+    /// Items in both selections
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        self.merge(other, false, true, false)
+    }
+
+    /// Items in this selection but not in `other`
+    #[must_use]
+    pub fn difference(self, other: Self) -> Self {
+        self.merge(other, true, false, false)
+    }
+
+    /// Items in exactly one of the selections
+    #[must_use]
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        self.merge(other, true, false, true)
+    }
+
+    fn position(&self, id: NodeId) -> Result<usize, usize> {
+        self.items.binary_search_by_key(&id, |i| i.id)
+    }
+
+    /// Merges two sorted selections, keeping items found only on the left, in both, or only on the right
+    fn merge(self, other: Self, left: bool, both: bool, right: bool) -> Self {
+        let mut items = Vec::with_capacity(self.items.len().max(other.items.len()));
+        let mut a = self.items.into_iter().peekable();
+        let mut b = other.items.into_iter().peekable();
+
+        loop {
+            let (item, keep) = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) if x.id < y.id => (a.next(), left),
+                (Some(x), Some(y)) if x.id > y.id => (b.next(), right),
+                (Some(_), Some(_)) => (a.next().and(b.next()), both),
+                (Some(_), None) => (a.next(), left),
+                (None, Some(_)) => (b.next(), right),
+                (None, None) => break,
+            };
+
+            if keep {
+                items.extend(item);
+            }
+        }
+
+        Self { items }
+    }
+}
+
+impl<'x, N> FromIterator<QueryItem<'x, N>> for Selection<'x, N> {
+    fn from_iter<I: IntoIterator<Item = QueryItem<'x, N>>>(iter: I) -> Self {
+        let mut items = iter.into_iter().collect::<Vec<_>>();
+        items.sort_by_key(|i| i.id);
+        items.dedup_by_key(|i| i.id);
+
+        Self { items }
+    }
+}
+
+impl<'x, N> Extend<QueryItem<'x, N>> for Selection<'x, N> {
+    fn extend<I: IntoIterator<Item = QueryItem<'x, N>>>(&mut self, iter: I) {
+        let other = iter.into_iter().collect();
+        *self = core::mem::take(self).union(other);
+    }
+}
+
+impl<'x, N> IntoIterator for Selection<'x, N> {
+    type Item = QueryItem<'x, N>;
+    type IntoIter = vec::IntoIter<QueryItem<'x, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, 'x, N> IntoIterator for &'a Selection<'x, N> {
+    type Item = &'a QueryItem<'x, N>;
+    type IntoIter = core::slice::Iter<'a, QueryItem<'x, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+struct MapNodeIter<'x, N> {
+    iter: Option<core::slice::Iter<'x, N>>,
+    recursive: bool,
+}
+
+impl<'x, N> MapNodeIter<'x, N> {
+    fn new(nodes: &'x [N], recursive: bool) -> Self {
+        Self {
+            iter: Some(nodes.iter()),
+            recursive,
+        }
+    }
+}
+
+impl<'x, N> Iterator for MapNodeIter<'x, N>
+where
+    N: Node,
+{
+    type Item = NodeIter<'x, N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.recursive {
+            self.iter
+                .as_mut()
+                .and_then(|i| Some(NodeIter::tree(i.next()?)))
+        } else {
+            self.iter.take().map(|i| NodeIter::direct(i))
+        }
+    }
+}
+
+/// An [`Iterator`] over matching elements
+pub struct QueryIter<'x, N: Node + 'x, F> {
+    nodes: MapNodeIter<'x, N>,
+    iter: Option<NodeIter<'x, N>>,
+    root: Root<'x, N>,
+    recursive: bool,
+    index: usize,
+    filter: F,
+    stats: Stats,
+    cancel: Option<CancelToken>,
+    metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl<'x, N, F> QueryIter<'x, N, F>
+where
+    N: Node,
+{
+    pub(crate) fn new(
+        nodes: &'x [N],
+        root: Root<'x, N>,
+        offset: usize,
+        recursive: bool,
+        filter: F,
+    ) -> Self {
+        Self {
+            nodes: MapNodeIter::new(nodes, recursive),
+            iter: None,
+            root,
+            recursive,
+            index: offset,
+            filter,
+            stats: Stats::default(),
+            cancel: None,
+            metrics: None,
+        }
+    }
+
+    /// Stops the iteration once `token` is cancelled
+    ///
+    /// The token is checked before each node is visited. See [`CancelToken`] for an example.
+    #[must_use]
+    pub fn cancel_on(mut self, token: &CancelToken) -> Self {
+        self.cancel = Some(token.clone());
+        self
+    }
+
+    /// Reports this query's [`Stats`] to `metrics` when the iterator is dropped
+    ///
+    /// The query is reported once, whether it ran to the end or was stopped early. See
+    /// [`Metrics`] for an example.
+    #[must_use]
+    pub fn report_to(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Returns counters for the work done so far
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<ul><li>One</li><li>Two</li></ul>").unwrap();
+    /// let mut items = soup.tag("li").all();
+    /// items.next();
+    ///
+    /// // Visited the `ul`, the first `li`, and nothing further
+    /// assert_eq!((items.stats().visited, items.stats().matched), (2, 1));
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+impl<'x, N, F> QueryIter<'x, N, F>
+where
+    N: Node,
+    F: Filter<N>,
+{
+    /// Visits the next node of the tree being traversed
+    ///
+    /// Returns `None` when the tree is finished, or `Some` with the node if it matched.
+    #[allow(clippy::option_option)]
+    fn step(&mut self) -> Option<Option<QueryItem<'x, N>>> {
+        if self.cancelled() {
+            self.nodes.iter = None;
+            return None;
+        }
+
+        let next = self.iter.as_mut()?.next()?;
+        let id = NodeId(self.index);
+        self.stats.visited += 1;
+
+        self.index += if self.recursive {
+            1
+        } else {
+            self.root.size(id.0, next)
+        };
+
+        let matches = self.filter.matches(next);
+
+        if self.recursive && !self.filter.can_match_descendants(next) {
+            if let Some(iter) = self.iter.as_mut() {
+                iter.skip_children();
+            }
+
+            self.index += self.root.size(id.0, next) - 1;
+        }
+
+        Some(matches.then(|| self.item(next, id)))
+    }
+
+    fn item(&mut self, item: &'x N, id: NodeId) -> QueryItem<'x, N> {
+        self.stats.matched += 1;
+
+        QueryItem {
+            item,
+            id,
+            root: self.root,
+        }
+    }
+
+    /// Visits `node` and its descendants by recursion, which needs no allocation
+    fn walk<B, G>(&mut self, node: &'x N, mut acc: B, f: &mut G) -> B
+    where
+        G: FnMut(B, QueryItem<'x, N>) -> B,
+    {
+        if self.cancelled() {
+            return acc;
+        }
+
+        let id = NodeId(self.index);
+        self.index += 1;
+        self.stats.visited += 1;
+
+        if self.filter.matches(node) {
+            let item = self.item(node, id);
+            acc = f(acc, item);
+        }
+
+        if self.filter.can_match_descendants(node) {
+            for child in node.children() {
+                acc = self.walk(child, acc, f);
+            }
+        } else {
+            self.index += self.root.size(id.0, node) - 1;
+        }
+
+        acc
+    }
+}
+
+impl<'x, N, F> Drop for QueryIter<'x, N, F>
+where
+    N: Node + 'x,
+{
+    fn drop(&mut self) {
+        if let Some(metrics) = self.metrics.take() {
+            metrics.query(self.stats);
+        }
+    }
+}
+
+impl<'x, N, F> Iterator for QueryIter<'x, N, F>
+where
+    N: Node,
+    F: Filter<N>,
+{
+    type Item = QueryItem<'x, N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.step() {
+                Some(Some(item)) => return Some(item),
+                Some(None) => {}
+                None => self.iter = Some(self.nodes.next()?),
+            }
+        }
+    }
+
+    fn fold<B, G>(mut self, init: B, mut f: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+
+        while let Some(item) = self.step() {
+            if let Some(item) = item {
+                acc = f(acc, item);
+            }
+        }
+
+        if !self.recursive {
+            for item in self.by_ref() {
+                acc = f(acc, item);
+            }
+
+            return acc;
+        }
+
+        for node in self.nodes.iter.take().into_iter().flatten() {
+            acc = self.walk(node, acc, &mut f);
+        }
+
+        acc
+    }
+}
+
+/// Position of a node within a [`Soup`]
+///
+/// Positions are ordered by their index, which is document order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// Index of the node in depth-first, pre-order traversal of the whole document
+    pub index: usize,
+
+    /// Child indices leading from the top level of the document to the node
+    pub path: Vec<usize>,
+}
+
+/// A match found by [`Soup::find_text`](crate::Soup::find_text)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMatch<'x> {
+    /// The text node containing the match, which can be resolved with [`Soup::get`]
+    pub node: NodeId,
+
+    /// Offset of the match within the node's text, in characters
+    pub offset: usize,
+
+    /// Byte range of the match within the node's text
+    pub range: Range<usize>,
+
+    /// The matched text
+    pub text: &'x str,
+}
+
+/// An [`Iterator`] over matching elements and their [`Position`]s
+pub struct PositionIter<'x, N, F> {
+    stack: Vec<core::slice::Iter<'x, N>>,
+    root: Root<'x, N>,
+    path: Vec<usize>,
+    next: usize,
+    index: usize,
+    recursive: bool,
+    filter: F,
+}
+
+impl<'x, N, F> PositionIter<'x, N, F>
+where
+    N: Node,
+{
+    pub(crate) fn new(
+        nodes: &'x [N],
+        root: Root<'x, N>,
+        offset: usize,
+        recursive: bool,
+        filter: F,
+    ) -> Self {
+        // Path of the node whose children are queried, if they are not the top level
+        let path = offset
+            .checked_sub(1)
+            .map(|parent| path_to(root, parent))
+            .unwrap_or_default();
+
+        Self {
+            stack: vec![nodes.iter()],
+            root,
+            path,
+            next: 0,
+            index: offset,
+            recursive,
+            filter,
+        }
+    }
+}
+
+impl<'x, N, F> Iterator for PositionIter<'x, N, F>
+where
+    N: Node,
+    F: Filter<N>,
+{
+    type Item = (Position, QueryItem<'x, N>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(node) = self.stack.last_mut()?.next() else {
+                self.stack.pop();
+                self.next = self.path.pop().map_or(0, |i| i + 1);
+                continue;
+            };
+
+            let mut path = self.path.clone();
+            path.push(self.next);
+
+            let position = Position {
+                index: self.index,
+                path,
+            };
+
+            self.index += 1;
+
+            if self.recursive {
+                self.stack.push(node.children().iter());
+                self.path.push(self.next);
+                self.next = 0;
+            } else {
+                self.index += self.root.size(position.index, node) - 1;
+                self.next += 1;
+            }
+
+            if self.filter.matches(node) {
+                let id = NodeId(position.index);
+
+                return Some((position, QueryItem {
+                    item: node,
+                    id,
+                    root: self.root,
+                }));
+            }
+        }
+    }
+}
+
+impl<'x, N, F> IntoIterator for Query<'x, N, F>
+where
+    N: Node,
+    F: Filter<N>,
+{
+    type Item = QueryItem<'x, N>;
+    type IntoIter = QueryIter<'x, N, F>;
 
     fn into_iter(self) -> Self::IntoIter {
-        QueryIter::new(&self.soup.nodes, self.recursive, self.filter)
+        QueryIter::new(
+            self.nodes,
+            self.root,
+            self.offset,
+            self.recursive,
+            self.filter,
+        )
     }
 }
 
@@ -393,4 +1688,409 @@ mod tests {
             q2.tag("a").first().map(|t| (*t).clone())
         );
     }
+
+    #[test]
+    fn test_positions() {
+        let soup = Soup::html_strict("<a>0</a><b><a>1</a><c><a>2</a></c></b><a>3</a>")
+            .expect("Failed to parse HTML");
+
+        let positions = soup
+            .tag("a")
+            .with_positions()
+            .map(|(p, a)| (p.index, p.path, a.all_text()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(positions, vec![
+            (0, vec![0], "0".into()),
+            (3, vec![1, 0], "1".into()),
+            (6, vec![1, 1, 0], "2".into()),
+            (8, vec![2], "3".into()),
+        ]);
+
+        let strict = soup
+            .strict()
+            .tag("a")
+            .with_positions()
+            .map(|(p, _)| (p.index, p.path))
+            .collect::<Vec<_>>();
+
+        assert_eq!(strict, vec![(0, vec![0]), (8, vec![2])]);
+
+        assert_eq!(soup.with_positions().count(), 10);
+    }
+
+    #[test]
+    fn test_node_id() {
+        let soup = Soup::html_strict("<a>0</a><b><a>1</a><c><a>2</a></c></b><a>3</a>")
+            .expect("Failed to parse HTML");
+
+        let ids = soup
+            .tag("a")
+            .all()
+            .map(|a| a.id().index())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 3, 6, 8]);
+
+        let strict = soup
+            .strict()
+            .tag("a")
+            .all()
+            .map(|a| a.id())
+            .collect::<Vec<_>>();
+        assert_eq!(strict, vec![NodeId(0), NodeId(8)]);
+
+        for (position, item) in soup.with_positions() {
+            assert_eq!(item.id().index(), position.index);
+
+            let resolved = soup.get(item.id()).expect("Failed to resolve id");
+            assert_eq!(*resolved, *item);
+        }
+
+        assert!(soup.get(NodeId(10)).is_none());
+    }
+
+    #[test]
+    fn test_query_ref() {
+        let soup = Soup::html_strict("<b><a>one</a><i><a>two</a></i></b><a>three</a>")
+            .expect("Failed to parse HTML");
+
+        let b = soup.tag("b").first().expect("Couldn't find b");
+
+        let owned = b.query();
+        let borrowed = b.query_ref();
+
+        assert_eq!(
+            owned
+                .tag("a")
+                .all()
+                .map(|a| a.all_text())
+                .collect::<Vec<_>>(),
+            borrowed
+                .tag("a")
+                .all()
+                .map(|a| a.all_text())
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(
+            borrowed
+                .strict()
+                .tag("a")
+                .all()
+                .map(|a| a.all_text())
+                .collect::<Vec<_>>(),
+            ["one"]
+        );
+
+        assert_eq!(borrowed.into_iter().count(), 5);
+    }
+
+    #[test]
+    fn test_query_ref_ids() {
+        let soup = Soup::html_strict(
+            r#"<html><body><div style="display:none"><p><b>x</b></p></div></body></html>"#,
+        )
+        .expect("Failed to parse HTML");
+
+        let div = soup.tag("div").first().expect("Couldn't find div");
+        let nested = div.query_ref().tag("b").first().expect("Couldn't find b");
+        let top = soup.tag("b").first().expect("Couldn't find b");
+
+        assert_eq!(nested.id(), top.id());
+        assert!(soup
+            .get(nested.id())
+            .is_some_and(|b| core::ptr::eq(b.node(), nested.node())));
+        assert_eq!(nested.xpath(), "/html/body/div/p/b");
+        assert!(nested.is_probably_hidden());
+
+        let positions = div
+            .query_ref()
+            .tag("b")
+            .with_positions()
+            .map(|(p, _)| p)
+            .chain(soup.tag("b").with_positions().map(|(p, _)| p))
+            .collect::<Vec<_>>();
+        assert_eq!(positions[0], positions[1]);
+        assert_eq!(
+            div.query_ref()
+                .select("p > b")
+                .map(|s| s.iter().map(QueryItem::id).collect::<Vec<_>>()),
+            Ok(vec![top.id()])
+        );
+    }
+
+    #[test]
+    fn test_resolve_ids() {
+        let soup =
+            Soup::html_strict("<ul><li>1</li><li><b>2</b><i>3</i></li></ul><p>4<br></p><!-- 5 -->")
+                .expect("Failed to parse HTML");
+        let slice = soup.nodes.as_slice();
+
+        for (item, uncached) in soup.iter().zip(SoupRef::from(slice)) {
+            let found = soup.get(item.id()).expect("Couldn't resolve id");
+
+            assert!(core::ptr::eq(found.node(), item.node()));
+            assert_eq!(found.xpath(), uncached.xpath());
+            assert_eq!(found.ancestors(), uncached.ancestors());
+        }
+
+        let count = soup.iter().count();
+        assert!(soup.get(NodeId(count - 1)).is_some());
+        assert!(soup.get(NodeId(count)).is_none());
+        #[cfg(feature = "std")]
+        assert_eq!(soup.sizes.get(slice).map(<[usize]>::len), Some(count));
+    }
+
+    #[test]
+    fn test_skip_ids() {
+        let soup = Soup::html_strict("<ul><li>1</li><li><b>2</b></li></ul><p>3<br></p><i>4</i>")
+            .expect("Failed to parse HTML");
+        let uncached = SoupRef::from(soup.nodes.as_slice());
+
+        let top = [0, 6, 9];
+        let ids = |item: QueryItem<'_, parser::HTMLNode<&str>>| item.id().index();
+
+        assert_eq!(soup.strict().all().map(ids).collect::<Vec<_>>(), top);
+        assert_eq!(uncached.strict().all().map(ids).collect::<Vec<_>>(), top);
+        assert_eq!(
+            soup.strict()
+                .with_positions()
+                .map(|(_, i)| ids(i))
+                .collect::<Vec<_>>(),
+            top
+        );
+
+        let select = |s: &str| {
+            soup.strict()
+                .select(s)
+                .expect("Failed to parse selector")
+                .iter()
+                .map(|i| i.id().index())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(select("i"), [9]);
+    }
+
+    #[test]
+    fn test_selection() {
+        let soup = Soup::html_strict("<a>0</a><b><a>1</a><c><a>2</a></c></b><a>3</a>")
+            .expect("Failed to parse HTML");
+
+        let ids = |s: &Selection<parser::HTMLNode<&str>>| {
+            s.iter().map(|i| i.id().index()).collect::<Vec<_>>()
+        };
+
+        let nested = soup
+            .tag("a")
+            .with_positions()
+            .filter(|(p, _)| p.path.len() > 1)
+            .map(|(_, a)| a)
+            .collect::<Selection<_>>();
+        assert_eq!(ids(&nested), [3, 6]);
+
+        let all = soup.tag("a").selection();
+        let top = soup.strict().tag("a").selection();
+
+        assert_eq!(ids(&all.clone().difference(nested.clone())), [0, 8]);
+        assert_eq!(ids(&top.clone().intersection(nested.clone())), []
+            as [usize; 0]);
+        assert_eq!(ids(&top.clone().union(nested.clone())), [0, 3, 6, 8]);
+        assert_eq!(
+            ids(&all.clone().symmetric_difference(top.clone())),
+            ids(&nested)
+        );
+
+        let mut twice = top.clone();
+        twice.extend(soup.tag("a").all().chain(soup.tag("a").all()));
+        assert_eq!(ids(&twice), ids(&all));
+        assert!(twice.contains(NodeId(6)));
+        assert!(!twice.insert(soup.tag("a").first().expect("Couldn't find a")));
+    }
+
+    #[test]
+    fn test_group_by() {
+        let soup = Soup::html_strict(
+            "<table><tr><td>a</td><td>b</td></tr><tr><td>c</td></tr><tr><th>d</th></tr></table>",
+        )
+        .expect("Failed to parse HTML");
+
+        let row = |id: NodeId| {
+            soup.tag("tr")
+                .all()
+                .filter(|tr| tr.id() < id)
+                .last()
+                .map(|tr| tr.id())
+        };
+
+        let rows = soup
+            .tag("td")
+            .group_by(|td| row(td.id()))
+            .into_values()
+            .map(|cells| cells.iter().map(|td| td.all_text()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows, [vec!["a", "b"], vec!["c"]]);
+        assert!(soup.tag("tbody").group_by(QueryItem::id).is_empty());
+    }
+
+    #[test]
+    fn test_item_queries() {
+        let soup = Soup::html_strict(
+            "<ul><li><a>1</a><ul><li><a>2</a></li></ul></li></ul><li><a>3</a></li>",
+        )
+        .expect("Failed to parse HTML");
+
+        let outer = soup.tag("ul").first().expect("Couldn't find ul");
+        let inner = outer.tag("ul").first().expect("Couldn't find nested ul");
+
+        let text = |item: &QueryItem<'_, parser::HTMLNode<&str>>| {
+            item.tag("a")
+                .all()
+                .map(|a| a.all_text())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(&outer), ["1", "2"]);
+        assert_eq!(text(&inner), ["2"]);
+        assert!(outer.strict().tag("a").first().is_none());
+        assert!(outer.tag("ul").all().all(|ul| ul.name() == Some(&"ul")));
+
+        let links = soup.tag("a").all().collect::<Vec<_>>();
+        assert!(links[1].descendant_of(&inner));
+        assert!(links[1].descendant_of(&outer));
+        assert!(!links[0].descendant_of(&inner));
+        assert!(!links[2].descendant_of(&outer));
+        assert!(inner.descendant_of(&outer));
+        assert!(!outer.descendant_of(&inner));
+
+        for (nested, top) in inner.tag("a").all().zip(soup.tag("a").all().skip(1)) {
+            assert_eq!(nested.id(), top.id());
+            assert_eq!(
+                soup.get(nested.id()).map(|a| a.all_text()),
+                Some("2".into())
+            );
+        }
+        assert_eq!(
+            outer.tag("li").all().map(|li| li.id()).collect::<Vec<_>>(),
+            soup.tag("li")
+                .all()
+                .filter(|li| li.descendant_of(&outer))
+                .map(|li| li.id())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "html-lenient")]
+    #[test]
+    fn test_xpath() {
+        let soup = Soup::html("<!-- a --><ul><li>One</li><!-- b --><li>Two <b>2</b></li></ul>");
+
+        let paths = soup.all().map(|n| n.xpath()).collect::<Vec<_>>();
+        assert_eq!(paths, [
+            "/node()",
+            "/html",
+            "/html/head",
+            "/html/body",
+            "/html/body/ul",
+            "/html/body/ul/li[1]",
+            "/html/body/ul/li[1]/text()",
+            "/html/body/ul/node()",
+            "/html/body/ul/li[2]",
+            "/html/body/ul/li[2]/text()",
+            "/html/body/ul/li[2]/b",
+            "/html/body/ul/li[2]/b/text()",
+        ]);
+
+        let ul = soup.tag("ul").first().expect("Couldn't find ul");
+        assert_eq!(
+            ul.tag("b").first().map(|b| b.xpath()),
+            Some("/html/body/ul/li[2]/b".into())
+        );
+    }
+
+    #[test]
+    fn test_slice_queries() {
+        let soup =
+            Soup::html_strict("<p>One</p><div><p>Two</p></div>").expect("Failed to parse HTML");
+
+        let nodes = soup.nodes.clone();
+        assert_eq!(nodes.tag("p").all().count(), 2);
+        assert_eq!(nodes.strict().tag("p").all().count(), 1);
+        assert_eq!(nodes[1..].tag("p").all().count(), 1);
+
+        let p = nodes[1..].tag("p").first().expect("Couldn't find p");
+        assert_eq!(p.id().index(), 1);
+        assert_eq!(p.all_text(), "Two");
+    }
+
+    #[test]
+    fn test_pruning() {
+        use crate::filter::{
+            Prune,
+            Tag,
+        };
+
+        let soup = Soup::html_strict(
+            "<div><p>1</p><div><p>2</p></div></div><p>3</p><section><p>4</p></section>",
+        )
+        .expect("Failed to parse HTML");
+
+        let all = soup.tag("p").all().map(|p| p.id()).collect::<Vec<_>>();
+        let mut pruned = soup
+            .filter(Prune {
+                filter: Tag { tag: "p" },
+                subtrees: Tag { tag: "div" },
+            })
+            .all();
+
+        let ids = pruned.by_ref().map(|p| p.id()).collect::<Vec<_>>();
+        assert_eq!(ids, all[2..]);
+        assert_eq!(pruned.stats().visited, 6);
+
+        let p = soup.get(ids[1]).expect("Invalid node id");
+        assert_eq!(p.all_text(), "4");
+    }
+
+    #[test]
+    fn test_fold() {
+        use crate::filter::{
+            Prune,
+            Tag,
+        };
+
+        fn ids<N: Node, F: Filter<N>>(iter: QueryIter<'_, N, F>) -> Vec<NodeId> {
+            iter.fold(Vec::new(), |mut ids, item| {
+                ids.push(item.id());
+                ids
+            })
+        }
+
+        let soup =
+            Soup::html_strict("<ul><li>1</li><li><ul><li>2</li></ul></li></ul><ol><li>3</li></ol>")
+                .expect("Failed to parse HTML");
+
+        let expected = soup
+            .tag("li")
+            .all()
+            .by_ref()
+            .map(|li| li.id())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids(soup.tag("li").all()), expected);
+        assert_eq!(ids(soup.strict().all()).len(), 2);
+
+        let mut partial = soup.tag("li").all();
+        partial.next();
+        assert_eq!(ids(partial), expected[1..]);
+
+        let pruned = || {
+            soup.filter(Prune {
+                filter: Tag { tag: "li" },
+                subtrees: Tag { tag: "ul" },
+            })
+            .all()
+        };
+        let stepped = pruned().by_ref().map(|li| li.id()).collect::<Vec<_>>();
+        assert_eq!(stepped.len(), 1);
+        assert_eq!(ids(pruned()), stepped);
+    }
 }
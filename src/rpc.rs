@@ -0,0 +1,262 @@
+use std::fmt;
+
+use crate::{
+    filter::{
+        Filter,
+        Tag,
+    },
+    parser::{
+        ns::{
+            QName,
+            SOAP,
+            SOAP_12,
+        },
+        XMLNode,
+    },
+    Node,
+    Queryable,
+    Soup,
+};
+
+/// A fault returned instead of a result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    /// `faultcode` in SOAP 1.1, the `Code` value in SOAP 1.2, or `faultCode` in XML-RPC
+    pub code: String,
+
+    /// `faultstring` in SOAP 1.1, the first `Reason` text in SOAP 1.2, or `faultString` in
+    /// XML-RPC
+    pub message: String,
+
+    /// Children of the SOAP `detail` element, if any
+    pub detail: Vec<XMLNode>,
+}
+
+/// Error returned when a response cannot be unwrapped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// A required element is missing, so the document is not a response of the expected kind
+    Missing(&'static str),
+
+    /// The server returned a fault
+    Fault(Fault),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(name) => write!(f, "missing {name} element"),
+            Self::Fault(fault) => write!(f, "fault {}: {}", fault.code, fault.message),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Returns the first child of `node` matching `filter`
+fn child<'x, F>(node: &'x XMLNode, filter: &F) -> Option<&'x XMLNode>
+where
+    F: Filter<XMLNode>,
+{
+    node.children().iter().find(|c| filter.matches(c))
+}
+
+/// Returns the trimmed text of the first child of `node` matching `filter`
+fn child_text<F>(node: &XMLNode, filter: &F) -> String
+where
+    F: Filter<XMLNode>,
+{
+    child(node, filter)
+        .map(|c| c.all_text().trim().to_string())
+        .unwrap_or_default()
+}
+
+fn elements(nodes: &[XMLNode]) -> Vec<XMLNode> {
+    nodes
+        .iter()
+        .filter(|n| n.name().is_some())
+        .cloned()
+        .collect()
+}
+
+/// Unwraps the `Body` of a SOAP 1.1 or 1.2 envelope
+///
+/// Returns the elements inside the body, or the fault if the body holds one.
+///
+/// # Errors
+/// If the document is not a SOAP envelope, or the body is a fault.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, rpc::{self, RpcError}};
+/// let soup = Soup::xml(r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+///     <soap:Body><GetPriceResponse><Price>1.90</Price></GetPriceResponse></soap:Body>
+/// </soap:Envelope>"#.as_bytes()).unwrap();
+///
+/// let payload = rpc::soap_body(&soup).unwrap();
+/// assert_eq!(payload.tag("Price").first().map(|p| p.all_text()), Some("1.90".into()));
+///
+/// let fault = Soup::xml(r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+///     <soap:Body><soap:Fault><faultcode>soap:Server</faultcode><faultstring>Down</faultstring></soap:Fault></soap:Body>
+/// </soap:Envelope>"#.as_bytes()).unwrap();
+///
+/// let Err(RpcError::Fault(fault)) = rpc::soap_body(&fault) else { panic!() };
+/// assert_eq!((fault.code.as_str(), fault.message.as_str()), ("soap:Server", "Down"));
+/// ```
+pub fn soap_body(soup: &Soup<XMLNode>) -> Result<Soup<XMLNode>, RpcError> {
+    let (ns, envelope) = [SOAP, SOAP_12]
+        .into_iter()
+        .find_map(|ns| Some((ns, soup.filter(QName::new(ns, "Envelope")).first()?)))
+        .ok_or(RpcError::Missing("Envelope"))?;
+
+    let body = child(&envelope, &QName::new(ns, "Body")).ok_or(RpcError::Missing("Body"))?;
+
+    let Some(fault) = child(body, &QName::new(ns, "Fault")) else {
+        return Ok(Soup::from_nodes(elements(body.children())));
+    };
+
+    let fault = if ns == SOAP {
+        Fault {
+            code: child_text(fault, &Tag { tag: "faultcode" }),
+            message: child_text(fault, &Tag { tag: "faultstring" }),
+            detail: child(fault, &Tag { tag: "detail" })
+                .map(|d| elements(d.children()))
+                .unwrap_or_default(),
+        }
+    } else {
+        Fault {
+            code: child(fault, &QName::new(ns, "Code"))
+                .map(|c| child_text(c, &QName::new(ns, "Value")))
+                .unwrap_or_default(),
+            message: child(fault, &QName::new(ns, "Reason"))
+                .map(|r| child_text(r, &QName::new(ns, "Text")))
+                .unwrap_or_default(),
+            detail: child(fault, &QName::new(ns, "Detail"))
+                .map(|d| elements(d.children()))
+                .unwrap_or_default(),
+        }
+    };
+
+    Err(RpcError::Fault(fault))
+}
+
+/// Unwraps the parameters of an XML-RPC `methodResponse`
+///
+/// Returns the `value` element of each parameter, or the fault if the response holds one.
+///
+/// # Errors
+/// If the document is not a method response, or the response is a fault.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, rpc::{self, RpcError}};
+/// let soup = Soup::xml(r#"<methodResponse><params>
+///     <param><value><string>South Dakota</string></value></param>
+/// </params></methodResponse>"#.as_bytes()).unwrap();
+///
+/// let values = rpc::xmlrpc_params(&soup).unwrap();
+/// assert_eq!(values.tag("string").first().map(|s| s.all_text()), Some("South Dakota".into()));
+///
+/// let fault = Soup::xml(r#"<methodResponse><fault><value><struct>
+///     <member><name>faultCode</name><value><int>4</int></value></member>
+///     <member><name>faultString</name><value><string>Too many parameters.</string></value></member>
+/// </struct></value></fault></methodResponse>"#.as_bytes()).unwrap();
+///
+/// let error = rpc::xmlrpc_params(&fault).unwrap_err();
+/// assert_eq!(error.to_string(), "fault 4: Too many parameters.");
+/// ```
+pub fn xmlrpc_params(soup: &Soup<XMLNode>) -> Result<Soup<XMLNode>, RpcError> {
+    let response = soup
+        .tag("methodResponse")
+        .first()
+        .ok_or(RpcError::Missing("methodResponse"))?;
+
+    if let Some(fault) = child(&response, &Tag { tag: "fault" }) {
+        let member = |name: &str| {
+            fault
+                .descendants()
+                .filter(|n| n.name().is_some_and(|n| n == "member"))
+                .find(|m| child_text(m, &Tag { tag: "name" }) == name)
+                .map(|m| child_text(m, &Tag { tag: "value" }))
+                .unwrap_or_default()
+        };
+
+        return Err(RpcError::Fault(Fault {
+            code: member("faultCode"),
+            message: member("faultString"),
+            detail: Vec::new(),
+        }));
+    }
+
+    let params = child(&response, &Tag { tag: "params" }).ok_or(RpcError::Missing("params"))?;
+
+    Ok(Soup::from_nodes(
+        params
+            .children()
+            .iter()
+            .filter(|p| p.name().is_some_and(|n| n == "param"))
+            .filter_map(|p| child(p, &Tag { tag: "value" }).cloned())
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soap_12() {
+        let xml = r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">
+            <env:Header><t:tx xmlns:t="urn:t">5</t:tx></env:Header>
+            <env:Body>
+                <env:Fault>
+                    <env:Code><env:Value>env:Sender</env:Value></env:Code>
+                    <env:Reason><env:Text xml:lang="en">Bad input</env:Text></env:Reason>
+                    <env:Detail><e:limit xmlns:e="urn:e">10</e:limit></env:Detail>
+                </env:Fault>
+            </env:Body>
+        </env:Envelope>"#;
+        let soup = Soup::xml(xml.as_bytes()).expect("Failed to parse XML");
+
+        let Err(RpcError::Fault(fault)) = soap_body(&soup) else {
+            panic!("Expected a fault");
+        };
+        assert_eq!(fault.code, "env:Sender");
+        assert_eq!(fault.message, "Bad input");
+        assert_eq!(fault.detail.len(), 1);
+        assert_eq!(fault.detail[0].all_text(), "10");
+
+        let ok = Soup::xml(xml.replace("Fault", "Result").as_bytes()).expect("Failed to parse XML");
+        let payload = soap_body(&ok).expect("Failed to unwrap body");
+        assert_eq!(payload.tag("Reason").all().count(), 1);
+
+        let plain =
+            Soup::xml(b"<Envelope><Body/></Envelope>".as_slice()).expect("Failed to parse XML");
+        assert_eq!(soap_body(&plain).err(), Some(RpcError::Missing("Envelope")));
+    }
+
+    #[test]
+    fn test_xmlrpc() {
+        let soup = Soup::xml(
+            b"<methodResponse><params><param><value><i4>1</i4></value></param><param><value>two</value></param></params></methodResponse>"
+                .as_slice(),
+        )
+        .expect("Failed to parse XML");
+
+        let values = xmlrpc_params(&soup).expect("Failed to unwrap params");
+        assert_eq!(
+            values
+                .iter()
+                .filter(|n| n.name().is_some_and(|n| n == "value"))
+                .map(|v| v.all_text())
+                .collect::<Vec<_>>(),
+            ["1", "two"]
+        );
+
+        let empty = Soup::xml(b"<methodResponse/>".as_slice()).expect("Failed to parse XML");
+        assert_eq!(
+            xmlrpc_params(&empty).err().map(|e| e.to_string()),
+            Some("missing params element".into())
+        );
+    }
+}
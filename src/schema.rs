@@ -0,0 +1,290 @@
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+};
+use core::fmt;
+
+use crate::{
+    filter::Filter,
+    query::{
+        QueryItem,
+        QueryIter,
+    },
+    Node,
+};
+
+/// Kind of value found in an attribute or text node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValueType {
+    /// An empty value, like a boolean attribute
+    Empty,
+    /// `true` or `false`, ignoring ASCII case
+    Boolean,
+    /// A whole number, like `-12`
+    Integer,
+    /// A decimal number, like `3.5` or `1e-3`
+    Number,
+    /// Anything else
+    Text,
+}
+
+impl ValueType {
+    /// Classifies `value`, ignoring surrounding whitespace
+    #[must_use]
+    pub fn of(value: &str) -> Self {
+        let value = value.trim();
+
+        if value.is_empty() {
+            Self::Empty
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            Self::Boolean
+        } else if value.parse::<i64>().is_ok() {
+            Self::Integer
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            Self::Number
+        } else {
+            Self::Text
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "empty",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Text => "text",
+        })
+    }
+}
+
+/// How often an attribute appears, and what its values look like
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttrStats {
+    /// Number of elements with the attribute
+    pub count: usize,
+
+    /// Number of values of each type
+    pub types: BTreeMap<ValueType, usize>,
+}
+
+/// How often a child element appears
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildStats {
+    /// Number of elements with at least one such child
+    pub count: usize,
+
+    /// Number of such children over all elements
+    pub total: usize,
+
+    /// Largest number of such children in one element
+    pub max: usize,
+}
+
+/// Attributes and children found on a set of elements, to help write extractors for
+/// undocumented formats
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, schema::ValueType};
+/// # #[cfg(feature = "xml")] {
+/// let soup = Soup::xml(r#"<feed>
+///     <item id="1" draft="false"><title>A</title><tag>x</tag><tag>y</tag></item>
+///     <item id="2"><title>B</title></item>
+///     <item id="n/a"><title>C</title><tag>z</tag></item>
+/// </feed>"#.as_bytes()).unwrap();
+///
+/// let schema = soup.tag("item").all().schema();
+/// assert_eq!(schema.count, 3);
+///
+/// let id = &schema.attrs["id"];
+/// assert_eq!((id.count, id.types[&ValueType::Integer], id.types[&ValueType::Text]), (3, 2, 1));
+/// assert_eq!(schema.attrs["draft"].count, 1);
+///
+/// let tag = &schema.children["tag"];
+/// assert_eq!((tag.count, tag.total, tag.max), (2, 3, 2));
+/// assert!(schema.is_required("title"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    /// Number of elements examined
+    pub count: usize,
+
+    /// Attributes, by name
+    pub attrs: BTreeMap<String, AttrStats>,
+
+    /// Child elements, by name
+    pub children: BTreeMap<String, ChildStats>,
+
+    /// Types of the non-whitespace text directly inside the elements
+    pub text: BTreeMap<ValueType, usize>,
+}
+
+impl Schema {
+    /// Examines every element of `items`, ignoring other nodes
+    #[must_use]
+    pub fn infer<'x, N, I>(items: I) -> Self
+    where
+        N: Node + 'x,
+        N::Text: AsRef<str>,
+        I: IntoIterator<Item = QueryItem<'x, N>>,
+    {
+        let mut schema = Self::default();
+
+        for item in items {
+            schema.add(item.node());
+        }
+
+        schema
+    }
+
+    /// Adds `node` to the schema, if it is an element
+    pub fn add<N>(&mut self, node: &N)
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let Some(attrs) = node.attrs() else {
+            return;
+        };
+        self.count += 1;
+
+        for (name, value) in attrs {
+            let stats = self.attrs.entry(name.as_ref().into()).or_default();
+            stats.count += 1;
+            *stats
+                .types
+                .entry(ValueType::of(value.as_ref()))
+                .or_default() += 1;
+        }
+
+        let mut counts = BTreeMap::<&str, usize>::new();
+
+        for child in node.children() {
+            if let Some(name) = child.name() {
+                *counts.entry(name.as_ref()).or_default() += 1;
+            } else if let Some(text) = child.text().map(AsRef::as_ref) {
+                if !text.trim().is_empty() {
+                    *self.text.entry(ValueType::of(text)).or_default() += 1;
+                }
+            }
+        }
+
+        for (name, count) in counts {
+            let stats = self.children.entry(name.into()).or_default();
+            stats.count += 1;
+            stats.total += count;
+            stats.max = stats.max.max(count);
+        }
+    }
+
+    /// Returns `true` if every element examined had the attribute or child `name`
+    ///
+    /// Attributes are checked first. Returns `false` if no elements were examined.
+    #[must_use]
+    pub fn is_required(&self, name: &str) -> bool {
+        let count = self
+            .attrs
+            .get(name)
+            .map(|a| a.count)
+            .or_else(|| self.children.get(name).map(|c| c.count));
+
+        self.count > 0 && count == Some(self.count)
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let types = |f: &mut fmt::Formatter<'_>, types: &BTreeMap<ValueType, usize>| {
+            for (i, (kind, count)) in types.iter().enumerate() {
+                let sep = if i == 0 { "" } else { ", " };
+                write!(f, "{sep}{kind} {count}")?;
+            }
+            Ok(())
+        };
+
+        write!(f, "{} elements", self.count)?;
+
+        for (name, stats) in &self.attrs {
+            write!(f, "\n@{name}: {}/{} (", stats.count, self.count)?;
+            types(f, &stats.types)?;
+            f.write_str(")")?;
+        }
+
+        for (name, stats) in &self.children {
+            write!(
+                f,
+                "\n<{name}>: {}/{}, up to {}",
+                stats.count, self.count, stats.max
+            )?;
+        }
+
+        if !self.text.is_empty() {
+            f.write_str("\ntext: ")?;
+            types(f, &self.text)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<N, F> QueryIter<'_, N, F>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+    F: Filter<N>,
+{
+    /// Infers a [`Schema`] from every matching element
+    #[must_use]
+    pub fn schema(self) -> Schema {
+        Schema::infer(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{
+        Queryable,
+        Soup,
+    };
+
+    #[test]
+    fn test_schema() {
+        let soup = Soup::html_strict(
+            r#"<ul><li data-n="1" hidden>One <b>!</b></li><li data-n="2.5">2</li><li><b>3</b><b>4</b></li></ul>"#,
+        )
+        .expect("Failed to parse HTML");
+
+        let schema = soup.tag("li").all().schema();
+        assert_eq!(schema.count, 3);
+        assert_eq!(
+            schema.attrs["data-n"].types,
+            BTreeMap::from([(ValueType::Integer, 1), (ValueType::Number, 1)])
+        );
+        assert_eq!(schema.attrs["hidden"].types[&ValueType::Empty], 1);
+        assert_eq!(schema.children["b"], ChildStats {
+            count: 2,
+            total: 3,
+            max: 2
+        });
+        assert_eq!(
+            schema.text,
+            BTreeMap::from([(ValueType::Integer, 1), (ValueType::Text, 1)])
+        );
+        assert!(!schema.is_required("data-n"));
+        assert!(!schema.is_required("missing"));
+
+        assert_eq!(
+            schema.to_string(),
+            "3 elements\n@data-n: 2/3 (integer 1, number 1)\n@hidden: 1/3 (empty 1)\n<b>: 2/3, up to 2\ntext: integer 1, text 1"
+        );
+        assert_eq!(ValueType::of(" TRUE "), ValueType::Boolean);
+        assert_eq!(ValueType::of("inf"), ValueType::Text);
+    }
+}
@@ -0,0 +1,1172 @@
+use alloc::{
+    string::String,
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    filter::Filter,
+    query::{
+        NodeId,
+        Query,
+        QueryItem,
+        Root,
+        Selection,
+    },
+    Node,
+};
+
+/// Kind of a [`SelectorError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorErrorKind {
+    /// The selector ended early, like `a[href`
+    UnexpectedEnd,
+    /// A character is not valid where it appears
+    UnexpectedCharacter(char),
+    /// The pseudo-class or pseudo-element is not supported
+    Unsupported(String),
+    /// The argument of an `:nth-` pseudo-class is not of the form `An+B`
+    InvalidNth,
+    /// A combinator or structural pseudo-class was used where elements are matched in isolation
+    NeedsContext,
+}
+
+/// Error returned when a CSS selector cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// Byte offset of the error in the selector
+    pub position: usize,
+    /// What went wrong
+    pub kind: SelectorErrorKind,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SelectorErrorKind::UnexpectedEnd => write!(f, "unexpected end of selector"),
+            SelectorErrorKind::UnexpectedCharacter(c) => {
+                write!(f, "unexpected character {c:?} at {}", self.position)
+            }
+            SelectorErrorKind::Unsupported(name) => {
+                write!(f, "unsupported pseudo-class {name:?} at {}", self.position)
+            }
+            SelectorErrorKind::InvalidNth => {
+                write!(f, "invalid An+B argument at {}", self.position)
+            }
+            SelectorErrorKind::NeedsContext => write!(
+                f,
+                "selector at {} depends on parents or siblings, which filters cannot see",
+                self.position
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelectorError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// ` `
+    Descendant,
+    /// `>`
+    Child,
+    /// `+`
+    NextSibling,
+    /// `~`
+    SubsequentSibling,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    /// `[a]`
+    Exists,
+    /// `[a=v]`
+    Equals,
+    /// `[a~=v]`
+    Includes,
+    /// `[a|=v]`
+    DashMatch,
+    /// `[a^=v]`
+    Prefix,
+    /// `[a$=v]`
+    Suffix,
+    /// `[a*=v]`
+    Substring,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AttrSelector {
+    name: String,
+    operator: Operator,
+    value: String,
+    ignore_case: bool,
+}
+
+impl AttrSelector {
+    fn matches(&self, actual: &str) -> bool {
+        let (actual, value) = if self.ignore_case {
+            (actual.to_ascii_lowercase(), self.value.to_ascii_lowercase())
+        } else {
+            (actual.into(), self.value.clone())
+        };
+
+        match self.operator {
+            Operator::Exists => true,
+            Operator::Equals => actual == value,
+            Operator::Includes => actual.split_ascii_whitespace().any(|v| v == value),
+            Operator::DashMatch => {
+                actual == value
+                    || actual
+                        .strip_prefix(value.as_str())
+                        .is_some_and(|r| r.starts_with('-'))
+            }
+            Operator::Prefix => !value.is_empty() && actual.starts_with(value.as_str()),
+            Operator::Suffix => !value.is_empty() && actual.ends_with(value.as_str()),
+            Operator::Substring => !value.is_empty() && actual.contains(value.as_str()),
+        }
+    }
+}
+
+/// Argument of the `:nth-` pseudo-classes, matching positions `a * n + b` for any `n >= 0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Nth {
+    a: i64,
+    b: i64,
+}
+
+impl Nth {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match s.as_str() {
+            "odd" => return Some(Self { a: 2, b: 1 }),
+            "even" => return Some(Self { a: 2, b: 0 }),
+            _ => {}
+        }
+
+        let Some((a, b)) = s.split_once('n') else {
+            return Some(Self {
+                a: 0,
+                b: s.parse().ok()?,
+            });
+        };
+
+        let a = match a {
+            "" | "+" => 1,
+            "-" => -1,
+            a => a.parse().ok()?,
+        };
+
+        let b = match b {
+            "" => 0,
+            b if b.starts_with(['+', '-']) => b.parse().ok()?,
+            _ => return None,
+        };
+
+        Some(Self { a, b })
+    }
+
+    /// Returns `true` if the 1-based `position` is matched
+    fn matches(self, position: usize) -> bool {
+        let Ok(position) = i64::try_from(position) else {
+            return false;
+        };
+
+        match self.a {
+            0 => position == self.b,
+            a => (position - self.b) % a == 0 && (position - self.b) / a >= 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pseudo {
+    Root,
+    Empty,
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    FirstOfType,
+    LastOfType,
+    OnlyOfType,
+    NthChild(Nth),
+    NthLastChild(Nth),
+    NthOfType(Nth),
+    NthLastOfType(Nth),
+    Not(Vec<Compound>),
+}
+
+impl Pseudo {
+    /// Returns `true` if matching depends on the element's parent or siblings
+    fn is_structural(&self) -> bool {
+        match self {
+            Self::Empty => false,
+            Self::Not(compounds) => compounds.iter().any(Compound::is_structural),
+            _ => true,
+        }
+    }
+}
+
+fn add(a: (u32, u32, u32), b: (u32, u32, u32)) -> (u32, u32, u32) {
+    (
+        a.0.saturating_add(b.0),
+        a.1.saturating_add(b.1),
+        a.2.saturating_add(b.2),
+    )
+}
+
+/// Position of an element among its siblings
+struct Context<'x, N> {
+    siblings: &'x [N],
+    index: usize,
+    depth: usize,
+}
+
+impl<N> Context<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// 1-based positions of the element among element siblings for which `same` returns
+    /// `true`, counted from the start and from the end
+    fn positions<F>(&self, same: F) -> (usize, usize)
+    where
+        F: Fn(&N) -> bool,
+    {
+        let is = |n: &&N| n.name().is_some() && same(n);
+
+        (
+            self.siblings[..self.index].iter().filter(is).count() + 1,
+            self.siblings[self.index + 1..].iter().filter(is).count() + 1,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Compound {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attrs: Vec<AttrSelector>,
+    pseudos: Vec<Pseudo>,
+}
+
+impl Compound {
+    fn is_structural(&self) -> bool {
+        self.pseudos.iter().any(Pseudo::is_structural)
+    }
+
+    fn specificity(&self) -> (u32, u32, u32) {
+        let count = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+
+        self.pseudos.iter().fold(
+            (
+                u32::from(self.id.is_some()),
+                count(self.classes.len() + self.attrs.len()),
+                u32::from(self.tag.is_some()),
+            ),
+            |total, pseudo| match pseudo {
+                Pseudo::Not(compounds) => add(
+                    total,
+                    compounds
+                        .iter()
+                        .map(Compound::specificity)
+                        .max()
+                        .unwrap_or_default(),
+                ),
+                _ => add(total, (0, 1, 0)),
+            },
+        )
+    }
+
+    /// Matches `node`, found at `context` if known
+    ///
+    /// Structural pseudo-classes never match without a context.
+    fn matches<N>(&self, node: &N, context: Option<&Context<'_, N>>) -> bool
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let Some(name) = node.name() else {
+            return false;
+        };
+
+        let attrs = node.attrs_ignore_case();
+        let attr = |name: &str| attrs.as_ref().and_then(|a| a.get(name));
+
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| tag.eq_ignore_ascii_case(name.as_ref()))
+            && self.id.as_ref().is_none_or(|id| attr("id") == Some(id))
+            && self.classes.iter().all(|class| {
+                attr("class").is_some_and(|c| c.split_ascii_whitespace().any(|c| c == class))
+            })
+            && self
+                .attrs
+                .iter()
+                .all(|a| attr(&a.name).is_some_and(|v| a.matches(v)))
+            && self.pseudos.iter().all(|p| {
+                let same_type = |n: &N| {
+                    n.name()
+                        .is_some_and(|n| n.as_ref().eq_ignore_ascii_case(name.as_ref()))
+                };
+
+                match (p, context) {
+                    (Pseudo::Empty, _) => node.children().iter().all(|c| {
+                        c.name().is_none() && c.text().is_none_or(|t| t.as_ref().is_empty())
+                    }),
+                    (Pseudo::Not(compounds), _) => {
+                        !compounds.iter().any(|c| c.matches(node, context))
+                    }
+                    (_, None) => false,
+                    (Pseudo::Root, Some(context)) => context.depth == 0,
+                    (Pseudo::FirstChild, Some(context)) => context.positions(|_| true).0 == 1,
+                    (Pseudo::LastChild, Some(context)) => context.positions(|_| true).1 == 1,
+                    (Pseudo::OnlyChild, Some(context)) => context.positions(|_| true) == (1, 1),
+                    (Pseudo::FirstOfType, Some(context)) => context.positions(same_type).0 == 1,
+                    (Pseudo::LastOfType, Some(context)) => context.positions(same_type).1 == 1,
+                    (Pseudo::OnlyOfType, Some(context)) => context.positions(same_type) == (1, 1),
+                    (Pseudo::NthChild(nth), Some(context)) => {
+                        nth.matches(context.positions(|_| true).0)
+                    }
+                    (Pseudo::NthLastChild(nth), Some(context)) => {
+                        nth.matches(context.positions(|_| true).1)
+                    }
+                    (Pseudo::NthOfType(nth), Some(context)) => {
+                        nth.matches(context.positions(same_type).0)
+                    }
+                    (Pseudo::NthLastOfType(nth), Some(context)) => {
+                        nth.matches(context.positions(same_type).1)
+                    }
+                }
+            })
+    }
+}
+
+/// A compound selector, followed by compounds each joined to the previous by a combinator
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Complex {
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+impl Complex {
+    /// Matches the last compound against the last element of `path`, then the compounds before
+    /// it against the elements their combinators lead to
+    ///
+    /// `path` holds the siblings and index of each element from the top of the queried data.
+    /// Candidates are kept on `pending` rather than the call stack, so long paths cannot
+    /// overflow it.
+    fn matches<N>(&self, path: &[(&[N], usize)], pending: &mut Vec<Pending>) -> bool
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let Some(&(_, index)) = path.last() else {
+            return false;
+        };
+
+        pending.clear();
+        pending.push(Pending {
+            compound: self.compounds.len() - 1,
+            depth: path.len(),
+            index,
+            ancestors: false,
+        });
+
+        while let Some(Pending {
+            compound,
+            depth,
+            index,
+            ancestors,
+        }) = pending.pop()
+        {
+            let parent = depth.checked_sub(2).map(|d| Pending {
+                compound,
+                depth: depth - 1,
+                index: path[d].1,
+                ancestors: true,
+            });
+
+            if ancestors {
+                pending.extend(parent);
+            }
+
+            let siblings = path[depth - 1].0;
+            let context = Context {
+                siblings,
+                index,
+                depth: depth - 1,
+            };
+
+            if !self.compounds[compound].matches(&siblings[index], Some(&context)) {
+                continue;
+            }
+
+            let Some(previous) = compound.checked_sub(1) else {
+                return true;
+            };
+
+            let parent = parent.map(|p| Pending {
+                compound: previous,
+                ancestors: false,
+                ..p
+            });
+            let mut elder = (0..index)
+                .rev()
+                .filter(|&j| siblings[j].name().is_some())
+                .map(|j| Pending {
+                    compound: previous,
+                    depth,
+                    index: j,
+                    ancestors: false,
+                });
+
+            match self.combinators[previous] {
+                Combinator::Descendant => pending.extend(parent.map(|p| Pending {
+                    ancestors: true,
+                    ..p
+                })),
+                Combinator::Child => pending.extend(parent),
+                Combinator::NextSibling => pending.extend(elder.next()),
+                Combinator::SubsequentSibling => pending.extend(elder),
+            }
+        }
+
+        false
+    }
+}
+
+/// A compound of a [`Complex`] selector still to be matched against an element
+#[derive(Debug, Clone, Copy)]
+struct Pending {
+    compound: usize,
+    /// Number of elements on the path to the element, which is the last of them unless
+    /// `index` chooses one of its elder siblings
+    depth: usize,
+    /// Index of the element among its siblings
+    index: usize,
+    /// Whether the ancestors of the element are also candidates
+    ancestors: bool,
+}
+
+/// A compiled CSS selector list, like `div.content > a[href], nav a`
+///
+/// Supports type, universal, id, class, and attribute selectors (with every operator and the `i`
+/// flag), the descendant, child (`>`), next-sibling (`+`) and subsequent-sibling (`~`)
+/// combinators, and the pseudo-classes `:root`, `:empty`, `:first-child`, `:last-child`,
+/// `:only-child`, `:first-of-type`, `:last-of-type`, `:only-of-type`, `:nth-child()`,
+/// `:nth-last-child()`, `:nth-of-type()`, `:nth-last-of-type()`, and `:not()` of compound
+/// selectors.
+///
+/// Type selectors and attribute names ignore ASCII case, as in HTML.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, selector::Selector};
+/// let soup = Soup::html_strict(r#"<ul><li>One</li><li class="x">Two</li><li>Three</li></ul>"#).unwrap();
+///
+/// let selector = Selector::parse("li.x + li, li:first-child").unwrap();
+/// let items = selector.select(soup.recursive()).into_iter().map(|li| li.all_text()).collect::<Vec<_>>();
+/// assert_eq!(items, ["One", "Three"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    selectors: Vec<Complex>,
+}
+
+impl Selector {
+    /// Parses a selector list
+    ///
+    /// # Errors
+    /// If the selector is invalid, or uses an unsupported pseudo-class.
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        Self::parse_with(selector, false)
+    }
+
+    /// Parses a selector list that can be fully matched as a [`Filter`]
+    ///
+    /// # Errors
+    /// Like [`Selector::parse`], and also if the selector uses a combinator or a structural
+    /// pseudo-class, like `:first-child`, which depend on the element's parents or siblings.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::selector::{Selector, SelectorErrorKind};
+    /// assert!(Selector::parse_isolated("a.nav[href]:not(:empty)").is_ok());
+    ///
+    /// let error = Selector::parse_isolated("nav > a").unwrap_err();
+    /// assert_eq!((error.position, error.kind), (4, SelectorErrorKind::NeedsContext));
+    /// ```
+    pub fn parse_isolated(selector: &str) -> Result<Self, SelectorError> {
+        Self::parse_with(selector, true)
+    }
+
+    fn parse_with(selector: &str, isolated: bool) -> Result<Self, SelectorError> {
+        let mut parser = Parser {
+            s: selector,
+            pos: 0,
+            isolated,
+        };
+
+        let selectors = parser.list(false)?;
+
+        Ok(Self {
+            selectors: selectors
+                .into_iter()
+                .map(|(compounds, combinators)| Complex {
+                    compounds,
+                    combinators,
+                })
+                .collect(),
+        })
+    }
+
+    /// Specificity of the most specific selector in the list, as `(ids, classes, types)`
+    ///
+    /// Attribute selectors and pseudo-classes count as classes, and `:not()` counts as its most
+    /// specific argument, as in CSS.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::selector::Selector;
+    /// let specificity = |s| Selector::parse(s).unwrap().specificity();
+    /// assert_eq!(specificity("ul#nav li.active > a[href]"), (1, 2, 3));
+    /// assert_eq!(specificity("p, .note:not(#x)"), (1, 1, 0));
+    /// ```
+    #[must_use]
+    pub fn specificity(&self) -> (u32, u32, u32) {
+        self.selectors
+            .iter()
+            .map(|s| {
+                s.compounds
+                    .iter()
+                    .map(Compound::specificity)
+                    .fold((0, 0, 0), add)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Executes `query`, keeping results that also match the selector
+    ///
+    /// The selector is matched against elements within the query's data, so ancestors and
+    /// siblings outside of it are never considered.
+    #[must_use]
+    pub fn select<'x, N, F>(&self, query: Query<'x, N, F>) -> Selection<'x, N>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+        F: Filter<N>,
+    {
+        let Query {
+            nodes,
+            root,
+            offset,
+            recursive,
+            filter,
+        } = query;
+
+        let mut walk = Walk {
+            selector: self,
+            root,
+            recursive,
+            filter,
+            path: Vec::new(),
+            index: offset,
+            items: Vec::new(),
+            pending: Vec::new(),
+        };
+
+        walk.walk(nodes);
+        walk.items.into_iter().collect()
+    }
+}
+
+/// State of [`Selector::select`] while visiting the queried data
+struct Walk<'s, 'x, N, F> {
+    selector: &'s Selector,
+    root: Root<'x, N>,
+    recursive: bool,
+    filter: F,
+    /// Siblings and index of each element from the top of the queried data
+    path: Vec<(&'x [N], usize)>,
+    /// Index of the next node in document order
+    index: usize,
+    items: Vec<QueryItem<'x, N>>,
+    /// Buffer for [`Complex::matches`]
+    pending: Vec<Pending>,
+}
+
+impl<'x, N, F> Walk<'_, 'x, N, F>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+    F: Filter<N>,
+{
+    /// Visits `nodes` and, if recursive, their descendants in document order
+    ///
+    /// `path` doubles as the stack of the traversal, so deep trees cannot overflow the call
+    /// stack.
+    fn walk(&mut self, nodes: &'x [N]) {
+        self.path.push((nodes, 0));
+
+        while let Some(&(siblings, i)) = self.path.last() {
+            let Some(node) = siblings.get(i) else {
+                self.path.pop();
+                self.next_sibling();
+                continue;
+            };
+
+            let id = NodeId(self.index);
+            self.index += 1;
+
+            if self.filter.matches(node)
+                && self
+                    .selector
+                    .selectors
+                    .iter()
+                    .any(|s| s.matches(&self.path, &mut self.pending))
+            {
+                self.items.push(QueryItem {
+                    item: node,
+                    id,
+                    root: self.root,
+                });
+            }
+
+            if self.recursive {
+                self.path.push((node.children(), 0));
+            } else {
+                self.index += self.root.size(id.0, node) - 1;
+                self.next_sibling();
+            }
+        }
+    }
+
+    fn next_sibling(&mut self) {
+        if let Some((_, i)) = self.path.last_mut() {
+            *i += 1;
+        }
+    }
+}
+
+/// Matches elements in isolation, without knowing their parents or siblings
+///
+/// Selectors with combinators or structural pseudo-classes, like `:first-child`, never match, and
+/// are rejected by [`Selector::parse_isolated`]. Use
+/// [`Queryable::select`](crate::Queryable::select) to match them.
+impl<N> Filter<N> for Selector
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    fn matches(&self, node: &N) -> bool {
+        self.selectors.iter().any(|s| {
+            matches!(s.compounds.as_slice(), [c] if !c.is_structural() && c.matches(node, None))
+        })
+    }
+}
+
+/// Converts a [`scraper::Selector`] by parsing its CSS serialization
+///
+/// The result matches against the whole tree with [`Selector::select`], so combinators work as
+/// they do in `scraper`.
+///
+/// # Errors
+/// If the selector uses a pseudo-class this crate does not support.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, selector::Selector};
+/// let soup = Soup::html_strict(r#"<nav><a class="nav" href="/">Home</a></nav><a class="nav" href="/x">Other</a>"#).unwrap();
+/// let selector = scraper::Selector::parse("nav > a.nav[href]").unwrap();
+/// let result = Selector::try_from(&selector).unwrap().select(soup.filter(()));
+/// assert_eq!(result.iter().map(|a| a.all_text()).collect::<Vec<_>>(), ["Home"]);
+/// ```
+#[cfg(feature = "html-lenient")]
+impl TryFrom<&scraper::Selector> for Selector {
+    type Error = SelectorError;
+
+    fn try_from(selector: &scraper::Selector) -> Result<Self, Self::Error> {
+        use cssparser::ToCss;
+
+        Self::parse(&selector.to_css_string())
+    }
+}
+
+type Parsed = (Vec<Compound>, Vec<Combinator>);
+
+struct Parser<'s> {
+    s: &'s str,
+    pos: usize,
+    /// Whether to reject selectors that need a [`Context`]
+    isolated: bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, kind: SelectorErrorKind) -> SelectorError {
+        SelectorError {
+            position: self.pos,
+            kind,
+        }
+    }
+
+    fn unexpected(&self) -> SelectorError {
+        match self.peek() {
+            Some(c) => self.error(SelectorErrorKind::UnexpectedCharacter(c)),
+            None => self.error(SelectorErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SelectorError> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Skips whitespace, returning `true` if there was any
+    fn whitespace(&mut self) -> bool {
+        let start = self.pos;
+
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+
+        self.pos > start
+    }
+
+    /// Parses an identifier, where `\` escapes the next character, like `md\:flex`
+    fn ident(&mut self) -> Result<String, SelectorError> {
+        let mut ident = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' => {
+                    self.pos += 1;
+                    ident.push(self.bump().ok_or_else(|| self.unexpected())?);
+                }
+                c if c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() => {
+                    ident.push(c);
+                    self.pos += c.len_utf8();
+                }
+                _ => break,
+            }
+        }
+
+        if ident.is_empty() {
+            return Err(self.unexpected());
+        }
+
+        Ok(ident)
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, SelectorError> {
+        let mut value = String::new();
+
+        loop {
+            match self.bump() {
+                Some('\\') => value.push(self.bump().ok_or_else(|| self.unexpected())?),
+                Some(c) if c == quote => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// Parses comma separated selectors, which must be compound selectors if `compound`
+    fn list(&mut self, compound: bool) -> Result<Vec<Parsed>, SelectorError> {
+        let mut selectors = Vec::new();
+
+        loop {
+            self.whitespace();
+            selectors.push(self.complex(compound)?);
+
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') if compound => return Ok(selectors),
+                None if !compound => return Ok(selectors),
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn complex(&mut self, compound: bool) -> Result<Parsed, SelectorError> {
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = Vec::new();
+
+        loop {
+            let space = self.whitespace();
+
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::NextSibling,
+                Some('~') => Combinator::SubsequentSibling,
+                Some(',' | ')') | None => return Ok((compounds, combinators)),
+                Some(_) if space => Combinator::Descendant,
+                Some(_) => return Err(self.unexpected()),
+            };
+
+            if compound {
+                return Err(self.unexpected());
+            }
+
+            if self.isolated {
+                return Err(self.error(SelectorErrorKind::NeedsContext));
+            }
+
+            if combinator != Combinator::Descendant {
+                self.pos += 1;
+                self.whitespace();
+            }
+
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let mut compound = Compound::default();
+        let start = self.pos;
+
+        if self.peek() == Some('*') {
+            self.pos += 1;
+        } else if self
+            .peek()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '\\' || !c.is_ascii())
+        {
+            compound.tag = Some(self.ident()?);
+        }
+
+        while let Some(c) = self.peek() {
+            match c {
+                '#' => {
+                    self.pos += 1;
+                    compound.id = Some(self.ident()?);
+                }
+                '.' => {
+                    self.pos += 1;
+                    compound.classes.push(self.ident()?);
+                }
+                '[' => {
+                    self.pos += 1;
+                    compound.attrs.push(self.attr()?);
+                }
+                ':' => {
+                    let start = self.pos;
+                    self.pos += 1;
+                    let pseudo = self.pseudo()?;
+
+                    if self.isolated && pseudo.is_structural() {
+                        return Err(SelectorError {
+                            position: start,
+                            kind: SelectorErrorKind::NeedsContext,
+                        });
+                    }
+
+                    compound.pseudos.push(pseudo);
+                }
+                _ => break,
+            }
+        }
+
+        if self.pos == start {
+            return Err(self.unexpected());
+        }
+
+        Ok(compound)
+    }
+
+    fn attr(&mut self) -> Result<AttrSelector, SelectorError> {
+        self.whitespace();
+        let name = self.ident()?;
+        self.whitespace();
+
+        let operator = match self.peek() {
+            Some(']') => {
+                self.pos += 1;
+
+                return Ok(AttrSelector {
+                    name,
+                    operator: Operator::Exists,
+                    value: String::new(),
+                    ignore_case: false,
+                });
+            }
+            Some('=') => Operator::Equals,
+            Some('~') => Operator::Includes,
+            Some('|') => Operator::DashMatch,
+            Some('^') => Operator::Prefix,
+            Some('$') => Operator::Suffix,
+            Some('*') => Operator::Substring,
+            _ => return Err(self.unexpected()),
+        };
+
+        self.pos += 1;
+
+        if operator != Operator::Equals {
+            self.expect('=')?;
+        }
+
+        self.whitespace();
+
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                self.string(quote)?
+            }
+            _ => self.ident()?,
+        };
+
+        self.whitespace();
+
+        let ignore_case = match self.peek() {
+            Some('i' | 'I') => true,
+            Some('s' | 'S') => false,
+            _ => {
+                self.expect(']')?;
+
+                return Ok(AttrSelector {
+                    name,
+                    operator,
+                    value,
+                    ignore_case: false,
+                });
+            }
+        };
+
+        self.pos += 1;
+        self.whitespace();
+        self.expect(']')?;
+
+        Ok(AttrSelector {
+            name,
+            operator,
+            value,
+            ignore_case,
+        })
+    }
+
+    fn pseudo(&mut self) -> Result<Pseudo, SelectorError> {
+        let start = self.pos;
+
+        if self.peek() == Some(':') {
+            return Err(self.error(SelectorErrorKind::Unsupported(String::from("::"))));
+        }
+
+        let name = self.ident()?.to_ascii_lowercase();
+
+        let simple = match name.as_str() {
+            "root" => Some(Pseudo::Root),
+            "empty" => Some(Pseudo::Empty),
+            "first-child" => Some(Pseudo::FirstChild),
+            "last-child" => Some(Pseudo::LastChild),
+            "only-child" => Some(Pseudo::OnlyChild),
+            "first-of-type" => Some(Pseudo::FirstOfType),
+            "last-of-type" => Some(Pseudo::LastOfType),
+            "only-of-type" => Some(Pseudo::OnlyOfType),
+            _ => None,
+        };
+
+        if let Some(pseudo) = simple {
+            return Ok(pseudo);
+        }
+
+        let unsupported = SelectorError {
+            position: start,
+            kind: SelectorErrorKind::Unsupported(name.clone()),
+        };
+
+        if self.peek() != Some('(') {
+            return Err(unsupported);
+        }
+
+        self.pos += 1;
+
+        if name == "not" {
+            let compounds = self
+                .list(true)?
+                .into_iter()
+                .flat_map(|(compounds, _)| compounds)
+                .collect();
+
+            self.expect(')')?;
+            return Ok(Pseudo::Not(compounds));
+        }
+
+        let arg_start = self.pos;
+        let len = self.s[arg_start..].find(')').ok_or(SelectorError {
+            position: self.s.len(),
+            kind: SelectorErrorKind::UnexpectedEnd,
+        })?;
+
+        let nth = Nth::parse(&self.s[arg_start..arg_start + len]).ok_or(SelectorError {
+            position: arg_start,
+            kind: SelectorErrorKind::InvalidNth,
+        })?;
+
+        self.pos = arg_start + len + 1;
+
+        match name.as_str() {
+            "nth-child" => Ok(Pseudo::NthChild(nth)),
+            "nth-last-child" => Ok(Pseudo::NthLastChild(nth)),
+            "nth-of-type" => Ok(Pseudo::NthOfType(nth)),
+            "nth-last-of-type" => Ok(Pseudo::NthLastOfType(nth)),
+            _ => Err(unsupported),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::*;
+
+    fn select(html: &str, selector: &str) -> Vec<String> {
+        let soup = Soup::html_strict(html).expect("Failed to parse HTML");
+
+        soup.select(selector)
+            .expect("Failed to parse selector")
+            .into_iter()
+            .map(|n| n.all_text())
+            .collect()
+    }
+
+    #[test]
+    fn test_select() {
+        let html = r#"<div class="content"><p>Intro <a href="/a">A</a></p><a href="/b">B</a><a>C</a></div><a href="/d">D</a>"#;
+
+        assert_eq!(select(html, "div.content > a[href]"), ["B"]);
+        assert_eq!(select(html, "div a"), ["A", "B", "C"]);
+        assert_eq!(select(html, "p + a"), ["B"]);
+        assert_eq!(select(html, "p ~ a:not([href])"), ["C"]);
+        assert_eq!(select(html, "a[href^='/'][href$=d]"), ["D"]);
+        assert_eq!(select(html, "a:root, p > *"), ["A", "D"]);
+        assert_eq!(select(html, "DIV > A:last-child"), ["C"]);
+
+        let list = "<ul><li>1</li><li>2</li><li>3</li><li>4</li><li>5</li></ul>";
+        assert_eq!(select(list, "li:nth-child(2n+1)"), ["1", "3", "5"]);
+        assert_eq!(select(list, "li:nth-child(-n + 2)"), ["1", "2"]);
+        assert_eq!(select(list, "li:nth-last-child(odd)"), ["1", "3", "5"]);
+        assert_eq!(select(list, "li:nth-of-type(4)"), ["4"]);
+        assert_eq!(select(list, "li:only-child"), Vec::<String>::new());
+
+        let classes = r#"<p class="md:flex a" lang="en-US">X</p><p lang="EN">Y</p><p></p>"#;
+        assert_eq!(select(classes, r"p.md\:flex"), ["X"]);
+        assert_eq!(select(classes, "[lang|=en]"), ["X"]);
+        assert_eq!(select(classes, "[lang='en' i]"), ["Y"]);
+        assert_eq!(select(classes, "[class~=a]"), ["X"]);
+        assert_eq!(select(classes, "p:empty").len(), 1);
+    }
+
+    #[test]
+    fn test_scope_and_filter() {
+        let soup = Soup::html_strict("<div><p><b>1</b></p></div><section><b>2</b></section>")
+            .expect("Failed to parse HTML");
+
+        let div = soup.tag("div").first().expect("Couldn't find div");
+        let scoped = div.query_ref().select("div b").expect("Failed to select");
+        assert!(scoped.is_empty());
+        assert_eq!(soup.select("p > b").map(|s| s.len()), Ok(1));
+        assert_eq!(soup.strict().select("*").map(|s| s.len()), Ok(2));
+
+        let selector = Selector::parse("section, div b").expect("Failed to parse selector");
+        assert_eq!(soup.filter(selector).all().count(), 1);
+
+        let id = soup
+            .select("section > b")
+            .expect("Failed to select")
+            .iter()
+            .next()
+            .map(QueryItem::id);
+        assert_eq!(id, soup.tag("b").all().last().map(|b| b.id()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |s| Selector::parse(s).err().map(|e| (e.position, e.kind));
+
+        assert_eq!(error("a[href"), Some((6, SelectorErrorKind::UnexpectedEnd)));
+        assert_eq!(error("a >"), Some((3, SelectorErrorKind::UnexpectedEnd)));
+        assert_eq!(
+            error("a::before"),
+            Some((2, SelectorErrorKind::Unsupported("::".into())))
+        );
+        assert_eq!(
+            error("p:hover"),
+            Some((2, SelectorErrorKind::Unsupported("hover".into())))
+        );
+        assert_eq!(
+            error("li:nth-child(x)"),
+            Some((13, SelectorErrorKind::InvalidNth))
+        );
+        assert_eq!(
+            error("a,,b"),
+            Some((2, SelectorErrorKind::UnexpectedCharacter(',')))
+        );
+        assert_eq!(
+            error(":not(a b)"),
+            Some((7, SelectorErrorKind::UnexpectedCharacter('b')))
+        );
+        let isolated = |s| {
+            Selector::parse_isolated(s)
+                .err()
+                .map(|e| (e.position, e.kind))
+        };
+        assert_eq!(isolated("a:empty, [x]"), None);
+        assert_eq!(isolated("p a"), Some((2, SelectorErrorKind::NeedsContext)));
+        assert_eq!(
+            isolated("p:not(:first-child)"),
+            Some((6, SelectorErrorKind::NeedsContext))
+        );
+
+        assert_eq!(
+            Selector::parse("a >").map_err(|e| e.to_string()),
+            Err("unexpected end of selector".into())
+        );
+    }
+
+    #[cfg(feature = "html-loose")]
+    #[test]
+    fn test_select_deep() {
+        let html = "<div>".repeat(100_000);
+        let soup = Soup::html_loose(&html);
+
+        let selection = soup.select("div > div").expect("Failed to parse selector");
+        assert_eq!(selection.iter().count(), 99_999);
+
+        let selection = soup
+            .select("body div div ~ p, div + div")
+            .expect("Failed to parse selector");
+        assert_eq!(selection.iter().count(), 0);
+
+        // Dropping a tree this deep recurses once per level, so take it apart from the top
+        let mut nodes = soup.nodes;
+        while let Some(mut node) = nodes.pop() {
+            if let parser::HTMLNode::Element { children, .. } = &mut node {
+                nodes.append(children);
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use alloc::{
+    collections::BTreeMap,
+    vec::Vec,
+};
+
+use crate::{
+    fingerprint::Fnv,
+    query::QueryItem,
+    Node,
+    Soup,
+};
+
+/// What goes into the shingles compared by [`Shingles::similarity`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShingleOptions {
+    /// Number of element names in each shingle: the element and up to `depth - 1` ancestors
+    pub depth: usize,
+
+    /// Include the `class` attribute of each element, which often identifies template regions
+    pub classes: bool,
+
+    /// Include a shingle for each text node, so pages with the same layout but different
+    /// content are told apart
+    pub text: bool,
+}
+
+impl Default for ShingleOptions {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            classes: true,
+            text: false,
+        }
+    }
+}
+
+/// A summary of a tree's structure, for comparing trees without keeping them around
+///
+/// Each element contributes one shingle: the hash of its name and the names of its nearest
+/// ancestors. Trees are compared by the weighted Jaccard similarity of their shingle counts.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, similarity::{ShingleOptions, Shingles}};
+/// let options = ShingleOptions::default();
+/// let shingles = |html| Shingles::of_soup(&Soup::html_strict(html).unwrap(), &options);
+///
+/// let article = shingles("<main><h1>A</h1><p>One</p><p>Two</p></main>");
+/// let redesign = shingles("<main><h1>B</h1><p>One</p><p>Two</p><aside>Ad</aside></main>");
+/// let listing = shingles("<ul><li>A</li><li>B</li><li>C</li></ul>");
+///
+/// assert!(article.similarity(&redesign) > 0.7);
+/// assert_eq!(article.similarity(&listing), 0.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Shingles {
+    counts: BTreeMap<u64, usize>,
+}
+
+impl Shingles {
+    /// Computes the shingles of a whole document
+    #[must_use]
+    pub fn of_soup<N>(soup: &Soup<N>, options: &ShingleOptions) -> Self
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let mut shingles = Self::default();
+        let mut path = Vec::new();
+
+        for node in &soup.nodes {
+            shingles.add(node, &mut path, options);
+        }
+
+        shingles
+    }
+
+    /// Computes the shingles of `node` and everything inside it
+    #[must_use]
+    pub fn of_node<N>(node: &N, options: &ShingleOptions) -> Self
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let mut shingles = Self::default();
+        shingles.add(node, &mut Vec::new(), options);
+        shingles
+    }
+
+    fn add<'a, N>(&mut self, node: &'a N, path: &mut Vec<&'a str>, options: &ShingleOptions)
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let mut fnv = Fnv::new();
+
+        if let Some(name) = node.name() {
+            path.push(name.as_ref());
+
+            fnv.write(b"<");
+            for name in path.iter().rev().take(options.depth.max(1)) {
+                fnv.write_str(name);
+            }
+
+            if options.classes {
+                fnv.write_str(node.get_str("class").unwrap_or_default());
+            }
+
+            *self.counts.entry(fnv.0).or_default() += 1;
+
+            for child in node.children() {
+                self.add(child, path, options);
+            }
+
+            path.pop();
+        } else if let Some(text) = node.text().filter(|_| options.text) {
+            let text = text.as_ref().trim();
+
+            if !text.is_empty() {
+                fnv.write(b"#");
+                fnv.write_str(text);
+
+                *self.counts.entry(fnv.0).or_default() += 1;
+            }
+        }
+    }
+
+    /// Number of shingles, counting repeats
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Returns `true` if the tree had no elements
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Similarity between the two trees, from `0.0` (nothing in common) to `1.0` (same shingles)
+    ///
+    /// Two empty trees are identical.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn similarity(&self, other: &Shingles) -> f64 {
+        let (mut shared, mut total) = (0, 0);
+
+        for (hash, &count) in &self.counts {
+            let other = other.counts.get(hash).copied().unwrap_or_default();
+            shared += count.min(other);
+            total += count.max(other);
+        }
+
+        total += other
+            .counts
+            .iter()
+            .filter(|(hash, _)| !self.counts.contains_key(hash))
+            .map(|(_, count)| count)
+            .sum::<usize>();
+
+        if total == 0 {
+            1.0
+        } else {
+            shared as f64 / total as f64
+        }
+    }
+}
+
+impl<N> Soup<N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Structural similarity to `other`, from `0.0` to `1.0`
+    ///
+    /// When comparing many documents, compute each one's [`Shingles`] once instead.
+    #[must_use]
+    pub fn similarity(&self, other: &Soup<N>, options: &ShingleOptions) -> f64 {
+        Shingles::of_soup(self, options).similarity(&Shingles::of_soup(other, options))
+    }
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Structural similarity of the item's subtree to `other`'s, from `0.0` to `1.0`
+    #[must_use]
+    pub fn similarity(&self, other: &QueryItem<'_, N>, options: &ShingleOptions) -> f64 {
+        Shingles::of_node(self.node(), options)
+            .similarity(&Shingles::of_node(other.node(), options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_similarity() {
+        let soup = |html| Soup::html_strict(html).expect("Failed to parse HTML");
+        let options = ShingleOptions::default();
+
+        let a = soup(
+            r#"<div class="card"><h2>A</h2><p>x</p></div><div class="card"><h2>B</h2><p>y</p></div>"#,
+        );
+        let b = soup(r#"<div class="card"><h2>C</h2><p>z</p></div>"#);
+        let c = soup(r#"<div class="tile"><h2>C</h2><p>z</p></div>"#);
+
+        assert!((a.similarity(&a, &options) - 1.0).abs() < f64::EPSILON);
+        assert!((a.similarity(&b, &options) - 0.5).abs() < f64::EPSILON);
+        assert!((a.similarity(&c, &options) - 2.0 / 7.0).abs() < f64::EPSILON);
+
+        let no_classes = ShingleOptions {
+            classes: false,
+            ..options
+        };
+        assert!((b.similarity(&c, &no_classes) - 1.0).abs() < f64::EPSILON);
+
+        let text = ShingleOptions {
+            text: true,
+            ..no_classes
+        };
+        assert!((b.similarity(&c, &text) - 1.0).abs() < f64::EPSILON);
+        assert!(b.similarity(&soup("<div><h2>D</h2><p>z</p></div>"), &text) < 1.0);
+
+        let cards = a.tag("div").all().collect::<Vec<_>>();
+        assert!((cards[0].similarity(&cards[1], &options) - 1.0).abs() < f64::EPSILON);
+        assert_eq!(Shingles::of_soup(&a, &options).len(), 6);
+        assert!(Shingles::default().similarity(&Shingles::default()) > 0.0);
+    }
+}
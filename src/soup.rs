@@ -1,16 +1,108 @@
+use alloc::vec::Vec;
+use core::fmt;
+
 use crate::{
     parser::Parser,
     query::{
+        NodeId,
+        PositionIter,
         QueryItem,
         QueryIter,
+        Root,
+        TextMatch,
     },
     Node,
+    ParentIter,
+    TextPattern,
 };
 
 /// Parsed nodes
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Soup<N = ()> {
     pub(crate) nodes: Vec<N>,
+    pub(crate) sizes: Sizes,
+}
+
+impl<N> Soup<N> {
+    pub(crate) fn from_nodes(nodes: Vec<N>) -> Self {
+        Self {
+            nodes,
+            sizes: Sizes::default(),
+        }
+    }
+
+    /// The whole document, for resolving ids
+    pub(crate) fn root(&self) -> Root<'_, N> {
+        Root::new(&self.nodes, Some(&self.sizes))
+    }
+}
+
+impl<N> fmt::Debug for Soup<N>
+where
+    N: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Soup")
+            .field("nodes", &self.nodes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Number of nodes in the subtree of every node of a document, in document order
+///
+/// Built the first time an id is resolved, so later lookups skip whole subtrees instead of
+/// walking them. Without `std` there is no cache, and subtrees are counted as needed.
+#[derive(Clone, Default)]
+pub(crate) struct Sizes {
+    #[cfg(feature = "std")]
+    sizes: std::sync::OnceLock<Vec<usize>>,
+}
+
+impl Sizes {
+    /// Returns the subtree sizes of `nodes`, which must be the document the cache belongs to
+    #[cfg_attr(feature = "std", allow(clippy::unnecessary_wraps))]
+    #[cfg_attr(not(feature = "std"), allow(clippy::unused_self))]
+    pub(crate) fn get<N>(&self, nodes: &[N]) -> Option<&[usize]>
+    where
+        N: Node,
+    {
+        #[cfg(feature = "std")]
+        {
+            Some(self.sizes.get_or_init(|| subtree_sizes(nodes)))
+        }
+
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = nodes;
+            None
+        }
+    }
+
+    /// Forgets the sizes, for when nodes are added or removed
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(feature = "std")]
+fn subtree_sizes<N>(nodes: &[N]) -> Vec<usize>
+where
+    N: Node,
+{
+    let mut sizes = Vec::new();
+    // Document index of each open node, with its remaining children
+    let mut stack = alloc::vec![(None, nodes.iter())];
+
+    while let Some((_, top)) = stack.last_mut() {
+        if let Some(node) = top.next() {
+            stack.push((Some(sizes.len()), node.children().iter()));
+            sizes.push(1);
+        } else if let Some((Some(index), _)) = stack.pop() {
+            sizes[index] = sizes.len() - index;
+        }
+    }
+
+    sizes
 }
 
 #[cfg(feature = "html-strict")]
@@ -22,11 +114,66 @@ impl Soup {
     pub fn html_strict(
         text: &str,
     ) -> Result<
-        Soup<<crate::parser::StrictHTMLParser as Parser>::Node>,
-        <crate::parser::StrictHTMLParser as Parser>::Error,
+        Soup<<crate::parser::StrictHTMLParser<'_> as Parser>::Node>,
+        <crate::parser::StrictHTMLParser<'_> as Parser>::Error,
     > {
         Soup::new::<crate::parser::StrictHTMLParser>(text)
     }
+
+    /// Attempts to create a new `Soup` instance from a string slice, with parser options.
+    ///
+    /// # Errors
+    /// If the text is invalid HTML, or nested deeper than the options allow.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::StrictHTMLOptions, prelude::*};
+    /// let text = "<!DOCTYPE html PUBLIC><p>Legacy</p>";
+    /// let soup = Soup::html_strict(text).unwrap();
+    /// assert_eq!(soup.tag("p").first().map(|p| p.all_text()), Some("Legacy".into()));
+    ///
+    /// let options = StrictHTMLOptions { sloppy_doctype: false, ..Default::default() };
+    /// assert!(Soup::html_strict_with(text, options).is_err());
+    /// ```
+    pub fn html_strict_with(
+        text: &str,
+        options: crate::parser::StrictHTMLOptions,
+    ) -> Result<
+        Soup<<crate::parser::StrictHTMLParser<'_> as Parser>::Node>,
+        crate::parser::StrictHTMLError<'_>,
+    > {
+        Ok(Soup::from_nodes(
+            crate::parser::StrictHTMLParser::parse_with(text, options)?,
+        ))
+    }
+
+    /// Attempts to create a new `Soup` instance that owns its text from a string.
+    ///
+    /// Unlike [`Soup::html_strict`], the resulting `Soup` does not borrow from `text`.
+    ///
+    /// # Errors
+    /// If the text is invalid HTML.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = {
+    ///     let text = String::from("<p>Hello!</p>");
+    ///     Soup::html_strict_owned(text).unwrap()
+    /// };
+    /// assert_eq!(soup.tag("p").first().map(|p| p.all_text()), Some("Hello!".into()));
+    /// ```
+    pub fn html_strict_owned<S>(
+        text: S,
+    ) -> Result<
+        Soup<<crate::parser::OwnedStrictHTMLParser<S> as Parser>::Node>,
+        <crate::parser::OwnedStrictHTMLParser<S> as Parser>::Error,
+    >
+    where
+        S: AsRef<str>,
+    {
+        Soup::new::<crate::parser::OwnedStrictHTMLParser<S>>(text)
+    }
 }
 
 #[cfg(feature = "html-lenient")]
@@ -40,6 +187,170 @@ impl Soup {
     {
         Soup::new::<crate::parser::LenientHTMLParser<S>>(text).unwrap()
     }
+
+    /// Creates a new `Soup` instance from a string slice, with parser options.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::LenientHTMLOptions, prelude::*};
+    /// let text = r#"<noscript><a href="/basic">Basic version</a></noscript>"#;
+    /// assert!(Soup::html(text).tag("a").first().is_none());
+    ///
+    /// let options = LenientHTMLOptions { disable_scripting: true };
+    /// let soup = Soup::html_with(text, options);
+    /// assert_eq!(soup.tag("a").first().and_then(|a| a.get("href").cloned()), Some("/basic".into()));
+    /// ```
+    #[must_use]
+    pub fn html_with<S>(
+        text: S,
+        options: crate::parser::LenientHTMLOptions,
+    ) -> Soup<<crate::parser::LenientHTMLParser<S> as Parser>::Node>
+    where
+        S: AsRef<str>,
+    {
+        Soup::from_nodes(crate::parser::LenientHTMLParser::parse_with(text, options))
+    }
+
+    /// Creates a new `Soup` instance from raw bytes and the value of a `Content-Type` header.
+    ///
+    /// The encoding is taken from a byte order mark, then the header's `charset` parameter,
+    /// then a `<meta>` declaration near the start of the document, falling back to UTF-8.
+    /// UTF-8, UTF-16 and windows-1252 (which covers `iso-8859-1` and `us-ascii`) are supported;
+    /// other labels are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_with_content_type(b"<p>caf\xe9</p>", Some("text/html; charset=iso-8859-1"));
+    /// assert_eq!(soup.tag("p").first().map(|p| p.all_text()), Some("caf\u{e9}".into()));
+    /// ```
+    #[must_use]
+    pub fn html_with_content_type(
+        bytes: &[u8],
+        content_type: Option<&str>,
+    ) -> Soup<<crate::parser::LenientHTMLParser<String> as Parser>::Node> {
+        Soup::html(crate::parser::html::charset::decode(bytes, content_type))
+    }
+}
+
+#[cfg(feature = "html-loose")]
+impl Soup {
+    /// Creates a new `Soup` instance from a string slice using the [`LooseHTMLParser`](crate::parser::LooseHTMLParser).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_loose("<ul><li>One<li>Two</ul>");
+    /// assert_eq!(soup.tag("li").all().count(), 2);
+    /// ```
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn html_loose(text: &str) -> Soup<<crate::parser::LooseHTMLParser<'_> as Parser>::Node> {
+        Soup::new::<crate::parser::LooseHTMLParser>(text).unwrap()
+    }
+
+    /// Creates a new `Soup` instance like [`Soup::html_loose`], stopping early if `token` is
+    /// cancelled
+    ///
+    /// # Errors
+    /// If `token` is cancelled before parsing finishes.
+    pub fn html_loose_cancellable<'a>(
+        text: &'a str,
+        token: &crate::cancel::CancelToken,
+    ) -> Result<Soup<crate::parser::HTMLNode<&'a str>>, crate::cancel::Cancelled> {
+        Ok(Soup::from_nodes(
+            crate::parser::LooseHTMLParser::parse_cancellable(text, token)?,
+        ))
+    }
+}
+
+#[cfg(feature = "json")]
+impl Soup {
+    /// Attempts to create a new `Soup` instance from JSON text.
+    ///
+    /// Object members are named by their key, so they can be found with [`Queryable::tag`](crate::Queryable::tag).
+    ///
+    /// # Errors
+    /// If the text is invalid JSON.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::json(r#"{"product": {"name": "Widget", "price": 12.5}}"#).unwrap();
+    /// let price = soup.tag("price").first().and_then(|p| p.text().cloned());
+    /// assert_eq!(price, Some("12.5".into()));
+    /// ```
+    pub fn json<S>(
+        text: S,
+    ) -> Result<
+        Soup<<crate::parser::JSONParser<S> as Parser>::Node>,
+        <crate::parser::JSONParser<S> as Parser>::Error,
+    >
+    where
+        S: AsRef<str>,
+    {
+        Soup::new::<crate::parser::JSONParser<S>>(text)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Soup {
+    /// Attempts to create a new `Soup` instance from TOML text.
+    ///
+    /// Tables and their keys are mapped like JSON objects, so a TOML file can be queried the
+    /// same way as [`Soup::json`].
+    ///
+    /// # Errors
+    /// If the text is invalid TOML.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::toml("[server]\nhost = \"example.com\"\nport = 8_080").unwrap();
+    /// let port = soup.tag("port").first().and_then(|p| p.text().cloned());
+    /// assert_eq!(port, Some("8080".into()));
+    /// ```
+    pub fn toml<S>(
+        text: S,
+    ) -> Result<
+        Soup<<crate::parser::TOMLParser<S> as Parser>::Node>,
+        <crate::parser::TOMLParser<S> as Parser>::Error,
+    >
+    where
+        S: AsRef<str>,
+    {
+        Soup::new::<crate::parser::TOMLParser<S>>(text)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Soup {
+    /// Attempts to create a new `Soup` instance from YAML text.
+    ///
+    /// Mappings and their keys are mapped like JSON objects, so a YAML file can be queried the
+    /// same way as [`Soup::json`]. Each document of the stream is a top-level node.
+    ///
+    /// # Errors
+    /// If the text is invalid YAML, or uses anchors, aliases, or tags.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::yaml("server:\n  host: example.com\n  ports: [80, 443]").unwrap();
+    /// let ports = soup.tag("ports").first().map(|p| p.all_text());
+    /// assert_eq!(ports, Some("80\n443".into()));
+    /// ```
+    pub fn yaml<S>(
+        text: S,
+    ) -> Result<
+        Soup<<crate::parser::YAMLParser<S> as Parser>::Node>,
+        <crate::parser::YAMLParser<S> as Parser>::Error,
+    >
+    where
+        S: AsRef<str>,
+    {
+        Soup::new::<crate::parser::YAMLParser<S>>(text)
+    }
 }
 
 #[cfg(feature = "xml")]
@@ -56,6 +367,33 @@ impl Soup {
     > {
         Soup::new::<crate::parser::XMLParser<R>>(reader)
     }
+
+    /// Creates a new `Soup` instance from a reader, with parser options.
+    ///
+    /// # Errors
+    /// If the text is invalid XML.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{parser::XMLOptions, prelude::*};
+    /// let text = "<doc>&company; &trade;</doc>";
+    /// assert!(Soup::xml(text.as_bytes()).is_err());
+    ///
+    /// let options = XMLOptions::new().with_entity("company", "ACME").with_entity("trade", "\u{2122}");
+    /// let soup = Soup::xml_with(text.as_bytes(), &options).unwrap();
+    /// assert_eq!(soup.tag("doc").first().map(|d| d.all_text()), Some("ACME \u{2122}".into()));
+    /// ```
+    pub fn xml_with<R: std::io::Read>(
+        reader: R,
+        options: &crate::parser::XMLOptions,
+    ) -> Result<
+        Soup<<crate::parser::XMLParser<R> as Parser>::Node>,
+        <crate::parser::XMLParser<R> as Parser>::Error,
+    > {
+        Ok(Soup::from_nodes(crate::parser::XMLParser::parse_with(
+            reader, options,
+        )?))
+    }
 }
 
 impl Soup {
@@ -64,9 +402,7 @@ impl Soup {
     /// # Errors
     /// If the text has an invalid format.
     pub fn new<P: Parser>(input: P::Input) -> Result<Soup<P::Node>, P::Error> {
-        Ok(Soup {
-            nodes: P::parse(input)?,
-        })
+        Ok(Soup::from_nodes(P::parse(input)?))
     }
 }
 
@@ -76,8 +412,260 @@ where
 {
     /// Query the data.
     #[must_use]
-    pub fn iter(&self) -> QueryIter<N, ()> {
-        QueryIter::new(&self.nodes, true, ())
+    pub fn iter(&self) -> QueryIter<'_, N, ()> {
+        QueryIter::new(&self.nodes, self.root(), 0, true, ())
+    }
+
+    /// Depth-first iterator over every node, each paired with its parent
+    ///
+    /// Top-level nodes are paired with `None`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<p>One</p><p><b>Two</b></p>").unwrap();
+    ///
+    /// let in_p = soup
+    ///     .with_parents()
+    ///     .filter(|(_, parent)| parent.and_then(|p| p.name()) == Some(&"p"))
+    ///     .count();
+    /// assert_eq!(in_p, 2);
+    /// ```
+    #[must_use]
+    pub fn with_parents(&self) -> ParentIter<'_, N> {
+        ParentIter::new(&self.nodes)
+    }
+
+    /// Number of levels of the deepest node, counting top-level nodes as one level
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<p>One</p><div><p>Two <b>2</b></p></div>").unwrap();
+    /// assert_eq!(soup.depth(), 4);
+    /// ```
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        let mut stack = alloc::vec![self.nodes.iter()];
+        let mut depth = 0;
+
+        while let Some(top) = stack.last_mut() {
+            if let Some(node) = top.next() {
+                depth = depth.max(stack.len());
+                stack.push(node.children().iter());
+            } else {
+                stack.pop();
+            }
+        }
+
+        depth
+    }
+
+    /// Checks that no node is nested more than `max_depth` levels deep
+    ///
+    /// Walking a tree by recursion, as [`Node::descendants`] users and many analyses do, uses
+    /// stack space for every level. Checking first protects against documents crafted to
+    /// exhaust the stack.
+    ///
+    /// # Errors
+    /// If a node is nested too deep.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// # #[cfg(feature = "html-loose")] {
+    /// let html = "<div>".repeat(1000);
+    /// let soup = Soup::html_loose(&html);
+    ///
+    /// let error = soup.check_depth(256).unwrap_err();
+    /// assert_eq!(error.to_string(), "node 256 is nested deeper than 256 levels");
+    /// # }
+    /// ```
+    pub fn check_depth(&self, max_depth: usize) -> Result<(), DepthError> {
+        let mut stack = alloc::vec![self.nodes.iter()];
+        let mut index = 0;
+
+        while let Some(top) = stack.last_mut() {
+            if let Some(node) = top.next() {
+                if stack.len() > max_depth {
+                    return Err(DepthError {
+                        node: NodeId(index),
+                        max_depth,
+                    });
+                }
+
+                index += 1;
+                stack.push(node.children().iter());
+            } else {
+                stack.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a [`NodeId`](crate::query::NodeId) obtained from this `Soup`.
+    ///
+    /// The first lookup records the size of every subtree. Later lookups skip whole subtrees,
+    /// only visiting the siblings of the node and of its ancestors.
+    #[must_use]
+    pub fn get(&self, id: NodeId) -> Option<QueryItem<'_, N>> {
+        self.root().get(id)
+    }
+
+    /// Query the data, along with the [`Position`](crate::query::Position) of each node.
+    #[must_use]
+    pub fn with_positions(&self) -> PositionIter<'_, N, ()> {
+        PositionIter::new(&self.nodes, self.root(), 0, true, ())
+    }
+}
+
+impl<N> Soup<N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Searches the text of the document, returning every match in document order
+    ///
+    /// Each text node is searched separately, so matches never span elements.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::prelude::*;
+    /// let soup = Soup::html_strict("<p>Crème brûlée</p><p>Brûlée again</p>").unwrap();
+    ///
+    /// let matches = soup.find_text("rûlée");
+    /// assert_eq!(matches.len(), 2);
+    /// assert_eq!((matches[0].offset, matches[0].range.clone()), (7, 8..15));
+    ///
+    /// let node = soup.get(matches[1].node).unwrap();
+    /// assert_eq!(node.text(), Some(&"Brûlée again"));
+    /// ```
+    #[must_use]
+    pub fn find_text<P>(&self, pattern: &P) -> Vec<TextMatch<'_>>
+    where
+        P: TextPattern + ?Sized,
+    {
+        let mut matches = Vec::new();
+
+        for item in self {
+            let Some(text) = item.node().text() else {
+                continue;
+            };
+            let text = text.as_ref();
+
+            let mut offset = 0;
+            let mut prev = 0;
+
+            for range in pattern.find_in(text) {
+                offset += text[prev..range.start].chars().count();
+                prev = range.start;
+
+                matches.push(TextMatch {
+                    node: item.id(),
+                    offset,
+                    text: &text[range.clone()],
+                    range,
+                });
+            }
+        }
+
+        matches
+    }
+}
+
+/// Error returned by [`Soup::check_depth`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepthError {
+    /// The first node found below the limit, which can be resolved with [`Soup::get`]
+    pub node: NodeId,
+
+    /// The limit that was exceeded
+    pub max_depth: usize,
+}
+
+impl fmt::Display for DepthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node {} is nested deeper than {} levels",
+            self.node.index(),
+            self.max_depth
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DepthError {}
+
+/// Borrowed view over parsed nodes
+///
+/// Queryable like a [`Soup`], but does not own its nodes.
+#[derive(Debug)]
+pub struct SoupRef<'x, N> {
+    pub(crate) nodes: &'x [N],
+    /// The whole document, which ids index into
+    pub(crate) root: Root<'x, N>,
+    /// Document index of the first of `nodes`
+    pub(crate) offset: usize,
+}
+
+impl<N> Copy for SoupRef<'_, N> {}
+
+impl<N> Clone for SoupRef<'_, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N> Soup<N> {
+    /// Borrow the `Soup` as a [`SoupRef`] view.
+    #[must_use]
+    pub fn view(&self) -> SoupRef<'_, N> {
+        SoupRef {
+            nodes: &self.nodes,
+            root: self.root(),
+            offset: 0,
+        }
+    }
+}
+
+impl<'x, N> From<&'x Soup<N>> for SoupRef<'x, N> {
+    fn from(soup: &'x Soup<N>) -> Self {
+        soup.view()
+    }
+}
+
+impl<'x, N> From<&'x [N]> for SoupRef<'x, N> {
+    fn from(nodes: &'x [N]) -> Self {
+        SoupRef {
+            nodes,
+            root: Root::new(nodes, None),
+            offset: 0,
+        }
+    }
+}
+
+impl<'x, N> SoupRef<'x, N>
+where
+    N: Node,
+{
+    /// Query the data.
+    #[must_use]
+    pub fn iter(self) -> QueryIter<'x, N, ()> {
+        QueryIter::new(self.nodes, self.root, self.offset, true, ())
+    }
+}
+
+impl<'x, N> IntoIterator for SoupRef<'x, N>
+where
+    N: Node,
+{
+    type Item = QueryItem<'x, N>;
+    type IntoIter = QueryIter<'x, N, ()>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
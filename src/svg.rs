@@ -0,0 +1,360 @@
+use alloc::vec::Vec;
+
+use crate::{
+    Node,
+    Soup,
+};
+
+/// The `viewBox` of an `svg`, `symbol`, `marker`, or `pattern` element
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewBox {
+    /// Left edge, in user units
+    pub min_x: f64,
+    /// Top edge, in user units
+    pub min_y: f64,
+    /// Width, in user units
+    pub width: f64,
+    /// Height, in user units
+    pub height: f64,
+}
+
+impl ViewBox {
+    /// Parses four numbers separated by whitespace or commas, like `0 0 24 24`
+    ///
+    /// Returns `None` if the width or height is negative.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut numbers = s
+            .split(|c: char| c == ',' || c.is_ascii_whitespace())
+            .filter(|n| !n.is_empty())
+            .map(str::parse::<f64>);
+
+        let mut next = || numbers.next()?.ok().filter(|n| n.is_finite());
+
+        let view_box = Self {
+            min_x: next()?,
+            min_y: next()?,
+            width: next()?,
+            height: next()?,
+        };
+
+        (next().is_none() && view_box.width >= 0.0 && view_box.height >= 0.0).then_some(view_box)
+    }
+
+    /// Reads the `viewBox` attribute of `node`
+    ///
+    /// Parsers that lowercase attribute names store it as `viewbox`, which is also checked.
+    #[must_use]
+    pub fn of<N>(node: &N) -> Option<Self>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        Self::parse(
+            node.get_str("viewBox")
+                .or_else(|| node.get_str("viewbox"))?,
+        )
+    }
+
+    /// Width divided by height, or `None` if the height is zero
+    #[must_use]
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        (self.height != 0.0).then(|| self.width / self.height)
+    }
+}
+
+/// Unit of a [`Length`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// No unit, meaning user units
+    User,
+    /// `px`
+    Px,
+    /// `in`
+    In,
+    /// `cm`
+    Cm,
+    /// `mm`
+    Mm,
+    /// `pt`
+    Pt,
+    /// `pc`
+    Pc,
+    /// `em`, relative to the font size
+    Em,
+    /// `ex`, relative to the height of a lowercase letter
+    Ex,
+    /// `%`, relative to the viewport
+    Percent,
+}
+
+const UNITS: [(&str, Unit); 9] = [
+    ("px", Unit::Px),
+    ("in", Unit::In),
+    ("cm", Unit::Cm),
+    ("mm", Unit::Mm),
+    ("pt", Unit::Pt),
+    ("pc", Unit::Pc),
+    ("em", Unit::Em),
+    ("ex", Unit::Ex),
+    ("%", Unit::Percent),
+];
+
+/// A length with its unit, like the `width` of an `svg` element
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Length {
+    /// The number
+    pub value: f64,
+    /// The unit the number is in
+    pub unit: Unit,
+}
+
+impl Length {
+    /// Parses a number followed by an optional unit, like `24`, `1.5em`, or `100%`
+    ///
+    /// Units are matched ignoring ASCII case.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_ascii_lowercase();
+
+        let (value, unit) = UNITS
+            .iter()
+            .find_map(|&(suffix, unit)| Some((s.strip_suffix(suffix)?, unit)))
+            .unwrap_or((&s, Unit::User));
+
+        Some(Self {
+            value: value.parse().ok().filter(|v: &f64| v.is_finite())?,
+            unit,
+        })
+    }
+
+    /// Reads the attribute `attr` of `node` as a length
+    #[must_use]
+    pub fn of<N>(node: &N, attr: &str) -> Option<Self>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        Self::parse(node.get_str(attr)?)
+    }
+
+    /// Converts to pixels at 96 pixels per inch, treating user units as pixels
+    ///
+    /// Returns `None` for relative units, which depend on fonts or the viewport.
+    #[must_use]
+    pub fn to_px(&self) -> Option<f64> {
+        let scale = match self.unit {
+            Unit::User | Unit::Px => 1.0,
+            Unit::In => 96.0,
+            Unit::Cm => 96.0 / 2.54,
+            Unit::Mm => 96.0 / 25.4,
+            Unit::Pt => 96.0 / 72.0,
+            Unit::Pc => 16.0,
+            Unit::Em | Unit::Ex | Unit::Percent => return None,
+        };
+
+        Some(self.value * scale)
+    }
+}
+
+/// A command of path data, with its arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// The command letter, like `M` or `c`; lowercase letters take relative coordinates
+    pub command: char,
+    /// The numbers following the command, including repeated argument groups
+    pub args: Vec<f64>,
+}
+
+/// Splits path data, like the `d` attribute of a `path` element, into commands
+///
+/// Numbers may be separated by whitespace, commas, signs, or a second decimal point, as in
+/// `M1.5.5-2`. Returns `None` if the data does not start with a command or has an invalid
+/// number or command.
+///
+/// # Example
+/// ```rust
+/// # use soupy::svg;
+/// let segments = svg::parse_path("M0,0 l10-5.5z").unwrap();
+/// assert_eq!(segments[1].command, 'l');
+/// assert_eq!(segments[1].args, [10.0, -5.5]);
+/// assert_eq!(segments.len(), 3);
+/// ```
+#[must_use]
+pub fn parse_path(d: &str) -> Option<Vec<Segment>> {
+    let mut segments = Vec::<Segment>::new();
+    let mut rest = d;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+
+        let Some(c) = rest.chars().next() else {
+            return Some(segments);
+        };
+
+        if c.is_ascii_alphabetic() {
+            if !"MmLlHhVvCcSsQqTtAaZz".contains(c) {
+                return None;
+            }
+
+            segments.push(Segment {
+                command: c,
+                args: Vec::new(),
+            });
+            rest = &rest[1..];
+            continue;
+        }
+
+        let len = number_len(rest);
+        let number = rest[..len].parse().ok()?;
+        segments.last_mut()?.args.push(number);
+        rest = &rest[len..];
+    }
+}
+
+/// Length of the number at the start of `s`
+fn number_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let mut dot = false;
+
+    while let Some(&b) = bytes.get(i) {
+        match b {
+            b'0'..=b'9' => {}
+            b'.' if !dot => dot = true,
+            b'e' | b'E'
+                if bytes
+                    .get(i + 1)
+                    .is_some_and(|b| b.is_ascii_digit() || *b == b'+' || *b == b'-') =>
+            {
+                i += 2;
+                while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                    i += 1;
+                }
+                return i;
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+
+    i
+}
+
+/// Parses the `d` attribute of `node`, like [`parse_path`]
+#[must_use]
+pub fn path_data<N>(node: &N) -> Option<Vec<Segment>>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    parse_path(node.get_str("d")?)
+}
+
+/// The `href` of `node`, or its `xlink:href` for older documents
+#[must_use]
+pub fn href<N>(node: &N) -> Option<&str>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    node.get_str("href").or_else(|| node.get_str("xlink:href"))
+}
+
+/// Finds the element that a `use` element, or anything else with an `href` like `#id`, refers to
+///
+/// Only references within the document are resolved.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, svg};
+/// # #[cfg(feature = "xml")] {
+/// let soup = Soup::xml(r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+///     <defs><symbol id="star" viewBox="0 0 24 24"><path d="M12 2l3 7h7z"/></symbol></defs>
+///     <use xlink:href="#star" width="2em"/>
+/// </svg>"##.as_bytes()).unwrap();
+///
+/// let icon = soup.tag("use").first().unwrap();
+/// let symbol = svg::resolve(&soup, &*icon).unwrap();
+/// assert_eq!(svg::ViewBox::of(symbol).map(|v| v.width), Some(24.0));
+/// assert_eq!(svg::Length::of(&*icon, "width").map(|l| l.unit), Some(svg::Unit::Em));
+/// # }
+/// ```
+#[must_use]
+pub fn resolve<'x, N>(soup: &'x Soup<N>, node: &N) -> Option<&'x N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    let id = href(node)?.strip_prefix('#')?;
+
+    soup.nodes
+        .iter()
+        .flat_map(Node::descendants)
+        .find(|n| n.get_str("id") == Some(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Queryable;
+
+    #[test]
+    fn test_svg() {
+        let soup = Soup::html_strict(
+            r##"<svg viewBox="0,0 100 50" width="2in"><g id="dot"><circle r="1"/></g><use href="#dot"/><use href="other.svg#x"/></svg>"##,
+        )
+        .expect("Failed to parse HTML");
+
+        let svg = soup.tag("svg").first().expect("Couldn't find svg");
+        let view_box = ViewBox::of(&*svg).expect("Missing viewBox");
+        assert_eq!(view_box.aspect_ratio(), Some(2.0));
+        assert_eq!(
+            Length::of(&*svg, "width").and_then(|l| l.to_px()),
+            Some(192.0)
+        );
+
+        let uses = soup.tag("use").all().collect::<Vec<_>>();
+        assert_eq!(
+            resolve(&soup, &*uses[0]).and_then(|n| n.get_str("id")),
+            Some("dot")
+        );
+        assert!(resolve(&soup, &*uses[1]).is_none());
+
+        assert_eq!(ViewBox::parse("0 0 -1 1"), None);
+        assert_eq!(ViewBox::parse("0 0 1"), None);
+        assert_eq!(
+            Length::parse("1e2"),
+            Some(Length {
+                value: 100.0,
+                unit: Unit::User
+            })
+        );
+        assert_eq!(Length::parse("1.5EM").map(|l| l.unit), Some(Unit::Em));
+        assert_eq!(Length::parse("3ex").and_then(|l| l.to_px()), None);
+        assert_eq!(Length::parse("12pt").and_then(|l| l.to_px()), Some(16.0));
+        assert_eq!(Length::parse("5furlongs"), None);
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let segments = parse_path("M1.5.5-2e1 L 3,4 5 6 Z").expect("Failed to parse path");
+        assert_eq!(segments, [
+            Segment {
+                command: 'M',
+                args: alloc::vec![1.5, 0.5, -20.0]
+            },
+            Segment {
+                command: 'L',
+                args: alloc::vec![3.0, 4.0, 5.0, 6.0]
+            },
+            Segment {
+                command: 'Z',
+                args: Vec::new()
+            },
+        ]);
+        assert_eq!(parse_path(""), Some(Vec::new()));
+        assert_eq!(parse_path("10 20"), None);
+        assert_eq!(parse_path("M0 0 X"), None);
+    }
+}
@@ -0,0 +1,236 @@
+use alloc::vec::Vec;
+use core::slice;
+
+use crate::{
+    Node,
+    Soup,
+};
+
+/// What [`transform`] does with a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action<N> {
+    /// Keep the node, transforming its children
+    Keep,
+
+    /// Put the given node in its place, without transforming it further
+    Replace(N),
+
+    /// Put the given node in its place, with the transformed children of the original
+    ///
+    /// Useful for changing an element's name or attributes while still transforming its content.
+    Edit(N),
+
+    /// Drop the node and everything inside it
+    Remove,
+
+    /// Drop the node, but put its transformed children in its place
+    Unwrap,
+}
+
+/// Nodes that can be copied with a different list of children, so trees can be rebuilt
+pub trait Rebuild: Node {
+    /// Copies the node, with `children` instead of its own
+    ///
+    /// Nodes that can't have children ignore `children`.
+    #[must_use]
+    fn with_children(&self, children: Vec<Self>) -> Self;
+}
+
+/// Builds a new tree from `nodes`, calling `f` on each node in document order
+///
+/// The original tree is left untouched, so this works on borrowed trees like
+/// [`Soup::html_strict`] produces. Nodes are only visited if their ancestors were kept or
+/// unwrapped. The tree is traversed with an explicit stack, so deep trees can't overflow the
+/// call stack.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, transform::Action};
+/// let soup = Soup::html_strict(r#"<div><script>x()</script><p><font>Hi</font> <a href="/">there</a></p></div>"#).unwrap();
+///
+/// let clean = soup.transform(|node| match node.name().copied() {
+///     Some("script") => Action::Remove,
+///     Some("font") => Action::Unwrap,
+///     _ => Action::Keep,
+/// });
+///
+/// let div = clean.tag("div").first().unwrap();
+/// assert_eq!((*div).to_string(), r#"<div><p>Hi<a href="/">there</a></p></div>"#);
+/// assert_eq!(soup.tag("script").all().count(), 1);
+/// ```
+pub fn transform<N, F>(nodes: &[N], mut f: F) -> Vec<N>
+where
+    N: Rebuild,
+    F: FnMut(&N) -> Action<N>,
+{
+    /// What becomes of the node being rebuilt
+    enum Output<'a, N> {
+        Keep(&'a N),
+        Edit(N),
+        Unwrap,
+    }
+
+    struct Frame<'a, N> {
+        /// The node being rebuilt; `None` for the top level
+        node: Option<Output<'a, N>>,
+        children: slice::Iter<'a, N>,
+        output: Vec<N>,
+    }
+
+    let mut stack = alloc::vec![Frame {
+        node: None,
+        children: nodes.iter(),
+        output: Vec::new(),
+    }];
+
+    while let Some(frame) = stack.last_mut() {
+        if let Some(node) = frame.children.next() {
+            let output = match f(node) {
+                Action::Keep => Output::Keep(node),
+                Action::Edit(n) => Output::Edit(n),
+                Action::Unwrap => Output::Unwrap,
+                Action::Replace(n) => {
+                    frame.output.push(n);
+                    continue;
+                }
+                Action::Remove => continue,
+            };
+
+            stack.push(Frame {
+                node: Some(output),
+                children: node.children().iter(),
+                output: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(frame) = stack.pop() else {
+            break;
+        };
+
+        let (Some(node), Some(parent)) = (frame.node, stack.last_mut()) else {
+            return frame.output;
+        };
+
+        match node {
+            Output::Keep(node) => parent.output.push(node.with_children(frame.output)),
+            Output::Edit(node) => parent.output.push(node.with_children(frame.output)),
+            Output::Unwrap => parent.output.extend(frame.output),
+        }
+    }
+
+    Vec::new()
+}
+
+impl<N> Soup<N>
+where
+    N: Rebuild,
+{
+    /// Builds a new document by calling `f` on each node, like [`transform`]
+    #[must_use]
+    pub fn transform<F>(&self, f: F) -> Soup<N>
+    where
+        F: FnMut(&N) -> Action<N>,
+    {
+        Soup::from_nodes(transform(&self.nodes, f))
+    }
+}
+
+#[cfg(any(
+    feature = "html-lenient",
+    feature = "html-loose",
+    feature = "html-strict"
+))]
+impl<S> Rebuild for crate::parser::HTMLNode<S>
+where
+    S: Clone,
+{
+    fn with_children(&self, children: Vec<Self>) -> Self {
+        match self {
+            Self::Element { name, attrs, .. } => Self::Element {
+                name: name.clone(),
+                attrs: attrs.clone(),
+                children,
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+impl Rebuild for crate::parser::XMLNode {
+    fn with_children(&self, children: Vec<Self>) -> Self {
+        match self {
+            Self::Element(e) => Self::Element(crate::parser::XMLElement {
+                prefix: e.prefix.clone(),
+                namespace: e.namespace.clone(),
+                namespaces: e.namespaces.clone(),
+                name: e.name.clone(),
+                attributes: e.attributes.clone(),
+                children,
+            }),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl Rebuild for crate::parser::JSONNode {
+    fn with_children(&self, children: Vec<Self>) -> Self {
+        use crate::parser::JSONValue;
+
+        let value = match &self.value {
+            JSONValue::Array(_) => JSONValue::Array(children),
+            JSONValue::Object(_) => JSONValue::Object(children),
+            other => other.clone(),
+        };
+
+        Self {
+            key: self.key.clone(),
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{
+        String,
+        ToString,
+    };
+
+    use super::*;
+    use crate::parser::HTMLNode;
+
+    #[test]
+    fn test_transform() {
+        let soup = Soup::html_strict("<ul><li>1</li><li>2</li><li><b>3</b></li></ul><hr><p>4</p>")
+            .expect("Failed to parse HTML");
+
+        let output = soup.transform(|node| match node {
+            HTMLNode::Element {
+                name: "ul" | "b", ..
+            } => Action::Unwrap,
+            HTMLNode::Void { .. } => Action::Remove,
+            HTMLNode::Text("2") => Action::Replace(HTMLNode::Text("two")),
+            HTMLNode::Element { name: "p", .. } => Action::Edit(HTMLNode::Element {
+                name: "div",
+                attrs: [("id", "x")].into(),
+                children: Vec::new(),
+            }),
+            _ => Action::Keep,
+        });
+        let html = output
+            .nodes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<String>();
+        assert_eq!(
+            html,
+            r#"<li>1</li><li>two</li><li>3</li><div id="x">4</div>"#
+        );
+
+        assert_eq!(soup.transform(|_| Action::Remove).nodes, []);
+        assert_eq!(soup.transform(|_| Action::Keep).nodes, soup.nodes);
+    }
+}
@@ -0,0 +1,232 @@
+use alloc::vec::Vec;
+use core::slice;
+
+use crate::{
+    query::QueryItem,
+    Node,
+    Soup,
+};
+
+/// What a [`Visitor`] wants to happen after visiting a node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Control {
+    /// Carry on into the node's children, then its siblings
+    #[default]
+    Continue,
+
+    /// Don't visit the node's children, but carry on with its siblings
+    SkipChildren,
+
+    /// End the walk; no other nodes are visited
+    Stop,
+}
+
+/// Callbacks for each kind of node in a tree, called by [`walk`] in document order
+///
+/// Every method does nothing by default. [`Visitor::enter`] calls the `visit_` method for the
+/// kind of node, so a visitor that overrides it handles every node itself.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, visit::{Control, Visitor}};
+/// #[derive(Default)]
+/// struct Outline {
+///     depth: usize,
+///     lines: Vec<String>,
+/// }
+///
+/// impl<N: Node<Text = &'static str>> Visitor<N> for Outline {
+///     fn visit_element(&mut self, node: &N) -> Control {
+///         let name = node.name().copied().unwrap_or_default();
+///         self.lines.push(format!("{}{name}", "  ".repeat(self.depth)));
+///         self.depth += 1;
+///
+///         if name == "svg" { Control::SkipChildren } else { Control::Continue }
+///     }
+///
+///     fn leave(&mut self, node: &N) -> Control {
+///         self.depth -= usize::from(node.name().is_some());
+///         Control::Continue
+///     }
+/// }
+///
+/// let soup = Soup::html_strict("<main><h1>Title</h1><svg><g></g></svg></main>").unwrap();
+/// let mut outline = Outline::default();
+/// soup.walk(&mut outline);
+///
+/// assert_eq!(outline.lines, ["main", "  h1", "  svg"]);
+/// ```
+pub trait Visitor<N>
+where
+    N: Node,
+{
+    /// Called when a node is reached, before its children
+    fn enter(&mut self, node: &N) -> Control {
+        if node.name().is_some() {
+            self.visit_element(node)
+        } else if let Some(text) = node.text() {
+            self.visit_text(node, text)
+        } else if let Some(comment) = node.comment() {
+            self.visit_comment(node, comment)
+        } else {
+            Control::Continue
+        }
+    }
+
+    /// Called after a node's children have been visited, or skipped
+    ///
+    /// Returning [`Control::SkipChildren`] is the same as [`Control::Continue`] here.
+    fn leave(&mut self, node: &N) -> Control {
+        let _ = node;
+        Control::Continue
+    }
+
+    /// Called by [`Visitor::enter`] for elements
+    fn visit_element(&mut self, node: &N) -> Control {
+        let _ = node;
+        Control::Continue
+    }
+
+    /// Called by [`Visitor::enter`] for text nodes, with their text
+    fn visit_text(&mut self, node: &N, text: &N::Text) -> Control {
+        let _ = (node, text);
+        Control::Continue
+    }
+
+    /// Called by [`Visitor::enter`] for comments, with their content
+    fn visit_comment(&mut self, node: &N, comment: &N::Text) -> Control {
+        let _ = (node, comment);
+        Control::Continue
+    }
+}
+
+/// Visits `nodes` and their descendants in document order
+///
+/// The tree is traversed with an explicit stack, so deep trees can't overflow the call stack.
+/// Returns [`Control::Stop`] if the visitor stopped the walk, or [`Control::Continue`].
+pub fn walk<N, V>(nodes: &[N], visitor: &mut V) -> Control
+where
+    N: Node,
+    V: Visitor<N> + ?Sized,
+{
+    let mut stack: Vec<(Option<&N>, slice::Iter<'_, N>)> = alloc::vec![(None, nodes.iter())];
+
+    while let Some((parent, children)) = stack.last_mut() {
+        let Some(node) = children.next() else {
+            let parent = *parent;
+            stack.pop();
+
+            if let Some(parent) = parent {
+                if visitor.leave(parent) == Control::Stop {
+                    return Control::Stop;
+                }
+            }
+
+            continue;
+        };
+
+        match visitor.enter(node) {
+            Control::Continue => stack.push((Some(node), node.children().iter())),
+            Control::SkipChildren => {
+                if visitor.leave(node) == Control::Stop {
+                    return Control::Stop;
+                }
+            }
+            Control::Stop => return Control::Stop,
+        }
+    }
+
+    Control::Continue
+}
+
+impl<N> Soup<N>
+where
+    N: Node,
+{
+    /// Visits every node of the document with `visitor`, like [`walk`]
+    pub fn walk<V>(&self, visitor: &mut V) -> Control
+    where
+        V: Visitor<N> + ?Sized,
+    {
+        walk(&self.nodes, visitor)
+    }
+}
+
+impl<N> QueryItem<'_, N>
+where
+    N: Node,
+{
+    /// Visits the item and its descendants with `visitor`, like [`walk`]
+    pub fn walk<V>(&self, visitor: &mut V) -> Control
+    where
+        V: Visitor<N> + ?Sized,
+    {
+        walk(slice::from_ref(self.node()), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::String,
+        vec::Vec,
+    };
+
+    use super::*;
+    use crate::Queryable;
+
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl<N> Visitor<N> for Log
+    where
+        N: Node<Text = &'static str>,
+    {
+        fn visit_element(&mut self, node: &N) -> Control {
+            let name = node.name().copied().unwrap_or_default();
+            self.0.push(alloc::format!("<{name}>"));
+
+            match name {
+                "skip" => Control::SkipChildren,
+                "stop" => Control::Stop,
+                _ => Control::Continue,
+            }
+        }
+
+        fn leave(&mut self, node: &N) -> Control {
+            if let Some(name) = node.name() {
+                self.0.push(alloc::format!("</{name}>"));
+            }
+            Control::Continue
+        }
+
+        fn visit_text(&mut self, _: &N, text: &&str) -> Control {
+            self.0.push((*text).into());
+            Control::Continue
+        }
+
+        fn visit_comment(&mut self, _: &N, comment: &&str) -> Control {
+            self.0.push(alloc::format!("<!--{comment}-->"));
+            Control::Continue
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let soup = Soup::html_strict(
+            "<a>1<!--c--><skip><b>2</b></skip></a><c><stop><d></d></stop></c><e></e>",
+        )
+        .expect("Failed to parse HTML");
+
+        let mut log = Log::default();
+        assert_eq!(soup.walk(&mut log), Control::Stop);
+        assert_eq!(log.0, [
+            "<a>", "1", "<!--c-->", "<skip>", "</skip>", "</a>", "<c>", "<stop>"
+        ]);
+
+        let mut log = Log::default();
+        let a = soup.tag("a").first().expect("Couldn't find a");
+        assert_eq!(a.walk(&mut log), Control::Continue);
+        assert_eq!(log.0.last().map(String::as_str), Some("</a>"));
+    }
+}
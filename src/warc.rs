@@ -0,0 +1,470 @@
+use std::{
+    fmt,
+    io::{
+        self,
+        BufRead,
+        Read,
+    },
+};
+
+use html5ever::tendril::StrTendril;
+
+use crate::{
+    parser::HTMLNode,
+    Soup,
+};
+
+/// Error returned when reading a WARC file
+#[derive(Debug)]
+pub enum WarcError {
+    /// The underlying reader failed
+    Io(io::Error),
+    /// The file is not valid WARC, for the given reason
+    Invalid(&'static str),
+}
+
+impl fmt::Display for WarcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read WARC file: {e}"),
+            Self::Invalid(reason) => write!(f, "invalid WARC file: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for WarcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for WarcError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// A single WARC record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarcRecord {
+    /// Named fields from the record header, in order
+    pub headers: Vec<(String, String)>,
+
+    /// The content block, such as a full HTTP response
+    pub block: Vec<u8>,
+}
+
+impl WarcRecord {
+    /// Returns the value of the header field `name`, ignoring case
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+
+    /// The `WARC-Type` of the record, like `response` or `request`
+    #[must_use]
+    pub fn record_type(&self) -> Option<&str> {
+        self.header("WARC-Type")
+    }
+
+    /// The `WARC-Target-URI` of the record
+    #[must_use]
+    pub fn target_uri(&self) -> Option<&str> {
+        self.header("WARC-Target-URI")
+    }
+
+    /// Parses the block of a `response` record as an HTTP response
+    ///
+    /// Chunked bodies are reassembled. With the `compression` feature, gzip and deflate
+    /// `Content-Encoding`s are also decoded.
+    ///
+    /// # Errors
+    /// If the record is not a `response` with an `application/http` block, or the block is not
+    /// a valid HTTP response.
+    pub fn http_response(&self) -> Result<HttpResponse, WarcError> {
+        if self.record_type() != Some("response")
+            || !self
+                .header("Content-Type")
+                .is_some_and(|t| t.starts_with("application/http"))
+        {
+            return Err(WarcError::Invalid("record is not an HTTP response"));
+        }
+
+        HttpResponse::parse(&self.block)
+    }
+}
+
+/// An HTTP response stored in a WARC record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// Status code
+    pub status: u16,
+
+    /// Header fields, in order
+    pub headers: Vec<(String, String)>,
+
+    /// Decoded body
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Returns the value of the header field `name`, ignoring case
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+
+    /// Whether the `Content-Type` of the response is HTML
+    #[must_use]
+    pub fn is_html(&self) -> bool {
+        self.header("Content-Type").is_some_and(|t| {
+            let mime = t.split(';').next().unwrap_or_default().trim();
+            mime.eq_ignore_ascii_case("text/html")
+                || mime.eq_ignore_ascii_case("application/xhtml+xml")
+        })
+    }
+
+    fn parse(block: &[u8]) -> Result<Self, WarcError> {
+        let mut reader = block;
+
+        let status_line =
+            read_line(&mut reader)?.ok_or(WarcError::Invalid("missing HTTP status line"))?;
+
+        let status = status_line
+            .strip_prefix("HTTP/")
+            .and_then(|s| s.split_ascii_whitespace().nth(1))
+            .and_then(|s| s.parse().ok())
+            .ok_or(WarcError::Invalid("invalid HTTP status line"))?;
+
+        let headers = read_headers(&mut reader)?;
+
+        let mut body = reader.to_vec();
+
+        if header(&headers, "Transfer-Encoding").is_some_and(|e| e.eq_ignore_ascii_case("chunked"))
+        {
+            body = dechunk(&body)?;
+        }
+
+        #[cfg(feature = "compression")]
+        if header(&headers, "Content-Encoding").is_some_and(|e| {
+            ["gzip", "x-gzip", "deflate"]
+                .iter()
+                .any(|name| e.eq_ignore_ascii_case(name))
+        }) {
+            body = match crate::parser::decompress(&body) {
+                Ok(std::borrow::Cow::Borrowed(_)) => crate::parser::inflate(&body),
+                Ok(std::borrow::Cow::Owned(decoded)) => Ok(decoded),
+                Err(e) => Err(e),
+            }
+            .map_err(|_| WarcError::Invalid("invalid HTTP content encoding"))?;
+        }
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// An HTML page from a WARC `response` record
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// The `WARC-Target-URI` of the record
+    pub uri: Option<String>,
+
+    /// HTTP status code
+    pub status: u16,
+
+    /// The parsed document
+    pub soup: Soup<HTMLNode<StrTendril>>,
+}
+
+/// Reads records from a WARC file
+///
+/// Records are read one at a time, so archives larger than memory can be processed. Compressed
+/// `.warc.gz` files should be decompressed first, for example with
+/// [`decompress`](crate::parser::decompress) when the `compression` feature is enabled.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, warc::WarcReader};
+/// let warc = "WARC/1.0\r\n\
+///     WARC-Type: response\r\n\
+///     WARC-Target-URI: https://example.com/\r\n\
+///     Content-Type: application/http; msgtype=response\r\n\
+///     Content-Length: 66\r\n\
+///     \r\n\
+///     HTTP/1.1 200 OK\r\n\
+///     Content-Type: text/html\r\n\
+///     \r\n\
+///     <title>Example</title>\r\n\r\n";
+///
+/// for page in WarcReader::new(warc.as_bytes()).pages() {
+///     let page = page.unwrap();
+///     let title = page.soup.tag("title").first().map(|t| t.all_text());
+///     assert_eq!(title, Some("Example".into()));
+/// }
+/// ```
+pub struct WarcReader<R> {
+    reader: R,
+}
+
+impl<R> WarcReader<R>
+where
+    R: BufRead,
+{
+    /// Creates a `WarcReader` over an uncompressed WARC file
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Iterates over the HTML pages in `response` records, skipping every other record
+    pub fn pages(self) -> Pages<R> {
+        Pages { records: self }
+    }
+
+    fn read_record(&mut self) -> Result<Option<WarcRecord>, WarcError> {
+        // Records are separated by blank lines
+        let version = loop {
+            match read_line(&mut self.reader)? {
+                Some(line) if line.is_empty() => {}
+                Some(line) => break line,
+                None => return Ok(None),
+            }
+        };
+
+        if !version.starts_with("WARC/") {
+            return Err(WarcError::Invalid("expected a WARC version line"));
+        }
+
+        let headers = read_headers(&mut self.reader)?;
+
+        let len: u64 = header(&headers, "Content-Length")
+            .and_then(|len| len.parse().ok())
+            .ok_or(WarcError::Invalid("missing or invalid Content-Length"))?;
+
+        // The length is untrusted, so the block grows as it is read rather than up front
+        let mut block = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut block)?;
+
+        if (block.len() as u64) < len {
+            return Err(WarcError::Invalid("truncated record"));
+        }
+
+        Ok(Some(WarcRecord { headers, block }))
+    }
+}
+
+impl<R> Iterator for WarcReader<R>
+where
+    R: BufRead,
+{
+    type Item = Result<WarcRecord, WarcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Iterator over HTML pages in a WARC file, created by [`WarcReader::pages`]
+pub struct Pages<R> {
+    records: WarcReader<R>,
+}
+
+impl<R> Iterator for Pages<R>
+where
+    R: BufRead,
+{
+    type Item = Result<Page, WarcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record = match self.records.next()? {
+                Ok(record) => record,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let Ok(response) = record.http_response() else {
+                continue;
+            };
+
+            if !response.is_html() {
+                continue;
+            }
+
+            return Some(Ok(Page {
+                uri: record.target_uri().map(ToString::to_string),
+                status: response.status,
+                soup: Soup::html_with_content_type(&response.body, response.header("Content-Type")),
+            }));
+        }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Reads a line without its line ending, or `None` at the end of input
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>, WarcError> {
+    let mut line = Vec::new();
+
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+
+    let line = line.strip_suffix(b"\n").unwrap_or(&line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+    Ok(Some(String::from_utf8_lossy(line).into_owned()))
+}
+
+/// Reads header fields up to and including the blank line after them
+fn read_headers(reader: &mut impl BufRead) -> Result<Vec<(String, String)>, WarcError> {
+    let mut headers = Vec::new();
+
+    loop {
+        let line = read_line(reader)?.ok_or(WarcError::Invalid("unterminated header"))?;
+
+        if line.is_empty() {
+            return Ok(headers);
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or(WarcError::Invalid("invalid header field"))?;
+
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+/// Reassembles a body sent with `Transfer-Encoding: chunked`
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, WarcError> {
+    let mut out = Vec::new();
+
+    loop {
+        let line = read_line(&mut body)?.ok_or(WarcError::Invalid("unterminated chunked body"))?;
+
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| WarcError::Invalid("invalid chunk size"))?;
+
+        if size == 0 {
+            return Ok(out);
+        }
+
+        let chunk = body
+            .get(..size)
+            .ok_or(WarcError::Invalid("truncated chunk"))?;
+
+        out.extend_from_slice(chunk);
+        body = &body[size..];
+
+        read_line(&mut body)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn record(warc_type: &str, uri: &str, content_type: &str, block: &str) -> String {
+        format!(
+            "WARC/1.1\r\nWARC-Type: {warc_type}\r\nWARC-Target-URI: {uri}\r\nContent-Type: \
+             {content_type}\r\nContent-Length: {}\r\n\r\n{block}\r\n\r\n",
+            block.len()
+        )
+    }
+
+    #[test]
+    fn test_records() {
+        let warc = [
+            record(
+                "warcinfo",
+                "",
+                "application/warc-fields",
+                "software: soupy\r\n",
+            ),
+            record(
+                "request",
+                "https://a.example/",
+                "application/http; msgtype=request",
+                "GET / HTTP/1.1\r\n\r\n",
+            ),
+            record(
+                "response",
+                "https://a.example/",
+                "application/http; msgtype=response",
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\r\n<p>A</p>",
+            ),
+            record(
+                "response",
+                "https://a.example/logo.png",
+                "application/http; msgtype=response",
+                "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\n\r\n\u{1}PNG",
+            ),
+            record(
+                "response",
+                "https://b.example/",
+                "application/http; msgtype=response",
+                "HTTP/1.1 404 Not Found\r\ncontent-type: text/html\r\nTransfer-Encoding: \
+                 chunked\r\n\r\n4\r\n<p>B\r\n5;x=y\r\n</p>\n\r\n0\r\n\r\n",
+            ),
+        ]
+        .concat();
+
+        let records = WarcReader::new(warc.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to read records");
+
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[0].record_type(), Some("warcinfo"));
+        assert!(records[1].http_response().is_err());
+
+        let pages = WarcReader::new(warc.as_bytes())
+            .pages()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to read pages");
+
+        let summary = pages
+            .iter()
+            .map(|p| {
+                (
+                    p.uri.as_deref(),
+                    p.status,
+                    p.soup.tag("p").first().map(|p| p.all_text()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(summary, [
+            (Some("https://a.example/"), 200, Some("A".into())),
+            (Some("https://b.example/"), 404, Some("B".into())),
+        ]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut reader = WarcReader::new(&b"HTTP/1.1 200 OK\r\n\r\n"[..]);
+        assert!(matches!(reader.next(), Some(Err(WarcError::Invalid(_)))));
+
+        for len in ["10", "99999999999999"] {
+            let truncated = format!("WARC/1.0\r\nContent-Length: {len}\r\n\r\nshort");
+            let mut reader = WarcReader::new(truncated.as_bytes());
+            assert!(matches!(
+                reader.next(),
+                Some(Err(WarcError::Invalid("truncated record")))
+            ));
+        }
+
+        assert!(WarcReader::new(&b"\r\n"[..]).next().is_none());
+    }
+}
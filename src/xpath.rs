@@ -0,0 +1,1371 @@
+use alloc::{
+    boxed::Box,
+    string::{
+        String,
+        ToString,
+    },
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt,
+    ops::RangeInclusive,
+};
+
+use crate::{
+    query::{
+        NodeId,
+        QueryItem,
+        Root,
+    },
+    Node,
+    Soup,
+};
+
+/// Kind of an [`XPathError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XPathErrorKind {
+    /// The expression ended early, like `//a[@href`
+    UnexpectedEnd,
+    /// A character is not valid where it appears
+    UnexpectedCharacter(char),
+    /// The axis is not supported, like `namespace::`
+    UnknownAxis(String),
+    /// The function is not supported
+    UnknownFunction(String),
+    /// The function was called with the wrong number of arguments
+    WrongArguments(String),
+    /// The expression, or an operand of `|`, doesn't select nodes, like `count(//a)`
+    NotNodeSet,
+}
+
+/// Error returned when an `XPath` expression cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XPathError {
+    /// Byte offset of the error in the expression
+    pub position: usize,
+    /// What went wrong
+    pub kind: XPathErrorKind,
+}
+
+impl fmt::Display for XPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            XPathErrorKind::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            XPathErrorKind::UnexpectedCharacter(c) => {
+                write!(f, "unexpected character {c:?} at {}", self.position)
+            }
+            XPathErrorKind::UnknownAxis(name) => {
+                write!(f, "unsupported axis {name:?} at {}", self.position)
+            }
+            XPathErrorKind::UnknownFunction(name) => {
+                write!(f, "unsupported function {name:?} at {}", self.position)
+            }
+            XPathErrorKind::WrongArguments(name) => {
+                write!(
+                    f,
+                    "wrong number of arguments to {name:?} at {}",
+                    self.position
+                )
+            }
+            XPathErrorKind::NotNodeSet => {
+                write!(f, "expression at {} does not select nodes", self.position)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for XPathError {}
+
+/// A node or attribute selected by an [`XPath`]
+#[derive(Debug)]
+pub enum XPathItem<'x, N> {
+    /// An element, text, comment, or other node
+    Node(QueryItem<'x, N>),
+    /// An attribute
+    Attribute {
+        /// The element the attribute is on
+        element: QueryItem<'x, N>,
+        /// Name of the attribute
+        name: &'x str,
+        /// Value of the attribute
+        value: &'x str,
+    },
+}
+
+impl<N> XPathItem<'_, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Returns the `XPath` string value: the text of a text node, the value of an attribute, or
+    /// every descendant text of an element joined together
+    #[must_use]
+    pub fn string_value(&self) -> String {
+        match self {
+            Self::Node(item) => string_value(item.node()),
+            Self::Attribute { value, .. } => (*value).to_string(),
+        }
+    }
+}
+
+/// An `XPath` 1.0 expression that selects nodes, like `//book[@lang='en']/title/text()`
+///
+/// Supports location paths with every axis except `namespace::`, abbreviated steps (`//`, `.`,
+/// `..`, `@`), the `*`, `text()`, `comment()`, and `node()` tests, predicates, unions with `|`,
+/// comparisons, `and`, `or`, `+`, `-`, and the `last`, `position`, `count`, `not`, `true`,
+/// `false`, `boolean`, `string`, `number`, `concat`, `contains`, `starts-with`,
+/// `normalize-space`, `string-length`, `name`, and `local-name` functions.
+///
+/// Names match elements and attributes by their name as parsed. For XML, that is the local
+/// name, so `atom:entry` and `entry` both match `<atom:entry>`.
+///
+/// # Example
+/// ```rust
+/// # use soupy::{prelude::*, xpath::XPath};
+/// # #[cfg(feature = "xml")] {
+/// let soup = Soup::xml(r#"<root>
+///     <complex id="1"><nested>One</nested></complex>
+///     <complex id="2"><nested>Two</nested><nested>Three</nested></complex>
+/// </root>"#.as_bytes()).unwrap();
+///
+/// let text = |expr| {
+///     soup.xpath(expr)
+///         .unwrap()
+///         .iter()
+///         .map(|i| i.string_value())
+///         .collect::<Vec<_>>()
+/// };
+///
+/// assert_eq!(text("/root/complex/nested/text()"), ["One", "Two", "Three"]);
+/// assert_eq!(text("//complex[count(nested) > 1]/@id"), ["2"]);
+/// assert_eq!(text("//nested[last()]"), ["One", "Three"]);
+/// assert_eq!(text("(//nested)[last()]"), ["Three"]);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPath {
+    expr: Expr,
+}
+
+impl XPath {
+    /// Parses an expression
+    ///
+    /// # Errors
+    /// If the expression is invalid, uses unsupported features, or doesn't select nodes.
+    pub fn parse(expr: &str) -> Result<Self, XPathError> {
+        let mut parser = Parser { s: expr, pos: 0 };
+
+        let expr = parser.expr()?;
+        parser.whitespace();
+
+        if parser.pos < parser.s.len() {
+            return Err(parser.unexpected());
+        }
+
+        if !expr.is_node_set() {
+            return Err(XPathError {
+                position: 0,
+                kind: XPathErrorKind::NotNodeSet,
+            });
+        }
+
+        Ok(Self { expr })
+    }
+
+    /// Selects nodes and attributes from `soup`, in document order
+    ///
+    /// The document itself, selected by `/`, is not included.
+    #[must_use]
+    pub fn evaluate<'x, N>(&self, soup: &'x Soup<N>) -> Vec<XPathItem<'x, N>>
+    where
+        N: Node,
+        N::Text: AsRef<str>,
+    {
+        let document = Document::new(soup.root());
+
+        let context = Context {
+            item: Item::Node(0),
+            position: 1,
+            size: 1,
+        };
+
+        let Value::Nodes(items) = document.eval(&self.expr, &context) else {
+            return Vec::new();
+        };
+
+        items
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Node(i) => Some(XPathItem::Node(document.item(i)?)),
+                Item::Attr(i, name, value) => Some(XPathItem::Attribute {
+                    element: document.item(i)?,
+                    name,
+                    value,
+                }),
+            })
+            .collect()
+    }
+}
+
+impl<N> Soup<N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    /// Selects nodes and attributes with an `XPath` expression, in document order
+    ///
+    /// See [`XPath`] for the supported subset.
+    ///
+    /// # Errors
+    /// If the expression can't be parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use soupy::{prelude::*, xpath::XPathItem};
+    /// # #[cfg(feature = "xml")] {
+    /// let soup = Soup::xml(r#"<feed><entry><link href="/a"/></entry></feed>"#.as_bytes()).unwrap();
+    ///
+    /// let Some(XPathItem::Attribute { value, .. }) = soup.xpath("//entry/link/@href").unwrap().pop() else {
+    ///     panic!();
+    /// };
+    /// assert_eq!(value, "/a");
+    /// # }
+    /// ```
+    pub fn xpath(&self, expr: &str) -> Result<Vec<XPathItem<'_, N>>, XPathError> {
+        Ok(XPath::parse(expr)?.evaluate(self))
+    }
+}
+
+/// Concatenates the text of `node` and its descendants
+fn string_value<N>(node: &N) -> String
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    if let Some(comment) = node.comment() {
+        return comment.as_ref().to_string();
+    }
+
+    node.descendants()
+        .filter_map(|n| n.text())
+        .map(AsRef::as_ref)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Itself,
+    Parent,
+    Ancestor,
+    AncestorOrSelf,
+    FollowingSibling,
+    PrecedingSibling,
+    Following,
+    Preceding,
+    Attribute,
+}
+
+impl Axis {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "child" => Self::Child,
+            "descendant" => Self::Descendant,
+            "descendant-or-self" => Self::DescendantOrSelf,
+            "self" => Self::Itself,
+            "parent" => Self::Parent,
+            "ancestor" => Self::Ancestor,
+            "ancestor-or-self" => Self::AncestorOrSelf,
+            "following-sibling" => Self::FollowingSibling,
+            "preceding-sibling" => Self::PrecedingSibling,
+            "following" => Self::Following,
+            "preceding" => Self::Preceding,
+            "attribute" => Self::Attribute,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Test {
+    /// `name` or `prefix:name`
+    Name(String),
+    /// `*`
+    Any,
+    /// `text()`
+    Text,
+    /// `comment()`
+    Comment,
+    /// `node()`
+    Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    axis: Axis,
+    test: Test,
+    predicates: Vec<Expr>,
+}
+
+impl Step {
+    /// `descendant-or-self::node()`, which `//` abbreviates
+    fn descendants() -> Self {
+        Self {
+            axis: Axis::DescendantOrSelf,
+            test: Test::Node,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Last,
+    Position,
+    Count,
+    Not,
+    True,
+    False,
+    Boolean,
+    String,
+    Number,
+    Concat,
+    Contains,
+    StartsWith,
+    NormalizeSpace,
+    StringLength,
+    Name,
+    LocalName,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "last" => Self::Last,
+            "position" => Self::Position,
+            "count" => Self::Count,
+            "not" => Self::Not,
+            "true" => Self::True,
+            "false" => Self::False,
+            "boolean" => Self::Boolean,
+            "string" => Self::String,
+            "number" => Self::Number,
+            "concat" => Self::Concat,
+            "contains" => Self::Contains,
+            "starts-with" => Self::StartsWith,
+            "normalize-space" => Self::NormalizeSpace,
+            "string-length" => Self::StringLength,
+            "name" => Self::Name,
+            "local-name" => Self::LocalName,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> RangeInclusive<usize> {
+        match self {
+            Self::Last | Self::Position | Self::True | Self::False => 0..=0,
+            Self::Count | Self::Not | Self::Boolean => 1..=1,
+            Self::String
+            | Self::Number
+            | Self::NormalizeSpace
+            | Self::StringLength
+            | Self::Name
+            | Self::LocalName => 0..=1,
+            Self::Contains | Self::StartsWith => 2..=2,
+            Self::Concat => 2..=usize::MAX,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    /// A location path, starting at the document if `absolute`, or else the context node
+    Path {
+        absolute: bool,
+        steps: Vec<Step>,
+    },
+    /// An expression filtered by predicates, then followed by more steps, like `(//a)[1]/b`
+    Filter {
+        expr: Box<Expr>,
+        predicates: Vec<Expr>,
+        steps: Vec<Step>,
+    },
+    Union(Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Call(Function, Vec<Expr>),
+    Binary(Box<Expr>, Operator, Box<Expr>),
+    Negate(Box<Expr>),
+}
+
+impl Expr {
+    fn is_node_set(&self) -> bool {
+        match self {
+            Self::Path { .. } | Self::Union(_) => true,
+            Self::Filter { expr, .. } => expr.is_node_set(),
+            _ => false,
+        }
+    }
+}
+
+/// A node, by index in [`Document::entries`], or an attribute with the index of its element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Item<'x> {
+    Node(usize),
+    Attr(usize, &'x str, &'x str),
+}
+
+impl<'x> Item<'x> {
+    /// Sorts attributes after their element and before its children
+    fn key(&self) -> (usize, Option<&'x str>) {
+        match *self {
+            Self::Node(i) => (i, None),
+            Self::Attr(i, name, _) => (i, Some(name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value<'x> {
+    Nodes(Vec<Item<'x>>),
+    String(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl Value<'_> {
+    fn boolean(&self) -> bool {
+        match self {
+            Self::Nodes(items) => !items.is_empty(),
+            Self::String(s) => !s.is_empty(),
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::Boolean(b) => *b,
+        }
+    }
+}
+
+struct Context<'x> {
+    item: Item<'x>,
+    /// Position of the item among the items being filtered, starting at 1
+    position: usize,
+    size: usize,
+}
+
+struct Entry<'x, N> {
+    /// `None` for the document
+    node: Option<&'x N>,
+    parent: usize,
+    /// Index after the last descendant
+    end: usize,
+}
+
+/// Every node in document order after the document itself, so axes can be walked without
+/// recursion
+struct Document<'x, N> {
+    root: Root<'x, N>,
+    entries: Vec<Entry<'x, N>>,
+}
+
+impl<'x, N> Document<'x, N>
+where
+    N: Node,
+    N::Text: AsRef<str>,
+{
+    fn new(root: Root<'x, N>) -> Self {
+        let mut entries = vec![Entry {
+            node: None,
+            parent: 0,
+            end: 0,
+        }];
+        let mut stack = vec![(0, root.nodes.iter())];
+
+        while let Some((index, children)) = stack.last_mut() {
+            let index = *index;
+
+            if let Some(node) = children.next() {
+                entries.push(Entry {
+                    node: Some(node),
+                    parent: index,
+                    end: 0,
+                });
+                stack.push((entries.len() - 1, node.children().iter()));
+            } else {
+                entries[index].end = entries.len();
+                stack.pop();
+            }
+        }
+
+        Self { root, entries }
+    }
+
+    fn item(&self, index: usize) -> Option<QueryItem<'x, N>> {
+        Some(QueryItem {
+            item: self.entries.get(index)?.node?,
+            id: NodeId(index - 1),
+            root: self.root,
+        })
+    }
+
+    fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let end = self.entries[index].end;
+        let mut next = index + 1;
+
+        core::iter::from_fn(move || {
+            let child = next;
+            next = self.entries.get(child).filter(|_| child < end)?.end;
+            Some(child)
+        })
+    }
+
+    /// Items on `axis` from `item`, closest first
+    fn axis(&self, axis: Axis, item: Item<'x>) -> Vec<Item<'x>> {
+        let index = match item {
+            Item::Node(index) => index,
+            Item::Attr(element, ..) => {
+                return match axis {
+                    Axis::Itself | Axis::DescendantOrSelf => vec![item],
+                    Axis::Parent => vec![Item::Node(element)],
+                    Axis::AncestorOrSelf => [item]
+                        .into_iter()
+                        .chain(self.axis(Axis::AncestorOrSelf, Item::Node(element)))
+                        .collect(),
+                    Axis::Ancestor => self.axis(Axis::AncestorOrSelf, Item::Node(element)),
+                    Axis::Following => (element + 1..self.entries.len()).map(Item::Node).collect(),
+                    Axis::Preceding => self.axis(Axis::Preceding, Item::Node(element)),
+                    _ => Vec::new(),
+                };
+            }
+        };
+
+        let entry = &self.entries[index];
+        let ancestors = core::iter::successors((index != 0).then_some(entry.parent), |&i| {
+            (i != 0).then(|| self.entries[i].parent)
+        });
+
+        let nodes = match axis {
+            Axis::Child => self.children(index).collect(),
+            Axis::Descendant => (index + 1..entry.end).collect(),
+            Axis::DescendantOrSelf => (index..entry.end).collect(),
+            Axis::Itself => vec![index],
+            Axis::Parent => ancestors.take(1).collect(),
+            Axis::Ancestor => ancestors.collect(),
+            Axis::AncestorOrSelf => core::iter::once(index).chain(ancestors).collect(),
+            Axis::FollowingSibling if index != 0 => self
+                .children(entry.parent)
+                .skip_while(|&i| i <= index)
+                .collect(),
+            Axis::PrecedingSibling if index != 0 => {
+                let mut siblings = self
+                    .children(entry.parent)
+                    .take_while(|&i| i < index)
+                    .collect::<Vec<_>>();
+                siblings.reverse();
+                siblings
+            }
+            Axis::Following => (entry.end..self.entries.len()).collect(),
+            Axis::Preceding => {
+                let ancestors = ancestors.collect::<Vec<_>>();
+                (1..index)
+                    .rev()
+                    .filter(|i| !ancestors.contains(i))
+                    .collect()
+            }
+            Axis::Attribute => {
+                return entry
+                    .node
+                    .and_then(Node::attrs)
+                    .into_iter()
+                    .flatten()
+                    .map(|(k, v)| Item::Attr(index, k.as_ref(), v.as_ref()))
+                    .collect();
+            }
+            Axis::FollowingSibling | Axis::PrecedingSibling => Vec::new(),
+        };
+
+        nodes.into_iter().map(Item::Node).collect()
+    }
+
+    fn test(&self, test: &Test, axis: Axis, item: Item<'_>) -> bool {
+        let name = |name: &str, pattern: &str| {
+            name == pattern
+                || pattern
+                    .split_once(':')
+                    .is_some_and(|(_, local)| name == local)
+        };
+
+        match (item, test) {
+            (Item::Attr(_, attr, _), Test::Name(pattern)) => name(attr, pattern),
+            (Item::Attr(..), Test::Any) => axis == Axis::Attribute,
+            (Item::Attr(..), Test::Node) => true,
+            (Item::Attr(..), _) => false,
+            (Item::Node(i), test) => {
+                let Some(node) = self.entries[i].node else {
+                    return *test == Test::Node;
+                };
+
+                match test {
+                    Test::Name(pattern) => node.name().is_some_and(|n| name(n.as_ref(), pattern)),
+                    Test::Any => node.name().is_some(),
+                    Test::Text => node.text().is_some(),
+                    Test::Comment => node.comment().is_some(),
+                    Test::Node => true,
+                }
+            }
+        }
+    }
+
+    fn string_value(&self, item: Item<'_>) -> String {
+        match item {
+            Item::Node(i) => match self.entries[i].node {
+                Some(node) => string_value(node),
+                None => self.root.nodes.iter().map(string_value).collect(),
+            },
+            Item::Attr(_, _, value) => value.to_string(),
+        }
+    }
+
+    fn name(&self, item: Item<'x>) -> &'x str {
+        match item {
+            Item::Node(i) => self.entries[i]
+                .node
+                .and_then(Node::name)
+                .map_or("", AsRef::as_ref),
+            Item::Attr(_, name, _) => name,
+        }
+    }
+
+    /// Applies `steps` to every item, returning the results in document order
+    fn steps(&self, mut items: Vec<Item<'x>>, steps: &[Step]) -> Vec<Item<'x>> {
+        for step in steps {
+            let mut next = Vec::new();
+
+            for &item in &items {
+                let matched = self
+                    .axis(step.axis, item)
+                    .into_iter()
+                    .filter(|&i| self.test(&step.test, step.axis, i))
+                    .collect();
+
+                next.extend(self.filter(matched, &step.predicates));
+            }
+
+            sort(&mut next);
+            items = next;
+        }
+
+        items
+    }
+
+    /// Keeps the items that match every predicate, in turn
+    fn filter(&self, mut items: Vec<Item<'x>>, predicates: &[Expr]) -> Vec<Item<'x>> {
+        for predicate in predicates {
+            let size = items.len();
+
+            items = items
+                .into_iter()
+                .enumerate()
+                .filter(|&(i, item)| {
+                    let context = Context {
+                        item,
+                        position: i + 1,
+                        size,
+                    };
+
+                    // A number selects the item at that position, like `[2]`
+                    match self.eval(predicate, &context) {
+                        #[allow(clippy::cast_precision_loss, clippy::float_cmp)]
+                        Value::Number(n) => n == context.position as f64,
+                        value => value.boolean(),
+                    }
+                })
+                .map(|(_, item)| item)
+                .collect();
+        }
+
+        items
+    }
+
+    fn eval(&self, expr: &Expr, context: &Context<'x>) -> Value<'x> {
+        match expr {
+            Expr::Path { absolute, steps } => {
+                let start = if *absolute {
+                    Item::Node(0)
+                } else {
+                    context.item
+                };
+
+                Value::Nodes(self.steps(vec![start], steps))
+            }
+            Expr::Filter {
+                expr,
+                predicates,
+                steps,
+            } => {
+                let Value::Nodes(items) = self.eval(expr, context) else {
+                    return Value::Nodes(Vec::new());
+                };
+
+                Value::Nodes(self.steps(self.filter(items, predicates), steps))
+            }
+            Expr::Union(exprs) => {
+                let mut items = Vec::new();
+
+                for expr in exprs {
+                    if let Value::Nodes(nodes) = self.eval(expr, context) {
+                        items.extend(nodes);
+                    }
+                }
+
+                sort(&mut items);
+                Value::Nodes(items)
+            }
+            Expr::Literal(s) => Value::String(s.clone()),
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Negate(expr) => Value::Number(-self.number(&self.eval(expr, context))),
+            Expr::Call(function, args) => self.call(*function, args, context),
+            Expr::Binary(left, Operator::Or, right) => Value::Boolean(
+                self.eval(left, context).boolean() || self.eval(right, context).boolean(),
+            ),
+            Expr::Binary(left, Operator::And, right) => Value::Boolean(
+                self.eval(left, context).boolean() && self.eval(right, context).boolean(),
+            ),
+            Expr::Binary(left, operator, right) => {
+                let (left, right) = (self.eval(left, context), self.eval(right, context));
+
+                match operator {
+                    Operator::Add => Value::Number(self.number(&left) + self.number(&right)),
+                    Operator::Subtract => Value::Number(self.number(&left) - self.number(&right)),
+                    _ => Value::Boolean(self.compare(&left, *operator, &right)),
+                }
+            }
+        }
+    }
+
+    fn call(&self, function: Function, args: &[Expr], context: &Context<'x>) -> Value<'x> {
+        let arg = |i: usize| args.get(i).map(|a| self.eval(a, context));
+        let string =
+            |i: usize| arg(i).map_or_else(|| self.string_value(context.item), |v| self.string(&v));
+        let first = |i: usize| match arg(i) {
+            Some(Value::Nodes(items)) => items.first().copied(),
+            Some(_) => None,
+            None => Some(context.item),
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        match function {
+            Function::Last => Value::Number(context.size as f64),
+            Function::Position => Value::Number(context.position as f64),
+            Function::Count => Value::Number(match arg(0) {
+                Some(Value::Nodes(items)) => items.len() as f64,
+                _ => 0.0,
+            }),
+            Function::Not => Value::Boolean(!arg(0).as_ref().is_some_and(Value::boolean)),
+            Function::True => Value::Boolean(true),
+            Function::False => Value::Boolean(false),
+            Function::Boolean => Value::Boolean(arg(0).as_ref().is_some_and(Value::boolean)),
+            Function::String => Value::String(string(0)),
+            Function::Number => Value::Number(match arg(0) {
+                Some(value) => self.number(&value),
+                None => parse_number(&self.string_value(context.item)),
+            }),
+            Function::Concat => Value::String((0..args.len()).map(&string).collect()),
+            Function::Contains => Value::Boolean(string(0).contains(&string(1))),
+            Function::StartsWith => Value::Boolean(string(0).starts_with(&string(1))),
+            Function::NormalizeSpace => Value::String(
+                string(0)
+                    .split_ascii_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            Function::StringLength => Value::Number(string(0).chars().count() as f64),
+            Function::Name => Value::String(first(0).map_or("", |i| self.name(i)).to_string()),
+            Function::LocalName => Value::String(
+                first(0)
+                    .map(|i| self.name(i))
+                    .map_or("", |n| n.rsplit(':').next().unwrap_or(n))
+                    .to_string(),
+            ),
+        }
+    }
+
+    fn string(&self, value: &Value<'_>) -> String {
+        match value {
+            Value::Nodes(items) => items
+                .first()
+                .map(|&i| self.string_value(i))
+                .unwrap_or_default(),
+            Value::String(s) => s.clone(),
+            Value::Number(n) => format_number(*n),
+            Value::Boolean(b) => b.to_string(),
+        }
+    }
+
+    fn number(&self, value: &Value<'_>) -> f64 {
+        match value {
+            Value::Number(n) => *n,
+            Value::Boolean(b) => f64::from(u8::from(*b)),
+            value => parse_number(&self.string(value)),
+        }
+    }
+
+    /// Compares two values, where node sets compare true if any of their nodes does
+    fn compare<'v>(&self, left: &Value<'v>, operator: Operator, right: &Value<'v>) -> bool {
+        let atoms = |value: &Value<'v>, other: &Value<'v>| -> Vec<Value<'v>> {
+            match (value, other) {
+                (Value::Nodes(_), Value::Boolean(_)) => vec![Value::Boolean(value.boolean())],
+                (Value::Nodes(items), _) => items
+                    .iter()
+                    .map(|&i| Value::String(self.string_value(i)))
+                    .collect(),
+                _ => vec![value.clone()],
+            }
+        };
+
+        let (left, right) = (atoms(left, right), atoms(right, left));
+
+        left.iter()
+            .any(|l| right.iter().any(|r| self.compare_atoms(l, operator, r)))
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn compare_atoms(&self, left: &Value<'_>, operator: Operator, right: &Value<'_>) -> bool {
+        let equal = match (left, right) {
+            (Value::Boolean(_), _) | (_, Value::Boolean(_)) => left.boolean() == right.boolean(),
+            (Value::Number(_), _) | (_, Value::Number(_)) => {
+                self.number(left) == self.number(right)
+            }
+            _ => self.string(left) == self.string(right),
+        };
+
+        let (l, r) = (self.number(left), self.number(right));
+
+        match operator {
+            Operator::Equal => equal,
+            Operator::NotEqual => !equal,
+            Operator::Less => l < r,
+            Operator::LessOrEqual => l <= r,
+            Operator::Greater => l > r,
+            Operator::GreaterOrEqual => l >= r,
+            _ => false,
+        }
+    }
+}
+
+/// Sorts items into document order, removing duplicates
+fn sort(items: &mut Vec<Item<'_>>) {
+    items.sort_by_key(Item::key);
+    items.dedup_by_key(|i| i.key());
+}
+
+fn parse_number(s: &str) -> f64 {
+    let s = s.trim();
+
+    // Rust also accepts forms like `inf` and `1e3`, which XPath does not
+    if s.is_empty()
+        || !s
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+    {
+        return f64::NAN;
+    }
+
+    s.parse().unwrap_or(f64::NAN)
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::float_cmp
+)]
+fn format_number(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".into()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.into()
+    } else if (-1e15..1e15).contains(&n) && (n as i64) as f64 == n {
+        // Integers are written without a fraction, like `2` instead of `2.0`
+        (n as i64).to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+struct Parser<'s> {
+    s: &'s str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn rest(&self) -> &str {
+        &self.s[self.pos..]
+    }
+
+    fn error(&self, kind: XPathErrorKind) -> XPathError {
+        XPathError {
+            position: self.pos,
+            kind,
+        }
+    }
+
+    fn unexpected(&self) -> XPathError {
+        match self.peek() {
+            Some(c) => self.error(XPathErrorKind::UnexpectedCharacter(c)),
+            None => self.error(XPathErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), XPathError> {
+        self.whitespace();
+
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes `token` after any whitespace, if it is next
+    fn eat(&mut self, token: &str) -> bool {
+        self.whitespace();
+
+        let word = token.starts_with(|c: char| c.is_ascii_alphabetic());
+        let after = self
+            .rest()
+            .get(token.len()..)
+            .and_then(|r| r.chars().next());
+
+        if self.rest().starts_with(token) && !(word && after.is_some_and(is_name_char)) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses a name, like `nested`, `starts-with`, or `atom:entry`
+    fn name(&mut self) -> Result<String, XPathError> {
+        let start = self.pos;
+
+        if !self
+            .peek()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || !c.is_ascii())
+        {
+            return Err(self.unexpected());
+        }
+
+        while self.peek().is_some_and(is_name_char) {
+            self.pos += self.peek().map_or(1, char::len_utf8);
+        }
+
+        let rest = self.rest();
+
+        if rest.starts_with(':') && !rest.starts_with("::") {
+            self.pos += 1;
+
+            if self.peek() == Some('*') {
+                self.pos += 1;
+            } else {
+                self.name()?;
+            }
+        }
+
+        Ok(self.s[start..self.pos].to_string())
+    }
+
+    fn expr(&mut self) -> Result<Expr, XPathError> {
+        self.binary(0)
+    }
+
+    /// Parses operators by precedence level, loosest first
+    fn binary(&mut self, level: usize) -> Result<Expr, XPathError> {
+        const LEVELS: &[&[(&str, Operator)]] = &[
+            &[("or", Operator::Or)],
+            &[("and", Operator::And)],
+            &[("=", Operator::Equal), ("!=", Operator::NotEqual)],
+            &[
+                ("<=", Operator::LessOrEqual),
+                ("<", Operator::Less),
+                (">=", Operator::GreaterOrEqual),
+                (">", Operator::Greater),
+            ],
+            &[("+", Operator::Add), ("-", Operator::Subtract)],
+        ];
+
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut expr = self.binary(level + 1)?;
+
+        'outer: loop {
+            for &(token, operator) in *operators {
+                if self.eat(token) {
+                    let right = self.binary(level + 1)?;
+                    expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+                    continue 'outer;
+                }
+            }
+
+            return Ok(expr);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, XPathError> {
+        if self.eat("-") {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+
+        let mut exprs = Vec::new();
+
+        loop {
+            self.whitespace();
+            exprs.push((self.pos, self.filter()?));
+
+            if !self.eat("|") {
+                break;
+            }
+        }
+
+        if exprs.len() == 1 {
+            return Ok(exprs.remove(0).1);
+        }
+
+        if let Some(&(position, _)) = exprs.iter().find(|(_, e)| !e.is_node_set()) {
+            return Err(XPathError {
+                position,
+                kind: XPathErrorKind::NotNodeSet,
+            });
+        }
+
+        Ok(Expr::Union(exprs.into_iter().map(|(_, e)| e).collect()))
+    }
+
+    /// Parses a primary expression with optional predicates and steps, or a location path
+    fn filter(&mut self) -> Result<Expr, XPathError> {
+        self.whitespace();
+
+        let expr = match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                self.expect(')')?;
+                expr
+            }
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let end = self.rest().find(quote).ok_or(XPathError {
+                    position: self.s.len(),
+                    kind: XPathErrorKind::UnexpectedEnd,
+                })?;
+                let literal = self.rest()[..end].to_string();
+                self.pos += end + 1;
+                return Ok(Expr::Literal(literal));
+            }
+            Some(c)
+                if c.is_ascii_digit()
+                    || (c == '.' && self.rest()[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+            {
+                let len = self
+                    .rest()
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(self.rest().len());
+                let number = self.rest()[..len].parse().map_err(|_| self.unexpected())?;
+                self.pos += len;
+                return Ok(Expr::Number(number));
+            }
+            _ => match self.call()? {
+                Some(expr) => expr,
+                None => return self.path(),
+            },
+        };
+
+        let mut predicates = Vec::new();
+
+        while self.eat("[") {
+            predicates.push(self.expr()?);
+            self.expect(']')?;
+        }
+
+        let steps = self.steps()?;
+
+        if (!predicates.is_empty() || !steps.is_empty()) && !expr.is_node_set() {
+            return Err(self.error(XPathErrorKind::NotNodeSet));
+        }
+
+        Ok(if predicates.is_empty() && steps.is_empty() {
+            expr
+        } else {
+            Expr::Filter {
+                expr: Box::new(expr),
+                predicates,
+                steps,
+            }
+        })
+    }
+
+    /// Parses a function call, or returns `None` if there isn't one
+    fn call(&mut self) -> Result<Option<Expr>, XPathError> {
+        let start = self.pos;
+
+        if !self.peek().is_some_and(char::is_alphabetic) {
+            return Ok(None);
+        }
+
+        let name = self.name()?;
+
+        if ["text", "comment", "node"].contains(&name.as_str()) || !self.eat("(") {
+            self.pos = start;
+            return Ok(None);
+        }
+
+        let error = |kind| XPathError {
+            position: start,
+            kind,
+        };
+
+        let function = Function::parse(&name)
+            .ok_or_else(|| error(XPathErrorKind::UnknownFunction(name.clone())))?;
+
+        let mut args = Vec::new();
+
+        if !self.eat(")") {
+            loop {
+                args.push(self.expr()?);
+
+                if !self.eat(",") {
+                    self.expect(')')?;
+                    break;
+                }
+            }
+        }
+
+        if !function.arity().contains(&args.len()) {
+            return Err(error(XPathErrorKind::WrongArguments(name)));
+        }
+
+        if function == Function::Count && !args[0].is_node_set() {
+            return Err(error(XPathErrorKind::NotNodeSet));
+        }
+
+        Ok(Some(Expr::Call(function, args)))
+    }
+
+    fn path(&mut self) -> Result<Expr, XPathError> {
+        let absolute = self.eat("//") || self.eat("/");
+        let mut steps = Vec::new();
+
+        if self.s[..self.pos].ends_with("//") {
+            steps.push(Step::descendants());
+        }
+
+        // A lone `/` selects the document
+        let starts_step = self.peek().is_some_and(|c| {
+            c.is_alphabetic() || matches!(c, '_' | '*' | '@' | '.') || !c.is_ascii()
+        });
+
+        if !absolute || !steps.is_empty() || starts_step {
+            steps.push(self.step()?);
+            steps.extend(self.steps()?);
+        }
+
+        Ok(Expr::Path { absolute, steps })
+    }
+
+    /// Parses steps, each after `/` or `//`
+    fn steps(&mut self) -> Result<Vec<Step>, XPathError> {
+        let mut steps = Vec::new();
+
+        loop {
+            if self.eat("//") {
+                steps.push(Step::descendants());
+            } else if !self.eat("/") {
+                return Ok(steps);
+            }
+
+            steps.push(self.step()?);
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, XPathError> {
+        self.whitespace();
+
+        let step = |axis, test| Step {
+            axis,
+            test,
+            predicates: Vec::new(),
+        };
+
+        if self.eat("..") {
+            return Ok(step(Axis::Parent, Test::Node));
+        }
+
+        if self.eat(".") {
+            return Ok(step(Axis::Itself, Test::Node));
+        }
+
+        let axis = if self.eat("@") {
+            Axis::Attribute
+        } else {
+            let start = self.pos;
+
+            match self.name() {
+                Ok(name) if self.eat("::") => Axis::parse(&name).ok_or(XPathError {
+                    position: start,
+                    kind: XPathErrorKind::UnknownAxis(name),
+                })?,
+                _ => {
+                    self.pos = start;
+                    Axis::Child
+                }
+            }
+        };
+
+        self.whitespace();
+
+        let test = if self.eat("*") {
+            Test::Any
+        } else {
+            let name = self.name()?;
+
+            let kind = match name.as_str() {
+                "text" => Some(Test::Text),
+                "comment" => Some(Test::Comment),
+                "node" => Some(Test::Node),
+                _ => None,
+            };
+
+            match kind {
+                Some(test) if self.eat("(") => {
+                    self.expect(')')?;
+                    test
+                }
+                _ => Test::Name(name),
+            }
+        };
+
+        let mut step = step(axis, test);
+
+        while self.eat("[") {
+            step.predicates.push(self.expr()?);
+            self.expect(']')?;
+        }
+
+        Ok(step)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.') || !c.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_axes() {
+        let soup = Soup::xml(
+            br#"<library xmlns:x="urn:x">
+                <!-- catalog -->
+                <book id="a" lang="en"><title>Rust</title><price>30</price></book>
+                <book id="b"><title>Go</title><price>25.5</price></book>
+                <x:book id="c" lang="en"><title>Zig</title><price>40</price></x:book>
+            </library>"#
+                .as_slice(),
+        )
+        .expect("Failed to parse XML");
+
+        let select = |expr: &str| {
+            soup.xpath(expr)
+                .unwrap_or_else(|e| panic!("{expr}: {e}"))
+                .iter()
+                .map(XPathItem::string_value)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(select("//book[@lang='en']/title"), ["Rust", "Zig"]);
+        assert_eq!(select("//x:book/@id"), ["a", "b", "c"]);
+        assert_eq!(select("//book[not(@lang)]/title/text()"), ["Go"]);
+        assert_eq!(select("//book[price > 28]/@id"), ["a", "c"]);
+        assert_eq!(select("//book[price < 28 or title = 'Zig']/@id"), [
+            "b", "c"
+        ]);
+        assert_eq!(select("//book[last() - 1]/@id"), ["b"]);
+        assert_eq!(select("//book[position() <= 2][2]/@id"), ["b"]);
+        assert_eq!(select("//title[. = 'Go']/../following-sibling::*/title"), [
+            "Zig"
+        ]);
+        assert_eq!(
+            select("//title[. = 'Go']/../preceding-sibling::book[1]/@id"),
+            ["a"]
+        );
+        assert_eq!(select("//price[.='40']/ancestor::*/@id"), ["c"]);
+        assert_eq!(
+            select("//title[starts-with(., 'R') or contains(., 'i')]"),
+            ["Rust", "Zig"]
+        );
+        assert_eq!(select("//book[1]/title | //book[3]/@lang"), ["Rust", "en"]);
+        assert_eq!(select("/library/comment()"), [" catalog "]);
+        assert_eq!(select("//@*[name() = 'id'][. != 'a']"), ["b", "c"]);
+        assert_eq!(select("//book[string-length(title) = 2]/title"), ["Go"]);
+        assert_eq!(
+            select("//book[title = concat('R', 'ust')]/descendant::text()"),
+            ["Rust", "30"]
+        );
+        assert_eq!(select("//book[count(*) = 2 and @id = 'b']/price"), ["25.5"]);
+        assert_eq!(select("/"), Vec::<String>::new());
+        assert_eq!(select("//book[@id='a']/following::title"), ["Go", "Zig"]);
+        assert_eq!(select("//title[.='Zig']/preceding::title"), ["Rust", "Go"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |expr| XPath::parse(expr).expect_err(expr);
+
+        assert_eq!(error("//a[@href").kind, XPathErrorKind::UnexpectedEnd);
+        assert_eq!(error("//a]").kind, XPathErrorKind::UnexpectedCharacter(']'));
+        assert_eq!(
+            error("namespace::x").kind,
+            XPathErrorKind::UnknownAxis("namespace".into())
+        );
+        assert_eq!(error("//a[foo()]").position, 4);
+        assert_eq!(
+            error("//a[contains(.)]").kind,
+            XPathErrorKind::WrongArguments("contains".into())
+        );
+        assert_eq!(error("count(//a)").kind, XPathErrorKind::NotNodeSet);
+        assert_eq!(error("//a | 'b'").kind, XPathErrorKind::NotNodeSet);
+        assert!(XPath::parse("/").is_ok());
+        assert!(XPath::parse("(//a)[1]/b").is_ok());
+    }
+}